@@ -2,10 +2,10 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use anyhow::Result;
-use bigdecimal::BigDecimal;
 use irontrade::api::Client;
-use irontrade::api::common::{Amount, CryptoPair, OrderStatus};
+use irontrade::api::common::{Amount, Instrument, OrderStatus};
 use irontrade::api::request::OrderRequest;
+use irontrade::bigdecimal::BigDecimal;
 use irontrade::simulated::SimulatedBrokerBuilder;
 use irontrade::simulated::SimulatedClient;
 use std::str::FromStr;
@@ -16,12 +16,12 @@ async fn main() -> Result<()> {
     let broker = SimulatedBrokerBuilder::new("GBP")
         .set_fee_percentage_up_to_one_hundred(BigDecimal::from_str("0.25")?)?
         .set_balance(BigDecimal::from(100))
-        .build();
+        .build()?;
 
     // Create a simulated client using the simulated broker
-    let mut client = SimulatedClient::new(broker);
+    let client = SimulatedClient::new(broker);
 
-    let avax_gbp_pair = CryptoPair::from_str("AVAX/GBP")?;
+    let avax_gbp_pair = Instrument::from_str("AVAX/GBP")?;
 
     // Set the price of AVAX
     client.set_notional_per_unit(avax_gbp_pair.clone(), BigDecimal::from_str("8.81")?)?;
@@ -5,10 +5,23 @@ pub use client::Client;
 mod client;
 
 pub mod request;
+pub mod response;
 pub mod common;
+pub mod validation;
+pub mod stats;
+pub mod credentials;
+pub mod timeframe;
+pub mod aggregation;
+pub mod calendar;
+pub mod retry;
+pub mod rate_limit;
+pub mod routing;
+pub mod mock;
+#[cfg(feature = "tracing")]
+pub mod instrumented;
 
 pub use market::Market;
-mod market;
+pub(crate) mod market;
 
 pub use environment::Environment;
 mod environment;
@@ -0,0 +1,136 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Merging of consecutive bars into longer ones.
+
+use crate::api::common::Bar;
+use crate::{Error, Result};
+
+/// Merges chronologically ordered bars into one bar starting at the first of them.
+pub fn merge_bars<'a, I>(bars: I) -> Option<Bar>
+where
+    I: IntoIterator<Item = &'a Bar>,
+{
+    let mut bars = bars.into_iter();
+    let mut merged = bars.next()?.clone();
+    for bar in bars {
+        merge_bar_into(&mut merged, bar);
+    }
+    Some(merged)
+}
+
+/// Extends `merged` with the bar following it.
+pub fn merge_bar_into(merged: &mut Bar, bar: &Bar) {
+    let volume = &merged.volume + &bar.volume;
+    merged.vwap = match (&merged.vwap, &bar.vwap) {
+        (Some(merged_vwap), Some(vwap)) if volume > 0 => {
+            Some((merged_vwap * &merged.volume + vwap * &bar.volume) / &volume)
+        }
+        _ => None,
+    };
+    merged.volume = volume;
+    merged.trade_count += bar.trade_count;
+    merged.low = merged.low.clone().min(bar.low.clone());
+    merged.high = merged.high.clone().max(bar.high.clone());
+    merged.close = bar.close.clone();
+}
+
+/// Merges chronologically ordered bars into buckets of `bucket_size` consecutive bars, the last
+/// bucket having the remaining bars.
+pub fn merge_bar_buckets(bars: &[Bar], bucket_size: usize) -> Result<Vec<Bar>> {
+    if bucket_size == 0 {
+        return Err(Error::InvalidArgument(
+            "Bucket size must be positive".into(),
+        ));
+    }
+    Ok(bars.chunks(bucket_size).filter_map(merge_bars).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use chrono::{DateTime, Duration, Utc};
+    use std::str::FromStr;
+
+    #[test]
+    fn merge_bars_into_one() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bars = vec![
+            create_bar(start, 10, 12, 8, 11),
+            create_bar(start + Duration::minutes(1), 11, 15, 9, 14),
+            create_bar(start + Duration::minutes(2), 14, 14, 7, 13),
+        ];
+
+        assert_eq!(merge_bars(&bars), Some(create_bar(start, 10, 15, 7, 13)));
+        assert_eq!(merge_bars(&[]), None);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_volumes() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let with_volume = |bar: Bar, volume: i32, trade_count: u64, vwap: Option<i32>| Bar {
+            volume: BigDecimal::from(volume),
+            trade_count,
+            vwap: vwap.map(BigDecimal::from),
+            ..bar
+        };
+        let first = with_volume(create_bar(start, 10, 12, 8, 11), 1, 2, Some(10));
+        let second = with_volume(
+            create_bar(start + Duration::minutes(1), 11, 15, 9, 14),
+            3,
+            4,
+            Some(14),
+        );
+
+        let merged = merge_bars(&[first.clone(), second.clone()]).unwrap();
+        assert_eq!(merged.volume, BigDecimal::from(4));
+        assert_eq!(merged.trade_count, 6);
+        assert_eq!(merged.vwap, Some(BigDecimal::from(13)));
+
+        let second = Bar {
+            vwap: None,
+            ..second
+        };
+        assert_eq!(merge_bars(&[first, second]).unwrap().vwap, None);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_bars_into_buckets() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bars = vec![
+            create_bar(start, 10, 12, 8, 11),
+            create_bar(start + Duration::minutes(1), 11, 15, 9, 14),
+            create_bar(start + Duration::minutes(2), 14, 14, 7, 13),
+        ];
+
+        assert_eq!(
+            merge_bar_buckets(&bars, 2)?,
+            vec![
+                create_bar(start, 10, 15, 8, 14),
+                create_bar(start + Duration::minutes(2), 14, 14, 7, 13),
+            ]
+        );
+        assert_eq!(
+            merge_bar_buckets(&bars, 0).unwrap_err().to_string(),
+            "Bucket size must be positive"
+        );
+        Ok(())
+    }
+
+    fn create_bar(date_time: DateTime<Utc>, open: i32, high: i32, low: i32, close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(open),
+            close: BigDecimal::from(close),
+            date_time,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::{Error, Result};
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc, Weekday};
+use dyn_clone::DynClone;
+use std::collections::HashSet;
+
+/// Decides when a market is open. Orders placed in the simulated environment while it is closed
+/// are accepted but only processed once it opens again.
+pub trait MarketCalendar: DynClone {
+    fn is_open(&self, date_time: &DateTime<Utc>) -> bool;
+
+    /// First time at or after `date_time` the market is open, if it ever opens again.
+    fn next_open(&self, date_time: &DateTime<Utc>) -> Option<DateTime<Utc>>;
+
+    /// First time at or after `date_time` the market is closed, if it ever closes.
+    fn next_close(&self, date_time: &DateTime<Utc>) -> Option<DateTime<Utc>>;
+}
+
+dyn_clone::clone_trait_object!(MarketCalendar);
+
+/// Whether a market is open at a point in time, and when it next opens and closes after it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MarketClock {
+    pub timestamp: DateTime<Utc>,
+    pub is_open: bool,
+    /// `None` when the market never opens again.
+    pub next_open: Option<DateTime<Utc>>,
+    /// `None` when the market never closes.
+    pub next_close: Option<DateTime<Utc>>,
+}
+
+impl MarketClock {
+    /// Clock of a market following `calendar` at `timestamp`.
+    pub fn from_calendar(calendar: &dyn MarketCalendar, timestamp: DateTime<Utc>) -> Self {
+        let is_open = calendar.is_open(&timestamp);
+        let (next_open, next_close) = if is_open {
+            let next_close = calendar.next_close(&timestamp);
+            (
+                next_close.and_then(|close| calendar.next_open(&close)),
+                next_close,
+            )
+        } else {
+            let next_open = calendar.next_open(&timestamp);
+            (
+                next_open,
+                next_open.and_then(|open| calendar.next_close(&open)),
+            )
+        };
+        Self {
+            timestamp,
+            is_open,
+            next_open,
+            next_close,
+        }
+    }
+}
+
+/// Never closes, like most crypto exchanges.
+#[derive(Clone, Debug, Default)]
+pub struct AlwaysOpenCalendar;
+
+impl MarketCalendar for AlwaysOpenCalendar {
+    fn is_open(&self, _date_time: &DateTime<Utc>) -> bool {
+        true
+    }
+
+    fn next_open(&self, date_time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        Some(*date_time)
+    }
+
+    fn next_close(&self, _date_time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        None
+    }
+}
+
+/// Open during a daily session on the trading days, except during outages. Open all day, every
+/// day, unless configured otherwise.
+#[derive(Clone, Debug)]
+pub struct SessionCalendar {
+    session: Option<(NaiveTime, NaiveTime)>,
+    trading_days: HashSet<Weekday>,
+    outages: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+#[derive(Debug)]
+pub struct SessionCalendarBuilder {
+    session: Option<(NaiveTime, NaiveTime)>,
+    trading_days: HashSet<Weekday>,
+    outages: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+impl SessionCalendarBuilder {
+    pub fn new() -> Self {
+        Self {
+            session: None,
+            trading_days: HashSet::from([
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ]),
+            outages: Vec::new(),
+        }
+    }
+
+    /// Sets the UTC times the market opens and closes at on every trading day.
+    pub fn set_session(&mut self, open: NaiveTime, close: NaiveTime) -> Result<&mut Self> {
+        if open >= close {
+            return Err(Error::InvalidArgument(
+                "Session must open before it closes".into(),
+            ));
+        }
+        self.session = Some((open, close));
+        Ok(self)
+    }
+
+    pub fn set_trading_days(&mut self, trading_days: HashSet<Weekday>) -> &mut Self {
+        self.trading_days = trading_days;
+        self
+    }
+
+    /// Closes the market from `start` until `end`, e.g. for exchange maintenance.
+    pub fn add_outage(&mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<&mut Self> {
+        if start >= end {
+            return Err(Error::InvalidArgument(
+                "Outage must start before it ends".into(),
+            ));
+        }
+        self.outages.push((start, end));
+        Ok(self)
+    }
+
+    pub fn build(&self) -> Result<SessionCalendar> {
+        if self.trading_days.is_empty() {
+            return Err(Error::InvalidArgument(
+                "Calendar must have at least one trading day".into(),
+            ));
+        }
+        Ok(SessionCalendar {
+            session: self.session,
+            trading_days: self.trading_days.clone(),
+            outages: self.outages.clone(),
+        })
+    }
+}
+
+impl Default for SessionCalendarBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionCalendar {
+    fn is_in_session(&self, date_time: &DateTime<Utc>) -> bool {
+        if !self.trading_days.contains(&date_time.weekday()) {
+            return false;
+        }
+        match &self.session {
+            Some((open, close)) => date_time.time() >= *open && date_time.time() < *close,
+            None => true,
+        }
+    }
+
+    fn get_outage(&self, date_time: &DateTime<Utc>) -> Option<&(DateTime<Utc>, DateTime<Utc>)> {
+        self.outages
+            .iter()
+            .find(|(start, end)| date_time >= start && date_time < end)
+    }
+
+    /// Start of the first session after the day of `date_time`.
+    fn next_session_open(&self, date_time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let open = self.session.map(|(open, _)| open).unwrap_or(NaiveTime::MIN);
+        (1..=7)
+            .filter_map(|days| date_time.date_naive().checked_add_days(Days::new(days)))
+            .find(|day| self.trading_days.contains(&day.weekday()))
+            .map(|day| day.and_time(open).and_utc())
+    }
+
+    /// End of the session `date_time` is in, if sessions ever end.
+    fn session_close(&self, date_time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if let Some((_, close)) = &self.session {
+            return Some(date_time.date_naive().and_time(*close).and_utc());
+        }
+        // Without daily sessions the market only closes on the first day that isn't traded
+        (1..=7)
+            .filter_map(|days| date_time.date_naive().checked_add_days(Days::new(days)))
+            .find(|day: &NaiveDate| !self.trading_days.contains(&day.weekday()))
+            .map(|day| day.and_time(NaiveTime::MIN).and_utc())
+    }
+}
+
+impl MarketCalendar for SessionCalendar {
+    fn is_open(&self, date_time: &DateTime<Utc>) -> bool {
+        self.is_in_session(date_time) && self.get_outage(date_time).is_none()
+    }
+
+    fn next_open(&self, date_time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut candidate = *date_time;
+        // Every step moves past a closed stretch, which there are a limited number of per week
+        for _ in 0..=self.outages.len() + 14 {
+            if let Some((_, end)) = self.get_outage(&candidate) {
+                candidate = *end;
+            } else if self.is_in_session(&candidate) {
+                return Some(candidate);
+            } else {
+                candidate = match &self.session {
+                    Some((open, _))
+                        if self.trading_days.contains(&candidate.weekday())
+                            && candidate.time() < *open =>
+                    {
+                        candidate.date_naive().and_time(*open).and_utc()
+                    }
+                    _ => self.next_session_open(&candidate)?,
+                };
+            }
+        }
+        None
+    }
+
+    fn next_close(&self, date_time: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if !self.is_open(date_time) {
+            return Some(*date_time);
+        }
+        let session_close = self.session_close(date_time);
+        let outage_start = self
+            .outages
+            .iter()
+            .map(|(start, _)| *start)
+            .filter(|start| start > date_time && session_close.is_none_or(|close| *start < close))
+            .min();
+        outage_start.or(session_close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    #[test]
+    fn session_calendar() -> Result<()> {
+        let calendar = SessionCalendarBuilder::new()
+            .set_session(
+                NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
+                NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+            )?
+            .set_trading_days(HashSet::from([
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]))
+            .build()?;
+
+        // 2025-12-17 is a Wednesday
+        let open = DateTime::<Utc>::from_str("2025-12-17T14:30:00+00:00")?;
+        assert!(calendar.is_open(&open));
+        assert!(!calendar.is_open(&(open - Duration::seconds(1))));
+        assert!(!calendar.is_open(&(open + Duration::hours(6) + Duration::minutes(30))));
+        assert!(!calendar.is_open(&(open + Duration::days(3))));
+        Ok(())
+    }
+
+    #[test]
+    fn session_calendar_outage() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T02:00:00+00:00")?;
+        let end = start + Duration::hours(1);
+        let calendar = SessionCalendarBuilder::new()
+            .add_outage(start, end)?
+            .build()?;
+
+        assert!(calendar.is_open(&(start - Duration::seconds(1))));
+        assert!(!calendar.is_open(&start));
+        assert!(calendar.is_open(&end));
+        Ok(())
+    }
+
+    #[test]
+    fn next_open_and_close() -> Result<()> {
+        // 2025-12-19 is a Friday
+        let friday_open = DateTime::<Utc>::from_str("2025-12-19T14:30:00+00:00")?;
+        let outage_start = friday_open + Duration::hours(1);
+        let calendar = SessionCalendarBuilder::new()
+            .set_session(
+                NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
+                NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+            )?
+            .set_trading_days(HashSet::from([
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]))
+            .add_outage(outage_start, outage_start + Duration::minutes(30))?
+            .build()?;
+
+        assert_eq!(
+            calendar.next_open(&(friday_open - Duration::hours(10))),
+            Some(friday_open)
+        );
+        assert_eq!(calendar.next_open(&friday_open), Some(friday_open));
+        assert_eq!(
+            calendar.next_open(&outage_start),
+            Some(outage_start + Duration::minutes(30))
+        );
+        assert_eq!(
+            calendar.next_open(&(friday_open + Duration::hours(7))),
+            Some(friday_open + Duration::days(3))
+        );
+        assert_eq!(calendar.next_close(&friday_open), Some(outage_start));
+        assert_eq!(
+            calendar.next_close(&(outage_start + Duration::hours(1))),
+            Some(friday_open + Duration::hours(6) + Duration::minutes(30))
+        );
+        assert_eq!(
+            calendar.next_close(&(friday_open - Duration::hours(1))),
+            Some(friday_open - Duration::hours(1))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn next_close_of_trading_days() -> Result<()> {
+        let calendar = SessionCalendarBuilder::new()
+            .set_trading_days(HashSet::from([Weekday::Wed, Weekday::Thu]))
+            .build()?;
+        // 2025-12-17 is a Wednesday
+        let wednesday = DateTime::<Utc>::from_str("2025-12-17T12:00:00+00:00")?;
+
+        assert_eq!(
+            calendar.next_close(&wednesday),
+            Some(DateTime::<Utc>::from_str("2025-12-19T00:00:00+00:00")?)
+        );
+        assert_eq!(
+            calendar.next_open(&(wednesday + Duration::days(2))),
+            Some(DateTime::<Utc>::from_str("2025-12-24T00:00:00+00:00")?)
+        );
+        assert_eq!(AlwaysOpenCalendar.next_close(&wednesday), None);
+        Ok(())
+    }
+
+    #[test]
+    fn clock_from_calendar() -> Result<()> {
+        let calendar = SessionCalendarBuilder::new()
+            .set_session(
+                NaiveTime::from_hms_opt(14, 30, 0).unwrap(),
+                NaiveTime::from_hms_opt(21, 0, 0).unwrap(),
+            )?
+            .build()?;
+        let timestamp = DateTime::<Utc>::from_str("2025-12-17T12:00:00+00:00")?;
+
+        assert_eq!(
+            MarketClock::from_calendar(&calendar, timestamp),
+            MarketClock {
+                timestamp,
+                is_open: false,
+                next_open: Some(DateTime::<Utc>::from_str("2025-12-17T14:30:00+00:00")?),
+                next_close: Some(DateTime::<Utc>::from_str("2025-12-17T21:00:00+00:00")?),
+            }
+        );
+        let clock = MarketClock::from_calendar(&calendar, timestamp + Duration::hours(3));
+        assert_eq!(
+            clock.next_open,
+            Some(DateTime::<Utc>::from_str("2025-12-18T14:30:00+00:00")?)
+        );
+        assert_eq!(
+            MarketClock::from_calendar(&AlwaysOpenCalendar, timestamp),
+            MarketClock {
+                timestamp,
+                is_open: true,
+                next_open: None,
+                next_close: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_session_and_outage() -> Result<()> {
+        let time = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
+        let err = SessionCalendarBuilder::new()
+            .set_session(time, time)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Session must open before it closes");
+
+        let date_time = DateTime::<Utc>::from_str("2025-12-17T02:00:00+00:00")?;
+        let err = SessionCalendarBuilder::new()
+            .add_outage(date_time, date_time)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Outage must start before it ends");
+
+        let err = SessionCalendarBuilder::new()
+            .set_trading_days(HashSet::new())
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Calendar must have at least one trading day"
+        );
+        Ok(())
+    }
+}
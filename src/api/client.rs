@@ -1,18 +1,131 @@
 // Copyright (C) 2025 Agostinho Junior
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::api::common::{Account, Order};
-use crate::api::request::OrderRequest;
-use anyhow::Result;
+use crate::{Error, Result};
+use crate::api::common::{
+    Account, AccountActivity, Asset, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, Trade,
+};
+use crate::api::request::{Cursor, OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::response::Page;
 use async_trait::async_trait;
+use bigdecimal::BigDecimal;
 
 #[async_trait]
 pub trait Client {
-    async fn place_order(&mut self, req: OrderRequest) -> Result<String>;
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId>;
 
-    async fn get_orders(&mut self) -> Result<Vec<Order>>;
+    /// Places several orders at once, returning their ids in the order of the requests.
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>>;
 
-    async fn get_order(&mut self, order_id: &str) -> Result<Order>;
+    /// Runs the checks of [Client::place_order] without placing the order, failing with the error
+    /// placing it would return.
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation>;
 
-    async fn get_account(&mut self) -> Result<Account>;
+    /// Orders matching the filter, in the order they were placed. Clients of providers returning
+    /// orders a page at a time fetch every page, up to the filter's limit, rather than truncating
+    /// the orders at the page size.
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>>;
+
+    /// Page of [Client::get_orders] at `cursor`, the cursor's offset counting from the filter's
+    /// offset. By default fetched with [Client::get_orders], asking for one more order than the
+    /// page holds to find out whether there is a next page.
+    async fn get_orders_page(&self, filter: &OrderFilter, cursor: &Cursor) -> Result<Page<Order>>
+    where
+        Self: Sync,
+    {
+        let remaining = filter
+            .limit
+            .map_or(usize::MAX, |limit| limit.saturating_sub(cursor.offset()));
+        let page_filter = OrderFilter {
+            offset: filter.offset.saturating_add(cursor.offset()),
+            limit: Some(remaining.min(cursor.limit().saturating_add(1))),
+            ..filter.clone()
+        };
+        let orders = self.get_orders(&page_filter).await?;
+        Ok(Page::new(orders, cursor))
+    }
+
+    /// Orders still working, i.e. new or partially filled, matching the filter in the order they
+    /// were placed.
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>>;
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order>;
+
+    /// Orders with the given ids, in the same order as the ids.
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>>;
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order>;
+
+    /// Fills of all orders within the filter's time range, in the order they happened.
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>>;
+
+    /// Page of [Client::get_trades] at `cursor`, by default taken from all the trades.
+    async fn get_trades_page(&self, filter: &TradeFilter, cursor: &Cursor) -> Result<Page<Trade>>
+    where
+        Self: Sync,
+    {
+        let trades = self.get_trades(filter).await?;
+        Ok(Page::new(trades.into_iter().skip(cursor.offset()).collect(), cursor))
+    }
+
+    /// Fills of a single order, in the order they happened.
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>>;
+
+    /// Ledger of the fills, fees, deposits, withdrawals and interest of the account within the
+    /// range, in the order they happened.
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>>;
+
+    /// Page of [Client::get_account_activities] at `cursor`, by default taken from all the
+    /// activities.
+    async fn get_account_activities_page(
+        &self,
+        range: &TimeRange,
+        cursor: &Cursor,
+    ) -> Result<Page<AccountActivity>>
+    where
+        Self: Sync,
+    {
+        let activities = self.get_account_activities(range).await?;
+        Ok(Page::new(activities.into_iter().skip(cursor.offset()).collect(), cursor))
+    }
+
+    async fn get_account(&self) -> Result<Account>;
+
+    /// Total account value in the account currency, i.e. the cash plus the positions valued at
+    /// their current prices.
+    async fn get_equity(&self) -> Result<BigDecimal>;
+
+    /// Trading rules and status of an asset pair.
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset>;
+
+    /// Pairs the client can currently trade.
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>>;
+
+    /// Position of a single asset, `None` when the account has no position in it.
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>>;
+
+    /// Cancels an open order, returning the state it ended in. An order filled or otherwise closed
+    /// before the cancellation reached it is returned as it is rather than as an error.
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        match self.cancel_orders(std::slice::from_ref(order_id)).await {
+            Ok(()) | Err(Error::OrderNotOpen(_)) => self.get_order(order_id).await,
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Cancels several open orders at once.
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()>;
+
+    /// Cancels every open order, returning the ids of the cancelled orders.
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>>;
+
+    /// Cancels every open order and closes every open position with a market order.
+    async fn close_all_positions(&self) -> Result<FlattenSummary>;
+
+    /// Stream of every order placement and status transition from now on.
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream>;
 }
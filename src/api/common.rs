@@ -3,33 +3,323 @@
 
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use std::string::ParseError;
 use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
+use tokio::sync::mpsc::UnboundedReceiver;
+use crate::api::timeframe::Timeframe;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Account {
     pub open_positions: HashMap<String, OpenPosition>,
     pub cash: BigDecimal,
     pub currency: String,
     pub buying_power: BigDecimal,
+    /// Cash plus the value of the open positions.
+    pub equity: BigDecimal,
+    /// Equity required to open the current positions, zero without margin.
+    pub initial_margin: BigDecimal,
+    /// Equity below which positions are liquidated, zero without margin.
+    pub maintenance_margin: BigDecimal,
+    /// Fees paid since the account was opened, valued in the currency.
+    pub fees_paid: BigDecimal,
+    /// Time of this snapshot of the account, `None` when the broker has no clock.
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Account {
+    /// Table of the equity, cash, buying power and open positions, sorted by asset symbol.
+    pub fn summary(&self) -> String {
+        let mut rows = vec![[
+            "Asset".to_string(),
+            "Quantity".to_string(),
+            "Average entry price".to_string(),
+            "Market value".to_string(),
+        ]];
+        let mut positions: Vec<&OpenPosition> = self.open_positions.values().collect();
+        positions.sort_by(|a, b| a.asset_symbol.cmp(&b.asset_symbol));
+        for position in positions {
+            rows.push([
+                position.asset_symbol.clone(),
+                position.quantity.to_string(),
+                format_optional(&position.average_entry_price),
+                format_optional(&position.market_value),
+            ]);
+        }
+        let mut widths = [0; 4];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        let mut summary = format!(
+            "Equity: {} {}\nCash: {} {}\nBuying power: {} {}\n",
+            self.equity, self.currency, self.cash, self.currency, self.buying_power, self.currency
+        );
+        for row in rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect();
+            summary.push_str(cells.join("  ").trim_end());
+            summary.push('\n');
+        }
+        summary
+    }
+}
+
+/// Formats the cash, buying power and number of open positions, e.g.
+/// `100 USD cash, 80 USD buying power, 2 open positions`.
+impl Display for Account {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} cash, {} {} buying power, {} open positions",
+            self.cash,
+            self.currency,
+            self.buying_power,
+            self.currency,
+            self.open_positions.len()
+        )
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Order {
-    pub order_id: String,
+    pub order_id: OrderId,
     pub asset_symbol: String,
     pub amount: Amount,
     pub limit_price: Option<BigDecimal>,
     pub filled_quantity: BigDecimal,
     pub average_fill_price: Option<BigDecimal>,
     pub status: OrderStatus,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_: OrderType,
     pub side: OrderSide,
+    /// Size of the visible slices of an iceberg order, `None` when the whole amount is displayed.
+    pub display_quantity: Option<BigDecimal>,
+    pub run_id: Option<String>,
+    pub strategy_id: Option<String>,
+    /// Time the order was placed at, `None` when the broker has no clock.
+    pub created_at: Option<DateTime<Utc>>,
+    pub tag: Option<String>,
+    pub metadata: HashMap<String, String>,
+    pub client_order_id: Option<String>,
+}
+
+/// Formats the side, amount, price and progress of the order, e.g.
+/// `Buy 2 BTC at 10, partially filled, 1 filled at 10 (order id)`.
+impl Display for Order {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let side = match self.side {
+            OrderSide::Buy => "Buy",
+            OrderSide::Sell => "Sell",
+        };
+        match &self.amount {
+            Amount::Quantity { quantity } => {
+                write!(f, "{} {} {}", side, quantity, self.asset_symbol)?
+            }
+            Amount::Notional { notional } => {
+                write!(f, "{} {} worth {}", side, self.asset_symbol, notional)?
+            }
+        }
+        match &self.limit_price {
+            Some(limit_price) => write!(f, " at {}", limit_price)?,
+            None => write!(f, " at market")?,
+        }
+        let status = match self.status {
+            OrderStatus::New => "new",
+            OrderStatus::PartiallyFilled => "partially filled",
+            OrderStatus::Filled => "filled",
+            OrderStatus::Cancelled => "cancelled",
+            OrderStatus::Expired => "expired",
+            OrderStatus::Unimplemented => "unimplemented",
+        };
+        write!(f, ", {}", status)?;
+        if self.filled_quantity != 0 {
+            write!(f, ", {} filled", self.filled_quantity)?;
+            if let Some(average_fill_price) = &self.average_fill_price {
+                write!(f, " at {}", average_fill_price)?;
+            }
+        }
+        write!(f, " ({})", self.order_id)
+    }
+}
+
+/// A single fill of an order, an order being filled by one or more trades.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trade {
+    pub trade_id: String,
+    pub order_id: OrderId,
+    pub asset_symbol: String,
+    pub side: OrderSide,
+    pub price: BigDecimal,
+    pub quantity: BigDecimal,
+    pub fee: BigDecimal,
+    /// Asset the fee was paid in, the bought asset for buys and the notional asset for sells.
+    pub fee_asset: String,
+    /// Time of the fill, `None` when the broker has no clock.
+    pub date_time: Option<DateTime<Utc>>,
+}
+
+/// Entry of the ledger of an account, see
+/// [Client::get_account_activities](crate::api::Client::get_account_activities).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountActivity {
+    /// Time of the activity, `None` when the broker has no clock.
+    pub date_time: Option<DateTime<Utc>>,
+    pub kind: ActivityKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ActivityKind {
+    Fill(Trade),
+    Fee {
+        order_id: OrderId,
+        asset: String,
+        amount: BigDecimal,
+    },
+    Deposit {
+        asset: String,
+        amount: BigDecimal,
+    },
+    Withdrawal {
+        asset: String,
+        amount: BigDecimal,
+    },
+    Interest {
+        asset: String,
+        amount: BigDecimal,
+    },
+}
+
+/// Actions taken to flatten an account by
+/// [Client::close_all_positions](crate::api::Client::close_all_positions).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlattenSummary {
+    pub cancelled_order_ids: Vec<OrderId>,
+    /// Market sell orders placed to close the open positions.
+    pub closing_order_ids: Vec<OrderId>,
+    /// Why the positions that couldn't be closed weren't, by asset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub failures: HashMap<String, String>,
+}
+
+/// Published by [Client::subscribe_order_updates](crate::api::Client::subscribe_order_updates)
+/// whenever an order is placed or changes status. `previous_status` is `None` for newly placed
+/// orders.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderUpdate {
+    pub previous_status: Option<OrderStatus>,
+    pub order: Order,
+}
+
+/// Asynchronous stream of [OrderUpdate]s. The client stops publishing updates to the stream once
+/// it's dropped.
+pub struct OrderUpdateStream {
+    receiver: UnboundedReceiver<OrderUpdate>,
+    /// Keeps the source of the updates alive as long as the stream.
+    _guard: Box<dyn Send + Sync>,
+}
+
+impl OrderUpdateStream {
+    /// Stream holding `guard` until dropped, e.g. to unregister the listener publishing to it.
+    pub(crate) fn with_guard<G>(receiver: UnboundedReceiver<OrderUpdate>, guard: G) -> Self
+    where
+        G: Send + Sync + 'static,
+    {
+        Self {
+            receiver,
+            _guard: Box::new(guard),
+        }
+    }
+
+    /// Waits for the next update, `None` once the client stopped publishing updates.
+    pub async fn next(&mut self) -> Option<OrderUpdate> {
+        self.receiver.recv().await
+    }
+
+    /// Returns the next update if one was already published.
+    pub fn try_next(&mut self) -> Option<OrderUpdate> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Blocks the current thread until the next update, must not be called from an async
+    /// runtime.
+    pub fn blocking_next(&mut self) -> Option<OrderUpdate> {
+        self.receiver.blocking_recv()
+    }
+}
+
+impl std::fmt::Debug for OrderUpdateStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrderUpdateStream")
+            .field("receiver", &self.receiver)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Buying power an order would reserve, see
+/// [Client::validate_order](crate::api::Client::validate_order).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderValidation {
+    pub reserved_asset: String,
+    pub reserved_amount: BigDecimal,
+    /// Buying power of the reserved asset left once the order is placed.
+    pub remaining_buying_power: BigDecimal,
+    /// Whether the order would wait for trading to resume before it can be filled.
+    pub trading_halted: bool,
+}
+
+/// Trading rules of an asset pair, see [Client::get_asset](crate::api::Client::get_asset).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Asset {
+    pub crypto_pair: Instrument,
+    pub status: AssetStatus,
+    /// Whether new orders of the pair are accepted.
+    pub tradable: bool,
+    /// Minimum notional of an order, `None` when unrestricted.
+    pub min_notional: Option<BigDecimal>,
+    pub max_notional: Option<BigDecimal>,
+    /// Maximum number of decimal places of order quantities, `None` when unrestricted.
+    pub quantity_scale: Option<i64>,
+    /// Maximum number of decimal places of limit prices, `None` when unrestricted.
+    pub price_scale: Option<i64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AssetStatus {
+    Active,
+    /// Orders are accepted but not filled until trading resumes.
+    Halted,
+    /// Orders are rejected.
+    Disabled,
 }
 
 #[derive(PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OpenPosition {
     pub asset_symbol: String,
     pub average_entry_price: Option<BigDecimal>,
@@ -37,64 +327,442 @@ pub struct OpenPosition {
     pub market_value: Option<BigDecimal>,
 }
 
+/// Formats the quantity, average entry price and market value of the position, e.g.
+/// `2 BTC at 10, worth 24`.
+impl Display for OpenPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.quantity, self.asset_symbol)?;
+        if let Some(average_entry_price) = &self.average_entry_price {
+            write!(f, " at {}", average_entry_price)?;
+        }
+        if let Some(market_value) = &self.market_value {
+            write!(f, ", worth {}", market_value)?;
+        }
+        Ok(())
+    }
+}
+
+fn format_optional(value: &Option<BigDecimal>) -> String {
+    value.as_ref().map_or("-".into(), BigDecimal::to_string)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum OrderStatus {
     New,
     PartiallyFilled,
     Filled,
+    Cancelled,
     Expired,
     Unimplemented,
 }
 
 #[derive(Hash, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum OrderType {
     Market,
     Limit,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum OrderSide {
     Buy,
     Sell,
 }
 
+/// How long an order stays open before the remainder not filled is cancelled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum TimeInForce {
+    /// Open until filled or cancelled.
+    #[default]
+    GoodTillCancelled,
+    /// Fills what it can when placed and cancels the rest.
+    ImmediateOrCancel,
+}
+
 #[derive(Hash, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Amount {
     Quantity { quantity: BigDecimal },
     Notional { notional: BigDecimal },
 }
 
-#[derive(Hash, PartialEq, Eq, Clone, Debug)]
-pub struct CryptoPair {
+/// Kind of market an [Instrument] trades on.
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AssetClass {
+    Crypto,
+    Equity,
+    Fx,
+}
+
+/// Something that can be traded: units of `quantity_coin`, priced in `notional_coin`.
+///
+/// Instruments are identified by their two assets alone, so that a simulated broker values a
+/// holding the same way whatever the asset class of the order that bought it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Instrument {
     pub notional_coin: String,
     pub quantity_coin: String,
+    pub asset_class: AssetClass,
+}
+
+/// Former name of [Instrument], from when only crypto pairs could be traded.
+pub type CryptoPair = Instrument;
+
+impl Instrument {
+    pub fn crypto(quantity_coin: &str, notional_coin: &str) -> Self {
+        Self::new(quantity_coin, notional_coin, AssetClass::Crypto)
+    }
+
+    /// Shares of the stock `symbol`, priced in `currency`.
+    pub fn equity(symbol: &str, currency: &str) -> Self {
+        Self::new(symbol, currency, AssetClass::Equity)
+    }
+
+    pub fn fx(base_currency: &str, quote_currency: &str) -> Self {
+        Self::new(base_currency, quote_currency, AssetClass::Fx)
+    }
+
+    /// Pair trading the notional coin of this pair for its quantity coin, e.g. `USD/GBP` for
+    /// `GBP/USD`.
+    pub fn inverse(&self) -> Self {
+        Self::new(&self.notional_coin, &self.quantity_coin, self.asset_class)
+    }
+
+    fn new(quantity_coin: &str, notional_coin: &str, asset_class: AssetClass) -> Self {
+        Self {
+            notional_coin: notional_coin.into(),
+            quantity_coin: quantity_coin.into(),
+            asset_class,
+        }
+    }
+}
+
+impl PartialEq for Instrument {
+    fn eq(&self, other: &Self) -> bool {
+        self.notional_coin == other.notional_coin && self.quantity_coin == other.quantity_coin
+    }
+}
+
+impl Eq for Instrument {}
+
+impl Hash for Instrument {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.notional_coin.hash(state);
+        self.quantity_coin.hash(state);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bar {
     pub low: BigDecimal,
     pub high: BigDecimal,
     pub open: BigDecimal,
     pub close: BigDecimal,
     pub date_time: DateTime<Utc>,
+    /// Quantity traded, zero when the source of the bar doesn't have it.
+    pub volume: BigDecimal,
+    pub trade_count: u64,
+    /// Volume weighted average price, when known.
+    pub vwap: Option<BigDecimal>,
+}
+
+/// View of a single timeframe of a pair, as of the time it was requested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeframeBars {
+    pub timeframe: Timeframe,
+    /// Last closed bar of the timeframe.
+    pub latest: Option<Bar>,
+    /// Bar of the period still in progress, aggregated from the minute bars closed so far.
+    pub in_progress: Option<Bar>,
+}
+
+/// Quote assets recognized at the end of symbols without a separator, longest first so that
+/// `BTCUSDT` is quoted in `USDT` rather than `T`.
+const KNOWN_QUOTE_ASSETS: [&str; 10] = [
+    "FDUSD", "USDT", "USDC", "BUSD", "USD", "EUR", "GBP", "JPY", "BTC", "ETH",
+];
+
+/// Parses a crypto pair, e.g. `BTC/USD`, `btc-usd` or `BTCUSD`, uppercasing both assets.
+///
+/// Symbols without a `/` or `-` separator are split before the longest known quote asset they
+/// end with, e.g. `USDT` for `ETHUSDT`.
+///
+/// Only crypto pairs round-trip through [Display]: an equity formats as its bare symbol, e.g.
+/// `AAPL`, which has no quote asset to parse, and an fx pair parses back as a crypto pair. Those
+/// are built with [Instrument::equity] and [Instrument::fx] instead.
+impl FromStr for Instrument {
+    type Err = InstrumentParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let symbol = s.trim().to_uppercase();
+        let (quantity_coin, notional_coin) = match symbol.split_once(['/', '-']) {
+            Some((_, notional_coin)) if notional_coin.contains(['/', '-']) => {
+                return Err(InstrumentParseError::TooManyAssets(s.into()));
+            }
+            Some(assets) => assets,
+            None => KNOWN_QUOTE_ASSETS
+                .iter()
+                .find_map(|quote| {
+                    let base = symbol.strip_suffix(quote)?;
+                    (!base.is_empty()).then_some((base, *quote))
+                })
+                .ok_or_else(|| InstrumentParseError::UnknownQuoteAsset(s.into()))?,
+        };
+        for asset in [quantity_coin, notional_coin] {
+            if asset.is_empty() {
+                return Err(InstrumentParseError::MissingAsset(s.into()));
+            }
+            if !asset.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(InstrumentParseError::InvalidAsset(s.into()));
+            }
+        }
+        Ok(Instrument::crypto(quantity_coin, notional_coin))
+    }
 }
 
-impl FromStr for CryptoPair {
+/// Why a symbol couldn't be parsed into an [Instrument].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InstrumentParseError {
+    #[error("{0:?} is missing an asset")]
+    MissingAsset(String),
+    #[error("{0:?} has more than two assets")]
+    TooManyAssets(String),
+    /// An asset has characters other than letters and digits.
+    #[error("{0:?} has an invalid asset")]
+    InvalidAsset(String),
+    /// The symbol has no separator and doesn't end with a known quote asset.
+    #[error("{0:?} doesn't end with a known quote asset")]
+    UnknownQuoteAsset(String),
+}
+
+/// Formats the symbol of the instrument, `AAPL` for an equity and `BTC/USD` for a pair.
+impl Display for Instrument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.asset_class {
+            AssetClass::Equity => f.write_str(&self.quantity_coin),
+            AssetClass::Crypto | AssetClass::Fx => f.write_fmt(format_args!(
+                "{}/{}",
+                self.quantity_coin, self.notional_coin
+            )),
+        }
+    }
+}
+
+/// Id the broker assigned to an [Order], kept apart from client order ids and asset symbols.
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct OrderId(String);
+
+impl OrderId {
+    pub fn new(order_id: impl Into<String>) -> Self {
+        OrderId(order_id.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for OrderId {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tokens: Vec<&str> = s.split("/").collect();
-        Ok(CryptoPair {
-            notional_coin: tokens[1].into(),
-            quantity_coin: tokens[0].into(),
-        })
+        Ok(OrderId(s.into()))
     }
 }
-impl Display for CryptoPair {
+
+impl Display for OrderId {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!(
-            "{}/{}",
-            self.quantity_coin, self.notional_coin
-        ))
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for OrderId {
+    fn from(order_id: String) -> Self {
+        OrderId(order_id)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instrument_symbol_and_identity() {
+        let equity = Instrument::equity("AAPL", "USD");
+        assert_eq!(equity.to_string(), "AAPL");
+        assert_eq!(Instrument::fx("EUR", "USD").to_string(), "EUR/USD");
+        assert_eq!(Instrument::from_str("BTC/USD").unwrap(), Instrument::crypto("BTC", "USD"));
+        // A holding of AAPL is valued with the price of the equity
+        assert_eq!(Instrument::crypto("AAPL", "USD"), equity);
+    }
+
+    #[test]
+    fn parse_instrument() {
+        let btc_usd = Instrument::crypto("BTC", "USD");
+        for symbol in ["BTC/USD", "btc-usd", " BTCUSD "] {
+            assert_eq!(Instrument::from_str(symbol), Ok(btc_usd.clone()));
+        }
+        assert_eq!(
+            Instrument::from_str("ethusdt"),
+            Ok(Instrument::crypto("ETH", "USDT"))
+        );
+
+        assert_eq!(
+            Instrument::from_str("BTC"),
+            Err(InstrumentParseError::UnknownQuoteAsset("BTC".into()))
+        );
+        assert_eq!(
+            Instrument::from_str("BTC/"),
+            Err(InstrumentParseError::MissingAsset("BTC/".into()))
+        );
+        assert_eq!(
+            Instrument::from_str("BTC/USD/EUR"),
+            Err(InstrumentParseError::TooManyAssets("BTC/USD/EUR".into()))
+        );
+        let err = Instrument::from_str("BT C/USD").unwrap_err();
+        assert_eq!(err.to_string(), "\"BT C/USD\" has an invalid asset");
+    }
+
+    #[test]
+    fn parse_formatted_instrument() {
+        let btc_usd = Instrument::crypto("BTC", "USD");
+        let parsed = Instrument::from_str(&btc_usd.to_string()).unwrap();
+        assert_eq!(parsed, btc_usd);
+        assert_eq!(parsed.asset_class, AssetClass::Crypto);
+
+        // Equity symbols have no quote asset to parse
+        assert_eq!(
+            Instrument::from_str(&Instrument::equity("AAPL", "USD").to_string()),
+            Err(InstrumentParseError::UnknownQuoteAsset("AAPL".into()))
+        );
+        // Fx pairs parse back as crypto pairs
+        let eur_usd = Instrument::fx("EUR", "USD");
+        let parsed = Instrument::from_str(&eur_usd.to_string()).unwrap();
+        assert_eq!(parsed, eur_usd);
+        assert_eq!(parsed.asset_class, AssetClass::Crypto);
+    }
+
+    fn partially_filled_order() -> Order {
+        Order {
+            order_id: OrderId::new("order"),
+            asset_symbol: "BTC".into(),
+            amount: Amount::Quantity {
+                quantity: BigDecimal::from(2),
+            },
+            limit_price: Some(BigDecimal::from(10)),
+            filled_quantity: BigDecimal::from(1),
+            average_fill_price: None,
+            status: OrderStatus::PartiallyFilled,
+            type_: OrderType::Limit,
+            side: OrderSide::Buy,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: Some(DateTime::from_timestamp(0, 0).unwrap()),
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
+        }
+    }
+
+    #[test]
+    fn display_account() {
+        let mut order = partially_filled_order();
+        order.average_fill_price = Some(BigDecimal::from(10));
+        let position = OpenPosition {
+            asset_symbol: "BTC".into(),
+            average_entry_price: Some(BigDecimal::from(10)),
+            quantity: BigDecimal::from(1),
+            market_value: Some(BigDecimal::from(12)),
+        };
+        let account = Account {
+            open_positions: HashMap::from([
+                ("BTC".into(), position),
+                (
+                    "ETH".into(),
+                    OpenPosition {
+                        asset_symbol: "ETH".into(),
+                        average_entry_price: None,
+                        quantity: BigDecimal::from(5),
+                        market_value: None,
+                    },
+                ),
+            ]),
+            cash: BigDecimal::from(100),
+            currency: "USD".into(),
+            buying_power: BigDecimal::from(80),
+            equity: BigDecimal::from(112),
+            initial_margin: BigDecimal::from(0),
+            maintenance_margin: BigDecimal::from(0),
+            fees_paid: BigDecimal::from(1),
+            updated_at: None,
+        };
+
+        assert_eq!(
+            order.to_string(),
+            "Buy 2 BTC at 10, partially filled, 1 filled at 10 (order)"
+        );
+        assert_eq!(account.open_positions["BTC"].to_string(), "1 BTC at 10, worth 12");
+        assert_eq!(
+            account.to_string(),
+            "100 USD cash, 80 USD buying power, 2 open positions"
+        );
+        assert_eq!(
+            account.summary(),
+            "Equity: 112 USD\n\
+             Cash: 100 USD\n\
+             Buying power: 80 USD\n\
+             Asset  Quantity  Average entry price  Market value\n\
+             BTC    1         10                   12\n\
+             ETH    5         -                    -\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_order() -> anyhow::Result<()> {
+        let order = partially_filled_order();
+
+        let json = serde_json::to_value(&order)?;
+        assert_eq!(json["order_id"], "order");
+        assert_eq!(json["amount"]["quantity"]["quantity"], "2");
+        assert_eq!(json["status"], "partially_filled");
+        assert_eq!(json["type"], "limit");
+        assert_eq!(json["created_at"], "1970-01-01T00:00:00Z");
+        assert_eq!(serde_json::from_value::<Order>(json)?, order);
+        Ok(())
     }
 }
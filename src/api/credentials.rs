@@ -0,0 +1,340 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{Result, anyhow};
+use dyn_clone::DynClone;
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+
+/// API key and secret of a provider.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub api_key: String,
+    pub api_secret: String,
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("api_key", &self.api_key)
+            .field("api_secret", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Source of the [Credentials] provider clients are created with, so that keys never have to be
+/// written in user code.
+pub trait CredentialsProvider: DynClone {
+    fn get_credentials(&self) -> Result<Credentials>;
+}
+
+dyn_clone::clone_trait_object!(CredentialsProvider);
+
+impl Debug for dyn CredentialsProvider + Send + Sync {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CredentialsProvider")
+    }
+}
+
+/// Reads the credentials from environment variables.
+#[derive(Debug, Clone)]
+pub struct EnvCredentialsProvider {
+    api_key_env: String,
+    api_secret_env: String,
+}
+
+impl EnvCredentialsProvider {
+    pub fn new(api_key_env: &str, api_secret_env: &str) -> Self {
+        Self {
+            api_key_env: api_key_env.into(),
+            api_secret_env: api_secret_env.into(),
+        }
+    }
+}
+
+impl CredentialsProvider for EnvCredentialsProvider {
+    fn get_credentials(&self) -> Result<Credentials> {
+        Ok(Credentials {
+            api_key: read_env_var(&self.api_key_env)?,
+            api_secret: read_env_var(&self.api_secret_env)?,
+        })
+    }
+}
+
+fn read_env_var(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| anyhow!("Environment variable {} is not set", name))
+}
+
+/// Reads the credentials from a file of `api_key = ...` and `api_secret = ...` lines, ignoring
+/// empty lines and lines starting with `#`.
+#[derive(Debug, Clone)]
+pub struct FileCredentialsProvider {
+    path: PathBuf,
+}
+
+impl FileCredentialsProvider {
+    pub fn new<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    fn get_value(&self, contents: &str, name: &str) -> Result<String> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim() == name)
+            .map(|(_, value)| value.trim().trim_matches('"').to_string())
+            .ok_or(anyhow!(
+                "Credentials file {} is missing {}",
+                self.path.display(),
+                name
+            ))
+    }
+}
+
+impl CredentialsProvider for FileCredentialsProvider {
+    fn get_credentials(&self) -> Result<Credentials> {
+        let contents = std::fs::read_to_string(&self.path)?;
+        Ok(Credentials {
+            api_key: self.get_value(&contents, "api_key")?,
+            api_secret: self.get_value(&contents, "api_secret")?,
+        })
+    }
+}
+
+/// Reads the credentials from the entries `api_key` and `api_secret` of a service in the OS
+/// keyring.
+#[cfg(feature = "keyring")]
+#[derive(Debug, Clone)]
+pub struct KeyringCredentialsProvider {
+    service: String,
+}
+
+#[cfg(feature = "keyring")]
+impl KeyringCredentialsProvider {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn get_password(&self, name: &str) -> Result<String> {
+        keyring::Entry::new(&self.service, name)?
+            .get_password()
+            .map_err(|err| anyhow!("Keyring entry {} of {}: {}", name, self.service, err))
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl CredentialsProvider for KeyringCredentialsProvider {
+    fn get_credentials(&self) -> Result<Credentials> {
+        Ok(Credentials {
+            api_key: self.get_password("api_key")?,
+            api_secret: self.get_password("api_secret")?,
+        })
+    }
+}
+
+/// API key, secret and base URL of a named profile of a [ProfileCredentialsProvider].
+#[cfg(feature = "config")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialsProfile {
+    pub credentials: Credentials,
+    /// Base URL of the account of the profile, e.g. of a paper or live trading API.
+    pub base_url: Option<String>,
+}
+
+/// Reads the credentials of a named profile, e.g. `paper` or `live`, from a TOML file:
+///
+/// ```toml
+/// [profiles.paper]
+/// api_key = "..."
+/// api_secret = "..."
+/// base_url = "https://paper-api.alpaca.markets"
+/// ```
+///
+/// The `APCA_API_KEY_ID`, `APCA_API_SECRET_KEY` and `APCA_API_BASE_URL` environment variables
+/// override the values of the file when set.
+#[cfg(feature = "config")]
+#[derive(Debug, Clone)]
+pub struct ProfileCredentialsProvider {
+    path: PathBuf,
+    profile: String,
+}
+
+#[cfg(feature = "config")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfilesFile {
+    #[serde(default)]
+    profiles: std::collections::HashMap<String, ProfileEntry>,
+}
+
+#[cfg(feature = "config")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileEntry {
+    api_key: Option<String>,
+    api_secret: Option<String>,
+    base_url: Option<String>,
+}
+
+#[cfg(feature = "config")]
+impl ProfileCredentialsProvider {
+    pub fn new<P>(path: P, profile: &str) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            profile: profile.into(),
+        }
+    }
+
+    pub fn get_profile(&self) -> Result<CredentialsProfile> {
+        let file: ProfilesFile = toml::from_str(&std::fs::read_to_string(&self.path)?)?;
+        let entry = file.profiles.get(&self.profile).ok_or(anyhow!(
+            "Credentials file {} doesn't have profile {}",
+            self.path.display(),
+            self.profile
+        ))?;
+        let get_value =
+            |env: &str, value: &Option<String>| std::env::var(env).ok().or_else(|| value.clone());
+        let get_required_value = |env: &str, value: &Option<String>, name: &str| {
+            get_value(env, value).ok_or(anyhow!(
+                "Profile {} of credentials file {} is missing {}",
+                self.profile,
+                self.path.display(),
+                name
+            ))
+        };
+        Ok(CredentialsProfile {
+            credentials: Credentials {
+                api_key: get_required_value("APCA_API_KEY_ID", &entry.api_key, "api_key")?,
+                api_secret: get_required_value(
+                    "APCA_API_SECRET_KEY",
+                    &entry.api_secret,
+                    "api_secret",
+                )?,
+            },
+            base_url: get_value("APCA_API_BASE_URL", &entry.base_url),
+        })
+    }
+}
+
+#[cfg(feature = "config")]
+impl CredentialsProvider for ProfileCredentialsProvider {
+    fn get_credentials(&self) -> Result<Credentials> {
+        Ok(self.get_profile()?.credentials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_credentials_provider_missing_variable() {
+        let provider = EnvCredentialsProvider::new(
+            "IRONTRADE_TEST_MISSING_KEY",
+            "IRONTRADE_TEST_MISSING_SECRET",
+        );
+
+        let err = provider.get_credentials().unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Environment variable IRONTRADE_TEST_MISSING_KEY is not set"
+        );
+    }
+
+    #[test]
+    fn file_credentials_provider() -> Result<()> {
+        let path = std::env::temp_dir().join("irontrade_test_credentials");
+        std::fs::write(
+            &path,
+            "# paper account\napi_key = \"key\"\napi_secret = secret\n",
+        )?;
+
+        let credentials = FileCredentialsProvider::new(&path).get_credentials()?;
+
+        assert_eq!(
+            credentials,
+            Credentials {
+                api_key: "key".into(),
+                api_secret: "secret".into(),
+            }
+        );
+        assert!(!format!("{:?}", credentials).contains("\"secret\""));
+
+        std::fs::write(&path, "api_key = key\n")?;
+        let err = FileCredentialsProvider::new(&path)
+            .get_credentials()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Credentials file {} is missing api_secret", path.display())
+        );
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn profile_credentials_provider() -> Result<()> {
+        let path = std::env::temp_dir().join("irontrade_test_credentials_profiles.toml");
+        std::fs::write(
+            &path,
+            r#"
+[profiles.paper]
+api_key = "paper_key"
+api_secret = "paper_secret"
+base_url = "https://paper-api.alpaca.markets"
+
+[profiles.live]
+api_key = "live_key"
+"#,
+        )?;
+
+        let profile = ProfileCredentialsProvider::new(&path, "paper").get_profile()?;
+        let live_err = ProfileCredentialsProvider::new(&path, "live")
+            .get_credentials()
+            .unwrap_err();
+        let missing_err = ProfileCredentialsProvider::new(&path, "staging")
+            .get_credentials()
+            .unwrap_err();
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(
+            profile,
+            CredentialsProfile {
+                credentials: Credentials {
+                    api_key: "paper_key".into(),
+                    api_secret: "paper_secret".into(),
+                },
+                base_url: Some("https://paper-api.alpaca.markets".into()),
+            }
+        );
+        assert_eq!(
+            live_err.to_string(),
+            format!(
+                "Profile live of credentials file {} is missing api_secret",
+                path.display()
+            )
+        );
+        assert_eq!(
+            missing_err.to_string(),
+            format!(
+                "Credentials file {} doesn't have profile staging",
+                path.display()
+            )
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::Result;
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::time::Instant;
+
+/// Wraps a [Client], [Market] or [Environment] and logs every call through [tracing], giving an
+/// audit of what a strategy asked the provider to do.
+///
+/// Each call logs its method and arguments when sent, then its latency with either the response
+/// or the error. Requests and responses are logged at the debug level and errors at the warn
+/// level, under the `irontrade::api::instrumented` target. Arguments and responses are logged
+/// through their [Debug] implementation, which redacts secrets such as the
+/// [Credentials](crate::api::credentials::Credentials) API secret.
+pub struct InstrumentedClient<T> {
+    inner: T,
+}
+
+impl<T> InstrumentedClient<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+async fn instrument<R, Fut>(
+    method: &'static str,
+    request: &(dyn Debug + Sync),
+    call: Fut,
+) -> Result<R>
+where
+    R: Debug,
+    Fut: Future<Output = Result<R>>,
+{
+    instrument_with(method, request, call, |response| format!("{response:?}")).await
+}
+
+async fn instrument_with<R, Fut>(
+    method: &'static str,
+    request: &(dyn Debug + Sync),
+    call: Fut,
+    describe: fn(&R) -> String,
+) -> Result<R>
+where
+    Fut: Future<Output = Result<R>>,
+{
+    tracing::debug!(method, ?request, "Sending request");
+    let start = Instant::now();
+    let result = call.await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+    match &result {
+        Ok(response) => {
+            tracing::debug!(
+                method,
+                latency_ms,
+                response = %describe(response),
+                "Received response"
+            )
+        }
+        Err(error) => tracing::warn!(method, latency_ms, %error, "Request failed"),
+    }
+    result
+}
+
+#[async_trait]
+impl<T> Client for InstrumentedClient<T>
+where
+    T: Client + Send + Sync,
+{
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        instrument("place_order", &req.clone(), self.inner.place_order(req)).await
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        instrument("place_orders", &reqs.clone(), self.inner.place_orders(reqs)).await
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        instrument("validate_order", req, self.inner.validate_order(req)).await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        instrument("get_orders", filter, self.inner.get_orders(filter)).await
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        instrument(
+            "get_open_orders",
+            filter,
+            self.inner.get_open_orders(filter),
+        )
+        .await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        instrument("get_order", order_id, self.inner.get_order(order_id)).await
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        instrument(
+            "get_orders_by_ids",
+            &order_ids,
+            self.inner.get_orders_by_ids(order_ids),
+        )
+        .await
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        instrument(
+            "get_order_by_client_id",
+            &client_order_id,
+            self.inner.get_order_by_client_id(client_order_id),
+        )
+        .await
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        instrument("get_trades", filter, self.inner.get_trades(filter)).await
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        instrument(
+            "get_order_fills",
+            order_id,
+            self.inner.get_order_fills(order_id),
+        )
+        .await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        instrument(
+            "get_account_activities",
+            range,
+            self.inner.get_account_activities(range),
+        )
+        .await
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        instrument("get_account", &(), self.inner.get_account()).await
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        instrument("get_equity", &(), self.inner.get_equity()).await
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        instrument("get_asset", crypto_pair, self.inner.get_asset(crypto_pair)).await
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        instrument("list_asset_pairs", &(), self.inner.list_asset_pairs()).await
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        instrument(
+            "get_position",
+            &asset_symbol,
+            self.inner.get_position(asset_symbol),
+        )
+        .await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        instrument("cancel_order", order_id, self.inner.cancel_order(order_id)).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        instrument(
+            "cancel_orders",
+            &order_ids,
+            self.inner.cancel_orders(order_ids),
+        )
+        .await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        instrument("cancel_all_orders", &(), self.inner.cancel_all_orders()).await
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        instrument("close_all_positions", &(), self.inner.close_all_positions()).await
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        instrument_with(
+            "subscribe_order_updates",
+            &(),
+            self.inner.subscribe_order_updates(),
+            |_| "<stream>".into(),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<T> Market for InstrumentedClient<T>
+where
+    T: Market + Send + Sync,
+{
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        instrument(
+            "get_latest_minute_bar",
+            crypto_pair,
+            self.inner.get_latest_minute_bar(crypto_pair),
+        )
+        .await
+    }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        instrument(
+            "get_latest_minute_bars",
+            &crypto_pairs,
+            self.inner.get_latest_minute_bars(crypto_pairs),
+        )
+        .await
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        instrument(
+            "get_timeframe_bars",
+            &(crypto_pair, timeframes),
+            self.inner.get_timeframe_bars(crypto_pair, timeframes),
+        )
+        .await
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        instrument(
+            "get_bars",
+            &(crypto_pair, start, end, timeframe),
+            self.inner.get_bars(crypto_pair, start, end, timeframe),
+        )
+        .await
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        instrument_with("get_calendar", &(), self.inner.get_calendar(), |_| {
+            "<calendar>".into()
+        })
+        .await
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        instrument("get_clock", &(), self.inner.get_clock()).await
+    }
+}
+
+impl<T> Environment for InstrumentedClient<T> where T: Environment + Send + Sync {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Amount;
+    use crate::simulated::{SimulatedBrokerBuilder, SimulatedClient};
+    use anyhow::Result;
+    use std::fmt::Write;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Keeps the level and fields of every event.
+    #[derive(Clone, Default)]
+    struct EventRecorder {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct EventLine(String);
+
+    impl Visit for EventLine {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            write!(self.0, " {}={:?}", field.name(), value).unwrap();
+        }
+    }
+
+    impl Subscriber for EventRecorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut line = EventLine(event.metadata().level().to_string());
+            event.record(&mut line);
+            self.events.lock().unwrap().push(line.0);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn log_requests_and_responses() -> Result<()> {
+        let recorder = EventRecorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let inner = SimulatedClient::new(broker);
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        inner.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(10))?;
+        let client = InstrumentedClient::new(inner);
+
+        let order_id = client
+            .place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            ))
+            .await?;
+        client
+            .get_order(&OrderId::new("unknown"))
+            .await
+            .unwrap_err();
+
+        let events = recorder.events.lock().unwrap();
+        assert_eq!(events.len(), 4);
+        assert!(events[0].starts_with("DEBUG message=Sending request method=\"place_order\""));
+        assert!(events[0].contains("side: Buy"));
+        assert!(events[1].starts_with("DEBUG message=Received response method=\"place_order\""));
+        assert!(events[1].contains(&format!("{order_id:?}")));
+        assert!(events[3].starts_with("WARN message=Request failed method=\"get_order\""));
+        assert!(events[3].ends_with("error=Order with id unknown doesn't exist"));
+        Ok(())
+    }
+}
@@ -1,14 +1,186 @@
 // Copyright (C) 2025 Agostinho Junior
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::api::common::{Bar, CryptoPair};
-use anyhow::Result;
+use crate::Result;
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::aggregation::merge_bars;
+use crate::api::common::{Bar, Instrument, TimeframeBars};
+use crate::api::timeframe::Timeframe;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 #[async_trait]
 pub trait Market {
     async fn get_latest_minute_bar(
         &self,
-        crypto_pair: &CryptoPair,
+        crypto_pair: &Instrument,
     ) -> Result<Option<Bar>>;
+
+    /// Latest minute bars of several pairs at once, leaving out the pairs without any bar.
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>>;
+
+    /// Returns one [TimeframeBars] per requested timeframe, in the same order, all built from the
+    /// same closed minute bars.
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>>;
+
+    /// Closed bars of `timeframe` whose period starts within `start` inclusive and `end`
+    /// exclusive, in chronological order.
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>>;
+
+    /// Calendar of the market's sessions, for strategies that must avoid trading while closed.
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>>;
+
+    /// Whether the market is open now and when it next opens and closes, by default from its
+    /// calendar at the current time.
+    async fn get_clock(&self) -> Result<MarketClock>
+    where
+        Self: Sync,
+    {
+        let calendar = self.get_calendar().await?;
+        Ok(MarketClock::from_calendar(calendar.as_ref(), Utc::now()))
+    }
+}
+
+pub(crate) fn check_timeframes(timeframes: &[Timeframe]) -> Result<()> {
+    for timeframe in timeframes {
+        timeframe.check()?;
+    }
+    Ok(())
+}
+
+/// Start of the earliest period needed to build the [TimeframeBars] of every timeframe at `now`.
+pub(crate) fn get_timeframes_start(
+    timeframes: &[Timeframe],
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    let mut start = now;
+    for timeframe in timeframes {
+        start = start.min(timeframe.period_start(now)? - timeframe.to_duration());
+    }
+    Ok(start)
+}
+
+/// Aggregates the ordered minute bars closed at `now` into the views of each timeframe.
+pub(crate) fn aggregate_timeframe_bars(
+    minute_bars: &[Bar],
+    timeframes: &[Timeframe],
+    now: DateTime<Utc>,
+) -> Result<Vec<TimeframeBars>> {
+    let mut timeframe_bars = Vec::new();
+    for timeframe in timeframes {
+        let period_start = timeframe.period_start(now)?;
+        let previous_period_start = period_start - timeframe.to_duration();
+        timeframe_bars.push(TimeframeBars {
+            timeframe: *timeframe,
+            latest: aggregate_bars(
+                minute_bars.iter().filter(|bar| {
+                    bar.date_time >= previous_period_start && bar.date_time < period_start
+                }),
+                previous_period_start,
+            ),
+            in_progress: aggregate_bars(
+                minute_bars
+                    .iter()
+                    .filter(|bar| bar.date_time >= period_start),
+                period_start,
+            ),
+        });
+    }
+    Ok(timeframe_bars)
+}
+
+fn aggregate_bars<'a, I>(bars: I, date_time: DateTime<Utc>) -> Option<Bar>
+where
+    I: Iterator<Item = &'a Bar>,
+{
+    merge_bars(bars).map(|merged| Bar { date_time, ..merged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    #[test]
+    fn aggregate_latest_and_in_progress_bars() -> Result<()> {
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let minute_bars = vec![
+            create_bar(hour - Duration::minutes(2), 10, 12, 8, 11),
+            create_bar(hour - Duration::minutes(1), 11, 15, 9, 14),
+            create_bar(hour, 14, 14, 13, 13),
+            create_bar(hour + Duration::minutes(1), 13, 16, 12, 15),
+        ];
+
+        let timeframe_bars = aggregate_timeframe_bars(
+            &minute_bars,
+            &[Timeframe::OneHour, Timeframe::OneMinute],
+            hour + Duration::minutes(2) + Duration::seconds(30),
+        )?;
+
+        assert_eq!(
+            timeframe_bars,
+            vec![
+                TimeframeBars {
+                    timeframe: Timeframe::OneHour,
+                    latest: Some(create_bar(hour - Duration::hours(1), 10, 15, 8, 14)),
+                    in_progress: Some(create_bar(hour, 14, 16, 12, 15)),
+                },
+                TimeframeBars {
+                    timeframe: Timeframe::OneMinute,
+                    latest: Some(minute_bars[3].clone()),
+                    in_progress: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_timeframes_start_of_longest_timeframe() -> Result<()> {
+        let now = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        assert_eq!(
+            get_timeframes_start(&[Timeframe::FiveMinutes, Timeframe::OneHour], now)?,
+            DateTime::<Utc>::from_str("2025-12-17T17:00:00+00:00")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn check_invalid_timeframes() {
+        let err = check_timeframes(&[Timeframe::OneMinute, Timeframe::Minutes(0)]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Timeframes must be a positive number of minutes"
+        );
+    }
+
+    fn create_bar(date_time: DateTime<Utc>, open: i32, high: i32, low: i32, close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(open),
+            close: BigDecimal::from(close),
+            date_time,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
 }
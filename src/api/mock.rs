@@ -0,0 +1,325 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use crate::{Error, Result};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Call made to a [MockClient], with its arguments formatted with [Debug].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    pub method: &'static str,
+    pub arguments: String,
+}
+
+struct Expectation {
+    method: &'static str,
+    response: Box<dyn Any + Send>,
+    delay: Duration,
+}
+
+/// [Client], [Market] and [Environment] answering each call with the next scripted response, so
+/// that strategies can be unit tested without a broker or network.
+///
+/// Calls must be made in the order they are expected, each one consuming its expectation. A call
+/// other than the next expected one, or with no expectation left, fails with [Error::Other].
+/// Methods are named as in the traits, e.g. `"place_order"`, and responses have the type these
+/// methods return, e.g. `Result<OrderId>` for `"place_order"`.
+#[derive(Default)]
+pub struct MockClient {
+    expectations: Mutex<VecDeque<Expectation>>,
+    calls: Mutex<Vec<MockCall>>,
+}
+
+impl MockClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects a call to `method` after the previously expected ones, answering it with
+    /// `response`.
+    pub fn expect<R>(&self, method: &'static str, response: Result<R>) -> &Self
+    where
+        R: Send + 'static,
+    {
+        self.expect_delayed(method, response, Duration::ZERO)
+    }
+
+    /// Expects a call to `method` like [MockClient::expect], answering it once `delay` elapsed,
+    /// e.g. to test timeouts.
+    pub fn expect_delayed<R>(
+        &self,
+        method: &'static str,
+        response: Result<R>,
+        delay: Duration,
+    ) -> &Self
+    where
+        R: Send + 'static,
+    {
+        self.expectations.lock().unwrap().push_back(Expectation {
+            method,
+            response: Box::new(response),
+            delay,
+        });
+        self
+    }
+
+    /// Calls made so far, in the order they were made.
+    pub fn get_calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Fails if an expected call wasn't made.
+    pub fn verify(&self) -> Result<()> {
+        let expectations = self.expectations.lock().unwrap();
+        match expectations.front() {
+            Some(expectation) => Err(Error::Other(anyhow!(
+                "Expected a call to {} that wasn't made",
+                expectation.method
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    async fn answer<R>(&self, method: &'static str, arguments: &(dyn Debug + Sync)) -> Result<R>
+    where
+        R: 'static,
+    {
+        self.calls.lock().unwrap().push(MockCall {
+            method,
+            arguments: format!("{arguments:?}"),
+        });
+        let expectation = {
+            let mut expectations = self.expectations.lock().unwrap();
+            match expectations.front() {
+                Some(expectation) if expectation.method == method => expectations.pop_front(),
+                Some(expectation) => {
+                    return Err(Error::Other(anyhow!(
+                        "Unexpected call to {method}, expected a call to {}",
+                        expectation.method
+                    )));
+                }
+                None => None,
+            }
+        };
+        let Some(expectation) = expectation else {
+            return Err(Error::Other(anyhow!(
+                "Unexpected call to {method}, no more calls were expected"
+            )));
+        };
+        if !expectation.delay.is_zero() {
+            tokio::time::sleep(expectation.delay).await;
+        }
+        match expectation.response.downcast::<Result<R>>() {
+            Ok(response) => *response,
+            Err(_) => Err(Error::Other(anyhow!(
+                "Response expected for {method} isn't a {}",
+                std::any::type_name::<Result<R>>()
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl Client for MockClient {
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        self.answer("place_order", &req).await
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        self.answer("place_orders", &reqs).await
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        self.answer("validate_order", req).await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.answer("get_orders", filter).await
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.answer("get_open_orders", filter).await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        self.answer("get_order", order_id).await
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.answer("get_orders_by_ids", &order_ids).await
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        self.answer("get_order_by_client_id", &client_order_id)
+            .await
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        self.answer("get_trades", filter).await
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.answer("get_order_fills", order_id).await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        self.answer("get_account_activities", range).await
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        self.answer("get_account", &()).await
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        self.answer("get_equity", &()).await
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.answer("get_asset", crypto_pair).await
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        self.answer("list_asset_pairs", &()).await
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.answer("get_position", &asset_symbol).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.answer("cancel_orders", &order_ids).await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.answer("cancel_all_orders", &()).await
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        self.answer("close_all_positions", &()).await
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        self.answer("subscribe_order_updates", &()).await
+    }
+}
+
+#[async_trait]
+impl Market for MockClient {
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        self.answer("get_latest_minute_bar", crypto_pair).await
+    }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        self.answer("get_latest_minute_bars", &crypto_pairs).await
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        self.answer("get_timeframe_bars", &(crypto_pair, timeframes))
+            .await
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        self.answer("get_bars", &(crypto_pair, start, end, timeframe))
+            .await
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        self.answer("get_calendar", &()).await
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        self.answer("get_clock", &()).await
+    }
+}
+
+impl Environment for MockClient {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Amount;
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn answer_expected_calls_in_order() -> Result<()> {
+        let mock = MockClient::new();
+        mock.expect("get_equity", Ok(BigDecimal::from(100)))
+            .expect("place_order", Ok(OrderId::new("order")))
+            .expect_delayed::<Vec<OrderId>>(
+                "cancel_all_orders",
+                Err(Error::Provider("Service unavailable".into())),
+                Duration::from_millis(10),
+            );
+
+        assert_eq!(mock.get_equity().await?, BigDecimal::from(100));
+        let req = OrderRequest::market_buy(
+            Instrument::from_str("BTC/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+        );
+        assert_eq!(mock.place_order(req.clone()).await?.as_str(), "order");
+        assert!(mock.verify().is_err());
+        let err = mock.cancel_all_orders().await.unwrap_err();
+        assert_eq!(err.to_string(), "Service unavailable");
+
+        mock.verify()?;
+        assert_eq!(
+            mock.get_calls()[1],
+            MockCall {
+                method: "place_order",
+                arguments: format!("{req:?}"),
+            }
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fail_unexpected_calls() -> Result<()> {
+        let mock = MockClient::new();
+        mock.expect("get_equity", Ok("100"));
+
+        let err = mock.get_account().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected call to get_account, expected a call to get_equity"
+        );
+        assert!(mock.get_equity().await.is_err());
+        let err = mock.get_equity().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected call to get_equity, no more calls were expected"
+        );
+        Ok(())
+    }
+}
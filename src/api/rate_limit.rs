@@ -0,0 +1,350 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Token bucket limiting how often requests are sent. Clones share the same bucket, so a limiter
+/// given to several components keeps all of their requests together within the limit.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    capacity: f64,
+    tokens_per_second: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Allows bursts of up to `requests` requests and `requests` requests every `period` overall.
+    pub fn new(requests: u32, period: Duration) -> Result<Self> {
+        match NonZeroU32::new(requests) {
+            Some(requests) if !period.is_zero() => Ok(Self::with_rate(requests, period)),
+            _ => Err(Error::InvalidArgument(
+                "Rate limits must allow requests over a positive period".into(),
+            )),
+        }
+    }
+
+    pub fn per_minute(requests: NonZeroU32) -> Self {
+        Self::with_rate(requests, Duration::from_secs(60))
+    }
+
+    fn with_rate(requests: NonZeroU32, period: Duration) -> Self {
+        let capacity = f64::from(requests.get());
+        Self {
+            capacity,
+            tokens_per_second: capacity / period.as_secs_f64(),
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            })),
+        }
+    }
+
+    /// Waits until a request can be sent without exceeding the limit.
+    pub async fn acquire(&self) {
+        let wait = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes a token, returning how long to wait for it if the bucket is empty. Tokens of
+    /// waiting requests are taken in advance, so that requests go out in turn.
+    fn reserve(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens =
+            (bucket.tokens + elapsed.as_secs_f64() * self.tokens_per_second).min(self.capacity);
+        bucket.refilled_at = bucket.refilled_at.max(now);
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(-bucket.tokens / self.tokens_per_second)
+    }
+}
+
+/// Wraps a [Client], [Market] or [Environment] and waits before each call until it is within
+/// the limit of its category, so that a misbehaving strategy can't exceed the rate limits of the
+/// provider.
+///
+/// Calls changing orders or positions are limited by the orders limiter, with one token per order
+/// placed, and every other call by the queries limiter. Giving both the same limiter keeps all calls
+/// within one limit.
+pub struct RateLimitedClient<T> {
+    inner: T,
+    orders: RateLimiter,
+    queries: RateLimiter,
+}
+
+impl<T> RateLimitedClient<T> {
+    pub fn new(inner: T, orders: RateLimiter, queries: RateLimiter) -> Self {
+        Self {
+            inner,
+            orders,
+            queries,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[async_trait]
+impl<T> Client for RateLimitedClient<T>
+where
+    T: Client + Send + Sync,
+{
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        self.orders.acquire().await;
+        self.inner.place_order(req).await
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        for _ in &reqs {
+            self.orders.acquire().await;
+        }
+        self.inner.place_orders(reqs).await
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        self.queries.acquire().await;
+        self.inner.validate_order(req).await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.queries.acquire().await;
+        self.inner.get_orders(filter).await
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.queries.acquire().await;
+        self.inner.get_open_orders(filter).await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        self.queries.acquire().await;
+        self.inner.get_order(order_id).await
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.queries.acquire().await;
+        self.inner.get_orders_by_ids(order_ids).await
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        self.queries.acquire().await;
+        self.inner.get_order_by_client_id(client_order_id).await
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        self.queries.acquire().await;
+        self.inner.get_trades(filter).await
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.queries.acquire().await;
+        self.inner.get_order_fills(order_id).await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        self.queries.acquire().await;
+        self.inner.get_account_activities(range).await
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        self.queries.acquire().await;
+        self.inner.get_account().await
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        self.queries.acquire().await;
+        self.inner.get_equity().await
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.queries.acquire().await;
+        self.inner.get_asset(crypto_pair).await
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        self.queries.acquire().await;
+        self.inner.list_asset_pairs().await
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.queries.acquire().await;
+        self.inner.get_position(asset_symbol).await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        self.orders.acquire().await;
+        self.inner.cancel_order(order_id).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.orders.acquire().await;
+        self.inner.cancel_orders(order_ids).await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.orders.acquire().await;
+        self.inner.cancel_all_orders().await
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        self.orders.acquire().await;
+        self.inner.close_all_positions().await
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        self.queries.acquire().await;
+        self.inner.subscribe_order_updates().await
+    }
+}
+
+#[async_trait]
+impl<T> Market for RateLimitedClient<T>
+where
+    T: Market + Send + Sync,
+{
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        self.queries.acquire().await;
+        self.inner.get_latest_minute_bar(crypto_pair).await
+    }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        self.queries.acquire().await;
+        self.inner.get_latest_minute_bars(crypto_pairs).await
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        self.queries.acquire().await;
+        self.inner.get_timeframe_bars(crypto_pair, timeframes).await
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        self.queries.acquire().await;
+        self.inner
+            .get_bars(crypto_pair, start, end, timeframe)
+            .await
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        self.queries.acquire().await;
+        self.inner.get_calendar().await
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        self.queries.acquire().await;
+        self.inner.get_clock().await
+    }
+}
+
+impl<T> Environment for RateLimitedClient<T> where T: Environment + Send + Sync {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Amount;
+    use crate::simulated::{SimulatedBrokerBuilder, SimulatedClient};
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn reserve_tokens() -> Result<()> {
+        let limiter = RateLimiter::new(2, Duration::from_secs(1))?;
+        let now = limiter.bucket.lock().unwrap().refilled_at;
+
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(limiter.reserve(now), Duration::ZERO);
+        assert_eq!(limiter.reserve(now), Duration::from_millis(500));
+        assert_eq!(limiter.clone().reserve(now), Duration::from_secs(1));
+        // Two tokens were refilled, both taken by the waiting requests
+        assert_eq!(
+            limiter.reserve(now + Duration::from_secs(1)),
+            Duration::from_millis(500)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_rate_limits() {
+        let err = RateLimiter::new(0, Duration::from_secs(1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Rate limits must allow requests over a positive period"
+        );
+        assert!(RateLimiter::new(1, Duration::ZERO).is_err());
+    }
+
+    #[tokio::test]
+    async fn limit_orders_apart_from_queries() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let inner = SimulatedClient::new(broker);
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        inner.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(10))?;
+        let client = RateLimitedClient::new(
+            inner,
+            RateLimiter::new(1, Duration::from_millis(100))?,
+            RateLimiter::new(100, Duration::from_secs(1))?,
+        );
+        let req = OrderRequest::market_buy(
+            crypto_pair,
+            Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+        );
+
+        let start = Instant::now();
+        client.place_order(req.clone()).await?;
+        for _ in 0..10 {
+            client.get_orders(&OrderFilter::default()).await?;
+        }
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        client.place_orders(vec![req.clone(), req]).await?;
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert_eq!(client.get_orders(&OrderFilter::default()).await?.len(), 3);
+        Ok(())
+    }
+}
@@ -1,50 +1,509 @@
 // Copyright (C) 2025 Agostinho Junior
 // SPDX-License-Identifier: GPL-3.0-or-later
 
+use std::collections::HashMap;
 use bigdecimal::BigDecimal;
-use crate::api::common::{Amount, CryptoPair, OrderSide};
+use chrono::{DateTime, Utc};
+use crate::api::common::{Amount, Instrument, Order, OrderSide, OrderStatus, TimeInForce, Trade};
+use crate::api::validation::Violation;
+use crate::{Error, Result};
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderRequest {
-    pub crypto_pair: CryptoPair,
+    pub crypto_pair: Instrument,
     pub amount: Amount,
     pub limit_price: Option<BigDecimal>,
+    /// Price at which the order becomes active, not supported by the simulated broker.
+    pub stop_price: Option<BigDecimal>,
     pub side: OrderSide,
+    pub time_in_force: TimeInForce,
+    pub display_quantity: Option<BigDecimal>,
+    /// Run the order belongs to, set by the runner unless the request already has one.
+    pub run_id: Option<String>,
+    /// Strategy the order belongs to, set by the runner unless the request already has one.
+    pub strategy_id: Option<String>,
+    /// Label of the order, e.g. "entry" or "hedge", kept on the placed [Order].
+    pub tag: Option<String>,
+    /// Arbitrary client data kept on the placed [Order].
+    pub metadata: HashMap<String, String>,
+    /// Id chosen by the caller, making retries of the request idempotent.
+    pub client_order_id: Option<String>,
 }
 
 impl OrderRequest {
-    pub fn market_buy(crypto_pair: CryptoPair, amount: Amount) -> Self {
+    pub fn market_buy(crypto_pair: Instrument, amount: Amount) -> Self {
         OrderRequest {
             crypto_pair,
             amount,
             limit_price: None,
+            stop_price: None,
             side: OrderSide::Buy,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         }
     }
 
-    pub fn market_sell(crypto_pair: CryptoPair, amount: Amount) -> Self {
+    pub fn market_sell(crypto_pair: Instrument, amount: Amount) -> Self {
         OrderRequest {
             crypto_pair,
             amount,
             limit_price: None,
+            stop_price: None,
             side: OrderSide::Sell,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         }
     }
 
-    pub fn limit_buy(crypto_pair: CryptoPair, amount: Amount, limit_price: BigDecimal) -> Self {
+    pub fn limit_buy(crypto_pair: Instrument, amount: Amount, limit_price: BigDecimal) -> Self {
         OrderRequest {
             crypto_pair,
             amount,
             limit_price: Some(limit_price),
+            stop_price: None,
             side: OrderSide::Buy,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         }
     }
 
-    pub fn limit_sell(crypto_pair: CryptoPair, amount: Amount, limit_price: BigDecimal) -> Self {
+    pub fn limit_sell(crypto_pair: Instrument, amount: Amount, limit_price: BigDecimal) -> Self {
         OrderRequest {
             crypto_pair,
             amount,
             limit_price: Some(limit_price),
+            stop_price: None,
             side: OrderSide::Sell,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         }
     }
+
+    /// Limit buy of which only `display_quantity` is working at a time, the next slice being
+    /// displayed once the previous one is filled.
+    pub fn iceberg_limit_buy(
+        crypto_pair: Instrument,
+        quantity: BigDecimal,
+        limit_price: BigDecimal,
+        display_quantity: BigDecimal,
+    ) -> Self {
+        OrderRequest {
+            display_quantity: Some(display_quantity),
+            ..Self::limit_buy(crypto_pair, Amount::Quantity { quantity }, limit_price)
+        }
+    }
+
+    /// Limit sell of which only `display_quantity` is working at a time, the next slice being
+    /// displayed once the previous one is filled.
+    pub fn iceberg_limit_sell(
+        crypto_pair: Instrument,
+        quantity: BigDecimal,
+        limit_price: BigDecimal,
+        display_quantity: BigDecimal,
+    ) -> Self {
+        OrderRequest {
+            display_quantity: Some(display_quantity),
+            ..Self::limit_sell(crypto_pair, Amount::Quantity { quantity }, limit_price)
+        }
+    }
+
+    /// Checks the request on its own, without a broker, failing on the first of
+    /// [OrderRequest::get_violations].
+    pub fn validate(&self) -> Result<()> {
+        match self.get_violations().first() {
+            Some(violation) => Err(Error::InvalidArgument(violation.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Rules the request breaks on its own, whatever the broker: the amount and prices must be
+    /// positive, iceberg orders must be limit orders with a display quantity up to their
+    /// quantity, and the limit price of a stop-limit buy (sell) must not be below (above) its
+    /// stop price.
+    pub fn get_violations(&self) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let (Amount::Quantity { quantity: value } | Amount::Notional { notional: value }) =
+            &self.amount;
+        if *value <= 0 {
+            violations.push(Violation::NonPositiveAmount);
+        }
+        if self.limit_price.as_ref().is_some_and(|limit_price| *limit_price <= 0) {
+            violations.push(Violation::NonPositiveLimitPrice);
+        }
+        if self.stop_price.as_ref().is_some_and(|stop_price| *stop_price <= 0) {
+            violations.push(Violation::NonPositiveStopPrice);
+        }
+        if let Some(display_quantity) = &self.display_quantity {
+            match (&self.amount, &self.limit_price) {
+                (Amount::Quantity { quantity }, Some(_)) => {
+                    if *display_quantity <= 0 || display_quantity > quantity {
+                        violations.push(Violation::InvalidDisplayQuantity);
+                    }
+                }
+                _ => violations.push(Violation::IcebergWithoutLimitQuantity),
+            }
+        }
+        if let (Some(limit_price), Some(stop_price)) = (&self.limit_price, &self.stop_price) {
+            let crosses_stop_price = match self.side {
+                OrderSide::Buy => limit_price < stop_price,
+                OrderSide::Sell => limit_price > stop_price,
+            };
+            if crosses_stop_price {
+                violations.push(Violation::LimitPriceBeyondStopPrice(self.side.clone()));
+            }
+        }
+        violations
+    }
+}
+
+/// Builds an [OrderRequest] field by field, checking on [OrderRequestBuilder::build] that the
+/// side and amount are set and that the request is valid, see [OrderRequest::validate].
+#[derive(Debug, Clone)]
+pub struct OrderRequestBuilder {
+    crypto_pair: Instrument,
+    side: Option<OrderSide>,
+    amount: Option<Amount>,
+    limit_price: Option<BigDecimal>,
+    stop_price: Option<BigDecimal>,
+    time_in_force: TimeInForce,
+    display_quantity: Option<BigDecimal>,
+    client_order_id: Option<String>,
+    tag: Option<String>,
+    metadata: HashMap<String, String>,
+}
+
+impl OrderRequestBuilder {
+    pub fn new(crypto_pair: Instrument) -> Self {
+        Self {
+            crypto_pair,
+            side: None,
+            amount: None,
+            limit_price: None,
+            stop_price: None,
+            time_in_force: TimeInForce::GoodTillCancelled,
+            display_quantity: None,
+            client_order_id: None,
+            tag: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn set_side(&mut self, side: OrderSide) -> &mut Self {
+        self.side = Some(side);
+        self
+    }
+
+    pub fn set_amount(&mut self, amount: Amount) -> &mut Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn set_limit_price(&mut self, limit_price: BigDecimal) -> &mut Self {
+        self.limit_price = Some(limit_price);
+        self
+    }
+
+    pub fn set_stop_price(&mut self, stop_price: BigDecimal) -> &mut Self {
+        self.stop_price = Some(stop_price);
+        self
+    }
+
+    pub fn set_time_in_force(&mut self, time_in_force: TimeInForce) -> &mut Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn set_display_quantity(&mut self, display_quantity: BigDecimal) -> &mut Self {
+        self.display_quantity = Some(display_quantity);
+        self
+    }
+
+    pub fn set_client_order_id(&mut self, client_order_id: impl Into<String>) -> &mut Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    pub fn set_tag(&mut self, tag: impl Into<String>) -> &mut Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Adds an entry to the metadata, replacing the value of an existing key.
+    pub fn set_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(&self) -> Result<OrderRequest> {
+        let side = self
+            .side
+            .clone()
+            .ok_or(Error::InvalidArgument("Order side must be set".into()))?;
+        let amount = self
+            .amount
+            .clone()
+            .ok_or(Error::InvalidArgument("Order amount must be set".into()))?;
+        let order_req = OrderRequest {
+            crypto_pair: self.crypto_pair.clone(),
+            amount,
+            limit_price: self.limit_price.clone(),
+            stop_price: self.stop_price.clone(),
+            side,
+            time_in_force: self.time_in_force.clone(),
+            display_quantity: self.display_quantity.clone(),
+            run_id: None,
+            strategy_id: None,
+            tag: self.tag.clone(),
+            metadata: self.metadata.clone(),
+            client_order_id: self.client_order_id.clone(),
+        };
+        order_req.validate()?;
+        Ok(order_req)
+    }
+}
+
+/// Criteria of [Client::get_orders](crate::api::Client::get_orders), every `None` criterion
+/// matching any order. The default filter returns all orders.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderFilter {
+    pub status: Option<OrderStatus>,
+    pub side: Option<OrderSide>,
+    pub crypto_pair: Option<Instrument>,
+    pub tag: Option<String>,
+    /// Only matches orders having all of these metadata entries.
+    pub metadata: HashMap<String, String>,
+    /// Only matches orders created strictly after this time.
+    pub created_after: Option<DateTime<Utc>>,
+    /// Number of matching orders to skip, in the order they were placed.
+    pub offset: usize,
+    /// Maximum number of orders to return.
+    pub limit: Option<usize>,
+}
+
+impl OrderFilter {
+    pub fn matches(&self, order: &Order) -> bool {
+        self.status.as_ref().is_none_or(|status| *status == order.status)
+            && self.side.as_ref().is_none_or(|side| *side == order.side)
+            && self
+                .crypto_pair
+                .as_ref()
+                .is_none_or(|crypto_pair| crypto_pair.to_string() == order.asset_symbol)
+            && self.tag.as_ref().is_none_or(|tag| order.tag.as_ref() == Some(tag))
+            && self
+                .metadata
+                .iter()
+                .all(|(key, value)| order.metadata.get(key) == Some(value))
+            && self.created_after.as_ref().is_none_or(|created_after| {
+                order
+                    .created_at
+                    .is_some_and(|created_at| created_at > *created_after)
+            })
+    }
+
+    /// Filters orders given in the order they were placed, then applies the offset and limit.
+    pub fn apply<'a, I>(&self, orders: I) -> Vec<Order>
+    where
+        I: IntoIterator<Item = &'a Order>,
+    {
+        orders
+            .into_iter()
+            .filter(|order| self.matches(order))
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Position of a page of a listing, e.g. of
+/// [Client::get_orders_page](crate::api::Client::get_orders_page): the number of items before the
+/// page and the maximum number of items in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cursor {
+    offset: usize,
+    limit: usize,
+}
+
+impl Cursor {
+    /// Cursor of the first page, holding up to `limit` items.
+    pub fn first(limit: usize) -> Result<Self> {
+        if limit == 0 {
+            return Err(Error::InvalidArgument("Page limit must be positive".into()));
+        }
+        Ok(Self { offset: 0, limit })
+    }
+
+    /// Cursor of the page following this one.
+    pub fn next(&self) -> Self {
+        Self {
+            offset: self.offset.saturating_add(self.limit),
+            limit: self.limit,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+/// Time range of [Client::get_trades](crate::api::Client::get_trades), the default filter
+/// returning all trades.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TradeFilter {
+    /// Only matches trades at or after this time.
+    pub start: Option<DateTime<Utc>>,
+    /// Only matches trades strictly before this time.
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TradeFilter {
+    pub fn matches(&self, trade: &Trade) -> bool {
+        TimeRange {
+            start: self.start,
+            end: self.end,
+        }
+        .contains(trade.date_time)
+    }
+}
+
+/// Time range of [Client::get_account_activities](crate::api::Client::get_account_activities),
+/// the default range containing all times.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeRange {
+    /// Start of the range, inclusive.
+    pub start: Option<DateTime<Utc>>,
+    /// End of the range, exclusive.
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    /// Whether the range contains a time, an unknown time only being contained by an unbounded
+    /// range.
+    pub fn contains(&self, date_time: Option<DateTime<Utc>>) -> bool {
+        if self.start.is_none() && self.end.is_none() {
+            return true;
+        }
+        date_time.is_some_and(|date_time| {
+            self.start.is_none_or(|start| date_time >= start)
+                && self.end.is_none_or(|end| date_time < end)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn build_order_request() -> anyhow::Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let order_req = OrderRequestBuilder::new(crypto_pair.clone())
+            .set_side(OrderSide::Buy)
+            .set_amount(Amount::Quantity {
+                quantity: BigDecimal::from(2),
+            })
+            .set_limit_price(BigDecimal::from(100))
+            .set_time_in_force(TimeInForce::ImmediateOrCancel)
+            .set_client_order_id("entry-1")
+            .set_tag("entry")
+            .set_metadata("signal", "breakout")
+            .build()?;
+
+        assert_eq!(
+            order_req,
+            OrderRequest {
+                time_in_force: TimeInForce::ImmediateOrCancel,
+                client_order_id: Some("entry-1".into()),
+                tag: Some("entry".into()),
+                metadata: HashMap::from([("signal".into(), "breakout".into())]),
+                ..OrderRequest::limit_buy(
+                    crypto_pair,
+                    Amount::Quantity {
+                        quantity: BigDecimal::from(2)
+                    },
+                    BigDecimal::from(100)
+                )
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn build_invalid_order_request() -> anyhow::Result<()> {
+        let mut builder = OrderRequestBuilder::new(Instrument::from_str("BTC/USD")?);
+        assert_eq!(builder.build().unwrap_err().to_string(), "Order side must be set");
+        builder.set_side(OrderSide::Sell);
+        assert_eq!(builder.build().unwrap_err().to_string(), "Order amount must be set");
+        builder.set_amount(Amount::Notional {
+            notional: BigDecimal::from(0),
+        });
+        assert_eq!(builder.build().unwrap_err().to_string(), "Amount must be positive");
+        builder.set_amount(Amount::Notional {
+            notional: BigDecimal::from(10),
+        });
+        builder.set_stop_price(BigDecimal::from(-1));
+        assert_eq!(builder.build().unwrap_err().to_string(), "Stop price must be positive");
+        builder.set_stop_price(BigDecimal::from(1));
+        assert!(builder.build().is_ok());
+        builder.set_limit_price(BigDecimal::from(2));
+        assert_eq!(
+            builder.build().unwrap_err().to_string(),
+            "Limit price of a stop-limit sell must not be above its stop price"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate_iceberg_order_request() -> anyhow::Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let mut order_req = OrderRequest::iceberg_limit_buy(
+            crypto_pair.clone(),
+            BigDecimal::from(10),
+            BigDecimal::from(100),
+            BigDecimal::from(2),
+        );
+        order_req.validate()?;
+
+        order_req.display_quantity = Some(BigDecimal::from(11));
+        assert_eq!(
+            order_req.validate().unwrap_err().to_string(),
+            "Display quantity must be positive and up to the order quantity"
+        );
+        order_req.limit_price = None;
+        assert_eq!(
+            order_req.validate().unwrap_err().to_string(),
+            "Iceberg orders must be limit orders with a quantity amount"
+        );
+        Ok(())
+    }
 }
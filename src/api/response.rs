@@ -0,0 +1,51 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::request::Cursor;
+
+/// Items of a listing at a [Cursor], with the cursor of the next page when there are more items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Cursor of the next page, `None` on the last page.
+    pub next: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Page at `cursor` from the items of the listing starting at the cursor's offset. Items past
+    /// the cursor's limit are dropped and only tell that there is a next page, so listings can
+    /// be fetched with one more item than the limit.
+    pub fn new(mut items: Vec<T>, cursor: &Cursor) -> Self {
+        let next = if items.len() > cursor.limit() {
+            items.truncate(cursor.limit());
+            Some(cursor.next())
+        } else {
+            None
+        };
+        Self { items, next }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn page_through_items() -> Result<()> {
+        let items: Vec<u32> = (0..5).collect();
+        let mut cursor = Some(Cursor::first(2)?);
+        let mut pages = Vec::new();
+        while let Some(page_cursor) = cursor {
+            let page = Page::new(items[page_cursor.offset()..].to_vec(), &page_cursor);
+            cursor = page.next;
+            pages.push(page.items);
+        }
+
+        assert_eq!(pages, vec![vec![0, 1], vec![2, 3], vec![4]]);
+        assert!(Cursor::first(0).is_err());
+        assert_eq!(Cursor::first(usize::MAX)?.next().offset(), usize::MAX);
+        Ok(())
+    }
+}
@@ -0,0 +1,615 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How many times and how long apart a failed request is attempted again. The backoff grows
+/// exponentially from the initial backoff up to the max backoff.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: true,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Attempts every request once.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn get_max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Longest wait a provider may ask for before retrying, e.g. with a `Retry-After` header.
+    pub fn get_max_retry_after(&self) -> Duration {
+        self.max_retry_after
+    }
+
+    /// Time to wait after the failed attempt number `attempt`, counting from one. With jitter
+    /// the backoff is picked at random between half and all of it, so that clients failing
+    /// together don't retry together.
+    pub fn get_backoff(&self, attempt: u32) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let backoff = self
+            .initial_backoff
+            .mul_f64(self.multiplier.powi(exponent).min(u32::MAX.into()))
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        backoff.mul_f64(rand::rng().random_range(0.5..=1.0))
+    }
+}
+
+#[derive(Debug)]
+pub struct RetryPolicyBuilder {
+    policy: RetryPolicy,
+}
+
+impl Default for RetryPolicyBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicyBuilder {
+    pub fn new() -> Self {
+        Self {
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Attempts including the first one, 3 by default.
+    pub fn set_max_attempts(&mut self, max_attempts: u32) -> &mut Self {
+        self.policy.max_attempts = max_attempts;
+        self
+    }
+
+    /// 200 milliseconds by default.
+    pub fn set_initial_backoff(&mut self, initial_backoff: Duration) -> &mut Self {
+        self.policy.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// 5 seconds by default.
+    pub fn set_max_backoff(&mut self, max_backoff: Duration) -> &mut Self {
+        self.policy.max_backoff = max_backoff;
+        self
+    }
+
+    /// Factor the backoff grows by after every attempt, 2 by default.
+    pub fn set_multiplier(&mut self, multiplier: f64) -> &mut Self {
+        self.policy.multiplier = multiplier;
+        self
+    }
+
+    /// Enabled by default.
+    pub fn set_jitter(&mut self, jitter: bool) -> &mut Self {
+        self.policy.jitter = jitter;
+        self
+    }
+
+    /// Gives up on requests the provider asks to retry later than `max_retry_after`, 60 seconds
+    /// by default.
+    pub fn set_max_retry_after(&mut self, max_retry_after: Duration) -> &mut Self {
+        self.policy.max_retry_after = max_retry_after;
+        self
+    }
+
+    pub fn build(&self) -> Result<RetryPolicy> {
+        if self.policy.max_attempts == 0 {
+            return Err(Error::InvalidArgument(
+                "Max attempts must be positive".into(),
+            ));
+        }
+        if self.policy.initial_backoff > self.policy.max_backoff {
+            return Err(Error::InvalidArgument(
+                "Initial backoff must not exceed the max backoff".into(),
+            ));
+        }
+        if self.policy.multiplier.is_nan() || self.policy.multiplier < 1.0 {
+            return Err(Error::InvalidArgument(
+                "Multiplier must be at least 1".into(),
+            ));
+        }
+        Ok(self.policy.clone())
+    }
+}
+
+/// Wraps a [Client], [Market] or [Environment] and attempts its calls again as set by a
+/// [RetryPolicy] when they fail with an [Error::ProviderUnavailable], e.g. a timeout or a 5xx
+/// status. Other errors, unexpected responses of the provider included, are returned right away.
+///
+/// Only calls that are safe to repeat are retried: queries, and orders placed with a client order
+/// id, which are looked up before placing them again in case the failed attempt placed them.
+/// Cancellations and orders without a client order id are attempted once.
+pub struct RetryClient<T> {
+    inner: T,
+    policy: RetryPolicy,
+}
+
+impl<T> RetryClient<T> {
+    pub fn new(inner: T, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    async fn retry<R, F, Fut>(&self, call: F) -> Result<R>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Err(Error::ProviderUnavailable(_)) if attempt < self.policy.get_max_attempts() => {
+                    tokio::time::sleep(self.policy.get_backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<T> RetryClient<T>
+where
+    T: Client + Send + Sync,
+{
+    /// Ids of the orders of the requests already placed, none when any of them wasn't.
+    async fn find_placed_orders(&self, reqs: &[OrderRequest]) -> Result<Option<Vec<OrderId>>> {
+        let mut order_ids = Vec::new();
+        for req in reqs {
+            let Some(client_order_id) = &req.client_order_id else {
+                return Ok(None);
+            };
+            match self.inner.get_order_by_client_id(client_order_id).await {
+                Ok(order) => order_ids.push(order.order_id),
+                Err(Error::UnknownClientOrderId(_)) => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(Some(order_ids))
+    }
+}
+
+#[async_trait]
+impl<T> Client for RetryClient<T>
+where
+    T: Client + Send + Sync,
+{
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        if req.client_order_id.is_none() {
+            return self.inner.place_order(req).await;
+        }
+        let req = &req;
+        self.retry(|| async move {
+            match self.find_placed_orders(std::slice::from_ref(req)).await? {
+                Some(mut order_ids) => Ok(order_ids.remove(0)),
+                None => self.inner.place_order(req.clone()).await,
+            }
+        })
+        .await
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        if reqs.iter().any(|req| req.client_order_id.is_none()) {
+            return self.inner.place_orders(reqs).await;
+        }
+        // Batches are placed as a whole, so either all or none of their orders were placed
+        let reqs = &reqs;
+        self.retry(|| async move {
+            if let Some(order_ids) = self.find_placed_orders(reqs).await? {
+                return Ok(order_ids);
+            }
+            self.inner.place_orders(reqs.clone()).await
+        })
+        .await
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        self.retry(|| self.inner.validate_order(req)).await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.retry(|| self.inner.get_orders(filter)).await
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.retry(|| self.inner.get_open_orders(filter)).await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        self.retry(|| self.inner.get_order(order_id)).await
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.retry(|| self.inner.get_orders_by_ids(order_ids)).await
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        self.retry(|| self.inner.get_order_by_client_id(client_order_id))
+            .await
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        self.retry(|| self.inner.get_trades(filter)).await
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.retry(|| self.inner.get_order_fills(order_id)).await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        self.retry(|| self.inner.get_account_activities(range))
+            .await
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        self.retry(|| self.inner.get_account()).await
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        self.retry(|| self.inner.get_equity()).await
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.retry(|| self.inner.get_asset(crypto_pair)).await
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        self.retry(|| self.inner.list_asset_pairs()).await
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.retry(|| self.inner.get_position(asset_symbol)).await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        self.inner.cancel_order(order_id).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.inner.cancel_orders(order_ids).await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.inner.cancel_all_orders().await
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        self.inner.close_all_positions().await
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        self.retry(|| self.inner.subscribe_order_updates()).await
+    }
+}
+
+#[async_trait]
+impl<T> Market for RetryClient<T>
+where
+    T: Market + Send + Sync,
+{
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        self.retry(|| self.inner.get_latest_minute_bar(crypto_pair))
+            .await
+    }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        self.retry(|| self.inner.get_latest_minute_bars(crypto_pairs))
+            .await
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        self.retry(|| self.inner.get_timeframe_bars(crypto_pair, timeframes))
+            .await
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        self.retry(|| self.inner.get_bars(crypto_pair, start, end, timeframe))
+            .await
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        self.retry(|| self.inner.get_calendar()).await
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        self.retry(|| self.inner.get_clock()).await
+    }
+}
+
+impl<T> Environment for RetryClient<T> where T: Environment + Send + Sync {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Amount;
+    use crate::simulated::{SimulatedBrokerBuilder, SimulatedClient};
+    use anyhow::Result;
+    use std::str::FromStr;
+    use std::sync::Mutex;
+
+    /// Fails the next calls to get orders, and places orders but fails to return their ids, as
+    /// when the response of a provider is lost.
+    struct FlakyClient {
+        inner: SimulatedClient,
+        failures: Mutex<u32>,
+        retryable: bool,
+    }
+
+    impl FlakyClient {
+        fn fail(&self) -> crate::Result<()> {
+            let mut failures = self.failures.lock().unwrap();
+            if *failures == 0 {
+                return Ok(());
+            }
+            *failures -= 1;
+            if self.retryable {
+                Err(Error::ProviderUnavailable("Request timed out".into()))
+            } else {
+                Err(Error::Provider("Invalid response".into()))
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Client for FlakyClient {
+        async fn place_order(&self, req: OrderRequest) -> crate::Result<OrderId> {
+            let order_id = self.inner.place_order(req).await?;
+            self.fail()?;
+            Ok(order_id)
+        }
+
+        async fn place_orders(&self, reqs: Vec<OrderRequest>) -> crate::Result<Vec<OrderId>> {
+            self.inner.place_orders(reqs).await
+        }
+
+        async fn validate_order(&self, req: &OrderRequest) -> crate::Result<OrderValidation> {
+            self.inner.validate_order(req).await
+        }
+
+        async fn get_orders(&self, filter: &OrderFilter) -> crate::Result<Vec<Order>> {
+            self.fail()?;
+            self.inner.get_orders(filter).await
+        }
+
+        async fn get_open_orders(&self, filter: &OrderFilter) -> crate::Result<Vec<Order>> {
+            self.inner.get_open_orders(filter).await
+        }
+
+        async fn get_order(&self, order_id: &OrderId) -> crate::Result<Order> {
+            self.inner.get_order(order_id).await
+        }
+
+        async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> crate::Result<Vec<Order>> {
+            self.inner.get_orders_by_ids(order_ids).await
+        }
+
+        async fn get_order_by_client_id(&self, client_order_id: &str) -> crate::Result<Order> {
+            self.inner.get_order_by_client_id(client_order_id).await
+        }
+
+        async fn get_trades(&self, filter: &TradeFilter) -> crate::Result<Vec<Trade>> {
+            self.inner.get_trades(filter).await
+        }
+
+        async fn get_order_fills(&self, order_id: &OrderId) -> crate::Result<Vec<Trade>> {
+            self.inner.get_order_fills(order_id).await
+        }
+
+        async fn get_account_activities(
+            &self,
+            range: &TimeRange,
+        ) -> crate::Result<Vec<AccountActivity>> {
+            self.inner.get_account_activities(range).await
+        }
+
+        async fn get_account(&self) -> crate::Result<Account> {
+            self.inner.get_account().await
+        }
+
+        async fn get_equity(&self) -> crate::Result<BigDecimal> {
+            self.inner.get_equity().await
+        }
+
+        async fn get_asset(&self, crypto_pair: &Instrument) -> crate::Result<Asset> {
+            self.inner.get_asset(crypto_pair).await
+        }
+
+        async fn list_asset_pairs(&self) -> crate::Result<Vec<Instrument>> {
+            self.inner.list_asset_pairs().await
+        }
+
+        async fn get_position(&self, asset_symbol: &str) -> crate::Result<Option<OpenPosition>> {
+            self.inner.get_position(asset_symbol).await
+        }
+
+        async fn cancel_orders(&self, order_ids: &[OrderId]) -> crate::Result<()> {
+            self.inner.cancel_orders(order_ids).await
+        }
+
+        async fn cancel_all_orders(&self) -> crate::Result<Vec<OrderId>> {
+            self.inner.cancel_all_orders().await
+        }
+
+        async fn close_all_positions(&self) -> crate::Result<FlattenSummary> {
+            self.inner.close_all_positions().await
+        }
+
+        async fn subscribe_order_updates(&self) -> crate::Result<OrderUpdateStream> {
+            self.inner.subscribe_order_updates().await
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_queries() -> Result<()> {
+        let client = create_retry_client(2, 3)?;
+
+        assert!(client.get_orders(&OrderFilter::default()).await?.is_empty());
+
+        let client = create_retry_client(3, 3)?;
+        let err = client
+            .get_orders(&OrderFilter::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Request timed out");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn attempt_once_on_unretryable_errors() -> Result<()> {
+        let mut client = create_retry_client(1, 3)?;
+        client.inner.retryable = false;
+
+        let err = client
+            .get_orders(&OrderFilter::default())
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "Invalid response");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn retry_orders_with_client_order_id_once_placed() -> Result<()> {
+        let client = create_retry_client(1, 3)?;
+        let mut req = OrderRequest::market_buy(
+            Instrument::from_str("BTC/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+        );
+
+        let err = client.place_order(req.clone()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Request timed out");
+
+        *client.inner.failures.lock().unwrap() = 1;
+        req.client_order_id = Some("retried".into());
+        let order_id = client.place_order(req).await?;
+
+        let orders = client
+            .inner
+            .inner
+            .get_orders(&OrderFilter::default())
+            .await?;
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[1].order_id, order_id);
+        Ok(())
+    }
+
+    fn create_retry_client(failures: u32, max_attempts: u32) -> Result<RetryClient<FlakyClient>> {
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let inner = SimulatedClient::new(broker);
+        inner.set_notional_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(10))?;
+        let policy = RetryPolicyBuilder::new()
+            .set_max_attempts(max_attempts)
+            .set_initial_backoff(Duration::from_millis(1))
+            .build()?;
+        Ok(RetryClient::new(
+            FlakyClient {
+                inner,
+                failures: Mutex::new(failures),
+                retryable: true,
+            },
+            policy,
+        ))
+    }
+
+    #[test]
+    fn exponential_backoff() -> Result<()> {
+        let policy = RetryPolicyBuilder::new()
+            .set_max_attempts(10)
+            .set_initial_backoff(Duration::from_millis(100))
+            .set_max_backoff(Duration::from_secs(1))
+            .set_jitter(false)
+            .build()?;
+
+        assert_eq!(policy.get_backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.get_backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.get_backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.get_backoff(u32::MAX), Duration::from_secs(1));
+        Ok(())
+    }
+
+    #[test]
+    fn backoff_with_jitter() {
+        let policy = RetryPolicy::default();
+        for _ in 0..100 {
+            let backoff = policy.get_backoff(2);
+            assert!(backoff >= Duration::from_millis(200) && backoff <= Duration::from_millis(400));
+        }
+    }
+
+    #[test]
+    fn build_invalid_policies() {
+        let err = RetryPolicyBuilder::new()
+            .set_max_attempts(0)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Max attempts must be positive");
+
+        let err = RetryPolicyBuilder::new()
+            .set_multiplier(0.5)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Multiplier must be at least 1");
+    }
+}
@@ -0,0 +1,662 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{
+    Account, AccountActivity, Asset, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderSide, OrderUpdateStream, OrderValidation, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::{Client, Environment};
+use crate::{Error, Result};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::task::AbortHandle;
+
+/// How a [RoutedClient] picks the venue of an order among the venues with a price of its pair.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoutingPolicy {
+    /// Venue with the best price after fees, the lowest for buys and the highest for sells.
+    #[default]
+    BestPrice,
+    /// Venue with the lowest fee, whatever its price.
+    LowestFee,
+}
+
+/// Trading venue of a [RoutedClient], whose market data prices the orders routed to it.
+#[derive(Clone)]
+struct Venue {
+    name: String,
+    environment: Arc<dyn Environment + Send + Sync>,
+    fee_multiplier: BigDecimal,
+}
+
+#[derive(Default)]
+pub struct RoutedClientBuilder {
+    venues: Vec<Venue>,
+    routing_policy: RoutingPolicy,
+}
+
+impl RoutedClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a venue charging `fee_percentage` of every order, e.g. `0.25` for 0.25%. Among venues
+    /// routed to equally, the first added one is picked.
+    pub fn add_venue<T>(
+        &mut self,
+        name: &str,
+        environment: T,
+        fee_percentage: BigDecimal,
+    ) -> Result<&mut Self>
+    where
+        T: Environment + Send + Sync + 'static,
+    {
+        if !(BigDecimal::from(0)..=BigDecimal::from(100)).contains(&fee_percentage) {
+            return Err(Error::InvalidArgument(
+                "Fee percentage must be between 0 and 100".into(),
+            ));
+        }
+        if self.venues.iter().any(|venue| venue.name == name) {
+            return Err(Error::InvalidArgument(format!(
+                "Venue {name} was already added"
+            )));
+        }
+        self.venues.push(Venue {
+            name: name.into(),
+            environment: Arc::new(environment),
+            fee_multiplier: fee_percentage / BigDecimal::from(100),
+        });
+        Ok(self)
+    }
+
+    /// Replaces the default [RoutingPolicy::BestPrice].
+    pub fn set_routing_policy(&mut self, routing_policy: RoutingPolicy) -> &mut Self {
+        self.routing_policy = routing_policy;
+        self
+    }
+
+    pub fn build(&self) -> Result<RoutedClient> {
+        if self.venues.is_empty() {
+            return Err(Error::InvalidArgument(
+                "Routed clients need at least one venue".into(),
+            ));
+        }
+        Ok(RoutedClient {
+            venues: self.venues.clone(),
+            routing_policy: self.routing_policy,
+            order_venues: RwLock::new(HashMap::new()),
+        })
+    }
+}
+
+/// [Client] trading on several venues as one account. Each order is routed to a venue as set by
+/// the [RoutingPolicy], among the venues whose market has a latest minute bar of its pair, while
+/// the orders, trades, positions and balances of every venue are merged.
+///
+/// Orders placed on the venues other than through the client are found by asking every venue.
+/// Accounts are merged only when every venue has the same currency.
+pub struct RoutedClient {
+    venues: Vec<Venue>,
+    routing_policy: RoutingPolicy,
+    /// Index of the venue of every order placed or looked up through the client.
+    order_venues: RwLock<HashMap<OrderId, usize>>,
+}
+
+impl RoutedClient {
+    /// Name of the venue an order was placed on.
+    pub async fn get_order_venue(&self, order_id: &OrderId) -> Result<&str> {
+        let venue_index = self.find_venue(order_id).await?;
+        Ok(&self.venues[venue_index].name)
+    }
+
+    /// Index of the venue `req` is routed to.
+    async fn route(&self, req: &OrderRequest) -> Result<usize> {
+        let mut best: Option<(usize, BigDecimal)> = None;
+        for (venue_index, venue) in self.venues.iter().enumerate() {
+            let Some(bar) = venue
+                .environment
+                .get_latest_minute_bar(&req.crypto_pair)
+                .await?
+            else {
+                continue;
+            };
+            // Lower scores are better
+            let score = match (self.routing_policy, &req.side) {
+                (RoutingPolicy::BestPrice, OrderSide::Buy) => {
+                    bar.close * (BigDecimal::from(1) + &venue.fee_multiplier)
+                }
+                (RoutingPolicy::BestPrice, OrderSide::Sell) => {
+                    -bar.close * (BigDecimal::from(1) - &venue.fee_multiplier)
+                }
+                (RoutingPolicy::LowestFee, _) => venue.fee_multiplier.clone(),
+            };
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_score)| score < *best_score)
+            {
+                best = Some((venue_index, score));
+            }
+        }
+        best.map(|(venue_index, _)| venue_index)
+            .ok_or_else(|| Error::InvalidAssetPair(req.crypto_pair.clone()))
+    }
+
+    /// Index of the venue of an order, asking every venue about orders placed elsewhere.
+    async fn find_venue(&self, order_id: &OrderId) -> Result<usize> {
+        if let Some(venue_index) = self.order_venues.read().unwrap().get(order_id) {
+            return Ok(*venue_index);
+        }
+        for (venue_index, venue) in self.venues.iter().enumerate() {
+            match venue.environment.get_order(order_id).await {
+                Ok(_) => {
+                    self.record_orders(venue_index, [order_id.clone()]);
+                    return Ok(venue_index);
+                }
+                Err(Error::UnknownOrder(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(Error::UnknownOrder(order_id.clone()))
+    }
+
+    fn record_orders(&self, venue_index: usize, order_ids: impl IntoIterator<Item = OrderId>) {
+        let mut order_venues = self.order_venues.write().unwrap();
+        for order_id in order_ids {
+            order_venues.insert(order_id, venue_index);
+        }
+    }
+
+    /// Order ids grouped by the index of their venue, keeping their order within each venue.
+    async fn group_by_venue(&self, order_ids: &[OrderId]) -> Result<BTreeMap<usize, Vec<OrderId>>> {
+        let mut groups: BTreeMap<usize, Vec<OrderId>> = BTreeMap::new();
+        for order_id in order_ids {
+            let venue_index = self.find_venue(order_id).await?;
+            groups
+                .entry(venue_index)
+                .or_default()
+                .push(order_id.clone());
+        }
+        Ok(groups)
+    }
+
+    /// Orders of every venue matching the filter, merged in the order they were placed.
+    async fn merge_orders(&self, filter: &OrderFilter, open_only: bool) -> Result<Vec<Order>> {
+        // Each venue returns the orders up to the end of the requested page
+        let venue_filter = OrderFilter {
+            offset: 0,
+            limit: filter.limit.map(|limit| filter.offset + limit),
+            ..filter.clone()
+        };
+        let mut orders = Vec::new();
+        for (venue_index, venue) in self.venues.iter().enumerate() {
+            let venue_orders = if open_only {
+                venue.environment.get_open_orders(&venue_filter).await?
+            } else {
+                venue.environment.get_orders(&venue_filter).await?
+            };
+            self.record_orders(
+                venue_index,
+                venue_orders.iter().map(|order| order.order_id.clone()),
+            );
+            orders.extend(venue_orders);
+        }
+        orders.sort_by_key(|order| order.created_at);
+        let orders = orders.into_iter().skip(filter.offset);
+        Ok(match filter.limit {
+            Some(limit) => orders.take(limit).collect(),
+            None => orders.collect(),
+        })
+    }
+}
+
+/// Position of an asset held on two venues.
+fn merge_positions(a: OpenPosition, b: OpenPosition) -> OpenPosition {
+    let quantity = &a.quantity + &b.quantity;
+    let average_entry_price = match (&a.average_entry_price, &b.average_entry_price) {
+        (Some(a_price), Some(b_price)) if quantity != 0 => {
+            Some((a_price * &a.quantity + b_price * &b.quantity) / &quantity)
+        }
+        _ => None,
+    };
+    OpenPosition {
+        asset_symbol: a.asset_symbol,
+        average_entry_price,
+        quantity,
+        market_value: a.market_value.zip(b.market_value).map(|(a, b)| a + b),
+    }
+}
+
+#[async_trait]
+impl Client for RoutedClient {
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        let venue_index = self.route(&req).await?;
+        let order_id = self.venues[venue_index]
+            .environment
+            .place_order(req)
+            .await?;
+        self.record_orders(venue_index, [order_id.clone()]);
+        Ok(order_id)
+    }
+
+    /// Places the orders routed to each venue as one batch per venue, so that a batch spanning
+    /// several venues can be placed in part.
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        let mut batches: BTreeMap<usize, Vec<(usize, OrderRequest)>> = BTreeMap::new();
+        for (req_index, req) in reqs.into_iter().enumerate() {
+            let venue_index = self.route(&req).await?;
+            batches
+                .entry(venue_index)
+                .or_default()
+                .push((req_index, req));
+        }
+        let mut order_ids = Vec::new();
+        for (venue_index, batch) in batches {
+            let (req_indexes, reqs): (Vec<usize>, Vec<OrderRequest>) = batch.into_iter().unzip();
+            let batch_order_ids = self.venues[venue_index]
+                .environment
+                .place_orders(reqs)
+                .await?;
+            self.record_orders(venue_index, batch_order_ids.iter().cloned());
+            order_ids.extend(req_indexes.into_iter().zip(batch_order_ids));
+        }
+        order_ids.sort_by_key(|(req_index, _)| *req_index);
+        Ok(order_ids
+            .into_iter()
+            .map(|(_, order_id)| order_id)
+            .collect())
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        let venue_index = self.route(req).await?;
+        self.venues[venue_index]
+            .environment
+            .validate_order(req)
+            .await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.merge_orders(filter, false).await
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.merge_orders(filter, true).await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        let venue_index = self.find_venue(order_id).await?;
+        self.venues[venue_index]
+            .environment
+            .get_order(order_id)
+            .await
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        let mut orders = Vec::new();
+        for order_id in order_ids {
+            orders.push(self.get_order(order_id).await?);
+        }
+        Ok(orders)
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        for (venue_index, venue) in self.venues.iter().enumerate() {
+            match venue
+                .environment
+                .get_order_by_client_id(client_order_id)
+                .await
+            {
+                Ok(order) => {
+                    self.record_orders(venue_index, [order.order_id.clone()]);
+                    return Ok(order);
+                }
+                Err(Error::UnknownClientOrderId(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(Error::UnknownClientOrderId(client_order_id.into()))
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        let mut trades = Vec::new();
+        for venue in &self.venues {
+            trades.extend(venue.environment.get_trades(filter).await?);
+        }
+        trades.sort_by_key(|trade| trade.date_time);
+        Ok(trades)
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        let venue_index = self.find_venue(order_id).await?;
+        self.venues[venue_index]
+            .environment
+            .get_order_fills(order_id)
+            .await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        let mut activities = Vec::new();
+        for venue in &self.venues {
+            activities.extend(venue.environment.get_account_activities(range).await?);
+        }
+        activities.sort_by_key(|activity| activity.date_time);
+        Ok(activities)
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        let mut merged: Option<Account> = None;
+        for venue in &self.venues {
+            let account = venue.environment.get_account().await?;
+            let Some(merged) = &mut merged else {
+                merged = Some(account);
+                continue;
+            };
+            if account.currency != merged.currency {
+                return Err(Error::Other(anyhow!(
+                    "Venue {} has an account in {} rather than {}",
+                    venue.name,
+                    account.currency,
+                    merged.currency
+                )));
+            }
+            merged.cash += account.cash;
+            merged.buying_power += account.buying_power;
+            merged.equity += account.equity;
+            merged.initial_margin += account.initial_margin;
+            merged.maintenance_margin += account.maintenance_margin;
+            merged.fees_paid += account.fees_paid;
+            // The merged snapshot is as old as the oldest venue snapshot
+            merged.updated_at = merged.updated_at.min(account.updated_at);
+            for (asset_symbol, position) in account.open_positions {
+                let position = match merged.open_positions.remove(&asset_symbol) {
+                    Some(merged_position) => merge_positions(merged_position, position),
+                    None => position,
+                };
+                merged.open_positions.insert(asset_symbol, position);
+            }
+        }
+        // Venues can't be empty
+        Ok(merged.unwrap())
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        let mut equity = BigDecimal::from(0);
+        for venue in &self.venues {
+            equity += venue.environment.get_equity().await?;
+        }
+        Ok(equity)
+    }
+
+    /// Asset of the first venue trading the pair.
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        let mut last_err = None;
+        for venue in &self.venues {
+            match venue.environment.get_asset(crypto_pair).await {
+                Ok(asset) => return Ok(asset),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        // Venues can't be empty
+        Err(last_err.unwrap())
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        let mut seen = HashSet::new();
+        let mut crypto_pairs = Vec::new();
+        for venue in &self.venues {
+            for crypto_pair in venue.environment.list_asset_pairs().await? {
+                if seen.insert(crypto_pair.clone()) {
+                    crypto_pairs.push(crypto_pair);
+                }
+            }
+        }
+        Ok(crypto_pairs)
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        let mut merged: Option<OpenPosition> = None;
+        for venue in &self.venues {
+            if let Some(position) = venue.environment.get_position(asset_symbol).await? {
+                merged = Some(match merged {
+                    Some(merged) => merge_positions(merged, position),
+                    None => position,
+                });
+            }
+        }
+        Ok(merged)
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        let venue_index = self.find_venue(order_id).await?;
+        self.venues[venue_index]
+            .environment
+            .cancel_order(order_id)
+            .await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        for (venue_index, order_ids) in self.group_by_venue(order_ids).await? {
+            self.venues[venue_index]
+                .environment
+                .cancel_orders(&order_ids)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        let mut order_ids = Vec::new();
+        for venue in &self.venues {
+            order_ids.extend(venue.environment.cancel_all_orders().await?);
+        }
+        Ok(order_ids)
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        let mut summary = FlattenSummary::default();
+        for (venue_index, venue) in self.venues.iter().enumerate() {
+            let venue_summary = venue.environment.close_all_positions().await?;
+            self.record_orders(venue_index, venue_summary.closing_order_ids.iter().cloned());
+            summary
+                .cancelled_order_ids
+                .extend(venue_summary.cancelled_order_ids);
+            summary
+                .closing_order_ids
+                .extend(venue_summary.closing_order_ids);
+            summary.failures.extend(venue_summary.failures);
+        }
+        Ok(summary)
+    }
+
+    /// Updates of every venue, in the order they are received.
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        let (sender, receiver) = unbounded_channel();
+        let mut tasks = Vec::new();
+        for venue in &self.venues {
+            let mut stream = venue.environment.subscribe_order_updates().await?;
+            let sender = sender.clone();
+            let task = tokio::spawn(async move {
+                while let Some(update) = stream.next().await {
+                    if sender.send(update).is_err() {
+                        break;
+                    }
+                }
+            });
+            tasks.push(task.abort_handle());
+        }
+        Ok(OrderUpdateStream::with_guard(receiver, AbortOnDrop(tasks)))
+    }
+}
+
+/// Aborts the tasks forwarding the updates of the venues, and so drops their streams, once the
+/// merged stream is dropped.
+struct AbortOnDrop(Vec<AbortHandle>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        for task in &self.0 {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, OrderStatus};
+    use crate::simulated::data::InMemoryBarDataSource;
+    use crate::simulated::time::ManualClock;
+    use crate::simulated::{
+        SimulatedBrokerBuilder, SimulatedClient, SimulatedContext, SimulatedEnvironment,
+        SimulatedEnvironmentBuilder,
+    };
+    use anyhow::Result;
+    use chrono::{DateTime, Duration, Utc};
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn route_orders_to_best_venue() -> Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let mut builder = RoutedClientBuilder::new();
+        builder
+            .add_venue("cheap", create_venue(10)?, BigDecimal::from(5))?
+            .add_venue("expensive", create_venue(11)?, BigDecimal::from(0))?;
+        let client = builder.build()?;
+
+        let buy = client.place_order(create_buy(&crypto_pair, 2)).await?;
+        let sell = client
+            .place_order(OrderRequest::market_sell(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            ))
+            .await
+            .unwrap_err();
+
+        assert_eq!(client.get_order_venue(&buy).await?, "cheap");
+        // Sells go to the expensive venue, where nothing was bought
+        assert!(matches!(sell, Error::InsufficientBuyingPower { .. }));
+
+        let client = builder
+            .set_routing_policy(RoutingPolicy::LowestFee)
+            .build()?;
+        let buy = client.place_order(create_buy(&crypto_pair, 1)).await?;
+        assert_eq!(client.get_order_venue(&buy).await?, "expensive");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn merge_venue_accounts() -> Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let client = RoutedClientBuilder::new()
+            .add_venue("a", create_venue(10)?, BigDecimal::from(0))?
+            .add_venue("b", create_venue(20)?, BigDecimal::from(0))?
+            .build()?;
+        client.place_order(create_buy(&crypto_pair, 2)).await?;
+        let b_order = client.venues[1]
+            .environment
+            .place_order(create_buy(&crypto_pair, 1))
+            .await?;
+
+        let account = client.get_account().await?;
+        assert_eq!(account.cash, BigDecimal::from(160));
+        let position = &account.open_positions["BTC"];
+        assert_eq!(position.quantity, BigDecimal::from(3));
+        assert_eq!(position.market_value, Some(BigDecimal::from(40)));
+        assert_eq!(
+            position
+                .average_entry_price
+                .as_ref()
+                .map(|price| price.round(2)),
+            Some(BigDecimal::from_str("13.33")?)
+        );
+        assert_eq!(client.get_equity().await?, BigDecimal::from(200));
+        assert_eq!(client.get_orders(&OrderFilter::default()).await?.len(), 2);
+        assert_eq!(client.get_order_venue(&b_order).await?, "b");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cancel_order_on_its_venue() -> Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let client = RoutedClientBuilder::new()
+            .add_venue("a", create_venue(10)?, BigDecimal::from(0))?
+            .add_venue("b", create_venue(20)?, BigDecimal::from(0))?
+            .build()?;
+        let order_id = client.venues[1]
+            .environment
+            .place_order(OrderRequest::limit_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+                BigDecimal::from(15),
+            ))
+            .await?;
+
+        let order = client.cancel_order(&order_id).await?;
+
+        assert_eq!(order.status, OrderStatus::Cancelled);
+        assert!(
+            client
+                .get_open_orders(&OrderFilter::default())
+                .await?
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn build_invalid_routed_clients() -> Result<()> {
+        let err = RoutedClientBuilder::new().build().err().unwrap();
+        assert_eq!(err.to_string(), "Routed clients need at least one venue");
+
+        let mut builder = RoutedClientBuilder::new();
+        builder.add_venue("a", create_venue(10)?, BigDecimal::from(0))?;
+        let err = builder
+            .add_venue("a", create_venue(10)?, BigDecimal::from(0))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Venue a was already added");
+        Ok(())
+    }
+
+    fn create_buy(crypto_pair: &Instrument, quantity: i32) -> OrderRequest {
+        OrderRequest::market_buy(
+            crypto_pair.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(quantity),
+            },
+        )
+    }
+
+    /// Venue with 100 USD, pricing BTC/USD at `price`.
+    fn create_venue(price: i32) -> Result<SimulatedEnvironment> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bar_data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start - Duration::minutes(1),
+            Duration::minutes(1),
+            [price, price, price],
+        );
+        let context = SimulatedContext::new(bar_data_source, ManualClock::new(start));
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let mut environment =
+            SimulatedEnvironmentBuilder::new(context, SimulatedClient::new(broker))
+                .set_crypto_pairs_to_trade(HashSet::from([crypto_pair]))
+                .build()?;
+        environment.init()?;
+        Ok(environment)
+    }
+}
@@ -0,0 +1,407 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::Result;
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Upper bounds, in milliseconds, of the latency buckets of [MethodStats].
+pub const LATENCY_BUCKET_BOUNDS_MILLIS: [u128; 7] = [10, 50, 100, 250, 500, 1000, 5000];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodStats {
+    pub calls: u64,
+    pub errors: u64,
+    /// Number of calls per bucket of [LATENCY_BUCKET_BOUNDS_MILLIS], with an extra last bucket
+    /// for the calls slower than the last bound.
+    pub latency_buckets: Vec<u64>,
+}
+
+impl Default for MethodStats {
+    fn default() -> Self {
+        Self {
+            calls: 0,
+            errors: 0,
+            latency_buckets: vec![0; LATENCY_BUCKET_BOUNDS_MILLIS.len() + 1],
+        }
+    }
+}
+
+impl MethodStats {
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.calls as f64
+    }
+
+    fn record(&mut self, latency: std::time::Duration, is_error: bool) {
+        self.calls += 1;
+        if is_error {
+            self.errors += 1;
+        }
+        let bucket = LATENCY_BUCKET_BOUNDS_MILLIS
+            .iter()
+            .position(|bound| latency.as_millis() <= *bound)
+            .unwrap_or(LATENCY_BUCKET_BOUNDS_MILLIS.len());
+        self.latency_buckets[bucket] += 1;
+    }
+}
+
+/// Statistics of every method called on a provider, keyed by method name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderStats {
+    pub methods: HashMap<String, MethodStats>,
+}
+
+/// Wraps a [Client], [Market] or [Environment] and records the latency and outcome of every call
+/// made through it, retrievable with [StatsClient::stats].
+pub struct StatsClient<T> {
+    inner: T,
+    stats: Mutex<ProviderStats>,
+}
+
+impl<T> StatsClient<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            stats: Mutex::new(ProviderStats::default()),
+        }
+    }
+
+    pub fn stats(&self) -> ProviderStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    pub fn reset_stats(&self) {
+        *self.stats.lock().unwrap() = ProviderStats::default();
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn record<R>(&self, method: &str, start: Instant, result: &Result<R>) {
+        self.stats
+            .lock()
+            .unwrap()
+            .methods
+            .entry(method.into())
+            .or_default()
+            .record(start.elapsed(), result.is_err());
+    }
+}
+
+#[async_trait]
+impl<T> Client for StatsClient<T>
+where
+    T: Client + Send + Sync,
+{
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        let start = Instant::now();
+        let result = self.inner.place_order(req).await;
+        self.record("place_order", start, &result);
+        result
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        let start = Instant::now();
+        let result = self.inner.place_orders(reqs).await;
+        self.record("place_orders", start, &result);
+        result
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        let start = Instant::now();
+        let result = self.inner.validate_order(req).await;
+        self.record("validate_order", start, &result);
+        result
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        let start = Instant::now();
+        let result = self.inner.get_orders(filter).await;
+        self.record("get_orders", start, &result);
+        result
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        let start = Instant::now();
+        let result = self.inner.get_open_orders(filter).await;
+        self.record("get_open_orders", start, &result);
+        result
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        let start = Instant::now();
+        let result = self.inner.get_order(order_id).await;
+        self.record("get_order", start, &result);
+        result
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        let start = Instant::now();
+        let result = self.inner.get_orders_by_ids(order_ids).await;
+        self.record("get_orders_by_ids", start, &result);
+        result
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        let start = Instant::now();
+        let result = self.inner.get_order_by_client_id(client_order_id).await;
+        self.record("get_order_by_client_id", start, &result);
+        result
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        let start = Instant::now();
+        let result = self.inner.get_trades(filter).await;
+        self.record("get_trades", start, &result);
+        result
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        let start = Instant::now();
+        let result = self.inner.get_order_fills(order_id).await;
+        self.record("get_order_fills", start, &result);
+        result
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        let start = Instant::now();
+        let result = self.inner.get_account_activities(range).await;
+        self.record("get_account_activities", start, &result);
+        result
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        let start = Instant::now();
+        let result = self.inner.get_account().await;
+        self.record("get_account", start, &result);
+        result
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        let start = Instant::now();
+        let result = self.inner.get_equity().await;
+        self.record("get_equity", start, &result);
+        result
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        let start = Instant::now();
+        let result = self.inner.get_asset(crypto_pair).await;
+        self.record("get_asset", start, &result);
+        result
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        let start = Instant::now();
+        let result = self.inner.list_asset_pairs().await;
+        self.record("list_asset_pairs", start, &result);
+        result
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        let start = Instant::now();
+        let result = self.inner.get_position(asset_symbol).await;
+        self.record("get_position", start, &result);
+        result
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        let start = Instant::now();
+        let result = self.inner.cancel_order(order_id).await;
+        self.record("cancel_order", start, &result);
+        result
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        let start = Instant::now();
+        let result = self.inner.cancel_orders(order_ids).await;
+        self.record("cancel_orders", start, &result);
+        result
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        let start = Instant::now();
+        let result = self.inner.cancel_all_orders().await;
+        self.record("cancel_all_orders", start, &result);
+        result
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        let start = Instant::now();
+        let result = self.inner.close_all_positions().await;
+        self.record("close_all_positions", start, &result);
+        result
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        let start = Instant::now();
+        let result = self.inner.subscribe_order_updates().await;
+        self.record("subscribe_order_updates", start, &result);
+        result
+    }
+}
+
+#[async_trait]
+impl<T> Market for StatsClient<T>
+where
+    T: Market + Send + Sync,
+{
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        let start = Instant::now();
+        let result = self.inner.get_latest_minute_bar(crypto_pair).await;
+        self.record("get_latest_minute_bar", start, &result);
+        result
+    }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        let start = Instant::now();
+        let result = self.inner.get_latest_minute_bars(crypto_pairs).await;
+        self.record("get_latest_minute_bars", start, &result);
+        result
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        let start = Instant::now();
+        let result = self.inner.get_timeframe_bars(crypto_pair, timeframes).await;
+        self.record("get_timeframe_bars", start, &result);
+        result
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        let call_start = Instant::now();
+        let result = self
+            .inner
+            .get_bars(crypto_pair, start, end, timeframe)
+            .await;
+        self.record("get_bars", call_start, &result);
+        result
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        let start = Instant::now();
+        let result = self.inner.get_calendar().await;
+        self.record("get_calendar", start, &result);
+        result
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        let start = Instant::now();
+        let result = self.inner.get_clock().await;
+        self.record("get_clock", start, &result);
+        result
+    }
+}
+
+impl<T> Environment for StatsClient<T> where T: Environment + Send + Sync {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Amount;
+    use crate::simulated::{SimulatedBrokerBuilder, SimulatedClient};
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn record_calls_and_errors() -> Result<()> {
+        let client = StatsClient::new(SimulatedClient::new(
+            SimulatedBrokerBuilder::new("USD").build()?,
+        ));
+
+        client.get_orders(&OrderFilter::default()).await?;
+        client.get_orders(&OrderFilter::default()).await?;
+        let result = client
+            .place_order(OrderRequest::market_buy(
+                Instrument::from_str("BTC/USD")?,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            ))
+            .await;
+        assert!(result.is_err());
+
+        let stats = client.stats();
+        let get_orders_stats = &stats.methods["get_orders"];
+        assert_eq!(get_orders_stats.calls, 2);
+        assert_eq!(get_orders_stats.errors, 0);
+        assert_eq!(get_orders_stats.latency_buckets.iter().sum::<u64>(), 2);
+        let place_order_stats = &stats.methods["place_order"];
+        assert_eq!(place_order_stats.calls, 1);
+        assert_eq!(place_order_stats.error_rate(), 1.0);
+
+        client.reset_stats();
+        assert!(client.stats().methods.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn record_cancel_order_as_one_call() -> Result<()> {
+        let client = StatsClient::new(SimulatedClient::new(
+            SimulatedBrokerBuilder::new("USD")
+                .set_balance(BigDecimal::from(100))
+                .build()?,
+        ));
+        client
+            .inner
+            .set_notional_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(10))?;
+        let order_id = client
+            .place_order(OrderRequest::limit_buy(
+                Instrument::from_str("BTC/USD")?,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+                BigDecimal::from(9),
+            ))
+            .await?;
+
+        client.cancel_order(&order_id).await?;
+
+        let stats = client.stats();
+        assert_eq!(stats.methods["cancel_order"].calls, 1);
+        assert!(!stats.methods.contains_key("cancel_orders"));
+        assert!(!stats.methods.contains_key("get_order"));
+        Ok(())
+    }
+
+    #[test]
+    fn record_latency_buckets() {
+        let mut method_stats = MethodStats::default();
+        method_stats.record(std::time::Duration::from_millis(5), false);
+        method_stats.record(std::time::Duration::from_millis(300), false);
+        method_stats.record(std::time::Duration::from_secs(10), true);
+
+        assert_eq!(method_stats.latency_buckets, vec![1, 0, 0, 0, 1, 0, 0, 1]);
+        assert_eq!(method_stats.errors, 1);
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::aggregation::merge_bar_into;
+use crate::api::common::Bar;
+use crate::{Error, Result};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+/// Period of a bar, a whole number of minutes. Periods are aligned to the UNIX epoch, so a one
+/// hour timeframe starts at every full hour, except that periods of whole weeks start on Mondays
+/// at midnight UTC, like the weekly bars of Alpaca.
+///
+/// Timeframes are equal when they have the same length, e.g. `Minutes(60)` equals `OneHour`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Timeframe {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+    OneDay,
+    /// Any other number of minutes, which must be positive.
+    Minutes(u32),
+}
+
+impl Timeframe {
+    pub fn num_minutes(&self) -> i64 {
+        match self {
+            Timeframe::OneMinute => 1,
+            Timeframe::FiveMinutes => 5,
+            Timeframe::FifteenMinutes => 15,
+            Timeframe::OneHour => 60,
+            Timeframe::OneDay => 24 * 60,
+            Timeframe::Minutes(minutes) => i64::from(*minutes),
+        }
+    }
+
+    pub fn to_duration(&self) -> Duration {
+        Duration::minutes(self.num_minutes())
+    }
+
+    /// Start of the period containing `date_time`.
+    pub fn period_start(&self, date_time: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        self.check()?;
+        // The UNIX epoch is a Thursday, four days after the Monday weeks start on
+        let offset = if self.num_minutes() % WEEK_MINUTES == 0 {
+            Duration::days(4)
+        } else {
+            Duration::zero()
+        };
+        Ok((date_time - offset)
+            .duration_trunc(self.to_duration())
+            .map_err(|err| Error::Other(err.into()))?
+            + offset)
+    }
+
+    /// Start of the first period starting at or after `date_time`.
+    pub(crate) fn next_period_start(&self, date_time: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        let period_start = self.period_start(date_time)?;
+        if period_start == date_time {
+            Ok(period_start)
+        } else {
+            Ok(period_start + self.to_duration())
+        }
+    }
+
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.num_minutes() <= 0 {
+            return Err(Error::InvalidArgument(
+                "Timeframes must be a positive number of minutes".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+const WEEK_MINUTES: i64 = 7 * 24 * 60;
+
+impl PartialEq for Timeframe {
+    fn eq(&self, other: &Self) -> bool {
+        self.num_minutes() == other.num_minutes()
+    }
+}
+
+impl Eq for Timeframe {}
+
+impl Hash for Timeframe {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.num_minutes().hash(state);
+    }
+}
+
+impl TryFrom<Duration> for Timeframe {
+    type Error = Error;
+
+    fn try_from(duration: Duration) -> Result<Self> {
+        let invalid =
+            || Error::InvalidArgument("Timeframes must be a positive number of minutes".into());
+        if duration.num_seconds() % 60 != 0 || duration.subsec_nanos() != 0 {
+            return Err(invalid());
+        }
+        let timeframe = match duration.num_minutes() {
+            1 => Timeframe::OneMinute,
+            5 => Timeframe::FiveMinutes,
+            15 => Timeframe::FifteenMinutes,
+            60 => Timeframe::OneHour,
+            1440 => Timeframe::OneDay,
+            minutes => Timeframe::Minutes(u32::try_from(minutes).map_err(|_| invalid())?),
+        };
+        timeframe.check()?;
+        Ok(timeframe)
+    }
+}
+
+impl Display for Timeframe {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let minutes = self.num_minutes();
+        if minutes > 0 && minutes % (24 * 60) == 0 {
+            write!(f, "{}d", minutes / (24 * 60))
+        } else if minutes > 0 && minutes % 60 == 0 {
+            write!(f, "{}h", minutes / 60)
+        } else {
+            write!(f, "{}m", minutes)
+        }
+    }
+}
+
+/// Aggregates chronologically ordered bars into one bar per period of `timeframe` having any bar.
+pub fn resample_bars(bars: &[Bar], timeframe: Timeframe) -> Result<Vec<Bar>> {
+    let mut resampler = BarResampler::new(timeframe)?;
+    let mut resampled_bars = Vec::new();
+    for bar in bars {
+        resampled_bars.extend(resampler.push(bar)?);
+    }
+    resampled_bars.extend(resampler.flush());
+    Ok(resampled_bars)
+}
+
+/// Resamples chronologically ordered bars pushed one at a time, e.g. from a stream, into bars of
+/// a longer timeframe.
+#[derive(Debug, Clone)]
+pub struct BarResampler {
+    timeframe: Timeframe,
+    in_progress: Option<Bar>,
+}
+
+impl BarResampler {
+    pub fn new(timeframe: Timeframe) -> Result<Self> {
+        timeframe.check()?;
+        Ok(Self {
+            timeframe,
+            in_progress: None,
+        })
+    }
+
+    /// Adds the next bar, returning the bar of the previous period once `bar` starts a new one.
+    pub fn push(&mut self, bar: &Bar) -> Result<Option<Bar>> {
+        let period_start = self.timeframe.period_start(bar.date_time)?;
+        if let Some(in_progress) = &mut self.in_progress
+            && in_progress.date_time == period_start
+        {
+            merge_bar_into(in_progress, bar);
+            return Ok(None);
+        }
+        Ok(self.in_progress.replace(Bar {
+            date_time: period_start,
+            ..bar.clone()
+        }))
+    }
+
+    /// Bar of the current period, aggregated from the bars pushed so far.
+    pub fn in_progress(&self) -> Option<&Bar> {
+        self.in_progress.as_ref()
+    }
+
+    /// Returns the bar of the current period, e.g. once the period is known to be over.
+    pub fn flush(&mut self) -> Option<Bar> {
+        self.in_progress.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn resample_bars_per_period() -> Result<()> {
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let minute_bars = vec![
+            create_bar(hour - Duration::minutes(2), 10, 12, 8, 11),
+            create_bar(hour - Duration::minutes(1), 11, 15, 9, 14),
+            create_bar(hour + Duration::minutes(1), 13, 16, 12, 15),
+        ];
+
+        assert_eq!(
+            resample_bars(&minute_bars, Timeframe::OneHour)?,
+            vec![
+                create_bar(hour - Duration::hours(1), 10, 15, 8, 14),
+                create_bar(hour, 13, 16, 12, 15),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resample_pushed_bars() -> Result<()> {
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let mut resampler = BarResampler::new(Timeframe::FiveMinutes)?;
+
+        assert_eq!(resampler.push(&create_bar(hour, 10, 12, 8, 11))?, None);
+        assert_eq!(
+            resampler.push(&create_bar(hour + Duration::minutes(4), 11, 15, 9, 14))?,
+            None
+        );
+        assert_eq!(
+            resampler.in_progress(),
+            Some(&create_bar(hour, 10, 15, 8, 14))
+        );
+        assert_eq!(
+            resampler.push(&create_bar(hour + Duration::minutes(5), 14, 14, 13, 13))?,
+            Some(create_bar(hour, 10, 15, 8, 14))
+        );
+        assert_eq!(
+            resampler.flush(),
+            Some(create_bar(hour + Duration::minutes(5), 14, 14, 13, 13))
+        );
+        assert_eq!(resampler.in_progress(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn weeks_start_on_mondays() -> Result<()> {
+        let week = Timeframe::Minutes(7 * 24 * 60);
+        let monday = DateTime::<Utc>::from_str("2025-12-15T00:00:00+00:00")?;
+
+        assert_eq!(week.period_start(monday)?, monday);
+        assert_eq!(
+            week.period_start(monday + Duration::days(3) + Duration::hours(18))?,
+            monday
+        );
+        assert_eq!(
+            week.period_start(monday - Duration::minutes(1))?,
+            monday - Duration::weeks(1)
+        );
+        assert_eq!(
+            Timeframe::Minutes(2 * 7 * 24 * 60).period_start(monday + Duration::days(1))?,
+            monday - Duration::weeks(1)
+        );
+        assert_eq!(
+            week.next_period_start(monday + Duration::minutes(1))?,
+            monday + Duration::weeks(1)
+        );
+        assert_eq!(week.next_period_start(monday)?, monday);
+        assert_eq!(
+            resample_bars(
+                &[create_bar(monday + Duration::days(2), 10, 12, 8, 11)],
+                week
+            )?,
+            vec![create_bar(monday, 10, 12, 8, 11)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn timeframe_from_duration() -> Result<()> {
+        assert_eq!(Timeframe::try_from(Duration::hours(1))?, Timeframe::OneHour);
+        assert_eq!(
+            Timeframe::try_from(Duration::minutes(60))?,
+            Timeframe::Minutes(60)
+        );
+        assert_eq!(Timeframe::try_from(Duration::hours(4))?.to_string(), "4h");
+        for duration in [
+            Duration::zero(),
+            Duration::seconds(90),
+            Duration::minutes(-5),
+        ] {
+            let err = Timeframe::try_from(duration).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Timeframes must be a positive number of minutes"
+            );
+        }
+        Ok(())
+    }
+
+    fn create_bar(date_time: DateTime<Utc>, open: i32, high: i32, low: i32, close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(open),
+            close: BigDecimal::from(close),
+            date_time,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+}
@@ -0,0 +1,382 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Amount, Instrument, OrderSide};
+use crate::api::request::OrderRequest;
+use bigdecimal::BigDecimal;
+use std::fmt::{Display, Formatter};
+
+/// A single rule an [OrderRequest] doesn't comply with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    NonPositiveAmount,
+    NonPositiveLimitPrice,
+    NonPositiveStopPrice,
+    UnknownCryptoPair(Instrument),
+    QuantityTooPrecise {
+        max_scale: i64,
+    },
+    LimitPriceTooPrecise {
+        max_scale: i64,
+    },
+    NotionalBelowMinimum {
+        notional: BigDecimal,
+        min_notional: BigDecimal,
+    },
+    NotionalAboveMaximum {
+        notional: BigDecimal,
+        max_notional: BigDecimal,
+    },
+    IcebergWithoutLimitQuantity,
+    InvalidDisplayQuantity,
+    /// The limit price of a stop-limit order of the side is below (buy) or above (sell) its
+    /// stop price.
+    LimitPriceBeyondStopPrice(OrderSide),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::NonPositiveAmount => write!(f, "Amount must be positive"),
+            Violation::NonPositiveLimitPrice => write!(f, "Limit price must be positive"),
+            Violation::NonPositiveStopPrice => write!(f, "Stop price must be positive"),
+            Violation::UnknownCryptoPair(crypto_pair) => {
+                write!(f, "{} does not have notional per unit", crypto_pair)
+            }
+            Violation::QuantityTooPrecise { max_scale } => {
+                write!(f, "Quantity must have at most {} decimal places", max_scale)
+            }
+            Violation::LimitPriceTooPrecise { max_scale } => {
+                write!(
+                    f,
+                    "Limit price must have at most {} decimal places",
+                    max_scale
+                )
+            }
+            Violation::NotionalBelowMinimum {
+                notional,
+                min_notional,
+            } => {
+                write!(
+                    f,
+                    "Notional {} is below the minimum of {}",
+                    notional, min_notional
+                )
+            }
+            Violation::NotionalAboveMaximum {
+                notional,
+                max_notional,
+            } => {
+                write!(
+                    f,
+                    "Notional {} is above the maximum of {}",
+                    notional, max_notional
+                )
+            }
+            Violation::IcebergWithoutLimitQuantity => {
+                write!(
+                    f,
+                    "Iceberg orders must be limit orders with a quantity amount"
+                )
+            }
+            Violation::InvalidDisplayQuantity => {
+                write!(
+                    f,
+                    "Display quantity must be positive and up to the order quantity"
+                )
+            }
+            Violation::LimitPriceBeyondStopPrice(OrderSide::Buy) => {
+                write!(
+                    f,
+                    "Limit price of a stop-limit buy must not be below its stop price"
+                )
+            }
+            Violation::LimitPriceBeyondStopPrice(OrderSide::Sell) => {
+                write!(
+                    f,
+                    "Limit price of a stop-limit sell must not be above its stop price"
+                )
+            }
+        }
+    }
+}
+
+/// Returned when an [OrderRequest] is rejected by an [OrderValidator], listing every violation
+/// found rather than only the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub violations: Vec<Violation>,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<String> = self.violations.iter().map(Violation::to_string).collect();
+        f.write_str(&messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks order requests before they are placed. The request must pass
+/// [OrderRequest::get_violations] and the pair must have a known price, precision and notional
+/// limits are optional.
+#[derive(Debug, Clone, Default)]
+pub struct OrderValidator {
+    max_quantity_scale: Option<i64>,
+    max_limit_price_scale: Option<i64>,
+    min_notional: Option<BigDecimal>,
+    max_notional: Option<BigDecimal>,
+}
+
+#[derive(Debug, Default)]
+pub struct OrderValidatorBuilder {
+    max_quantity_scale: Option<i64>,
+    max_limit_price_scale: Option<i64>,
+    min_notional: Option<BigDecimal>,
+    max_notional: Option<BigDecimal>,
+}
+
+impl OrderValidatorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_max_quantity_scale(&mut self, max_quantity_scale: i64) -> &mut Self {
+        self.max_quantity_scale = Some(max_quantity_scale);
+        self
+    }
+
+    pub fn set_max_limit_price_scale(&mut self, max_limit_price_scale: i64) -> &mut Self {
+        self.max_limit_price_scale = Some(max_limit_price_scale);
+        self
+    }
+
+    pub fn set_min_notional(&mut self, min_notional: BigDecimal) -> &mut Self {
+        self.min_notional = Some(min_notional);
+        self
+    }
+
+    pub fn set_max_notional(&mut self, max_notional: BigDecimal) -> &mut Self {
+        self.max_notional = Some(max_notional);
+        self
+    }
+
+    pub fn build(&self) -> OrderValidator {
+        OrderValidator {
+            max_quantity_scale: self.max_quantity_scale,
+            max_limit_price_scale: self.max_limit_price_scale,
+            min_notional: self.min_notional.clone(),
+            max_notional: self.max_notional.clone(),
+        }
+    }
+}
+
+impl OrderValidator {
+    pub fn get_max_quantity_scale(&self) -> Option<i64> {
+        self.max_quantity_scale
+    }
+
+    pub fn get_max_limit_price_scale(&self) -> Option<i64> {
+        self.max_limit_price_scale
+    }
+
+    pub fn get_min_notional(&self) -> Option<&BigDecimal> {
+        self.min_notional.as_ref()
+    }
+
+    pub fn get_max_notional(&self) -> Option<&BigDecimal> {
+        self.max_notional.as_ref()
+    }
+
+    /// Validates the request against `notional_per_unit`, the current price of its pair, or
+    /// `None` if the pair is unknown.
+    pub fn validate(
+        &self,
+        order_req: &OrderRequest,
+        notional_per_unit: Option<&BigDecimal>,
+    ) -> Result<(), ValidationError> {
+        let mut violations = order_req.get_violations();
+
+        let (quantity, notional) = match &order_req.amount {
+            Amount::Quantity { quantity } => (Some(quantity), None),
+            Amount::Notional { notional } => (None, Some(notional.clone())),
+        };
+        if let (Some(quantity), Some(max_scale)) = (quantity, self.max_quantity_scale)
+            && get_scale(quantity) > max_scale
+        {
+            violations.push(Violation::QuantityTooPrecise { max_scale });
+        }
+
+        if let (Some(limit_price), Some(max_scale)) =
+            (&order_req.limit_price, self.max_limit_price_scale)
+            && get_scale(limit_price) > max_scale
+        {
+            violations.push(Violation::LimitPriceTooPrecise { max_scale });
+        }
+
+        if notional_per_unit.is_none() {
+            violations.push(Violation::UnknownCryptoPair(order_req.crypto_pair.clone()));
+        }
+
+        let price = order_req.limit_price.as_ref().or(notional_per_unit);
+        let notional = notional.or(quantity
+            .zip(price)
+            .map(|(quantity, price)| quantity * price));
+        if let Some(notional) = notional {
+            if let Some(min_notional) = &self.min_notional
+                && notional < *min_notional
+            {
+                violations.push(Violation::NotionalBelowMinimum {
+                    notional: notional.clone(),
+                    min_notional: min_notional.clone(),
+                });
+            }
+            if let Some(max_notional) = &self.max_notional
+                && notional > *max_notional
+            {
+                violations.push(Violation::NotionalAboveMaximum {
+                    notional,
+                    max_notional: max_notional.clone(),
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { violations })
+        }
+    }
+}
+
+fn get_scale(value: &BigDecimal) -> i64 {
+    value.normalized().fractional_digit_count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn validate_valid_order_request() -> Result<()> {
+        let validator = OrderValidator::default();
+        let order_req = OrderRequest::limit_buy(
+            Instrument::from_str("BTC/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from_str("0.5")?,
+            },
+            BigDecimal::from(100),
+        );
+        assert_eq!(
+            validator.validate(&order_req, Some(&BigDecimal::from(101))),
+            Ok(())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate_lists_all_violations() -> Result<()> {
+        let validator = OrderValidator::default();
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let mut order_req = OrderRequest::limit_sell(
+            crypto_pair.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(-1),
+            },
+            BigDecimal::from(0),
+        );
+        order_req.stop_price = Some(BigDecimal::from(-1));
+
+        let err = validator.validate(&order_req, None).unwrap_err();
+
+        assert_eq!(
+            err.violations,
+            vec![
+                Violation::NonPositiveAmount,
+                Violation::NonPositiveLimitPrice,
+                Violation::NonPositiveStopPrice,
+                Violation::LimitPriceBeyondStopPrice(OrderSide::Sell),
+                Violation::UnknownCryptoPair(crypto_pair),
+            ]
+        );
+        assert_eq!(
+            err.to_string(),
+            "Amount must be positive; Limit price must be positive; Stop price must be positive; \
+             Limit price of a stop-limit sell must not be above its stop price; \
+             BTC/USD does not have notional per unit"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate_precision() -> Result<()> {
+        let validator = OrderValidatorBuilder::new()
+            .set_max_quantity_scale(2)
+            .set_max_limit_price_scale(1)
+            .build();
+        let order_req = OrderRequest::limit_buy(
+            Instrument::from_str("BTC/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from_str("0.125")?,
+            },
+            BigDecimal::from_str("100.50")?,
+        );
+
+        let err = validator
+            .validate(&order_req, Some(&BigDecimal::from(100)))
+            .unwrap_err();
+
+        assert_eq!(
+            err.violations,
+            vec![Violation::QuantityTooPrecise { max_scale: 2 }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate_notional_limits() -> Result<()> {
+        let validator = OrderValidatorBuilder::new()
+            .set_min_notional(BigDecimal::from(10))
+            .set_max_notional(BigDecimal::from(1000))
+            .build();
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let market_buy = |quantity: i32| {
+            OrderRequest::market_buy(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(quantity),
+                },
+            )
+        };
+        let notional_per_unit = BigDecimal::from(5);
+
+        assert_eq!(
+            validator
+                .validate(&market_buy(1), Some(&notional_per_unit))
+                .unwrap_err()
+                .violations,
+            vec![Violation::NotionalBelowMinimum {
+                notional: BigDecimal::from(5),
+                min_notional: BigDecimal::from(10),
+            }]
+        );
+        assert_eq!(
+            validator
+                .validate(&market_buy(201), Some(&notional_per_unit))
+                .unwrap_err()
+                .violations,
+            vec![Violation::NotionalAboveMaximum {
+                notional: BigDecimal::from(1005),
+                max_notional: BigDecimal::from(1000),
+            }]
+        );
+        assert!(
+            validator
+                .validate(&market_buy(2), Some(&notional_per_unit))
+                .is_ok()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,891 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Runs a [Strategy] over historical bars, driving a [SimulatedEnvironment] one bar at a time.
+
+use crate::api::common::{Account, Bar, Instrument, Order, Trade};
+use crate::api::request::{OrderFilter, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use crate::simulated::data::{BarDataSource, InMemoryBarDataSource};
+use crate::simulated::synthetic::SyntheticBarsBuilder;
+use crate::simulated::time::ManualClock;
+use crate::simulated::{
+    RandomSource, SimulatedBroker, SimulatedBrokerBuilder, SimulatedClient, SimulatedContext,
+    SimulatedEnvironment, SimulatedEnvironmentBuilder, SimulationConfig,
+};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub use checkpoint::{BacktestCheckpoint, CheckpointOrder};
+mod checkpoint;
+
+pub use equity::{EquityCurve, EquityPoint};
+mod equity;
+
+pub use per_pair::PerPairBacktestResult;
+mod per_pair;
+
+pub use trade_log::{RoundTrip, TradeLog};
+mod trade_log;
+
+/// Trading decisions taken at the close of every bar of a [Backtest].
+#[async_trait]
+pub trait Strategy: Send {
+    /// Called once the bar ending at `now` has closed, trading through `environment`.
+    async fn on_bar(
+        &mut self,
+        environment: &(dyn Environment + Send + Sync),
+        now: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Called instead of [Strategy::on_bar] for the bars of the warm-up window preceding the
+    /// backtest, e.g. to prime indicators, without being able to trade.
+    async fn on_warm_up_bar(
+        &mut self,
+        _market: &(dyn Market + Send + Sync),
+        _now: DateTime<Utc>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// State kept in checkpoints, e.g. of indicators, see [BacktestBuilder::set_checkpoints].
+    fn save_state(&self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Restores the state saved in the checkpoint a backtest resumes from.
+    fn restore_state(&mut self, _state: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Backtest of the pairs to trade from `start` until `end`, with bars of a [BarDataSource] and a
+/// broker built anew for every run.
+#[derive(Clone)]
+pub struct Backtest {
+    bar_data_source: Box<dyn BarDataSource + Send + Sync>,
+    synthetic_bars: Option<SyntheticBarsBuilder>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    broker_builder: SimulatedBrokerBuilder,
+    crypto_pairs_to_trade: HashSet<Instrument>,
+    bar_timeframe: Timeframe,
+    warm_up_bars: u32,
+    checkpoints: Option<Checkpoints>,
+    simulation_config: Option<SimulationConfig>,
+}
+
+#[derive(Debug, Clone)]
+struct Checkpoints {
+    dir: PathBuf,
+    interval: Duration,
+}
+
+pub struct BacktestBuilder {
+    bar_data_source: Box<dyn BarDataSource + Send + Sync>,
+    synthetic_bars: Option<SyntheticBarsBuilder>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    broker_builder: SimulatedBrokerBuilder,
+    crypto_pairs_to_trade: HashSet<Instrument>,
+    bar_timeframe: Timeframe,
+    warm_up_bars: u32,
+    checkpoints: Option<Checkpoints>,
+    simulation_config: Option<SimulationConfig>,
+}
+
+/// State of the account once a [Backtest] ended.
+#[derive(Debug)]
+pub struct BacktestResult {
+    pub account: Account,
+    pub orders: Vec<Order>,
+    pub trades: Vec<Trade>,
+    /// Equity at the start of the backtest and at the close of every bar.
+    pub equity_curve: EquityCurve,
+    /// Round trips of the trades, with their excursions over the bars of the backtest.
+    pub trade_log: TradeLog,
+    pub broker: SimulatedBroker,
+    /// Randomness the backtest ran with, reproducing it when set.
+    pub simulation_config: Option<SimulationConfig>,
+}
+
+impl BacktestBuilder {
+    /// The broker starts with the balances of `broker_builder`.
+    pub fn new<B>(
+        bar_data_source: B,
+        broker_builder: SimulatedBrokerBuilder,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self
+    where
+        B: BarDataSource + Send + Sync + 'static,
+    {
+        Self {
+            bar_data_source: Box::new(bar_data_source),
+            synthetic_bars: None,
+            start,
+            end,
+            broker_builder,
+            crypto_pairs_to_trade: HashSet::new(),
+            bar_timeframe: Timeframe::OneMinute,
+            warm_up_bars: 0,
+            checkpoints: None,
+            simulation_config: None,
+        }
+    }
+
+    /// Runs over the bars of `synthetic_bars`, generated at the start of every run with the seed
+    /// of the simulation config when set.
+    pub fn with_synthetic_bars(
+        synthetic_bars: SyntheticBarsBuilder,
+        broker_builder: SimulatedBrokerBuilder,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            synthetic_bars: Some(synthetic_bars),
+            ..Self::new(InMemoryBarDataSource::default(), broker_builder, start, end)
+        }
+    }
+
+    pub fn set_crypto_pairs_to_trade(
+        &mut self,
+        crypto_pairs_to_trade: HashSet<Instrument>,
+    ) -> &mut Self {
+        self.crypto_pairs_to_trade = crypto_pairs_to_trade;
+        self
+    }
+
+    /// Timeframe of the bars the strategy is called on, one minute by default.
+    pub fn set_bar_timeframe(&mut self, bar_timeframe: Timeframe) -> &mut Self {
+        self.bar_timeframe = bar_timeframe;
+        self
+    }
+
+    /// Number of bars before `start` given to [Strategy::on_warm_up_bar]. The warm-up window is
+    /// left out of the results, which start with the equity at `start`.
+    pub fn set_warm_up_bars(&mut self, warm_up_bars: u32) -> &mut Self {
+        self.warm_up_bars = warm_up_bars;
+        self
+    }
+
+    /// Writes a [BacktestCheckpoint] to `dir` at the first bar once `interval` of simulated time
+    /// went by since the last one. Runs resume from the latest checkpoint in `dir`, skipping the
+    /// warm-up, and end the same way as an uninterrupted run.
+    pub fn set_checkpoints(&mut self, dir: impl Into<PathBuf>, interval: Duration) -> &mut Self {
+        self.checkpoints = Some(Checkpoints {
+            dir: dir.into(),
+            interval,
+        });
+        self
+    }
+
+    /// Seeds every source of randomness of the broker and environment, so that repeated runs
+    /// place the same orders with the same ids at the same prices.
+    pub fn set_simulation_config(&mut self, simulation_config: SimulationConfig) -> &mut Self {
+        self.simulation_config = Some(simulation_config);
+        self
+    }
+
+    pub fn build(&self) -> Result<Backtest> {
+        self.bar_timeframe.check()?;
+        if let Some(checkpoints) = &self.checkpoints
+            && checkpoints.interval <= Duration::zero()
+        {
+            return Err(Error::InvalidArgument(
+                "Checkpoint interval must be positive".into(),
+            ));
+        }
+        if self.start >= self.end {
+            return Err(Error::InvalidArgument(
+                "Backtest must start before it ends".into(),
+            ));
+        }
+        Ok(Backtest {
+            bar_data_source: self.bar_data_source.clone(),
+            synthetic_bars: self.synthetic_bars.clone(),
+            start: self.start,
+            end: self.end,
+            broker_builder: self.broker_builder.clone(),
+            crypto_pairs_to_trade: self.crypto_pairs_to_trade.clone(),
+            bar_timeframe: self.bar_timeframe,
+            warm_up_bars: self.warm_up_bars,
+            checkpoints: self.checkpoints.clone(),
+            simulation_config: self.simulation_config,
+        })
+    }
+}
+
+impl Backtest {
+    /// Calls `strategy` at the close of every bar until the end of the backtest, the clock of the
+    /// environment moving to the close before each call.
+    pub async fn run<S>(&self, strategy: &mut S) -> Result<BacktestResult>
+    where
+        S: Strategy,
+    {
+        let checkpoint = match &self.checkpoints {
+            Some(checkpoints) => BacktestCheckpoint::read_latest(&checkpoints.dir)?,
+            None => None,
+        };
+        let mut broker_builder = self.broker_builder.clone();
+        if let Some(checkpoint) = &checkpoint {
+            broker_builder.restore_balances(&checkpoint.balances, &checkpoint.average_entry_prices);
+            if let Some(strategy_state) = &checkpoint.strategy_state {
+                strategy.restore_state(strategy_state)?;
+            }
+        }
+        let start = checkpoint
+            .as_ref()
+            .map_or(self.start, |checkpoint| checkpoint.date_time);
+        if let Some(simulation_config) = &self.simulation_config {
+            simulation_config.seed_broker(&mut broker_builder);
+        }
+
+        let bar_duration = self.bar_timeframe.to_duration();
+        let warm_up_start = match checkpoint {
+            Some(_) => start,
+            None => self.start - bar_duration * self.warm_up_bars as i32,
+        };
+        let clock = ManualClock::new(warm_up_start);
+        let mut broker = broker_builder.build()?;
+        if let Some(checkpoint) = &checkpoint {
+            for checkpoint_order in checkpoint.open_orders.clone() {
+                broker.restore_order(checkpoint_order);
+            }
+            broker.set_rng_states(&checkpoint.rng_states);
+        }
+        let client = SimulatedClient::new_shared(broker);
+        let bar_data_source = self.get_bar_data_source()?;
+        let context = SimulatedContext::new(bar_data_source.clone(), clock.clone());
+        let mut environment_builder = SimulatedEnvironmentBuilder::new(context, client.clone());
+        environment_builder
+            .set_crypto_pairs_to_trade(self.crypto_pairs_to_trade.clone())
+            .set_bar_timeframe(self.bar_timeframe);
+        if let Some(simulation_config) = &self.simulation_config {
+            simulation_config.seed_environment(&mut environment_builder);
+        }
+        let mut environment = environment_builder.build()?;
+        if let Some(rng_state) = checkpoint
+            .as_ref()
+            .and_then(|checkpoint| checkpoint.rng_states.get(&RandomSource::BarPrices))
+        {
+            environment.set_rng_state(rng_state);
+        }
+        environment.init()?;
+
+        let mut now = warm_up_start + bar_duration;
+        while now <= start {
+            clock.set(now);
+            environment.update()?;
+            strategy.on_warm_up_bar(&environment, now).await?;
+            now += bar_duration;
+        }
+
+        let (mut equity_curve, previous_trades) = match checkpoint {
+            Some(checkpoint) => (checkpoint.equity_curve, checkpoint.trades),
+            None => {
+                let mut equity_curve = EquityCurve::new();
+                equity_curve.sample(&environment, start).await?;
+                (equity_curve, Vec::new())
+            }
+        };
+        let mut last_checkpoint_time = start;
+        let mut now = start + bar_duration;
+        while now <= self.end {
+            clock.set(now);
+            environment.update()?;
+            strategy.on_bar(&environment, now).await?;
+            equity_curve.sample(&environment, now).await?;
+            if let Some(checkpoints) = &self.checkpoints
+                && now - last_checkpoint_time >= checkpoints.interval
+            {
+                let mut trades = previous_trades.clone();
+                trades.extend(environment.get_trades(&TradeFilter::default()).await?);
+                let strategy_state = strategy.save_state()?;
+                let mut checkpoint = client.read_broker(|broker| BacktestCheckpoint {
+                    date_time: now,
+                    balances: get_balances(broker),
+                    average_entry_prices: get_average_entry_prices(broker),
+                    trades,
+                    open_orders: broker.get_checkpoint_orders(),
+                    equity_curve: equity_curve.clone(),
+                    strategy_state,
+                    rng_states: broker.get_rng_states(),
+                });
+                checkpoint
+                    .rng_states
+                    .insert(RandomSource::BarPrices, environment.get_rng_state());
+                checkpoint.write(&checkpoints.dir)?;
+                last_checkpoint_time = now;
+            }
+            now += bar_duration;
+        }
+        self.get_result(
+            &environment,
+            &client,
+            &bar_data_source,
+            equity_curve,
+            previous_trades,
+        )
+        .await
+    }
+
+    /// Bars of a run, generated anew when synthetic, every source of randomness of the run being
+    /// seeded by the simulation config.
+    fn get_bar_data_source(&self) -> Result<Box<dyn BarDataSource + Send + Sync>> {
+        let Some(synthetic_bars) = &self.synthetic_bars else {
+            return Ok(self.bar_data_source.clone());
+        };
+        let mut synthetic_bars = synthetic_bars.clone();
+        if let Some(simulation_config) = &self.simulation_config {
+            simulation_config.seed_synthetic_bars(&mut synthetic_bars);
+        }
+        Ok(Box::new(synthetic_bars.build()?))
+    }
+
+    async fn get_result(
+        &self,
+        environment: &SimulatedEnvironment,
+        client: &SimulatedClient,
+        bar_data_source: &(dyn BarDataSource + Send + Sync),
+        equity_curve: EquityCurve,
+        previous_trades: Vec<Trade>,
+    ) -> Result<BacktestResult> {
+        let mut trades = previous_trades;
+        trades.extend(environment.get_trades(&TradeFilter::default()).await?);
+        let mut trade_log = TradeLog::from_trades(&trades);
+        let instruments = client.read_broker(|broker| self.get_instruments(broker, &trades));
+        for round_trip in trade_log.round_trips_mut() {
+            round_trip.instrument = instruments.get(&round_trip.asset_symbol).cloned();
+            let bars = self.get_round_trip_bars(bar_data_source, round_trip)?;
+            round_trip.set_excursions(&bars);
+        }
+        Ok(BacktestResult {
+            account: environment.get_account().await?,
+            orders: environment.get_orders(&OrderFilter::default()).await?,
+            trades,
+            equity_curve,
+            trade_log,
+            broker: client.read_broker(SimulatedBroker::clone),
+            simulation_config: self.simulation_config,
+        })
+    }
+
+    /// Instruments by asset symbol of the orders of `trades` and of the pairs to trade. Symbols
+    /// of neither, e.g. of orders placed before a checkpoint, are parsed as crypto pairs.
+    fn get_instruments(
+        &self,
+        broker: &SimulatedBroker,
+        trades: &[Trade],
+    ) -> HashMap<String, Instrument> {
+        let mut instruments: HashMap<String, Instrument> = self
+            .crypto_pairs_to_trade
+            .iter()
+            .map(|crypto_pair| (crypto_pair.to_string(), crypto_pair.clone()))
+            .collect();
+        for trade in trades {
+            if let Ok(instrument) = broker.get_order_instrument(&trade.order_id) {
+                instruments.insert(trade.asset_symbol.clone(), instrument);
+            } else if !instruments.contains_key(&trade.asset_symbol)
+                && let Ok(crypto_pair) = Instrument::from_str(&trade.asset_symbol)
+            {
+                instruments.insert(trade.asset_symbol.clone(), crypto_pair);
+            }
+        }
+        instruments
+    }
+
+    /// Bars of the asset of a round trip from its entry until its exit.
+    fn get_round_trip_bars(
+        &self,
+        bar_data_source: &(dyn BarDataSource + Send + Sync),
+        round_trip: &RoundTrip,
+    ) -> Result<Vec<Bar>> {
+        let (Some(entry_time), Some(exit_time), Some(crypto_pair)) = (
+            round_trip.entry_time,
+            round_trip.exit_time,
+            &round_trip.instrument,
+        ) else {
+            return Ok(Vec::new());
+        };
+        let bar_duration = self.bar_timeframe.to_duration();
+        let mut bars: Vec<Bar> = Vec::new();
+        let mut date_time = entry_time;
+        while date_time <= exit_time {
+            if let Some(bar) = bar_data_source.get_bar(crypto_pair, &date_time, bar_duration)?
+                && bars
+                    .last()
+                    .is_none_or(|last| last.date_time < bar.date_time)
+            {
+                bars.push(bar);
+            }
+            date_time += bar_duration;
+        }
+        Ok(bars)
+    }
+}
+
+fn get_balances(broker: &SimulatedBroker) -> HashMap<String, BigDecimal> {
+    let mut assets = broker.get_purchased_asset_symbols();
+    assets.insert(broker.get_currency());
+    assets
+        .into_iter()
+        .map(|asset| {
+            let balance = broker.get_balance(&asset);
+            (asset, balance)
+        })
+        .collect()
+}
+
+fn get_average_entry_prices(broker: &SimulatedBroker) -> HashMap<String, BigDecimal> {
+    broker
+        .get_purchased_asset_symbols()
+        .into_iter()
+        .filter_map(|asset| {
+            let average_entry_price = broker.get_average_entry_price(&asset)?;
+            Some((asset, average_entry_price))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, AssetClass, OrderSide};
+    use crate::api::request::OrderRequest;
+    use crate::simulated::fill::{PriceImprovementFillPolicy, TouchFillPolicy};
+    use crate::simulated::synthetic::SyntheticModel;
+    use anyhow::Result;
+    use std::fs;
+
+    /// Buys one unit once the price drops below 10 and sells it once it rises above 10.
+    struct MeanReversion {
+        crypto_pair: Instrument,
+    }
+
+    #[async_trait]
+    impl Strategy for MeanReversion {
+        async fn on_bar(
+            &mut self,
+            environment: &(dyn Environment + Send + Sync),
+            _now: DateTime<Utc>,
+        ) -> crate::Result<()> {
+            let Some(bar) = environment.get_latest_minute_bar(&self.crypto_pair).await? else {
+                return Ok(());
+            };
+            let quantity = Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            };
+            let is_holding = environment
+                .get_position(&self.crypto_pair.quantity_coin)
+                .await?
+                .is_some_and(|position| position.quantity > 0);
+            if bar.close < 10 && !is_holding {
+                environment
+                    .place_order(OrderRequest::market_buy(self.crypto_pair.clone(), quantity))
+                    .await?;
+            } else if bar.close > 10 && is_holding {
+                environment
+                    .place_order(OrderRequest::market_sell(
+                        self.crypto_pair.clone(),
+                        quantity,
+                    ))
+                    .await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Buys one unit at the close of every bar with a limit order.
+    struct LimitBuyer {
+        crypto_pair: Instrument,
+    }
+
+    #[async_trait]
+    impl Strategy for LimitBuyer {
+        async fn on_bar(
+            &mut self,
+            environment: &(dyn Environment + Send + Sync),
+            _now: DateTime<Utc>,
+        ) -> crate::Result<()> {
+            if let Some(bar) = environment.get_latest_minute_bar(&self.crypto_pair).await? {
+                environment
+                    .place_order(OrderRequest::limit_buy(
+                        self.crypto_pair.clone(),
+                        Amount::Quantity {
+                            quantity: BigDecimal::from(1),
+                        },
+                        bar.close,
+                    ))
+                    .await?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Records the closes of the latest bars it was called with.
+    #[derive(Default)]
+    struct CloseRecorder {
+        warm_up_closes: Vec<BigDecimal>,
+        closes: Vec<BigDecimal>,
+    }
+
+    #[async_trait]
+    impl Strategy for CloseRecorder {
+        async fn on_bar(
+            &mut self,
+            environment: &(dyn Environment + Send + Sync),
+            _now: DateTime<Utc>,
+        ) -> crate::Result<()> {
+            let crypto_pair = Instrument::from_str("BTC/USD")?;
+            if let Some(bar) = environment.get_latest_minute_bar(&crypto_pair).await? {
+                self.closes.push(bar.close);
+            }
+            Ok(())
+        }
+
+        async fn on_warm_up_bar(
+            &mut self,
+            market: &(dyn Market + Send + Sync),
+            _now: DateTime<Utc>,
+        ) -> crate::Result<()> {
+            let crypto_pair = Instrument::from_str("BTC/USD")?;
+            if let Some(bar) = market.get_latest_minute_bar(&crypto_pair).await? {
+                self.warm_up_closes.push(bar.close);
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn run_backtest() -> Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bar_data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::minutes(1),
+            [10, 8, 9, 12, 11],
+        );
+        let mut broker_builder = SimulatedBrokerBuilder::new("USD");
+        broker_builder.set_balance(BigDecimal::from(100));
+        let backtest = BacktestBuilder::new(
+            bar_data_source,
+            broker_builder,
+            start,
+            start + Duration::minutes(5),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([crypto_pair.clone()]))
+        .build()?;
+
+        let result = backtest
+            .run(&mut MeanReversion {
+                crypto_pair: crypto_pair.clone(),
+            })
+            .await?;
+
+        let sides: Vec<OrderSide> = result
+            .orders
+            .iter()
+            .map(|order| order.side.clone())
+            .collect();
+        assert_eq!(sides, vec![OrderSide::Buy, OrderSide::Sell]);
+        assert_eq!(result.trades.len(), 2);
+        assert_eq!(result.account.cash, BigDecimal::from(102));
+        assert_eq!(result.broker.get_equity(), BigDecimal::from(102));
+        let equities: Vec<BigDecimal> = result
+            .equity_curve
+            .points()
+            .iter()
+            .map(|point| point.equity.clone())
+            .collect();
+        // Bought at 9 and sold at 11, the position being valued at the current price meanwhile
+        assert_eq!(
+            equities,
+            [100, 100, 100, 103, 102, 102].map(BigDecimal::from)
+        );
+        let round_trip = &result.trade_log.round_trips()[0];
+        assert_eq!(round_trip.profit_and_loss, BigDecimal::from(2));
+        assert_eq!(round_trip.duration(), Some(Duration::minutes(2)));
+        assert_eq!(
+            round_trip.max_favorable_excursion,
+            Some(BigDecimal::from(3))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reproduce_backtest_from_seed() -> Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bar_data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::minutes(1),
+            [10, 8, 9, 12, 11],
+        );
+        let mut broker_builder = SimulatedBrokerBuilder::new("USD");
+        broker_builder.set_balance(BigDecimal::from(100));
+        let backtest = BacktestBuilder::new(
+            bar_data_source,
+            broker_builder,
+            start,
+            start + Duration::minutes(5),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([crypto_pair.clone()]))
+        .set_simulation_config(SimulationConfig::new(42))
+        .build()?;
+        let mut strategy = MeanReversion { crypto_pair };
+
+        let first = backtest.run(&mut strategy).await?;
+        let second = backtest.run(&mut strategy).await?;
+
+        assert_eq!(first.simulation_config, Some(SimulationConfig::new(42)));
+        assert_eq!(first.orders, second.orders);
+        assert_eq!(first.trades, second.trades);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn reproduce_random_fills_and_bars_from_seed() -> Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let mut strategy = LimitBuyer {
+            crypto_pair: crypto_pair.clone(),
+        };
+
+        // Built anew every time, so that unseeded fill policies would draw different numbers
+        let first = create_random_backtest(&crypto_pair, 42)?
+            .run(&mut strategy)
+            .await?;
+        let second = create_random_backtest(&crypto_pair, 42)?
+            .run(&mut strategy)
+            .await?;
+        let third = create_random_backtest(&crypto_pair, 43)?
+            .run(&mut strategy)
+            .await?;
+
+        assert!(!first.trades.is_empty());
+        assert_eq!(first.trades, second.trades);
+        let prices = |result: &BacktestResult| {
+            result
+                .trades
+                .iter()
+                .map(|trade| trade.price.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_ne!(prices(&first), prices(&third));
+        Ok(())
+    }
+
+    /// Backtest of synthetic bars whose limit orders get filled at random better prices.
+    fn create_random_backtest(crypto_pair: &Instrument, master_seed: u64) -> Result<Backtest> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let mut synthetic_bars = SyntheticBarsBuilder::new(
+            SyntheticModel::GeometricBrownianMotion {
+                drift: 0.0,
+                volatility: 0.01,
+            },
+            start,
+            10,
+        );
+        synthetic_bars.add_pair(crypto_pair.clone(), BigDecimal::from(10));
+        let mut broker_builder = SimulatedBrokerBuilder::new("USD");
+        broker_builder
+            .set_balance(BigDecimal::from(1000))
+            .set_fill_policy(PriceImprovementFillPolicy::new(
+                TouchFillPolicy,
+                0.5,
+                BigDecimal::from(1),
+            )?);
+        Ok(BacktestBuilder::with_synthetic_bars(
+            synthetic_bars,
+            broker_builder,
+            start,
+            start + Duration::minutes(10),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([crypto_pair.clone()]))
+        .set_simulation_config(SimulationConfig::new(master_seed))
+        .build()?)
+    }
+
+    #[tokio::test]
+    async fn resume_from_checkpoint() -> Result<()> {
+        let checkpoint_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bar_data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::minutes(1),
+            [10, 8, 9, 12, 11],
+        );
+        let mut broker_builder = SimulatedBrokerBuilder::new("USD");
+        broker_builder.set_balance(BigDecimal::from(100));
+        let mut backtest_builder = BacktestBuilder::new(
+            bar_data_source,
+            broker_builder,
+            start,
+            start + Duration::minutes(3),
+        );
+        backtest_builder
+            .set_crypto_pairs_to_trade(HashSet::from([crypto_pair.clone()]))
+            .set_checkpoints(&checkpoint_dir, Duration::minutes(2));
+        let mut strategy = MeanReversion { crypto_pair };
+
+        // Interrupted after the third bar, having checkpointed the second one
+        backtest_builder.build()?.run(&mut strategy).await?;
+        backtest_builder.end = start + Duration::minutes(5);
+        let result = backtest_builder.build()?.run(&mut strategy).await?;
+
+        // Orders closed before the checkpoint aren't kept, unlike trades
+        assert_eq!(result.orders.len(), 1);
+        assert_eq!(result.trades.len(), 2);
+        assert_eq!(result.account.cash, BigDecimal::from(102));
+        let equities: Vec<BigDecimal> = result
+            .equity_curve
+            .points()
+            .iter()
+            .map(|point| point.equity.clone())
+            .collect();
+        assert_eq!(
+            equities,
+            [100, 100, 100, 103, 102, 102].map(BigDecimal::from)
+        );
+        assert_eq!(
+            result.trade_log.round_trips()[0].profit_and_loss,
+            BigDecimal::from(2)
+        );
+        fs::remove_dir_all(checkpoint_dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resume_from_checkpoint_with_open_orders() -> Result<()> {
+        let checkpoint_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let mut strategy = LimitBuyer {
+            crypto_pair: crypto_pair.clone(),
+        };
+        let uninterrupted = create_random_backtest(&crypto_pair, 42)?
+            .run(&mut strategy)
+            .await?;
+        let mut backtest = create_random_backtest(&crypto_pair, 42)?;
+        backtest.checkpoints = Some(Checkpoints {
+            dir: checkpoint_dir.clone(),
+            interval: Duration::minutes(2),
+        });
+        let end = backtest.end;
+
+        // Interrupted after the third bar, having checkpointed the second one before the limit
+        // orders of the first two got filled
+        backtest.end = backtest.start + Duration::minutes(3);
+        backtest.run(&mut strategy).await?;
+        let checkpoint = BacktestCheckpoint::read_latest(&checkpoint_dir)?.unwrap();
+        assert_eq!(checkpoint.open_orders.len(), 2);
+        backtest.end = end;
+        let resumed = backtest.run(&mut strategy).await?;
+
+        assert_eq!(resumed.trades, uninterrupted.trades);
+        assert_eq!(resumed.equity_curve, uninterrupted.equity_curve);
+        assert_eq!(resumed.account.cash, uninterrupted.account.cash);
+        assert_eq!(
+            resumed.broker.get_open_orders(&OrderFilter::default()),
+            uninterrupted
+                .broker
+                .get_open_orders(&OrderFilter::default())
+        );
+        fs::remove_dir_all(checkpoint_dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn run_equity_backtest() -> Result<()> {
+        let equity = Instrument::equity("AAPL", "USD");
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bar_data_source = InMemoryBarDataSource::from_closes(
+            equity.clone(),
+            start,
+            Duration::minutes(1),
+            [10, 8, 9, 12, 11],
+        );
+        let mut broker_builder = SimulatedBrokerBuilder::new("USD");
+        broker_builder.set_balance(BigDecimal::from(100));
+        let backtest = BacktestBuilder::new(
+            bar_data_source,
+            broker_builder,
+            start,
+            start + Duration::minutes(5),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([equity.clone()]))
+        .build()?;
+
+        let result = backtest
+            .run(&mut MeanReversion {
+                crypto_pair: equity.clone(),
+            })
+            .await?;
+
+        assert_eq!(result.account.cash, BigDecimal::from(102));
+        let round_trip = &result.trade_log.round_trips()[0];
+        assert_eq!(round_trip.asset_symbol, "AAPL");
+        let instrument = round_trip.instrument.as_ref().unwrap();
+        assert_eq!(*instrument, equity);
+        assert_eq!(instrument.asset_class, AssetClass::Equity);
+        assert_eq!(
+            round_trip.max_favorable_excursion,
+            Some(BigDecimal::from(3))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn warm_up_before_start() -> Result<()> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bar_data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start - Duration::minutes(3),
+            Duration::minutes(1),
+            [1, 2, 3, 4, 5],
+        );
+        let backtest = BacktestBuilder::new(
+            bar_data_source,
+            SimulatedBrokerBuilder::new("USD"),
+            start,
+            start + Duration::minutes(2),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([crypto_pair]))
+        .set_warm_up_bars(2)
+        .build()?;
+        let mut strategy = CloseRecorder::default();
+
+        let result = backtest.run(&mut strategy).await?;
+
+        assert_eq!(strategy.warm_up_closes, [2, 3].map(BigDecimal::from));
+        assert_eq!(strategy.closes, [4, 5].map(BigDecimal::from));
+        assert_eq!(result.equity_curve.points()[0].date_time, start);
+        assert_eq!(result.equity_curve.points().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn backtest_must_start_before_it_ends() {
+        let start = Utc::now();
+        let err = BacktestBuilder::new(
+            InMemoryBarDataSource::default(),
+            SimulatedBrokerBuilder::new("USD"),
+            start,
+            start,
+        )
+        .build()
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "Backtest must start before it ends");
+    }
+}
@@ -0,0 +1,566 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{
+    Amount, AssetClass, Instrument, Order, OrderId, OrderSide, OrderStatus, OrderType, Trade,
+};
+use crate::backtest::EquityCurve;
+use crate::simulated::{RandomSource, RngState};
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const COMPLETE_FILE: &str = "complete";
+const BALANCES_FILE: &str = "balances.csv";
+const TRADES_FILE: &str = "trades.csv";
+const EQUITY_FILE: &str = "equity.csv";
+const OPEN_ORDERS_FILE: &str = "open_orders.csv";
+const RNG_STATES_FILE: &str = "rng_states.csv";
+const STRATEGY_STATE_FILE: &str = "strategy_state";
+
+/// State of a [Backtest](crate::backtest::Backtest) at the close of a bar, from which it can be
+/// resumed as if it had never been interrupted.
+///
+/// Closed orders aren't kept, only their trades.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacktestCheckpoint {
+    pub date_time: DateTime<Utc>,
+    /// Balance of the currency and of every asset purchased, the buying power reserved by the
+    /// open orders included.
+    pub balances: HashMap<String, BigDecimal>,
+    pub average_entry_prices: HashMap<String, BigDecimal>,
+    /// Every trade since the start of the backtest.
+    pub trades: Vec<Trade>,
+    /// Orders still open, in the order they were placed.
+    pub open_orders: Vec<CheckpointOrder>,
+    pub equity_curve: EquityCurve,
+    /// See [Strategy::save_state](crate::backtest::Strategy::save_state).
+    pub strategy_state: Option<String>,
+    /// States of the seeded random number generators of the broker and the environment.
+    pub rng_states: HashMap<RandomSource, RngState>,
+}
+
+/// Open order of a [BacktestCheckpoint], with the buying power it still reserves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckpointOrder {
+    pub order: Order,
+    /// Instrument the order was placed for, with its asset class.
+    pub instrument: Instrument,
+    pub reserved: BigDecimal,
+    /// Price the buying power of a buy order was reserved at, `None` when reserved by notional.
+    pub reservation_price: Option<BigDecimal>,
+}
+
+impl BacktestCheckpoint {
+    /// Writes the checkpoint to a directory of its own within `dir`, then removes the older
+    /// checkpoints. A checkpoint only counts once fully written.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        let checkpoint_dir = dir.join(self.date_time.format("%Y%m%dT%H%M%SZ").to_string());
+        fs::create_dir_all(&checkpoint_dir).map_err(io_error)?;
+
+        let mut balances = String::from("asset,balance,average_entry_price\n");
+        for (asset, balance) in &self.balances {
+            let average_entry_price = self
+                .average_entry_prices
+                .get(asset)
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            balances += &format!("{asset},{balance},{average_entry_price}\n");
+        }
+        write_file(&checkpoint_dir.join(BALANCES_FILE), &balances)?;
+
+        let mut trades = String::from(
+            "trade_id,order_id,asset_symbol,side,price,quantity,fee,fee_asset,timestamp\n",
+        );
+        for trade in &self.trades {
+            trades += &format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                trade.trade_id,
+                trade.order_id,
+                trade.asset_symbol,
+                format_side(&trade.side),
+                trade.price,
+                trade.quantity,
+                trade.fee,
+                trade.fee_asset,
+                trade.date_time.map(format_date_time).unwrap_or_default()
+            );
+        }
+        write_file(&checkpoint_dir.join(TRADES_FILE), &trades)?;
+
+        let mut open_orders = String::from(
+            "order_id,asset_symbol,quantity_coin,notional_coin,asset_class,side,quantity,notional,\
+            limit_price,filled_quantity,average_fill_price,status,display_quantity,run_id,\
+            strategy_id,created_at,tag,metadata,client_order_id,reserved,reservation_price\n",
+        );
+        for CheckpointOrder {
+            order,
+            instrument,
+            reserved,
+            reservation_price,
+        } in &self.open_orders
+        {
+            let (quantity, notional) = match &order.amount {
+                Amount::Quantity { quantity } => (quantity.to_string(), String::new()),
+                Amount::Notional { notional } => (String::new(), notional.to_string()),
+            };
+            let mut metadata: Vec<String> = order
+                .metadata
+                .iter()
+                .map(|(key, value)| format!("{}={}", escape(key), escape(value)))
+                .collect();
+            metadata.sort();
+            let values = [
+                order.order_id.to_string(),
+                order.asset_symbol.clone(),
+                instrument.quantity_coin.clone(),
+                instrument.notional_coin.clone(),
+                format_asset_class(&instrument.asset_class).into(),
+                format_side(&order.side).into(),
+                quantity,
+                notional,
+                format_optional(order.limit_price.as_ref()),
+                order.filled_quantity.to_string(),
+                format_optional(order.average_fill_price.as_ref()),
+                format_status(&order.status).into(),
+                format_optional(order.display_quantity.as_ref()),
+                escape_optional(order.run_id.as_deref()),
+                escape_optional(order.strategy_id.as_deref()),
+                order.created_at.map(format_date_time).unwrap_or_default(),
+                escape_optional(order.tag.as_deref()),
+                metadata.join(";"),
+                escape_optional(order.client_order_id.as_deref()),
+                reserved.to_string(),
+                format_optional(reservation_price.as_ref()),
+            ];
+            open_orders += &(values.join(",") + "\n");
+        }
+        write_file(&checkpoint_dir.join(OPEN_ORDERS_FILE), &open_orders)?;
+        write_file(
+            &checkpoint_dir.join(EQUITY_FILE),
+            &self.equity_curve.to_csv(),
+        )?;
+        if let Some(strategy_state) = &self.strategy_state {
+            write_file(&checkpoint_dir.join(STRATEGY_STATE_FILE), strategy_state)?;
+        }
+        let mut rng_states = String::from("source,state\n");
+        for (source, rng_state) in &self.rng_states {
+            rng_states += &format!("{},{}\n", format_random_source(source), rng_state);
+        }
+        write_file(&checkpoint_dir.join(RNG_STATES_FILE), &rng_states)?;
+        write_file(
+            &checkpoint_dir.join(COMPLETE_FILE),
+            &format_date_time(self.date_time),
+        )?;
+
+        for older_dir in list_checkpoint_dirs(dir)? {
+            if older_dir != checkpoint_dir {
+                fs::remove_dir_all(older_dir).map_err(io_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Latest complete checkpoint written within `dir`, if any.
+    pub fn read_latest(dir: &Path) -> Result<Option<Self>> {
+        let Some(checkpoint_dir) = list_checkpoint_dirs(dir)?
+            .into_iter()
+            .filter(|checkpoint_dir| checkpoint_dir.join(COMPLETE_FILE).exists())
+            .max()
+        else {
+            return Ok(None);
+        };
+        let invalid =
+            || Error::InvalidArgument(format!("Invalid checkpoint {}", checkpoint_dir.display()));
+        let parse_decimal = |value: &str| BigDecimal::from_str(value).map_err(|_| invalid());
+        let parse_date_time = |value: &str| DateTime::<Utc>::from_str(value).map_err(|_| invalid());
+        let parse_optional_decimal = |value: &str| match value {
+            "" => Ok(None),
+            value => parse_decimal(value).map(Some),
+        };
+        let parse_side = |value: &str| match value {
+            "buy" => Ok(OrderSide::Buy),
+            "sell" => Ok(OrderSide::Sell),
+            _ => Err(invalid()),
+        };
+
+        let date_time = parse_date_time(&read_file(&checkpoint_dir.join(COMPLETE_FILE))?)?;
+
+        let mut balances = HashMap::new();
+        let mut average_entry_prices = HashMap::new();
+        for line in read_lines(&checkpoint_dir.join(BALANCES_FILE))? {
+            let [asset, balance, average_entry_price] = line.split(',').collect::<Vec<_>>()[..]
+            else {
+                return Err(invalid());
+            };
+            balances.insert(asset.to_string(), parse_decimal(balance)?);
+            if !average_entry_price.is_empty() {
+                average_entry_prices.insert(asset.to_string(), parse_decimal(average_entry_price)?);
+            }
+        }
+
+        let mut trades = Vec::new();
+        for line in read_lines(&checkpoint_dir.join(TRADES_FILE))? {
+            let [
+                trade_id,
+                order_id,
+                asset_symbol,
+                side,
+                price,
+                quantity,
+                fee,
+                fee_asset,
+                timestamp,
+            ] = line.split(',').collect::<Vec<_>>()[..]
+            else {
+                return Err(invalid());
+            };
+            trades.push(Trade {
+                trade_id: trade_id.into(),
+                order_id: OrderId::new(order_id),
+                asset_symbol: asset_symbol.into(),
+                side: parse_side(side)?,
+                price: parse_decimal(price)?,
+                quantity: parse_decimal(quantity)?,
+                fee: parse_decimal(fee)?,
+                fee_asset: fee_asset.into(),
+                date_time: match timestamp {
+                    "" => None,
+                    timestamp => Some(parse_date_time(timestamp)?),
+                },
+            });
+        }
+
+        let mut open_orders = Vec::new();
+        for line in read_lines(&checkpoint_dir.join(OPEN_ORDERS_FILE))? {
+            let [
+                order_id,
+                asset_symbol,
+                quantity_coin,
+                notional_coin,
+                asset_class,
+                side,
+                quantity,
+                notional,
+                limit_price,
+                filled_quantity,
+                average_fill_price,
+                status,
+                display_quantity,
+                run_id,
+                strategy_id,
+                created_at,
+                tag,
+                metadata,
+                client_order_id,
+                reserved,
+                reservation_price,
+            ] = line.split(',').collect::<Vec<_>>()[..]
+            else {
+                return Err(invalid());
+            };
+            let amount = match (quantity, notional) {
+                (quantity, "") => Amount::Quantity {
+                    quantity: parse_decimal(quantity)?,
+                },
+                ("", notional) => Amount::Notional {
+                    notional: parse_decimal(notional)?,
+                },
+                _ => return Err(invalid()),
+            };
+            let mut metadata_entries = HashMap::new();
+            for entry in metadata.split(';').filter(|entry| !entry.is_empty()) {
+                let (key, value) = entry.split_once('=').ok_or_else(invalid)?;
+                metadata_entries.insert(unescape(key), unescape(value));
+            }
+            let limit_price = parse_optional_decimal(limit_price)?;
+            open_orders.push(CheckpointOrder {
+                order: Order {
+                    order_id: OrderId::new(order_id),
+                    asset_symbol: asset_symbol.into(),
+                    amount,
+                    type_: match limit_price {
+                        Some(_) => OrderType::Limit,
+                        None => OrderType::Market,
+                    },
+                    limit_price,
+                    filled_quantity: parse_decimal(filled_quantity)?,
+                    average_fill_price: parse_optional_decimal(average_fill_price)?,
+                    status: parse_status(status).ok_or_else(invalid)?,
+                    side: parse_side(side)?,
+                    display_quantity: parse_optional_decimal(display_quantity)?,
+                    run_id: unescape_optional(run_id),
+                    strategy_id: unescape_optional(strategy_id),
+                    created_at: match created_at {
+                        "" => None,
+                        created_at => Some(parse_date_time(created_at)?),
+                    },
+                    tag: unescape_optional(tag),
+                    metadata: metadata_entries,
+                    client_order_id: unescape_optional(client_order_id),
+                },
+                instrument: Instrument {
+                    notional_coin: notional_coin.into(),
+                    quantity_coin: quantity_coin.into(),
+                    asset_class: parse_asset_class(asset_class).ok_or_else(invalid)?,
+                },
+                reserved: parse_decimal(reserved)?,
+                reservation_price: parse_optional_decimal(reservation_price)?,
+            });
+        }
+
+        let mut rng_states = HashMap::new();
+        for line in read_lines(&checkpoint_dir.join(RNG_STATES_FILE))? {
+            let (source, rng_state) = line.split_once(',').ok_or_else(invalid)?;
+            rng_states.insert(
+                parse_random_source(source).ok_or_else(invalid)?,
+                RngState::from_str(rng_state)?,
+            );
+        }
+
+        let strategy_state_path = checkpoint_dir.join(STRATEGY_STATE_FILE);
+        Ok(Some(Self {
+            date_time,
+            balances,
+            average_entry_prices,
+            trades,
+            open_orders,
+            equity_curve: EquityCurve::from_csv(&read_file(&checkpoint_dir.join(EQUITY_FILE))?)?,
+            strategy_state: if strategy_state_path.exists() {
+                Some(read_file(&strategy_state_path)?)
+            } else {
+                None
+            },
+            rng_states,
+        }))
+    }
+}
+
+fn list_checkpoint_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut checkpoint_dirs = Vec::new();
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_dir() {
+            checkpoint_dirs.push(path);
+        }
+    }
+    Ok(checkpoint_dirs)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).map_err(io_error)
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(io_error)
+}
+
+/// Lines of a CSV file, without its header.
+fn read_lines(path: &Path) -> Result<Vec<String>> {
+    Ok(read_file(path)?
+        .lines()
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+fn format_date_time(date_time: DateTime<Utc>) -> String {
+    date_time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn format_optional(value: Option<&BigDecimal>) -> String {
+    value.map(ToString::to_string).unwrap_or_default()
+}
+
+/// Escapes the characters separating values, e.g. in tags, for them to be written as is.
+fn escape(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace(',', "%2C")
+        .replace('\n', "%0A")
+        .replace(';', "%3B")
+        .replace('=', "%3D")
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("%3D", "=")
+        .replace("%3B", ";")
+        .replace("%0A", "\n")
+        .replace("%2C", ",")
+        .replace("%25", "%")
+}
+
+fn escape_optional(value: Option<&str>) -> String {
+    value.map(escape).unwrap_or_default()
+}
+
+fn unescape_optional(value: &str) -> Option<String> {
+    match value {
+        "" => None,
+        value => Some(unescape(value)),
+    }
+}
+
+fn format_side(side: &OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+fn format_status(status: &OrderStatus) -> &'static str {
+    match status {
+        OrderStatus::New => "new",
+        OrderStatus::PartiallyFilled => "partially_filled",
+        OrderStatus::Filled => "filled",
+        OrderStatus::Cancelled => "cancelled",
+        OrderStatus::Expired => "expired",
+        OrderStatus::Unimplemented => "unimplemented",
+    }
+}
+
+fn parse_status(value: &str) -> Option<OrderStatus> {
+    match value {
+        "new" => Some(OrderStatus::New),
+        "partially_filled" => Some(OrderStatus::PartiallyFilled),
+        "filled" => Some(OrderStatus::Filled),
+        "cancelled" => Some(OrderStatus::Cancelled),
+        "expired" => Some(OrderStatus::Expired),
+        "unimplemented" => Some(OrderStatus::Unimplemented),
+        _ => None,
+    }
+}
+
+fn format_asset_class(asset_class: &AssetClass) -> &'static str {
+    match asset_class {
+        AssetClass::Crypto => "crypto",
+        AssetClass::Equity => "equity",
+        AssetClass::Fx => "fx",
+    }
+}
+
+fn parse_asset_class(value: &str) -> Option<AssetClass> {
+    match value {
+        "crypto" => Some(AssetClass::Crypto),
+        "equity" => Some(AssetClass::Equity),
+        "fx" => Some(AssetClass::Fx),
+        _ => None,
+    }
+}
+
+fn format_random_source(source: &RandomSource) -> &'static str {
+    match source {
+        RandomSource::BarPrices => "bar_prices",
+        RandomSource::Fills => "fills",
+        RandomSource::SyntheticBars => "synthetic_bars",
+        RandomSource::Ids => "ids",
+    }
+}
+
+fn parse_random_source(value: &str) -> Option<RandomSource> {
+    match value {
+        "bar_prices" => Some(RandomSource::BarPrices),
+        "fills" => Some(RandomSource::Fills),
+        "synthetic_bars" => Some(RandomSource::SyntheticBars),
+        "ids" => Some(RandomSource::Ids),
+        _ => None,
+    }
+}
+
+fn io_error(err: std::io::Error) -> Error {
+    Error::Other(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+
+    #[test]
+    fn write_and_read_latest() -> Result<()> {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let date_time = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let mut equity_curve = EquityCurve::new();
+        equity_curve.push(date_time, BigDecimal::from(100))?;
+        let checkpoint = BacktestCheckpoint {
+            date_time,
+            balances: HashMap::from([
+                ("USD".into(), BigDecimal::from(91)),
+                ("BTC".into(), BigDecimal::from(1)),
+            ]),
+            average_entry_prices: HashMap::from([("BTC".into(), BigDecimal::from(9))]),
+            trades: vec![Trade {
+                trade_id: "trade".into(),
+                order_id: OrderId::new("order"),
+                asset_symbol: "BTC/USD".into(),
+                side: OrderSide::Buy,
+                price: BigDecimal::from(9),
+                quantity: BigDecimal::from(1),
+                fee: BigDecimal::from(0),
+                fee_asset: "BTC".into(),
+                date_time: Some(date_time),
+            }],
+            open_orders: vec![CheckpointOrder {
+                order: Order {
+                    order_id: OrderId::new("open order"),
+                    asset_symbol: "BTC/USD".into(),
+                    amount: Amount::Quantity {
+                        quantity: BigDecimal::from(2),
+                    },
+                    limit_price: Some(BigDecimal::from(8)),
+                    filled_quantity: BigDecimal::from(1),
+                    average_fill_price: Some(BigDecimal::from(8)),
+                    status: OrderStatus::PartiallyFilled,
+                    type_: OrderType::Limit,
+                    side: OrderSide::Buy,
+                    display_quantity: None,
+                    run_id: Some("run".into()),
+                    strategy_id: None,
+                    created_at: Some(date_time),
+                    tag: Some("dip, 10%".into()),
+                    metadata: HashMap::from([
+                        ("reason".into(), "a=b;c".into()),
+                        ("signal".into(), "sma".into()),
+                    ]),
+                    client_order_id: None,
+                },
+                instrument: Instrument::crypto("BTC", "USD"),
+                reserved: BigDecimal::from(8),
+                reservation_price: Some(BigDecimal::from(8)),
+            }],
+            equity_curve,
+            strategy_state: Some("sma=9.5\nbars=20".into()),
+            rng_states: HashMap::from([(
+                RandomSource::Ids,
+                RngState::of(&ChaCha12Rng::seed_from_u64(42)),
+            )]),
+        };
+        let later_checkpoint = BacktestCheckpoint {
+            date_time: date_time + chrono::Duration::minutes(1),
+            strategy_state: None,
+            ..checkpoint.clone()
+        };
+
+        checkpoint.write(&dir)?;
+        assert_eq!(BacktestCheckpoint::read_latest(&dir)?, Some(checkpoint));
+        later_checkpoint.write(&dir)?;
+        assert_eq!(
+            BacktestCheckpoint::read_latest(&dir)?,
+            Some(later_checkpoint)
+        );
+        assert_eq!(list_checkpoint_dirs(&dir)?.len(), 1);
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::Client;
+use crate::api::timeframe::Timeframe;
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::str::FromStr;
+
+/// Equity of an account at a point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EquityPoint {
+    pub date_time: DateTime<Utc>,
+    pub equity: BigDecimal,
+}
+
+/// Equity of an account sampled over time, in chronological order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EquityCurve {
+    points: Vec<EquityPoint>,
+}
+
+impl EquityCurve {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sample, which must be later than the previous one.
+    pub fn push(&mut self, date_time: DateTime<Utc>, equity: BigDecimal) -> Result<()> {
+        if self
+            .points
+            .last()
+            .is_some_and(|point| point.date_time >= date_time)
+        {
+            return Err(Error::InvalidArgument(
+                "Equity samples must be in chronological order".into(),
+            ));
+        }
+        self.points.push(EquityPoint { date_time, equity });
+        Ok(())
+    }
+
+    /// Samples the equity of `client` as of `now`, e.g. from a live trading loop.
+    pub async fn sample<C>(&mut self, client: &C, now: DateTime<Utc>) -> Result<()>
+    where
+        C: Client + ?Sized,
+    {
+        let equity = client.get_equity().await?;
+        self.push(now, equity)
+    }
+
+    pub fn points(&self) -> &[EquityPoint] {
+        &self.points
+    }
+
+    /// Last sample of every period of `timeframe` having any, dated at the start of the period.
+    pub fn resample(&self, timeframe: Timeframe) -> Result<EquityCurve> {
+        let mut points: Vec<EquityPoint> = Vec::new();
+        for point in &self.points {
+            let period_start = timeframe.period_start(point.date_time)?;
+            match points.last_mut() {
+                Some(last) if last.date_time == period_start => last.equity = point.equity.clone(),
+                _ => points.push(EquityPoint {
+                    date_time: period_start,
+                    equity: point.equity.clone(),
+                }),
+            }
+        }
+        Ok(EquityCurve { points })
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("timestamp,equity\n");
+        for point in &self.points {
+            csv += &format!("{},{}\n", format_date_time(point.date_time), point.equity);
+        }
+        csv
+    }
+
+    /// Parses the output of [EquityCurve::to_csv].
+    pub fn from_csv(csv: &str) -> Result<Self> {
+        let mut equity_curve = Self::new();
+        for line in csv.lines().skip(1).filter(|line| !line.is_empty()) {
+            let invalid = || Error::InvalidArgument(format!("Invalid equity sample {line}"));
+            let (timestamp, equity) = line.split_once(',').ok_or_else(invalid)?;
+            equity_curve.push(
+                DateTime::<Utc>::from_str(timestamp).map_err(|_| invalid())?,
+                BigDecimal::from_str(equity).map_err(|_| invalid())?,
+            )?;
+        }
+        Ok(equity_curve)
+    }
+
+    /// Array of `{"timestamp": .., "equity": ..}` objects, the equity being a string to keep its
+    /// precision.
+    pub fn to_json(&self) -> String {
+        let points: Vec<String> = self
+            .points
+            .iter()
+            .map(|point| {
+                format!(
+                    r#"{{"timestamp":"{}","equity":"{}"}}"#,
+                    format_date_time(point.date_time),
+                    point.equity
+                )
+            })
+            .collect();
+        format!("[{}]", points.join(","))
+    }
+}
+
+fn format_date_time(date_time: DateTime<Utc>) -> String {
+    date_time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    #[test]
+    fn resample_and_export() -> Result<()> {
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let mut equity_curve = EquityCurve::new();
+        equity_curve.push(hour, BigDecimal::from(100))?;
+        equity_curve.push(hour + Duration::minutes(30), BigDecimal::from(105))?;
+        equity_curve.push(hour + Duration::minutes(90), BigDecimal::from_str("98.5")?)?;
+
+        let hourly = equity_curve.resample(Timeframe::OneHour)?;
+
+        assert_eq!(
+            hourly.to_csv(),
+            "timestamp,equity\n2025-12-17T18:00:00Z,105\n2025-12-17T19:00:00Z,98.5\n"
+        );
+        assert_eq!(
+            hourly.to_json(),
+            r#"[{"timestamp":"2025-12-17T18:00:00Z","equity":"105"},{"timestamp":"2025-12-17T19:00:00Z","equity":"98.5"}]"#
+        );
+        assert_eq!(EquityCurve::from_csv(&equity_curve.to_csv())?, equity_curve);
+        let err = equity_curve.push(hour, BigDecimal::from(1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Equity samples must be in chronological order"
+        );
+        Ok(())
+    }
+}
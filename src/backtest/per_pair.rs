@@ -0,0 +1,218 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Instrument, Trade};
+use crate::backtest::{Backtest, BacktestResult, Strategy};
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Results of [Backtest::run_per_pair], one independent backtest per pair.
+#[derive(Debug)]
+pub struct PerPairBacktestResult {
+    pub results: HashMap<Instrument, BacktestResult>,
+}
+
+impl PerPairBacktestResult {
+    /// Trades of every pair in time order.
+    pub fn get_trades(&self) -> Vec<Trade> {
+        let mut trades: Vec<Trade> = self
+            .results
+            .values()
+            .flat_map(|result| result.trades.iter().cloned())
+            .collect();
+        trades.sort_by_key(|trade| trade.date_time);
+        trades
+    }
+
+    /// Pairs with the equity their backtest ended with, from the highest equity down.
+    pub fn rank_by_equity(&self) -> Vec<(&Instrument, BigDecimal)> {
+        let mut ranking: Vec<(&Instrument, BigDecimal)> = self
+            .results
+            .iter()
+            .map(|(crypto_pair, result)| (crypto_pair, result.broker.get_equity()))
+            .collect();
+        ranking.sort_by(|(_, a), (_, b)| b.cmp(a));
+        ranking
+    }
+}
+
+impl Backtest {
+    /// Backtests every pair to trade on its own, with a strategy created for it and a broker
+    /// starting with the full balances, running up to `worker_count` pairs at once on the worker
+    /// threads of the runtime. Only suited to strategies trading a single pair, as no backtest
+    /// sees the others.
+    ///
+    /// Checkpoints of each pair are kept in a subdirectory named after it.
+    pub async fn run_per_pair<S, F>(
+        &self,
+        create_strategy: F,
+        worker_count: usize,
+    ) -> Result<PerPairBacktestResult>
+    where
+        S: Strategy + 'static,
+        F: Fn(&Instrument) -> S,
+    {
+        if worker_count == 0 {
+            return Err(Error::InvalidArgument(
+                "Worker count must be at least 1".into(),
+            ));
+        }
+        let semaphore = Arc::new(Semaphore::new(worker_count));
+        let mut handles = Vec::new();
+        for crypto_pair in &self.crypto_pairs_to_trade {
+            let backtest = self.for_pair(crypto_pair);
+            let mut strategy = create_strategy(crypto_pair);
+            let semaphore = semaphore.clone();
+            let handle = tokio::spawn(async move {
+                let _permit = semaphore
+                    .acquire_owned()
+                    .await
+                    .map_err(|err| Error::Other(err.into()))?;
+                backtest.run(&mut strategy).await
+            });
+            handles.push((crypto_pair.clone(), handle));
+        }
+
+        let mut results = HashMap::new();
+        for (crypto_pair, handle) in handles {
+            let result = handle.await.map_err(|err| Error::Other(err.into()))??;
+            results.insert(crypto_pair, result);
+        }
+        Ok(PerPairBacktestResult { results })
+    }
+
+    fn for_pair(&self, crypto_pair: &Instrument) -> Backtest {
+        let mut backtest = self.clone();
+        backtest.crypto_pairs_to_trade = HashSet::from([crypto_pair.clone()]);
+        if let Some(checkpoints) = &mut backtest.checkpoints {
+            checkpoints.dir = checkpoints
+                .dir
+                .join(crypto_pair.to_string().replace('/', "-"));
+        }
+        backtest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Environment;
+    use crate::api::common::Amount;
+    use crate::api::request::OrderRequest;
+    use crate::backtest::BacktestBuilder;
+    use crate::simulated::SimulatedBrokerBuilder;
+    use crate::simulated::data::InMemoryBarDataSourceBuilder;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use chrono::{DateTime, Duration, Utc};
+    use std::str::FromStr;
+
+    /// Buys one unit at the first bar and holds it.
+    struct BuyAndHold {
+        crypto_pair: Instrument,
+        bought: bool,
+    }
+
+    #[async_trait]
+    impl Strategy for BuyAndHold {
+        async fn on_bar(
+            &mut self,
+            environment: &(dyn Environment + Send + Sync),
+            _now: DateTime<Utc>,
+        ) -> crate::Result<()> {
+            if !self.bought {
+                environment
+                    .place_order(OrderRequest::market_buy(
+                        self.crypto_pair.clone(),
+                        Amount::Quantity {
+                            quantity: BigDecimal::from(1),
+                        },
+                    ))
+                    .await?;
+                self.bought = true;
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn run_every_pair_on_its_own() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let btc_usd = Instrument::from_str("BTC/USD")?;
+        let eth_usd = Instrument::from_str("ETH/USD")?;
+        let sol_usd = Instrument::from_str("SOL/USD")?;
+        let mut bar_data_source_builder = InMemoryBarDataSourceBuilder::new();
+        for (crypto_pair, closes) in [
+            (btc_usd.clone(), [10, 10, 14, 15]),
+            (eth_usd.clone(), [10, 10, 8, 7]),
+            (sol_usd.clone(), [10, 10, 11, 12]),
+        ] {
+            bar_data_source_builder.add_closes(crypto_pair, start, Duration::minutes(1), closes);
+        }
+        let mut broker_builder = SimulatedBrokerBuilder::new("USD");
+        broker_builder.set_balance(BigDecimal::from(100));
+        let backtest = BacktestBuilder::new(
+            bar_data_source_builder.build(),
+            broker_builder,
+            start,
+            start + Duration::minutes(4),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([
+            btc_usd.clone(),
+            eth_usd.clone(),
+            sol_usd.clone(),
+        ]))
+        .build()?;
+
+        let result = backtest
+            .run_per_pair(
+                |crypto_pair| BuyAndHold {
+                    crypto_pair: crypto_pair.clone(),
+                    bought: false,
+                },
+                2,
+            )
+            .await?;
+
+        assert_eq!(result.results.len(), 3);
+        assert_eq!(result.get_trades().len(), 3);
+        assert_eq!(
+            result.rank_by_equity(),
+            vec![
+                (&btc_usd, BigDecimal::from(105)),
+                (&sol_usd, BigDecimal::from(102)),
+                (&eth_usd, BigDecimal::from(97)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn run_per_pair_without_workers() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let backtest = BacktestBuilder::new(
+            InMemoryBarDataSourceBuilder::new().build(),
+            SimulatedBrokerBuilder::new("USD"),
+            start,
+            start + Duration::minutes(1),
+        )
+        .build()?;
+
+        let err = backtest
+            .run_per_pair(
+                |crypto_pair| BuyAndHold {
+                    crypto_pair: crypto_pair.clone(),
+                    bought: false,
+                },
+                0,
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "Worker count must be at least 1");
+        Ok(())
+    }
+}
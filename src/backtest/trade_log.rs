@@ -0,0 +1,254 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Bar, Instrument, OrderSide, Trade};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+/// Position in an asset held from the fill that opened it until the fill that closed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTrip {
+    pub asset_symbol: String,
+    /// Instrument traded, which the asset symbol of an equity doesn't tell, once known, e.g. as
+    /// set by a [Backtest](crate::backtest::Backtest) from the instruments of its orders.
+    pub instrument: Option<Instrument>,
+    /// [OrderSide::Buy] for a long position, [OrderSide::Sell] for a short one.
+    pub side: OrderSide,
+    /// Quantity of the entry fills.
+    pub quantity: BigDecimal,
+    /// Average price of the entry fills, weighted by quantity.
+    pub entry_price: BigDecimal,
+    /// Average price of the exit fills, weighted by quantity.
+    pub exit_price: BigDecimal,
+    pub entry_time: Option<DateTime<Utc>>,
+    pub exit_time: Option<DateTime<Utc>>,
+    /// Fees of every fill, those paid in the traded asset valued at the price of their fill.
+    pub fees: BigDecimal,
+    /// Notional gained, net of fees.
+    pub profit_and_loss: BigDecimal,
+    /// Largest move of the price against the position per unit, once bars were given.
+    pub max_adverse_excursion: Option<BigDecimal>,
+    /// Largest move of the price in favour of the position per unit, once bars were given.
+    pub max_favorable_excursion: Option<BigDecimal>,
+}
+
+impl RoundTrip {
+    pub fn duration(&self) -> Option<Duration> {
+        Some(self.exit_time? - self.entry_time?)
+    }
+
+    /// Sets the excursions from the bars of the asset while the position was held.
+    pub fn set_excursions(&mut self, bars: &[Bar]) {
+        let zero = BigDecimal::from(0);
+        let lowest = bars.iter().map(|bar| &bar.low).min();
+        let highest = bars.iter().map(|bar| &bar.high).max();
+        let (Some(lowest), Some(highest)) = (lowest, highest) else {
+            return;
+        };
+        let (adverse, favorable) = match self.side {
+            OrderSide::Buy => (&self.entry_price - lowest, highest - &self.entry_price),
+            OrderSide::Sell => (highest - &self.entry_price, &self.entry_price - lowest),
+        };
+        self.max_adverse_excursion = Some(adverse.max(zero.clone()));
+        self.max_favorable_excursion = Some(favorable.max(zero));
+    }
+}
+
+/// Fills grouped into [RoundTrip]s, a round trip of an asset closing once its position is back
+/// to zero. A fill crossing zero closes the round trip and opens one on the other side.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TradeLog {
+    round_trips: Vec<RoundTrip>,
+    open_round_trips: HashMap<String, OpenRoundTrip>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpenRoundTrip {
+    side: OrderSide,
+    /// Quantity held, net of the fees paid in the asset.
+    position: BigDecimal,
+    entry_quantity: BigDecimal,
+    entry_notional: BigDecimal,
+    exit_quantity: BigDecimal,
+    exit_notional: BigDecimal,
+    entry_time: Option<DateTime<Utc>>,
+    fees: BigDecimal,
+    notional_fees: BigDecimal,
+}
+
+impl TradeLog {
+    /// Round trips of `trades`, which must be in chronological order.
+    pub fn from_trades(trades: &[Trade]) -> Self {
+        let mut trade_log = Self::default();
+        for trade in trades {
+            trade_log.add_trade(trade);
+        }
+        trade_log
+    }
+
+    /// Closed round trips, in the order they were closed.
+    pub fn round_trips(&self) -> &[RoundTrip] {
+        &self.round_trips
+    }
+
+    pub fn round_trips_mut(&mut self) -> &mut [RoundTrip] {
+        &mut self.round_trips
+    }
+
+    pub fn add_trade(&mut self, trade: &Trade) {
+        let zero = BigDecimal::from(0);
+        let quantity_coin = trade.asset_symbol.split('/').next().unwrap_or_default();
+        let is_fee_in_asset = trade.fee_asset == quantity_coin;
+        let mut remaining = trade.quantity.clone();
+        while remaining > zero {
+            let open_round_trip = self
+                .open_round_trips
+                .entry(trade.asset_symbol.clone())
+                .or_insert_with(|| OpenRoundTrip {
+                    side: trade.side.clone(),
+                    position: BigDecimal::from(0),
+                    entry_quantity: BigDecimal::from(0),
+                    entry_notional: BigDecimal::from(0),
+                    exit_quantity: BigDecimal::from(0),
+                    exit_notional: BigDecimal::from(0),
+                    entry_time: trade.date_time,
+                    fees: BigDecimal::from(0),
+                    notional_fees: BigDecimal::from(0),
+                });
+            let quantity = if open_round_trip.side == trade.side {
+                remaining.clone()
+            } else {
+                remaining.clone().min(open_round_trip.position.clone())
+            };
+            // Share of the fee of the part of the fill in this round trip
+            let fee = &trade.fee * &quantity / &trade.quantity;
+            if is_fee_in_asset {
+                open_round_trip.fees += &fee * &trade.price;
+            } else {
+                open_round_trip.fees += &fee;
+                open_round_trip.notional_fees += &fee;
+            }
+            if open_round_trip.side == trade.side {
+                open_round_trip.entry_quantity += &quantity;
+                open_round_trip.entry_notional += &quantity * &trade.price;
+                open_round_trip.position += &quantity;
+                if is_fee_in_asset {
+                    open_round_trip.position -= &fee;
+                }
+            } else {
+                open_round_trip.exit_quantity += &quantity;
+                open_round_trip.exit_notional += &quantity * &trade.price;
+                open_round_trip.position -= &quantity;
+            }
+            remaining -= &quantity;
+            if open_round_trip.position <= zero {
+                let open_round_trip = self.open_round_trips.remove(&trade.asset_symbol).unwrap();
+                self.round_trips
+                    .push(open_round_trip.close(&trade.asset_symbol, trade.date_time));
+            }
+        }
+    }
+}
+
+impl OpenRoundTrip {
+    fn close(self, asset_symbol: &str, exit_time: Option<DateTime<Utc>>) -> RoundTrip {
+        let gross_profit = match self.side {
+            OrderSide::Buy => &self.exit_notional - &self.entry_notional,
+            OrderSide::Sell => &self.entry_notional - &self.exit_notional,
+        };
+        let average = |notional: &BigDecimal, quantity: &BigDecimal| {
+            if *quantity == 0 {
+                BigDecimal::from(0)
+            } else {
+                notional / quantity
+            }
+        };
+        RoundTrip {
+            asset_symbol: asset_symbol.into(),
+            instrument: None,
+            entry_price: average(&self.entry_notional, &self.entry_quantity),
+            exit_price: average(&self.exit_notional, &self.exit_quantity),
+            side: self.side,
+            quantity: self.entry_quantity,
+            entry_time: self.entry_time,
+            exit_time,
+            fees: self.fees,
+            profit_and_loss: gross_profit - self.notional_fees,
+            max_adverse_excursion: None,
+            max_favorable_excursion: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::OrderId;
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn group_fills_into_round_trips() -> Result<()> {
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let trades = vec![
+            create_trade(OrderSide::Buy, 10, 2, hour),
+            create_trade(OrderSide::Buy, 13, 1, hour + Duration::minutes(1)),
+            create_trade(OrderSide::Sell, 12, 1, hour + Duration::minutes(2)),
+            // Closes the long position and opens a short one
+            create_trade(OrderSide::Sell, 15, 3, hour + Duration::minutes(3)),
+        ];
+
+        let mut trade_log = TradeLog::from_trades(&trades);
+
+        assert_eq!(trade_log.round_trips().len(), 1);
+        let round_trip = &mut trade_log.round_trips_mut()[0];
+        assert_eq!(round_trip.side, OrderSide::Buy);
+        assert_eq!(round_trip.quantity, BigDecimal::from(3));
+        assert_eq!(round_trip.entry_price, BigDecimal::from(11));
+        assert_eq!(round_trip.exit_price, BigDecimal::from(14));
+        // Sold 12 + 2 * 15 for 33, with fees of 1 per unit sold
+        assert_eq!(round_trip.profit_and_loss, BigDecimal::from(6));
+        assert_eq!(round_trip.fees, BigDecimal::from(3));
+        assert_eq!(round_trip.duration(), Some(Duration::minutes(3)));
+
+        round_trip.set_excursions(&[create_bar(9, 16), create_bar(10, 15)]);
+        assert_eq!(round_trip.max_adverse_excursion, Some(BigDecimal::from(2)));
+        assert_eq!(
+            round_trip.max_favorable_excursion,
+            Some(BigDecimal::from(5))
+        );
+        Ok(())
+    }
+
+    fn create_trade(side: OrderSide, price: i32, quantity: i32, date_time: DateTime<Utc>) -> Trade {
+        Trade {
+            trade_id: "trade".into(),
+            order_id: OrderId::new("order"),
+            asset_symbol: "BTC/USD".into(),
+            side: side.clone(),
+            price: BigDecimal::from(price),
+            quantity: BigDecimal::from(quantity),
+            // Fees of sells are paid in dollars, buys don't have any
+            fee: match side {
+                OrderSide::Buy => BigDecimal::from(0),
+                OrderSide::Sell => BigDecimal::from(quantity),
+            },
+            fee_asset: "USD".into(),
+            date_time: Some(date_time),
+        }
+    }
+
+    fn create_bar(low: i32, high: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(low),
+            close: BigDecimal::from(high),
+            date_time: Utc::now(),
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Synchronous wrappers of [Client] and [Market], for code that doesn't run on an async runtime.
+
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{Cursor, OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::response::Page;
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Market};
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use tokio::runtime::{Builder, Runtime};
+
+/// Runs the methods of a [Client] or [Market] to completion on its own single threaded runtime.
+/// The methods must not be called from within an async runtime, as blocking on one would panic.
+#[derive(Debug)]
+pub struct Blocking<T> {
+    inner: T,
+    runtime: Runtime,
+}
+
+impl<T> Blocking<T> {
+    pub fn new(inner: T) -> Result<Self> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|err| Error::Other(err.into()))?;
+        Ok(Self { inner, runtime })
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> Blocking<T>
+where
+    T: Client,
+{
+    pub fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        self.runtime.block_on(self.inner.place_order(req))
+    }
+
+    pub fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        self.runtime.block_on(self.inner.place_orders(reqs))
+    }
+
+    pub fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        self.runtime.block_on(self.inner.validate_order(req))
+    }
+
+    pub fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_orders(filter))
+    }
+
+    pub fn get_orders_page(&self, filter: &OrderFilter, cursor: &Cursor) -> Result<Page<Order>>
+    where
+        T: Sync,
+    {
+        self.runtime
+            .block_on(self.inner.get_orders_page(filter, cursor))
+    }
+
+    pub fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_open_orders(filter))
+    }
+
+    pub fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        self.runtime.block_on(self.inner.get_order(order_id))
+    }
+
+    pub fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.runtime
+            .block_on(self.inner.get_orders_by_ids(order_ids))
+    }
+
+    pub fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        self.runtime
+            .block_on(self.inner.get_order_by_client_id(client_order_id))
+    }
+
+    pub fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        self.runtime.block_on(self.inner.get_trades(filter))
+    }
+
+    pub fn get_trades_page(&self, filter: &TradeFilter, cursor: &Cursor) -> Result<Page<Trade>>
+    where
+        T: Sync,
+    {
+        self.runtime
+            .block_on(self.inner.get_trades_page(filter, cursor))
+    }
+
+    pub fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.runtime.block_on(self.inner.get_order_fills(order_id))
+    }
+
+    pub fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        self.runtime
+            .block_on(self.inner.get_account_activities(range))
+    }
+
+    pub fn get_account_activities_page(
+        &self,
+        range: &TimeRange,
+        cursor: &Cursor,
+    ) -> Result<Page<AccountActivity>>
+    where
+        T: Sync,
+    {
+        self.runtime
+            .block_on(self.inner.get_account_activities_page(range, cursor))
+    }
+
+    pub fn get_account(&self) -> Result<Account> {
+        self.runtime.block_on(self.inner.get_account())
+    }
+
+    pub fn get_equity(&self) -> Result<BigDecimal> {
+        self.runtime.block_on(self.inner.get_equity())
+    }
+
+    pub fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.runtime.block_on(self.inner.get_asset(crypto_pair))
+    }
+
+    pub fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        self.runtime.block_on(self.inner.list_asset_pairs())
+    }
+
+    pub fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.runtime.block_on(self.inner.get_position(asset_symbol))
+    }
+
+    pub fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        T: Sync,
+    {
+        self.runtime.block_on(self.inner.cancel_order(order_id))
+    }
+
+    pub fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.runtime.block_on(self.inner.cancel_orders(order_ids))
+    }
+
+    pub fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.runtime.block_on(self.inner.cancel_all_orders())
+    }
+
+    pub fn close_all_positions(&self) -> Result<FlattenSummary> {
+        self.runtime.block_on(self.inner.close_all_positions())
+    }
+
+    /// The updates can be received with [OrderUpdateStream::blocking_next].
+    pub fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        self.runtime.block_on(self.inner.subscribe_order_updates())
+    }
+}
+
+impl<T> Blocking<T>
+where
+    T: Market,
+{
+    pub fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        self.runtime
+            .block_on(self.inner.get_latest_minute_bar(crypto_pair))
+    }
+
+    pub fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        self.runtime
+            .block_on(self.inner.get_latest_minute_bars(crypto_pairs))
+    }
+
+    pub fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        self.runtime
+            .block_on(self.inner.get_timeframe_bars(crypto_pair, timeframes))
+    }
+
+    pub fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        self.runtime
+            .block_on(self.inner.get_bars(crypto_pair, start, end, timeframe))
+    }
+
+    pub fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        self.runtime.block_on(self.inner.get_calendar())
+    }
+
+    pub fn get_clock(&self) -> Result<MarketClock>
+    where
+        T: Sync,
+    {
+        self.runtime.block_on(self.inner.get_clock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, OrderStatus};
+    use crate::simulated::{SimulatedBrokerBuilder, SimulatedClient};
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn blocking_client() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let client = SimulatedClient::new(broker);
+        let crypto_pair = Instrument::from_str("GBP/USD")?;
+        client.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(2))?;
+        let client = Blocking::new(client)?;
+        let mut updates = client.subscribe_order_updates()?;
+
+        let order_id = client.place_order(OrderRequest::market_buy(
+            crypto_pair,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+        ))?;
+
+        assert_eq!(client.get_order(&order_id)?.status, OrderStatus::Filled);
+        assert_eq!(client.get_account()?.cash, BigDecimal::from(80));
+        assert_eq!(updates.blocking_next().unwrap().order.order_id, order_id);
+        Ok(())
+    }
+}
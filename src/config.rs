@@ -0,0 +1,281 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Typed configuration of simulated environments, loaded from TOML:
+//!
+//! ```toml
+//! pairs = ["BTC/USD"]
+//!
+//! [broker]
+//! currency = "USD"
+//! balance = "1000"
+//! fee_percentage = "0.25"
+//!
+//! [environment]
+//! bar_price_model = "uniform"
+//! seed = 42
+//!
+//! [risk]
+//! cash_reserve = { percentage = "5" }
+//! max_price_age_seconds = 300
+//!
+//! [providers.alpaca]
+//! api_key_env = "ALPACA_API_KEY"
+//! api_secret_env = "ALPACA_API_SECRET"
+//! ```
+
+use crate::api::common::Instrument;
+use crate::api::credentials::{Credentials, CredentialsProvider, EnvCredentialsProvider};
+use crate::api::validation::OrderValidatorBuilder;
+use crate::simulated::price::BarPriceModel;
+use crate::simulated::{CashReserve, SimulatedBrokerBuilder};
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use chrono::Duration;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct IrontradeConfig {
+    pub broker: BrokerConfig,
+    #[serde(default)]
+    pub pairs: Vec<String>,
+    #[serde(default)]
+    pub environment: EnvironmentConfig,
+    /// Bar data source to be created by the caller, since its kind depends on the user's data.
+    pub data_source: Option<DataSourceConfig>,
+    #[serde(default)]
+    pub providers: HashMap<String, ProviderConfig>,
+    #[serde(default)]
+    pub risk: RiskConfig,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BrokerConfig {
+    pub currency: String,
+    pub balance: Option<BigDecimal>,
+    pub fee_percentage: Option<BigDecimal>,
+    pub slippage_percentage: Option<BigDecimal>,
+    /// Additional notional assets with their starting balances.
+    #[serde(default)]
+    pub notional_assets: HashMap<String, BigDecimal>,
+    pub margin: Option<MarginConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MarginConfig {
+    pub leverage: BigDecimal,
+    pub maintenance_margin_percentage: BigDecimal,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EnvironmentConfig {
+    pub bar_duration_seconds: Option<i64>,
+    pub refresh_duration_seconds: Option<i64>,
+    #[serde(default)]
+    pub bar_price_model: BarPriceModel,
+    pub seed: Option<u64>,
+    pub run_id: Option<String>,
+    pub strategy_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DataSourceConfig {
+    pub kind: String,
+    pub path: Option<String>,
+}
+
+/// Credentials of a provider, referenced by the names of the environment variables holding them
+/// so that secrets stay out of configuration files.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderConfig {
+    pub api_key_env: String,
+    pub api_secret_env: String,
+    pub base_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RiskConfig {
+    pub cash_reserve: Option<CashReserve>,
+    pub max_price_age_seconds: Option<i64>,
+    pub min_notional: Option<BigDecimal>,
+    pub max_notional: Option<BigDecimal>,
+    pub max_quantity_scale: Option<i64>,
+    pub max_limit_price_scale: Option<i64>,
+    pub max_consecutive_rejections: Option<u32>,
+}
+
+impl IrontradeConfig {
+    pub fn load<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    pub fn get_crypto_pairs(&self) -> Result<HashSet<Instrument>> {
+        self.pairs
+            .iter()
+            .map(|pair| Ok(Instrument::from_str(pair)?))
+            .collect()
+    }
+
+    /// Builder of the broker described by the broker and risk settings.
+    pub fn get_broker_builder(&self) -> Result<SimulatedBrokerBuilder> {
+        let mut builder = SimulatedBrokerBuilder::new(&self.broker.currency);
+        if let Some(balance) = &self.broker.balance {
+            builder.set_balance(balance.clone());
+        }
+        if let Some(fee_percentage) = &self.broker.fee_percentage {
+            builder.set_fee_percentage_up_to_one_hundred(fee_percentage.clone())?;
+        }
+        if let Some(slippage_percentage) = &self.broker.slippage_percentage {
+            builder.set_slippage_percentage(slippage_percentage.clone())?;
+        }
+        for (notional_asset, balance) in &self.broker.notional_assets {
+            builder.add_notional_asset(notional_asset, Some(balance.clone()));
+        }
+        if let Some(margin) = &self.broker.margin {
+            builder.set_margin(
+                margin.leverage.clone(),
+                margin.maintenance_margin_percentage.clone(),
+            )?;
+        }
+        if let Some(cash_reserve) = &self.risk.cash_reserve {
+            builder.set_cash_reserve(cash_reserve.clone())?;
+        }
+        if let Some(max_price_age_seconds) = self.risk.max_price_age_seconds {
+            builder.set_max_price_age(Duration::seconds(max_price_age_seconds));
+        }
+        if let Some(max_consecutive_rejections) = self.risk.max_consecutive_rejections {
+            builder.set_max_consecutive_rejections(max_consecutive_rejections)?;
+        }
+        let mut validator_builder = OrderValidatorBuilder::new();
+        if let Some(min_notional) = &self.risk.min_notional {
+            validator_builder.set_min_notional(min_notional.clone());
+        }
+        if let Some(max_notional) = &self.risk.max_notional {
+            validator_builder.set_max_notional(max_notional.clone());
+        }
+        if let Some(max_quantity_scale) = self.risk.max_quantity_scale {
+            validator_builder.set_max_quantity_scale(max_quantity_scale);
+        }
+        if let Some(max_limit_price_scale) = self.risk.max_limit_price_scale {
+            validator_builder.set_max_limit_price_scale(max_limit_price_scale);
+        }
+        builder.set_order_validator(validator_builder.build());
+        Ok(builder)
+    }
+}
+
+impl ProviderConfig {
+    pub fn get_credentials_provider(&self) -> EnvCredentialsProvider {
+        EnvCredentialsProvider::new(&self.api_key_env, &self.api_secret_env)
+    }
+
+    /// Reads the API key and secret from their environment variables.
+    pub fn get_credentials(&self) -> Result<Credentials> {
+        self.get_credentials_provider().get_credentials()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+pairs = ["BTC/USD", "ETH/USD"]
+
+[broker]
+currency = "USD"
+balance = "1000"
+fee_percentage = "0.25"
+notional_assets = { USDT = "50" }
+
+[environment]
+bar_price_model = "close_weighted"
+seed = 7
+
+[data_source]
+kind = "csv"
+path = "bars.csv"
+
+[risk]
+cash_reserve = { percentage = "10" }
+min_notional = "1"
+
+[providers.alpaca]
+api_key_env = "IRONTRADE_TEST_MISSING_KEY"
+api_secret_env = "IRONTRADE_TEST_MISSING_SECRET"
+"#;
+
+    #[test]
+    fn from_toml() -> Result<()> {
+        let config = IrontradeConfig::from_toml(CONFIG)?;
+
+        assert_eq!(config.broker.currency, "USD");
+        assert_eq!(config.broker.balance, Some(BigDecimal::from(1000)));
+        assert_eq!(config.get_crypto_pairs()?.len(), 2);
+        assert_eq!(
+            config.environment.bar_price_model,
+            BarPriceModel::CloseWeighted
+        );
+        assert_eq!(config.environment.seed, Some(7));
+        assert_eq!(
+            config.data_source,
+            Some(DataSourceConfig {
+                kind: "csv".into(),
+                path: Some("bars.csv".into()),
+            })
+        );
+        assert_eq!(
+            config.risk.cash_reserve,
+            Some(CashReserve::Percentage(BigDecimal::from(10)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_broker_builder() -> Result<()> {
+        let config = IrontradeConfig::from_toml(CONFIG)?;
+
+        let broker = config.get_broker_builder()?.build()?;
+
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(1000));
+        assert_eq!(broker.get_balance("USDT"), BigDecimal::from(50));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(900));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_credentials() -> Result<()> {
+        let config = IrontradeConfig::from_toml(CONFIG)?;
+
+        let err = config.providers["alpaca"].get_credentials().unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Environment variable IRONTRADE_TEST_MISSING_KEY is not set"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_field() {
+        let err = IrontradeConfig::from_toml("[broker]\ncurrency = \"USD\"\nleverage = 2");
+        assert!(err.is_err());
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Instrument, InstrumentParseError, OrderId};
+use crate::api::validation::ValidationError;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::convert::Infallible;
+
+/// Errors returned by [Client](crate::api::Client), [Market](crate::api::Market) and the
+/// simulated broker, so that callers can match on the failure instead of its message.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Not enough {asset} buying power")]
+    InsufficientBuyingPower {
+        asset: String,
+        needed: BigDecimal,
+        available: BigDecimal,
+    },
+    #[error("Order with id {0} doesn't exist")]
+    UnknownOrder(OrderId),
+    #[error("Order with client order id {0} doesn't exist")]
+    UnknownClientOrderId(String),
+    #[error("Order with id {0} is not open")]
+    OrderNotOpen(OrderId),
+    /// The pair has no known price.
+    #[error("{0} does not have notional per unit")]
+    InvalidAssetPair(Instrument),
+    #[error("{0} is not a valid notional asset")]
+    InvalidNotionalAsset(String),
+    #[error("Trading {0} is disabled")]
+    TradingDisabled(Instrument),
+    #[error("{crypto_pair} price was last set at {updated_at} and is stale")]
+    StalePrice {
+        crypto_pair: Instrument,
+        updated_at: DateTime<Utc>,
+    },
+    #[error("Account {0} doesn't exist")]
+    UnknownAccount(String),
+    #[error("Account {0} already exists")]
+    AccountAlreadyExists(String),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    /// An argument or setting out of its valid range.
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// A request to a live provider failed or returned an unexpected response.
+    #[error("{0}")]
+    Provider(String),
+    /// A request to a live provider timed out, didn't reach it or was turned away with a 429 or
+    /// 5xx status, and may succeed if attempted again.
+    #[error("{0}")]
+    ProviderUnavailable(String),
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        // Errors of this crate may have been wrapped by a component still using anyhow
+        match err.downcast::<Error>() {
+            Ok(err) => err,
+            Err(err) => Self::Other(err),
+        }
+    }
+}
+
+impl From<InstrumentParseError> for Error {
+    fn from(err: InstrumentParseError) -> Self {
+        Self::InvalidArgument(err.to_string())
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(err: Infallible) -> Self {
+        match err {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn from_anyhow_keeps_typed_errors() {
+        let err = Error::from(anyhow::Error::from(Error::UnknownOrder(OrderId::new("order"))));
+        assert!(matches!(err, Error::UnknownOrder(order_id) if order_id.as_str() == "order"));
+
+        let err = Error::from(anyhow!("Something went wrong"));
+        assert!(matches!(err, Error::Other(_)));
+        assert_eq!(err.to_string(), "Something went wrong");
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Technical indicators updated one bar at a time, computed in [BigDecimal] like the prices of
+//! the bars they are computed from.
+
+use crate::api::common::Bar;
+use crate::indicators::rolling::RollingMean;
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+
+pub mod rolling;
+
+/// Number of decimal places indicator values are rounded to, keeping their precision bounded as
+/// they are updated.
+pub const INDICATOR_SCALE: i64 = 12;
+
+/// Value of a bar an indicator is computed from.
+pub type BarExtractor = fn(&Bar) -> BigDecimal;
+
+pub fn close(bar: &Bar) -> BigDecimal {
+    bar.close.clone()
+}
+
+pub fn high(bar: &Bar) -> BigDecimal {
+    bar.high.clone()
+}
+
+pub fn low(bar: &Bar) -> BigDecimal {
+    bar.low.clone()
+}
+
+pub fn volume(bar: &Bar) -> BigDecimal {
+    bar.volume.clone()
+}
+
+/// Average of the high, low and close.
+pub fn typical_price(bar: &Bar) -> BigDecimal {
+    ((&bar.high + &bar.low + &bar.close) / BigDecimal::from(3)).round(INDICATOR_SCALE)
+}
+
+/// Simple moving average of the last `period` values.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    extractor: BarExtractor,
+    mean: RollingMean,
+}
+
+impl Sma {
+    /// Average of the closes.
+    pub fn new(period: usize) -> Result<Self> {
+        Self::with_extractor(period, close)
+    }
+
+    pub fn with_extractor(period: usize, extractor: BarExtractor) -> Result<Self> {
+        check_period(period)?;
+        Ok(Self {
+            extractor,
+            mean: RollingMean::new(period)?,
+        })
+    }
+
+    /// Adds the next value, returning the average once `period` values were added.
+    pub fn push(&mut self, value: BigDecimal) -> Option<BigDecimal> {
+        self.mean.push(value)
+    }
+
+    pub fn push_bar(&mut self, bar: &Bar) -> Option<BigDecimal> {
+        self.push((self.extractor)(bar))
+    }
+
+    pub fn value(&self) -> Option<BigDecimal> {
+        self.mean.value()
+    }
+}
+
+/// Exponential moving average weighting each value by `2 / (period + 1)`, starting from the
+/// simple moving average of the first `period` values.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    extractor: BarExtractor,
+    smoothing: BigDecimal,
+    warm_up: Sma,
+    value: Option<BigDecimal>,
+}
+
+impl Ema {
+    /// Average of the closes.
+    pub fn new(period: usize) -> Result<Self> {
+        Self::with_extractor(period, close)
+    }
+
+    pub fn with_extractor(period: usize, extractor: BarExtractor) -> Result<Self> {
+        check_period(period)?;
+        Ok(Self {
+            extractor,
+            smoothing: BigDecimal::from(2) / BigDecimal::from(period as u64 + 1),
+            warm_up: Sma::with_extractor(period, extractor)?,
+            value: None,
+        })
+    }
+
+    /// Adds the next value, returning the average once `period` values were added.
+    pub fn push(&mut self, value: BigDecimal) -> Option<BigDecimal> {
+        self.value = match self.value.take() {
+            Some(previous) => {
+                Some((&previous + (value - &previous) * &self.smoothing).round(INDICATOR_SCALE))
+            }
+            None => self.warm_up.push(value),
+        };
+        self.value.clone()
+    }
+
+    pub fn push_bar(&mut self, bar: &Bar) -> Option<BigDecimal> {
+        self.push((self.extractor)(bar))
+    }
+
+    pub fn value(&self) -> Option<BigDecimal> {
+        self.value.clone()
+    }
+}
+
+fn check_period(period: usize) -> Result<()> {
+    if period == 0 {
+        return Err(Error::InvalidArgument(
+            "Indicator period must be at least 1".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::Instrument;
+    use crate::simulated::data::InMemoryBarDataSource;
+    use anyhow::Result;
+    use chrono::{DateTime, Duration, Utc};
+    use std::str::FromStr;
+
+    #[test]
+    fn sma_of_closes() -> Result<()> {
+        let mut sma = Sma::new(3)?;
+
+        let averages: Vec<Option<BigDecimal>> = create_bars([1, 2, 3, 4, 8])?
+            .iter()
+            .map(|bar| sma.push_bar(bar))
+            .collect();
+
+        assert_eq!(
+            averages,
+            vec![
+                None,
+                None,
+                Some(BigDecimal::from(2)),
+                Some(BigDecimal::from(3)),
+                Some(BigDecimal::from(5)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ema_starts_from_sma() -> Result<()> {
+        let mut ema = Ema::new(3)?;
+
+        let averages: Vec<Option<BigDecimal>> = [2, 4, 6, 10, 4]
+            .into_iter()
+            .map(|value| ema.push(BigDecimal::from(value)))
+            .collect();
+
+        assert_eq!(
+            averages,
+            vec![
+                None,
+                None,
+                Some(BigDecimal::from(4)),
+                Some(BigDecimal::from(7)),
+                Some(BigDecimal::from_str("5.5")?),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn indicators_of_other_values() -> Result<()> {
+        let mut sma = Sma::with_extractor(2, high)?;
+        let mut ema = Ema::with_extractor(1, |bar| &bar.close - &bar.open)?;
+
+        for bar in create_bars([5, 7])? {
+            sma.push_bar(&bar);
+            ema.push_bar(&bar);
+        }
+
+        // Bars open one below and peak one above their close
+        assert_eq!(sma.value(), Some(BigDecimal::from(7)));
+        assert_eq!(ema.value(), Some(BigDecimal::from(1)));
+        Ok(())
+    }
+
+    #[test]
+    fn zero_period() {
+        assert_eq!(
+            Sma::new(0).unwrap_err().to_string(),
+            "Indicator period must be at least 1"
+        );
+        assert!(Ema::new(0).is_err());
+    }
+
+    fn create_bars<const N: usize>(closes: [i32; N]) -> Result<Vec<Bar>> {
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bars = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::minutes(1),
+            closes,
+        )
+        .get_bars(&crypto_pair);
+        Ok(bars
+            .into_iter()
+            .map(|bar| Bar {
+                open: &bar.close - BigDecimal::from(1),
+                high: &bar.close + BigDecimal::from(1),
+                ..bar
+            })
+            .collect())
+    }
+}
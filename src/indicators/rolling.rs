@@ -0,0 +1,333 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Statistics over the last values of a series, each updated in amortized constant time.
+
+use crate::api::common::Bar;
+use crate::indicators::INDICATOR_SCALE;
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use std::collections::VecDeque;
+
+/// Last `period` values pushed, oldest first.
+#[derive(Debug, Clone)]
+pub struct RollingWindow<T> {
+    period: usize,
+    values: VecDeque<T>,
+}
+
+impl<T> RollingWindow<T> {
+    pub fn new(period: usize) -> Result<Self> {
+        check_period(period)?;
+        Ok(Self {
+            period,
+            values: VecDeque::with_capacity(period + 1),
+        })
+    }
+
+    /// Adds the next value, returning the oldest one once the window was full.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        self.values.push_back(value);
+        if self.values.len() > self.period {
+            return self.values.pop_front();
+        }
+        None
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.period
+    }
+
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    pub fn values(&self) -> &VecDeque<T> {
+        &self.values
+    }
+}
+
+/// Mean of the last `period` values.
+#[derive(Debug, Clone)]
+pub struct RollingMean {
+    window: RollingWindow<BigDecimal>,
+    sum: BigDecimal,
+}
+
+impl RollingMean {
+    pub fn new(period: usize) -> Result<Self> {
+        Ok(Self {
+            window: RollingWindow::new(period)?,
+            sum: BigDecimal::from(0),
+        })
+    }
+
+    /// Adds the next value, returning the mean once `period` values were added.
+    pub fn push(&mut self, value: BigDecimal) -> Option<BigDecimal> {
+        self.sum += &value;
+        if let Some(oldest) = self.window.push(value) {
+            self.sum -= oldest;
+        }
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<BigDecimal> {
+        if !self.window.is_full() {
+            return None;
+        }
+        Some((&self.sum / BigDecimal::from(self.window.period() as u64)).round(INDICATOR_SCALE))
+    }
+}
+
+/// Population standard deviation of the last `period` values.
+#[derive(Debug, Clone)]
+pub struct RollingStd {
+    window: RollingWindow<BigDecimal>,
+    sum: BigDecimal,
+    sum_of_squares: BigDecimal,
+}
+
+impl RollingStd {
+    pub fn new(period: usize) -> Result<Self> {
+        Ok(Self {
+            window: RollingWindow::new(period)?,
+            sum: BigDecimal::from(0),
+            sum_of_squares: BigDecimal::from(0),
+        })
+    }
+
+    /// Adds the next value, returning the standard deviation once `period` values were added.
+    pub fn push(&mut self, value: BigDecimal) -> Option<BigDecimal> {
+        self.sum += &value;
+        self.sum_of_squares += &value * &value;
+        if let Some(oldest) = self.window.push(value) {
+            self.sum_of_squares -= &oldest * &oldest;
+            self.sum -= oldest;
+        }
+        self.value()
+    }
+
+    pub fn value(&self) -> Option<BigDecimal> {
+        if !self.window.is_full() {
+            return None;
+        }
+        let count = BigDecimal::from(self.window.period() as u64);
+        // Exact until the division, so never negative
+        let variance = (&count * &self.sum_of_squares - &self.sum * &self.sum) / (&count * &count);
+        variance.sqrt().map(|std| std.round(INDICATOR_SCALE))
+    }
+}
+
+/// Maximum of the last `period` values.
+#[derive(Debug, Clone)]
+pub struct RollingMax<T> {
+    extremum: RollingExtremum<T>,
+}
+
+impl<T: Ord + Clone> RollingMax<T> {
+    pub fn new(period: usize) -> Result<Self> {
+        Ok(Self {
+            extremum: RollingExtremum::new(period, true)?,
+        })
+    }
+
+    /// Adds the next value, returning the maximum once `period` values were added.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        self.extremum.push(value)
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.extremum.value()
+    }
+}
+
+/// Minimum of the last `period` values.
+#[derive(Debug, Clone)]
+pub struct RollingMin<T> {
+    extremum: RollingExtremum<T>,
+}
+
+impl<T: Ord + Clone> RollingMin<T> {
+    pub fn new(period: usize) -> Result<Self> {
+        Ok(Self {
+            extremum: RollingExtremum::new(period, false)?,
+        })
+    }
+
+    /// Adds the next value, returning the minimum once `period` values were added.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        self.extremum.push(value)
+    }
+
+    pub fn value(&self) -> Option<T> {
+        self.extremum.value()
+    }
+}
+
+/// Highest high of the last `period` bars.
+#[derive(Debug, Clone)]
+pub struct HighestHigh {
+    max: RollingMax<BigDecimal>,
+}
+
+impl HighestHigh {
+    pub fn new(period: usize) -> Result<Self> {
+        Ok(Self {
+            max: RollingMax::new(period)?,
+        })
+    }
+
+    pub fn push_bar(&mut self, bar: &Bar) -> Option<BigDecimal> {
+        self.max.push(bar.high.clone())
+    }
+
+    pub fn value(&self) -> Option<BigDecimal> {
+        self.max.value()
+    }
+}
+
+/// Lowest low of the last `period` bars.
+#[derive(Debug, Clone)]
+pub struct LowestLow {
+    min: RollingMin<BigDecimal>,
+}
+
+impl LowestLow {
+    pub fn new(period: usize) -> Result<Self> {
+        Ok(Self {
+            min: RollingMin::new(period)?,
+        })
+    }
+
+    pub fn push_bar(&mut self, bar: &Bar) -> Option<BigDecimal> {
+        self.min.push(bar.low.clone())
+    }
+
+    pub fn value(&self) -> Option<BigDecimal> {
+        self.min.value()
+    }
+}
+
+/// Maximum or minimum of a window, keeping only the values that can still become the extremum
+/// once the values before them leave the window.
+#[derive(Debug, Clone)]
+struct RollingExtremum<T> {
+    period: usize,
+    is_max: bool,
+    push_count: usize,
+    /// Candidates with the number of values pushed before them, the extremum first.
+    candidates: VecDeque<(usize, T)>,
+}
+
+impl<T: Ord + Clone> RollingExtremum<T> {
+    fn new(period: usize, is_max: bool) -> Result<Self> {
+        check_period(period)?;
+        Ok(Self {
+            period,
+            is_max,
+            push_count: 0,
+            candidates: VecDeque::new(),
+        })
+    }
+
+    fn push(&mut self, value: T) -> Option<T> {
+        while let Some((_, last)) = self.candidates.back()
+            && !self.outlasts(last, &value)
+        {
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((self.push_count, value));
+        self.push_count += 1;
+        if let Some((index, _)) = self.candidates.front()
+            && *index + self.period < self.push_count
+        {
+            self.candidates.pop_front();
+        }
+        self.value()
+    }
+
+    /// Whether `older` can still be the extremum once `newer` is in the window.
+    fn outlasts(&self, older: &T, newer: &T) -> bool {
+        if self.is_max {
+            older > newer
+        } else {
+            older < newer
+        }
+    }
+
+    fn value(&self) -> Option<T> {
+        if self.push_count < self.period {
+            return None;
+        }
+        self.candidates.front().map(|(_, value)| value.clone())
+    }
+}
+
+fn check_period(period: usize) -> Result<()> {
+    if period == 0 {
+        return Err(Error::InvalidArgument(
+            "Rolling window period must be at least 1".into(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn rolling_max_and_min() -> Result<()> {
+        let mut max = RollingMax::new(3)?;
+        let mut min = RollingMin::new(3)?;
+
+        let mut extremums = Vec::new();
+        for value in [5, 1, 4, 2, 3, 0, 6] {
+            extremums.push((max.push(value), min.push(value)));
+        }
+
+        assert_eq!(
+            extremums,
+            vec![
+                (None, None),
+                (None, None),
+                (Some(5), Some(1)),
+                (Some(4), Some(1)),
+                (Some(4), Some(2)),
+                (Some(3), Some(0)),
+                (Some(6), Some(0)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rolling_mean_and_std() -> Result<()> {
+        let mut mean = RollingMean::new(4)?;
+        let mut std = RollingStd::new(4)?;
+
+        for value in [100, 2, 4, 4, 4] {
+            mean.push(BigDecimal::from(value));
+            std.push(BigDecimal::from(value));
+        }
+        assert_eq!(mean.value(), Some(BigDecimal::from_str("3.5")?));
+        assert_eq!(std.value(), Some(BigDecimal::from_str("0.866025403784")?));
+
+        mean.push(BigDecimal::from(4));
+        std.push(BigDecimal::from(4));
+        assert_eq!(mean.value(), Some(BigDecimal::from(4)));
+        assert_eq!(std.value(), Some(BigDecimal::from(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn zero_period() {
+        assert_eq!(
+            RollingWindow::<i32>::new(0).unwrap_err().to_string(),
+            "Rolling window period must be at least 1"
+        );
+        assert!(RollingMax::<i32>::new(0).is_err());
+    }
+}
@@ -3,8 +3,21 @@
 
 #![doc = include_str!("../README.md")]
 
+pub use error::{Error, Result};
+mod error;
+
+/// Decimal type of every price, quantity and balance, re-exported so that users build them with
+/// the same version of the crate.
+pub use bigdecimal;
+
 pub mod api;
+pub mod backtest;
+pub mod blocking;
+pub mod indicators;
 pub mod simulated;
 
 #[cfg(feature = "live_market")]
-pub mod live_market;
\ No newline at end of file
+pub mod live_market;
+
+#[cfg(feature = "config")]
+pub mod config;
\ No newline at end of file
@@ -1,104 +1,454 @@
 // Copyright (C) 2025 Agostinho Junior
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::api::common::{Account, Bar, CryptoPair, Order};
-use crate::api::request::OrderRequest;
-use crate::api::{Client, Environment, Market};
-use anyhow::Result;
-use async_trait::async_trait;
-use live_market::LiveMarket;
-
-struct LiveEnvironment {
-    client: Box<dyn Client + Send + Sync>,
-    market: LiveMarket,
-}
+//! Live market data, each provider behind its own feature: `alpaca` for the Alpaca data API,
+//! `binance` for Binance klines and `market_stream` for the Alpaca websocket stream.
 
-pub fn create_env<T>(client: T) -> impl Environment
-where
-    T: Client + Send + Sync + 'static,
-{
-    LiveEnvironment {
-        client: Box::new(client),
-        market: LiveMarket,
-    }
-}
+#[cfg(feature = "alpaca")]
+pub use environment::{create_env, create_env_with_market};
+#[cfg(feature = "alpaca")]
+mod environment;
 
-#[async_trait]
-impl Client for LiveEnvironment {
-    async fn place_order(&mut self, req: OrderRequest) -> Result<String> {
-        self.client.place_order(req).await
-    }
+#[cfg(feature = "alpaca")]
+pub use market::{LiveMarket, LiveMarketBuilder};
 
-    async fn get_orders(&mut self) -> Result<Vec<Order>> {
-        self.client.get_orders().await
-    }
+#[cfg(feature = "binance")]
+pub use binance::{BinanceBarDataSource, to_binance_symbol};
+#[cfg(feature = "binance")]
+mod binance;
 
-    async fn get_order(&mut self, order_id: &str) -> Result<Order> {
-        self.client.get_order(order_id).await
-    }
+#[cfg(feature = "alpaca")]
+mod cache;
 
-    async fn get_account(&mut self) -> Result<Account> {
-        self.client.get_account().await
-    }
-}
+#[cfg(feature = "alpaca")]
+pub use alpaca_data::AlpacaBarDataSource;
+#[cfg(feature = "alpaca")]
+mod alpaca_data;
 
-#[async_trait]
-impl Market for LiveEnvironment {
-    async fn get_latest_minute_bar(&self, crypto_pair: &CryptoPair) -> Result<Option<Bar>> {
-        self.market.get_latest_minute_bar(crypto_pair).await
-    }
-}
+mod data;
+
+pub use paper::{PaperEnvironment, PaperEnvironmentBuilder};
+mod paper;
+
+#[cfg(any(feature = "alpaca", feature = "binance"))]
+mod http;
 
-impl Environment for LiveEnvironment {}
+pub use recorder::{BarRecorder, RecordedBarDataSource};
+mod recorder;
 
-mod live_market {
+#[cfg(feature = "market_stream")]
+pub use stream::{BarUpdate, MarketChannel, MarketEvent, MarketStream, QuoteUpdate, TradeUpdate};
+#[cfg(feature = "market_stream")]
+mod stream;
+
+#[cfg(feature = "market_stream")]
+pub use bar_builder::BarBuilder;
+#[cfg(feature = "market_stream")]
+mod bar_builder;
+
+#[cfg(feature = "alpaca")]
+mod market {
     use crate::api::Market;
-    use crate::api::common::{Bar, CryptoPair};
-    use anyhow::Result;
+    use crate::api::calendar::{AlwaysOpenCalendar, MarketCalendar, MarketClock};
+    use crate::api::common::{AssetClass, Bar, Instrument, TimeframeBars};
+    use crate::api::credentials::{Credentials, CredentialsProvider};
+    use crate::api::market::{aggregate_timeframe_bars, check_timeframes, get_timeframes_start};
+    use crate::api::rate_limit::RateLimiter;
+    use crate::api::retry::RetryPolicy;
+    use crate::api::timeframe::Timeframe;
+    use crate::live_market::cache::LatestBarCache;
+    use crate::live_market::http::execute_request;
+    use crate::{Error, Result};
     use async_trait::async_trait;
     use bigdecimal::BigDecimal;
-    use chrono::{DateTime, Utc};
-    use reqwest::header::{HeaderMap, HeaderValue};
+    use chrono::{DateTime, Duration, SecondsFormat, Utc};
+    use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
     use serde::Deserialize;
     use serde::de::DeserializeOwned;
     use serde_this_or_that::as_string;
     use std::collections::HashMap;
+    use std::num::NonZeroU32;
     use std::str::FromStr;
 
-    pub struct LiveMarket;
+    const DEFAULT_BASE_URL: &str = "https://data.alpaca.markets";
+    const DEFAULT_REGION: &str = "eu-1";
+    const BASIC_PLAN_REQUESTS_PER_MINUTE: NonZeroU32 = NonZeroU32::new(200).unwrap();
+
+    /// Market data of the Alpaca crypto data API.
+    #[derive(Debug, Clone)]
+    pub struct LiveMarket {
+        base_url: String,
+        trading_base_url: Option<String>,
+        region: String,
+        header_map: HeaderMap,
+        retry_policy: RetryPolicy,
+        rate_limiter: Option<RateLimiter>,
+        latest_bar_cache: Option<LatestBarCache>,
+    }
+
+    impl Default for LiveMarket {
+        fn default() -> Self {
+            Self {
+                base_url: DEFAULT_BASE_URL.into(),
+                trading_base_url: None,
+                region: DEFAULT_REGION.into(),
+                header_map: HeaderMap::from_iter([(
+                    reqwest::header::ACCEPT,
+                    HeaderValue::from_static("application/json"),
+                )]),
+                retry_policy: RetryPolicy::default(),
+                rate_limiter: Some(RateLimiter::per_minute(BASIC_PLAN_REQUESTS_PER_MINUTE)),
+                latest_bar_cache: None,
+            }
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub struct LiveMarketBuilder {
+        base_url: Option<String>,
+        trading_base_url: Option<String>,
+        region: Option<String>,
+        credentials: Option<Credentials>,
+        credentials_provider: Option<Box<dyn CredentialsProvider + Send + Sync>>,
+        headers: Vec<(String, String)>,
+        retry_policy: Option<RetryPolicy>,
+        rate_limiter: Option<Option<RateLimiter>>,
+        latest_bar_ttl: Option<std::time::Duration>,
+    }
+
+    impl LiveMarketBuilder {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Defaults to `https://data.alpaca.markets`.
+        pub fn set_base_url(&mut self, base_url: &str) -> &mut Self {
+            self.base_url = Some(base_url.trim_end_matches('/').into());
+            self
+        }
+
+        /// Base URL of the trading API the market clock is read from, e.g.
+        /// `https://paper-api.alpaca.markets`, with the same credentials as the data API.
+        pub fn set_trading_base_url(&mut self, trading_base_url: &str) -> &mut Self {
+            self.trading_base_url = Some(trading_base_url.trim_end_matches('/').into());
+            self
+        }
+
+        /// Location of the crypto exchange data is read from, `eu-1` by default.
+        pub fn set_region(&mut self, region: &str) -> &mut Self {
+            self.region = Some(region.into());
+            self
+        }
+
+        /// Authenticates the requests, which Alpaca rate limits less than anonymous ones.
+        pub fn set_credentials(&mut self, credentials: Credentials) -> &mut Self {
+            self.credentials = Some(credentials);
+            self
+        }
+
+        /// Reads the credentials when built, e.g. from environment variables with an
+        /// [EnvCredentialsProvider](crate::api::credentials::EnvCredentialsProvider).
+        pub fn set_credentials_provider<P>(&mut self, credentials_provider: P) -> &mut Self
+        where
+            P: CredentialsProvider + Send + Sync + 'static,
+        {
+            self.credentials_provider = Some(Box::new(credentials_provider));
+            self
+        }
+
+        /// Sends a header with every request.
+        pub fn add_header(&mut self, name: &str, value: &str) -> &mut Self {
+            self.headers.push((name.into(), value.into()));
+            self
+        }
+
+        /// Retries requests failing with a transport error, a 429 or a 5xx status, as set by
+        /// [RetryPolicy::default] unless set otherwise.
+        pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) -> &mut Self {
+            self.retry_policy = Some(retry_policy);
+            self
+        }
+
+        /// Limits the requests sent, by default to the 200 requests per minute of the Alpaca
+        /// basic plan. Markets given clones of the same limiter share it.
+        pub fn set_rate_limiter(&mut self, rate_limiter: RateLimiter) -> &mut Self {
+            self.rate_limiter = Some(Some(rate_limiter));
+            self
+        }
+
+        /// Sends requests without limiting them, e.g. when the limit is enforced elsewhere.
+        pub fn disable_rate_limiter(&mut self) -> &mut Self {
+            self.rate_limiter = Some(None);
+            self
+        }
+
+        /// Reuses the latest minute bar of a pair fetched within the last `ttl`, e.g. five seconds,
+        /// rather than fetching it again for every caller. Clones of the market share the cache.
+        pub fn set_latest_bar_ttl(&mut self, ttl: std::time::Duration) -> &mut Self {
+            self.latest_bar_ttl = Some(ttl);
+            self
+        }
+
+        pub fn build(&self) -> Result<LiveMarket> {
+            let mut market = LiveMarket {
+                latest_bar_cache: self.latest_bar_ttl.map(LatestBarCache::new),
+                ..LiveMarket::default()
+            };
+            if let Some(rate_limiter) = &self.rate_limiter {
+                market.rate_limiter = rate_limiter.clone();
+            }
+            if let Some(retry_policy) = &self.retry_policy {
+                market.retry_policy = retry_policy.clone();
+            }
+            if let Some(base_url) = &self.base_url {
+                market.base_url = base_url.clone();
+            }
+            market.trading_base_url = self.trading_base_url.clone();
+            if let Some(region) = &self.region {
+                market.region = region.clone();
+            }
+            for (name, value) in &self.headers {
+                let name = HeaderName::from_str(name)
+                    .map_err(|_| Error::InvalidArgument(format!("Invalid header name {}", name)))?;
+                market.header_map.insert(name, parse_header_value(value)?);
+            }
+            let credentials = match &self.credentials_provider {
+                Some(credentials_provider) => Some(credentials_provider.get_credentials()?),
+                None => self.credentials.clone(),
+            };
+            if let Some(credentials) = credentials {
+                let mut api_key = parse_header_value(&credentials.api_key)?;
+                let mut api_secret = parse_header_value(&credentials.api_secret)?;
+                api_key.set_sensitive(true);
+                api_secret.set_sensitive(true);
+                market.header_map.insert("APCA-API-KEY-ID", api_key);
+                market.header_map.insert("APCA-API-SECRET-KEY", api_secret);
+            }
+            Ok(market)
+        }
+    }
+
+    fn parse_header_value(value: &str) -> Result<HeaderValue> {
+        HeaderValue::from_str(value)
+            .map_err(|_| Error::InvalidArgument("Invalid header value".into()))
+    }
 
     #[async_trait]
     impl Market for LiveMarket {
-        async fn get_latest_minute_bar(&self, crypto_pair: &CryptoPair) -> Result<Option<Bar>> {
-            let symbol = crypto_pair.to_string().replace("/", "%2F");
+        async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+            match &self.latest_bar_cache {
+                Some(cache) => {
+                    cache
+                        .get_or_fetch(crypto_pair, self.fetch_latest_minute_bar(crypto_pair))
+                        .await
+                }
+                None => self.fetch_latest_minute_bar(crypto_pair).await,
+            }
+        }
+
+        async fn get_latest_minute_bars(
+            &self,
+            crypto_pairs: &[Instrument],
+        ) -> Result<HashMap<Instrument, Bar>> {
+            let mut bars = HashMap::new();
+            // Each asset class has its own endpoint
+            for asset_class in [AssetClass::Crypto, AssetClass::Equity, AssetClass::Fx] {
+                let instruments: Vec<&Instrument> = crypto_pairs
+                    .iter()
+                    .filter(|crypto_pair| crypto_pair.asset_class == asset_class)
+                    .collect();
+                if instruments.is_empty() {
+                    continue;
+                }
+                let symbols: Vec<String> = instruments.iter().map(|i| to_symbol(i)).collect();
+                let url = format!(
+                    "{}?symbols={}",
+                    self.get_bars_url(asset_class, true)?,
+                    symbols.join(",")
+                );
+                let historical_bars_response: HistoricalBarsResponse =
+                    self.execute_request(&url).await?;
+                for instrument in instruments {
+                    if let Some(bar_response) =
+                        historical_bars_response.bars.get(&instrument.to_string())
+                    {
+                        bars.insert(instrument.clone(), bar_response.to_bar()?);
+                    }
+                }
+            }
+            Ok(bars)
+        }
+
+        async fn get_timeframe_bars(
+            &self,
+            crypto_pair: &Instrument,
+            timeframes: &[Timeframe],
+        ) -> Result<Vec<TimeframeBars>> {
+            check_timeframes(timeframes)?;
+            let now = Utc::now();
+            let start = get_timeframes_start(timeframes, now)?;
+            let mut minute_bars = self
+                .get_historical_bars(crypto_pair, "1Min", start, None)
+                .await?;
+            // The bar of the current minute is still open
+            minute_bars.retain(|bar| bar.date_time + Duration::minutes(1) <= now);
+            aggregate_timeframe_bars(&minute_bars, timeframes, now)
+        }
+
+        async fn get_bars(
+            &self,
+            crypto_pair: &Instrument,
+            start: DateTime<Utc>,
+            end: DateTime<Utc>,
+            timeframe: Timeframe,
+        ) -> Result<Vec<Bar>> {
+            check_timeframes(&[timeframe])?;
+            let now = Utc::now();
+            let mut bars = self
+                .get_historical_bars(
+                    crypto_pair,
+                    &to_alpaca_timeframe(timeframe)?,
+                    start,
+                    Some(end),
+                )
+                .await?;
+            bars.retain(|bar| {
+                bar.date_time >= start
+                    && bar.date_time < end
+                    && bar.date_time + timeframe.to_duration() <= now
+            });
+            Ok(bars)
+        }
+
+        async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+            // Alpaca trades crypto around the clock, the sessions of equities aren't modelled yet
+            Ok(Box::new(AlwaysOpenCalendar))
+        }
+
+        /// Clock of the US equity market from the trading API once its base URL is set, otherwise
+        /// of the crypto market, which is always open.
+        async fn get_clock(&self) -> Result<MarketClock> {
+            match &self.trading_base_url {
+                Some(trading_base_url) => {
+                    let url = format!("{trading_base_url}/v2/clock");
+                    let clock_response: ClockResponse = self.execute_request(&url).await?;
+                    clock_response.to_clock()
+                }
+                None => Ok(MarketClock::from_calendar(&AlwaysOpenCalendar, Utc::now())),
+            }
+        }
+    }
+
+    impl LiveMarket {
+        /// Location of the crypto exchange data is read from.
+        pub(crate) fn get_region(&self) -> &str {
+            &self.region
+        }
+
+        async fn execute_request<T>(&self, url: &str) -> Result<T>
+        where
+            T: DeserializeOwned,
+        {
+            execute_request(
+                url,
+                &self.header_map,
+                &self.retry_policy,
+                self.rate_limiter.as_ref(),
+            )
+            .await
+        }
+
+        async fn fetch_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
             let url = format!(
-                "https://data.alpaca.markets/v1beta3/crypto/eu-1/latest/bars?symbols={symbol}"
+                "{}?symbols={}",
+                self.get_bars_url(crypto_pair.asset_class, true)?,
+                to_symbol(crypto_pair)
             );
-            let historical_bars_response: HistoricalBarsResponse = execute_request(&url).await?;
-            let bar_response = &historical_bars_response.bars[&crypto_pair.to_string()];
-            Ok(Some(Bar {
-                low: BigDecimal::from_str(&bar_response.low)?,
-                high: BigDecimal::from_str(&bar_response.high)?,
-                open: BigDecimal::from_str(&bar_response.open)?,
-                close: BigDecimal::from_str(&bar_response.close)?,
-                date_time: DateTime::<Utc>::from_str(&bar_response.timestamp)?,
-            }))
-        }
-    }
-
-    async fn execute_request<T>(url: &str) -> Result<T>
-    where
-        T: DeserializeOwned,
-    {
-        let mut header_map = HeaderMap::new();
-        header_map.insert("accept", HeaderValue::from_str("application/json")?);
-        let client = reqwest::ClientBuilder::new()
-            .default_headers(header_map)
-            .build()?;
-        let result = client.get(url).send().await;
-        match result {
-            Ok(response) => Ok(response.json().await?),
-            Err(err) => anyhow::bail!(err),
+            let historical_bars_response: HistoricalBarsResponse =
+                self.execute_request(&url).await?;
+            historical_bars_response
+                .bars
+                .get(&crypto_pair.to_string())
+                .map(BarResponse::to_bar)
+                .transpose()
+        }
+
+        fn get_crypto_url(&self, path: &str) -> String {
+            format!("{}/v1beta3/crypto/{}/{}", self.base_url, self.region, path)
+        }
+
+        /// Url of the historical bars of an asset class, or of its latest bars if `latest`.
+        fn get_bars_url(&self, asset_class: AssetClass, latest: bool) -> Result<String> {
+            match (asset_class, latest) {
+                (AssetClass::Crypto, false) => Ok(self.get_crypto_url("bars")),
+                (AssetClass::Crypto, true) => Ok(self.get_crypto_url("latest/bars")),
+                (AssetClass::Equity, false) => Ok(format!("{}/v2/stocks/bars", self.base_url)),
+                (AssetClass::Equity, true) => {
+                    Ok(format!("{}/v2/stocks/bars/latest", self.base_url))
+                }
+                (AssetClass::Fx, _) => Err(Error::InvalidArgument(
+                    "Alpaca doesn't have market data of FX pairs".into(),
+                )),
+            }
+        }
+
+        /// Bars from `start` onwards, following every page of the response.
+        pub(super) async fn get_historical_bars(
+            &self,
+            crypto_pair: &Instrument,
+            timeframe: &str,
+            start: DateTime<Utc>,
+            end: Option<DateTime<Utc>>,
+        ) -> Result<Vec<Bar>> {
+            let mut base_url = format!(
+                "{}?symbols={}&timeframe={timeframe}&start={}&limit=10000",
+                self.get_bars_url(crypto_pair.asset_class, false)?,
+                to_symbol(crypto_pair),
+                start.to_rfc3339_opts(SecondsFormat::Secs, true)
+            );
+            if let Some(end) = end {
+                base_url += &format!("&end={}", end.to_rfc3339_opts(SecondsFormat::Secs, true));
+            }
+            let mut bars = Vec::new();
+            let mut page_token: Option<String> = None;
+            loop {
+                let url = match &page_token {
+                    Some(page_token) => format!("{base_url}&page_token={page_token}"),
+                    None => base_url.clone(),
+                };
+                let response: MultiBarsResponse = self.execute_request(&url).await?;
+                if let Some(bar_responses) = response.bars.get(&crypto_pair.to_string()) {
+                    for bar_response in bar_responses {
+                        bars.push(bar_response.to_bar()?);
+                    }
+                }
+                page_token = response.next_page_token;
+                if page_token.is_none() {
+                    break;
+                }
+            }
+            Ok(bars)
+        }
+    }
+
+    /// Symbol of an instrument in the query of a request, `BTC%2FUSD` or `AAPL`.
+    fn to_symbol(instrument: &Instrument) -> String {
+        instrument.to_string().replace("/", "%2F")
+    }
+
+    /// Timeframe parameter of the Alpaca bars API, which only has bars of up to 59 minutes, up
+    /// to 23 hours, a day or a week.
+    fn to_alpaca_timeframe(timeframe: Timeframe) -> Result<String> {
+        let minutes = timeframe.num_minutes();
+        match minutes {
+            1..60 => Ok(format!("{minutes}Min")),
+            _ if minutes % 60 == 0 && minutes < 24 * 60 => Ok(format!("{}Hour", minutes / 60)),
+            1440 => Ok("1Day".into()),
+            10080 => Ok("1Week".into()),
+            _ => Err(Error::InvalidArgument(format!(
+                "Bars of {} minutes are not available",
+                minutes
+            ))),
         }
     }
 
@@ -108,7 +458,13 @@ mod live_market {
     }
 
     #[derive(Deserialize, Debug)]
-    struct BarResponse {
+    struct MultiBarsResponse {
+        bars: HashMap<String, Vec<BarResponse>>,
+        next_page_token: Option<String>,
+    }
+
+    #[derive(Deserialize, Debug)]
+    pub(super) struct BarResponse {
         #[serde(rename = "o", deserialize_with = "as_string")]
         open: String,
 
@@ -123,6 +479,62 @@ mod live_market {
 
         #[serde(rename = "t")]
         timestamp: String,
+
+        #[serde(rename = "v", deserialize_with = "as_string")]
+        volume: String,
+
+        #[serde(rename = "n")]
+        trade_count: u64,
+
+        #[serde(rename = "vw", deserialize_with = "as_string")]
+        vwap: String,
+    }
+
+    impl BarResponse {
+        pub(super) fn to_bar(&self) -> Result<Bar> {
+            Ok(Bar {
+                low: parse_decimal(&self.low)?,
+                high: parse_decimal(&self.high)?,
+                open: parse_decimal(&self.open)?,
+                close: parse_decimal(&self.close)?,
+                date_time: DateTime::<Utc>::from_str(&self.timestamp)
+                    .map_err(|err| Error::Provider(format!("Invalid bar timestamp: {}", err)))?,
+                volume: parse_decimal(&self.volume)?,
+                trade_count: self.trade_count,
+                vwap: Some(parse_decimal(&self.vwap)?),
+            })
+        }
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct ClockResponse {
+        timestamp: String,
+        is_open: bool,
+        next_open: String,
+        next_close: String,
+    }
+
+    impl ClockResponse {
+        fn to_clock(&self) -> Result<MarketClock> {
+            Ok(MarketClock {
+                timestamp: parse_clock_time(&self.timestamp)?,
+                is_open: self.is_open,
+                next_open: Some(parse_clock_time(&self.next_open)?),
+                next_close: Some(parse_clock_time(&self.next_close)?),
+            })
+        }
+    }
+
+    /// Parses a time of the clock, given in the New York time zone.
+    fn parse_clock_time(value: &str) -> Result<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|date_time| date_time.with_timezone(&Utc))
+            .map_err(|err| Error::Provider(format!("Invalid clock time {}: {}", value, err)))
+    }
+
+    fn parse_decimal(value: &str) -> Result<BigDecimal> {
+        BigDecimal::from_str(value)
+            .map_err(|err| Error::Provider(format!("Invalid bar price {}: {}", value, err)))
     }
 
     #[cfg(test)]
@@ -130,10 +542,90 @@ mod live_market {
         use super::*;
         use std::str::FromStr;
 
+        #[test]
+        fn alpaca_timeframes() -> Result<()> {
+            assert_eq!(to_alpaca_timeframe(Timeframe::FifteenMinutes)?, "15Min");
+            assert_eq!(to_alpaca_timeframe(Timeframe::Minutes(240))?, "4Hour");
+            assert_eq!(to_alpaca_timeframe(Timeframe::OneDay)?, "1Day");
+            assert_eq!(
+                to_alpaca_timeframe(Timeframe::Minutes(7 * 24 * 60))?,
+                "1Week"
+            );
+            assert!(to_alpaca_timeframe(Timeframe::Minutes(90)).is_err());
+            Ok(())
+        }
+
+        #[test]
+        fn build_live_market() -> Result<()> {
+            let market = LiveMarketBuilder::new()
+                .set_base_url("https://example.com/")
+                .set_region("us")
+                .set_credentials(Credentials {
+                    api_key: "key".into(),
+                    api_secret: "hunter2".into(),
+                })
+                .add_header("user-agent", "irontrade")
+                .build()?;
+
+            assert_eq!(
+                market.get_crypto_url("bars"),
+                "https://example.com/v1beta3/crypto/us/bars"
+            );
+            assert_eq!(
+                market.get_bars_url(AssetClass::Equity, true)?,
+                "https://example.com/v2/stocks/bars/latest"
+            );
+            assert_eq!(to_symbol(&Instrument::equity("AAPL", "USD")), "AAPL");
+            assert_eq!(to_symbol(&Instrument::crypto("BTC", "USD")), "BTC%2FUSD");
+            assert_eq!(market.header_map["APCA-API-KEY-ID"], "key");
+            assert_eq!(market.header_map["user-agent"], "irontrade");
+            assert!(!format!("{:?}", market).contains("hunter2"));
+
+            let err = LiveMarketBuilder::new()
+                .add_header("invalid header", "value")
+                .build()
+                .unwrap_err();
+            assert_eq!(err.to_string(), "Invalid header name invalid header");
+            Ok(())
+        }
+
+        #[test]
+        fn convert_clock() -> anyhow::Result<()> {
+            let clock_response = ClockResponse {
+                timestamp: "2025-12-17T09:00:00.123-05:00".into(),
+                is_open: false,
+                next_open: "2025-12-17T09:30:00-05:00".into(),
+                next_close: "2025-12-17T16:00:00-05:00".into(),
+            };
+
+            let clock = clock_response.to_clock()?;
+
+            assert_eq!(
+                clock.timestamp,
+                DateTime::<Utc>::from_str("2025-12-17T14:00:00.123Z")?
+            );
+            assert_eq!(
+                clock.next_open,
+                Some(DateTime::<Utc>::from_str("2025-12-17T14:30:00Z")?)
+            );
+            assert_eq!(
+                clock.next_close,
+                Some(DateTime::<Utc>::from_str("2025-12-17T21:00:00Z")?)
+            );
+            let err = ClockResponse {
+                timestamp: "yesterday".into(),
+                ..clock_response
+            }
+            .to_clock()
+            .unwrap_err();
+            assert!(err.to_string().starts_with("Invalid clock time yesterday"));
+            Ok(())
+        }
+
         #[tokio::test]
         async fn get_latest_bar() -> Result<()> {
-            let market = LiveMarket;
-            let crypto_pair = CryptoPair::from_str("BTC/USD")?;
+            let market = LiveMarket::default();
+            let crypto_pair = Instrument::from_str("BTC/USD")?;
             let latest_bar = market.get_latest_minute_bar(&crypto_pair).await?;
             assert!(latest_bar.is_some());
             Ok(())
@@ -0,0 +1,73 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Bar, Instrument};
+use crate::live_market::LiveMarket;
+use crate::live_market::data::DayBarCache;
+use crate::live_market::http::block_on_thread;
+use crate::simulated::data::BarDataSource;
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+
+/// Historical Alpaca crypto bars for backtests, fetched a day of minute bars at a time on demand
+/// and cached as CSV files in `cache_dir/alpaca-REGION`, so that later runs over the same days
+/// don't need the network.
+///
+/// Each bar returned covers the period of the requested duration containing the requested time.
+#[derive(Debug, Clone)]
+pub struct AlpacaBarDataSource {
+    cache: DayBarCache,
+    market: LiveMarket,
+}
+
+impl AlpacaBarDataSource {
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        Self::with_market(cache_dir, LiveMarket::default())
+    }
+
+    /// Fetches the bars with `market`, e.g. one of another region or authenticated.
+    pub fn with_market(cache_dir: impl Into<PathBuf>, market: LiveMarket) -> Self {
+        Self {
+            cache: DayBarCache::new(cache_dir.into(), &format!("alpaca-{}", market.get_region())),
+            market,
+        }
+    }
+}
+
+impl BarDataSource for AlpacaBarDataSource {
+    fn get_bar(
+        &self,
+        crypto_pair: &Instrument,
+        date_time: &DateTime<Utc>,
+        bar_duration: chrono::Duration,
+    ) -> anyhow::Result<Option<Bar>> {
+        Ok(self
+            .cache
+            .get_bar(crypto_pair, date_time, bar_duration, |start, end| {
+                block_on_thread(self.market.get_historical_bars(
+                    crypto_pair,
+                    "1Min",
+                    start,
+                    Some(end),
+                ))
+            })?)
+    }
+
+    fn get_minute_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<Bar>> {
+        Ok(self
+            .cache
+            .get_minute_bars(crypto_pair, start, end, |start, end| {
+                block_on_thread(self.market.get_historical_bars(
+                    crypto_pair,
+                    "1Min",
+                    start,
+                    Some(end),
+                ))
+            })?)
+    }
+}
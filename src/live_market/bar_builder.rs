@@ -0,0 +1,168 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::Result;
+use crate::api::common::{Bar, Instrument};
+use crate::api::timeframe::{BarResampler, Timeframe};
+use crate::live_market::{BarUpdate, TradeUpdate};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Builds bars of `timeframe` from the trades of a [MarketStream](crate::live_market::MarketStream),
+/// without waiting for the provider to publish its bars.
+///
+/// Trades of a period already completed, e.g. received late, are ignored.
+#[derive(Debug, Clone)]
+pub struct BarBuilder {
+    timeframe: Timeframe,
+    pairs: HashMap<Instrument, PairBars>,
+}
+
+#[derive(Debug, Clone)]
+struct PairBars {
+    resampler: BarResampler,
+    /// Start of the period following the last one flushed.
+    next_period_start: Option<DateTime<Utc>>,
+}
+
+impl BarBuilder {
+    pub fn new(timeframe: Timeframe) -> Result<Self> {
+        timeframe.check()?;
+        Ok(Self {
+            timeframe,
+            pairs: HashMap::new(),
+        })
+    }
+
+    /// Adds a trade, returning the completed bar of its pair once the trade starts a new period.
+    pub fn push_trade(&mut self, trade: &TradeUpdate) -> Result<Option<BarUpdate>> {
+        let period_start = self.timeframe.period_start(trade.date_time)?;
+        let pair_bars = match self.pairs.get_mut(&trade.crypto_pair) {
+            Some(pair_bars) => pair_bars,
+            None => self
+                .pairs
+                .entry(trade.crypto_pair.clone())
+                .or_insert(PairBars {
+                    resampler: BarResampler::new(self.timeframe)?,
+                    next_period_start: None,
+                }),
+        };
+        let next_period_start = pair_bars
+            .resampler
+            .in_progress()
+            .map(|bar| bar.date_time)
+            .or(pair_bars.next_period_start);
+        if next_period_start.is_some_and(|next_period_start| period_start < next_period_start) {
+            return Ok(None);
+        }
+
+        let completed = pair_bars.resampler.push(&Bar {
+            low: trade.price.clone(),
+            high: trade.price.clone(),
+            open: trade.price.clone(),
+            close: trade.price.clone(),
+            date_time: trade.date_time,
+            volume: trade.quantity.clone(),
+            trade_count: 1,
+            vwap: Some(trade.price.clone()),
+        })?;
+        Ok(completed.map(|bar| BarUpdate {
+            crypto_pair: trade.crypto_pair.clone(),
+            bar,
+        }))
+    }
+
+    /// Bar of the current period of a pair, aggregated from the trades pushed so far.
+    pub fn in_progress(&self, crypto_pair: &Instrument) -> Option<&Bar> {
+        self.pairs
+            .get(crypto_pair)
+            .and_then(|pair_bars| pair_bars.resampler.in_progress())
+    }
+
+    /// Completes the bars whose period is over at `now`, as a pair may not trade again for a
+    /// while, e.g. when called on a timer.
+    pub fn flush_until(&mut self, now: DateTime<Utc>) -> Vec<BarUpdate> {
+        let mut completed = Vec::new();
+        let duration = self.timeframe.to_duration();
+        for (crypto_pair, pair_bars) in &mut self.pairs {
+            if pair_bars
+                .resampler
+                .in_progress()
+                .is_some_and(|bar| bar.date_time + duration <= now)
+                && let Some(bar) = pair_bars.resampler.flush()
+            {
+                pair_bars.next_period_start = Some(bar.date_time + duration);
+                completed.push(BarUpdate {
+                    crypto_pair: crypto_pair.clone(),
+                    bar,
+                });
+            }
+        }
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    #[test]
+    fn build_bars_from_trades() -> Result<()> {
+        let mut bar_builder = BarBuilder::new(Timeframe::OneMinute)?;
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let minute = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+
+        assert_eq!(bar_builder.push_trade(&create_trade(minute, 10, 1))?, None);
+        assert_eq!(
+            bar_builder.push_trade(&create_trade(minute + Duration::seconds(30), 13, 3))?,
+            None
+        );
+        assert_eq!(
+            bar_builder.in_progress(&crypto_pair).map(|bar| &bar.vwap),
+            Some(&Some(BigDecimal::from_str("12.25")?))
+        );
+        let completed = bar_builder
+            .push_trade(&create_trade(minute + Duration::seconds(70), 12, 1))?
+            .unwrap();
+        assert_eq!(completed.crypto_pair, crypto_pair);
+        assert_eq!(completed.bar.date_time, minute);
+        assert_eq!(completed.bar.open, BigDecimal::from(10));
+        assert_eq!(completed.bar.high, BigDecimal::from(13));
+        assert_eq!(completed.bar.close, BigDecimal::from(13));
+        assert_eq!(completed.bar.volume, BigDecimal::from(4));
+        assert_eq!(completed.bar.trade_count, 2);
+
+        // Nothing traded since, the bar of the second minute is completed on a timer
+        assert!(
+            bar_builder
+                .flush_until(minute + Duration::seconds(90))
+                .is_empty()
+        );
+        let flushed = bar_builder.flush_until(minute + Duration::minutes(2));
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].bar.date_time, minute + Duration::minutes(1));
+        assert_eq!(bar_builder.in_progress(&crypto_pair), None);
+
+        // A late trade of the completed minute
+        assert_eq!(
+            bar_builder.push_trade(&create_trade(minute + Duration::seconds(110), 11, 1))?,
+            None
+        );
+        assert_eq!(bar_builder.in_progress(&crypto_pair), None);
+        Ok(())
+    }
+
+    fn create_trade(date_time: DateTime<Utc>, price: i32, quantity: i32) -> TradeUpdate {
+        TradeUpdate {
+            crypto_pair: Instrument::from_str("BTC/USD").unwrap(),
+            price: BigDecimal::from(price),
+            quantity: BigDecimal::from(quantity),
+            taker_side: None,
+            date_time,
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{AssetClass, Bar, Instrument};
+use crate::api::retry::RetryPolicy;
+use crate::live_market::data::DayBarCache;
+use crate::live_market::http::{block_on_thread, execute_request};
+use crate::simulated::data::BarDataSource;
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const KLINES_URL: &str = "https://api.binance.com/api/v3/klines";
+const KLINES_LIMIT: usize = 1000;
+
+/// Historical Binance spot klines for backtests, which go further back than the Alpaca history
+/// and need no credentials. Bars are fetched and cached the same way as
+/// [AlpacaBarDataSource](crate::live_market::AlpacaBarDataSource), in `cache_dir/binance`.
+#[derive(Debug, Clone)]
+pub struct BinanceBarDataSource {
+    cache: DayBarCache,
+}
+
+impl BinanceBarDataSource {
+    pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache: DayBarCache::new(cache_dir.into(), "binance"),
+        }
+    }
+}
+
+impl BarDataSource for BinanceBarDataSource {
+    fn get_bar(
+        &self,
+        crypto_pair: &Instrument,
+        date_time: &DateTime<Utc>,
+        bar_duration: Duration,
+    ) -> anyhow::Result<Option<Bar>> {
+        check_crypto(crypto_pair)?;
+        Ok(self
+            .cache
+            .get_bar(crypto_pair, date_time, bar_duration, |start, end| {
+                block_on_thread(get_minute_klines(crypto_pair, start, end))
+            })?)
+    }
+
+    fn get_minute_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<Bar>> {
+        check_crypto(crypto_pair)?;
+        Ok(self
+            .cache
+            .get_minute_bars(crypto_pair, start, end, |start, end| {
+                block_on_thread(get_minute_klines(crypto_pair, start, end))
+            })?)
+    }
+}
+
+fn check_crypto(crypto_pair: &Instrument) -> Result<()> {
+    if crypto_pair.asset_class != AssetClass::Crypto {
+        return Err(Error::InvalidArgument(
+            "Binance only has market data of crypto pairs".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Binance spot symbol of a pair, e.g. BTCUSDT for BTC/USD, as Binance quotes dollars in USDT.
+pub fn to_binance_symbol(crypto_pair: &Instrument) -> String {
+    let notional_coin = match crypto_pair.notional_coin.as_str() {
+        "USD" => "USDT",
+        notional_coin => notional_coin,
+    };
+    format!("{}{}", crypto_pair.quantity_coin, notional_coin).to_uppercase()
+}
+
+/// Minute klines within `start` inclusive and `end` exclusive, following every page.
+async fn get_minute_klines(
+    crypto_pair: &Instrument,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<Bar>> {
+    let symbol = to_binance_symbol(crypto_pair);
+    let mut bars = Vec::new();
+    let mut page_start = start;
+    while page_start < end {
+        let url = format!(
+            "{KLINES_URL}?symbol={symbol}&interval=1m&startTime={}&endTime={}&limit={KLINES_LIMIT}",
+            page_start.timestamp_millis(),
+            end.timestamp_millis() - 1
+        );
+        let klines: Vec<Vec<KlineValue>> =
+            execute_request(&url, &HeaderMap::new(), &RetryPolicy::default(), None).await?;
+        let page_bars = klines
+            .iter()
+            .map(|kline| to_bar(kline))
+            .collect::<Result<Vec<_>>>()?;
+        let Some(last_bar) = page_bars.last() else {
+            break;
+        };
+        page_start = last_bar.date_time + Duration::minutes(1);
+        let is_last_page = page_bars.len() < KLINES_LIMIT;
+        bars.extend(page_bars);
+        if is_last_page {
+            break;
+        }
+    }
+    Ok(bars)
+}
+
+/// Field of a kline, which Binance returns as an array of times and decimal strings.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum KlineValue {
+    Integer(i64),
+    Text(String),
+}
+
+fn to_bar(kline: &[KlineValue]) -> Result<Bar> {
+    let invalid_kline = || Error::Provider(format!("Invalid kline: {:?}", kline));
+    let parse_decimal = |index: usize| match kline.get(index) {
+        Some(KlineValue::Text(value)) => BigDecimal::from_str(value).map_err(|_| invalid_kline()),
+        _ => Err(invalid_kline()),
+    };
+    let (Some(KlineValue::Integer(open_time)), Some(KlineValue::Integer(trade_count))) =
+        (kline.first(), kline.get(8))
+    else {
+        return Err(invalid_kline());
+    };
+    let volume = parse_decimal(5)?;
+    // Klines have the notional traded rather than the average price
+    let vwap = (volume > 0).then(|| parse_decimal(7).map(|notional| notional / &volume));
+    Ok(Bar {
+        low: parse_decimal(3)?,
+        high: parse_decimal(2)?,
+        open: parse_decimal(1)?,
+        close: parse_decimal(4)?,
+        date_time: DateTime::from_timestamp_millis(*open_time).ok_or_else(invalid_kline)?,
+        trade_count: u64::try_from(*trade_count).map_err(|_| invalid_kline())?,
+        vwap: vwap.transpose()?,
+        volume,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn binance_symbols() -> Result<()> {
+        assert_eq!(
+            to_binance_symbol(&Instrument::from_str("BTC/USD")?),
+            "BTCUSDT"
+        );
+        assert_eq!(
+            to_binance_symbol(&Instrument::from_str("eth/btc")?),
+            "ETHBTC"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn kline_to_bar() -> Result<()> {
+        let text = |value: &str| KlineValue::Text(value.into());
+        let kline = vec![
+            KlineValue::Integer(1765994400000),
+            text("10.5"),
+            text("12"),
+            text("8.25"),
+            text("11"),
+            text("4"),
+            KlineValue::Integer(1765994459999),
+            text("42"),
+            KlineValue::Integer(3),
+            text("2"),
+            text("21"),
+            text("0"),
+        ];
+
+        assert_eq!(
+            to_bar(&kline)?,
+            Bar {
+                low: BigDecimal::from_str("8.25")?,
+                high: BigDecimal::from(12),
+                open: BigDecimal::from_str("10.5")?,
+                close: BigDecimal::from(11),
+                date_time: DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?,
+                volume: BigDecimal::from(4),
+                trade_count: 3,
+                vwap: Some(BigDecimal::from_str("10.5")?),
+            }
+        );
+        assert!(to_bar(&kline[..3]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::Result;
+use crate::api::common::{Bar, Instrument};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+type CachedBar = Arc<tokio::sync::Mutex<Option<(Instant, Option<Bar>)>>>;
+
+/// Latest bars fetched within the last `ttl`, shared by the clones of the cache. Concurrent
+/// requests of the same pair wait for a single fetch.
+#[derive(Debug, Clone)]
+pub(super) struct LatestBarCache {
+    ttl: Duration,
+    bars: Arc<Mutex<HashMap<Instrument, CachedBar>>>,
+}
+
+impl LatestBarCache {
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            bars: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub(super) async fn get_or_fetch<F>(
+        &self,
+        crypto_pair: &Instrument,
+        fetch: F,
+    ) -> Result<Option<Bar>>
+    where
+        F: Future<Output = Result<Option<Bar>>>,
+    {
+        let cached_bar = self
+            .bars
+            .lock()
+            .unwrap()
+            .entry(crypto_pair.clone())
+            .or_default()
+            .clone();
+        let mut cached_bar = cached_bar.lock().await;
+        if let Some((fetched_at, bar)) = cached_bar.as_ref()
+            && fetched_at.elapsed() < self.ttl
+        {
+            return Ok(bar.clone());
+        }
+        let bar = fetch.await?;
+        *cached_bar = Some((Instant::now(), bar.clone()));
+        Ok(bar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use chrono::DateTime;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn reuse_fresh_bars() -> Result<()> {
+        let cache = LatestBarCache::new(Duration::from_secs(60));
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            let count = fetches.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Some(create_bar(count as i32)))
+        };
+
+        let shared_cache = cache.clone();
+        let (first, second) = tokio::join!(
+            cache.get_or_fetch(&crypto_pair, fetch()),
+            shared_cache.get_or_fetch(&crypto_pair, fetch())
+        );
+        assert_eq!(first?, Some(create_bar(1)));
+        assert_eq!(second?, Some(create_bar(1)));
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        let expired_cache = LatestBarCache::new(Duration::ZERO);
+        expired_cache.get_or_fetch(&crypto_pair, fetch()).await?;
+        expired_cache.get_or_fetch(&crypto_pair, fetch()).await?;
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    fn create_bar(close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(close),
+            high: BigDecimal::from(close),
+            open: BigDecimal::from(close),
+            close: BigDecimal::from(close),
+            date_time: DateTime::UNIX_EPOCH,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+}
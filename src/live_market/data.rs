@@ -0,0 +1,292 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Bar, Instrument};
+use crate::api::timeframe::{Timeframe, resample_bars};
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, SecondsFormat, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Time the bars of the current day are reused for before being fetched again.
+const IN_PROGRESS_DAY_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+type DayBars = HashMap<(Instrument, NaiveDate), CachedDay>;
+
+#[derive(Debug)]
+struct CachedDay {
+    bars: Arc<Vec<Bar>>,
+    /// When the bars were fetched, if the day was still in progress.
+    fetched_at: Option<Instant>,
+}
+
+/// Minute bars of whole days fetched from a provider, kept in memory and in CSV files of a
+/// directory of `cache_dir` per feed once the day is over. The bars of the current day are only
+/// kept in memory, for a minute.
+#[derive(Debug, Clone)]
+pub(super) struct DayBarCache {
+    cache_dir: PathBuf,
+    day_bars: Arc<Mutex<DayBars>>,
+    read_only: bool,
+}
+
+impl DayBarCache {
+    /// Cache of the bars of `feed`, e.g. a provider and region, in `cache_dir/feed`.
+    pub(super) fn new(cache_dir: PathBuf, feed: &str) -> Self {
+        Self {
+            cache_dir: cache_dir.join(feed),
+            day_bars: Arc::new(Mutex::new(HashMap::new())),
+            read_only: false,
+        }
+    }
+
+    /// Cache that never writes to `cache_dir`, e.g. to replay recorded bars.
+    pub(super) fn read_only(cache_dir: PathBuf) -> Self {
+        Self {
+            cache_dir,
+            day_bars: Arc::new(Mutex::new(HashMap::new())),
+            read_only: true,
+        }
+    }
+
+    /// Bar of the period of `bar_duration` containing `date_time`, resampled from the minute bars
+    /// returned by `fetch` for the days of the period not cached yet.
+    pub(super) fn get_bar<F>(
+        &self,
+        crypto_pair: &Instrument,
+        date_time: &DateTime<Utc>,
+        bar_duration: chrono::Duration,
+        fetch: F,
+    ) -> Result<Option<Bar>>
+    where
+        F: Fn(DateTime<Utc>, DateTime<Utc>) -> Result<Vec<Bar>>,
+    {
+        let timeframe = Timeframe::try_from(bar_duration)?;
+        let start = timeframe.period_start(*date_time)?;
+        let end = start + timeframe.to_duration();
+        let minute_bars = self.get_minute_bars(crypto_pair, start, end, fetch)?;
+        Ok(resample_bars(&minute_bars, timeframe)?.pop())
+    }
+
+    /// Minute bars within `start` inclusive and `end` exclusive, fetching the days not cached yet
+    /// with `fetch`.
+    pub(super) fn get_minute_bars<F>(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        fetch: F,
+    ) -> Result<Vec<Bar>>
+    where
+        F: Fn(DateTime<Utc>, DateTime<Utc>) -> Result<Vec<Bar>>,
+    {
+        let mut minute_bars = Vec::new();
+        let mut day = start.date_naive();
+        while day.and_time(NaiveTime::MIN).and_utc() < end {
+            let day_bars = self.get_day_bars(crypto_pair, day, &fetch)?;
+            minute_bars.extend(
+                day_bars
+                    .iter()
+                    .filter(|bar| bar.date_time >= start && bar.date_time < end)
+                    .cloned(),
+            );
+            day = day + Days::new(1);
+        }
+        Ok(minute_bars)
+    }
+
+    fn get_day_bars<F>(
+        &self,
+        crypto_pair: &Instrument,
+        day: NaiveDate,
+        fetch: &F,
+    ) -> Result<Arc<Vec<Bar>>>
+    where
+        F: Fn(DateTime<Utc>, DateTime<Utc>) -> Result<Vec<Bar>>,
+    {
+        let key = (crypto_pair.clone(), day);
+        if let Some(cached_day) = self.day_bars.lock().unwrap().get(&key)
+            && cached_day
+                .fetched_at
+                .is_none_or(|fetched_at| fetched_at.elapsed() < IN_PROGRESS_DAY_TTL)
+        {
+            return Ok(cached_day.bars.clone());
+        }
+        let start = day.and_time(NaiveTime::MIN).and_utc();
+        let end = start + Days::new(1);
+        // Days still in progress are fetched again, as they may get more bars
+        let is_complete = end <= Utc::now();
+        let path = get_day_path(&self.cache_dir, crypto_pair, day);
+        let bars = if path.exists() {
+            read_cache(&path)?
+        } else {
+            let mut bars = fetch(start, end)?;
+            bars.retain(|bar| bar.date_time >= start && bar.date_time < end);
+            if is_complete && !self.read_only {
+                write_cache(&path, &bars)?;
+            }
+            bars
+        };
+        let bars = Arc::new(bars);
+        self.day_bars.lock().unwrap().insert(
+            key,
+            CachedDay {
+                bars: bars.clone(),
+                fetched_at: (!is_complete).then(Instant::now),
+            },
+        );
+        Ok(bars)
+    }
+}
+
+/// CSV file of the minute bars of a pair and day.
+pub(super) fn get_day_path(dir: &Path, crypto_pair: &Instrument, day: NaiveDate) -> PathBuf {
+    dir.join(crypto_pair.to_string().replace('/', "_"))
+        .join(format!("{}.csv", day.format("%Y-%m-%d")))
+}
+
+pub(super) const CACHE_HEADER: &str = "timestamp,open,high,low,close,volume,trade_count,vwap";
+
+pub(super) fn read_cache(path: &Path) -> Result<Vec<Bar>> {
+    let content = fs::read_to_string(path).map_err(|err| Error::Other(err.into()))?;
+    let invalid_line =
+        |line: &str| Error::InvalidArgument(format!("Invalid bar {} in {}", line, path.display()));
+    let mut bars = Vec::new();
+    for line in content.lines().skip(1).filter(|line| !line.is_empty()) {
+        let fields: Vec<&str> = line.split(',').collect();
+        let [timestamp, open, high, low, close, volume, trade_count, vwap] = fields[..] else {
+            return Err(invalid_line(line));
+        };
+        let parse_decimal =
+            |value: &str| BigDecimal::from_str(value).map_err(|_| invalid_line(line));
+        bars.push(Bar {
+            low: parse_decimal(low)?,
+            high: parse_decimal(high)?,
+            open: parse_decimal(open)?,
+            close: parse_decimal(close)?,
+            date_time: DateTime::<Utc>::from_str(timestamp).map_err(|_| invalid_line(line))?,
+            volume: parse_decimal(volume)?,
+            trade_count: trade_count.parse().map_err(|_| invalid_line(line))?,
+            vwap: match vwap {
+                "" => None,
+                vwap => Some(parse_decimal(vwap)?),
+            },
+        });
+    }
+    Ok(bars)
+}
+
+fn write_cache(path: &Path, bars: &[Bar]) -> Result<()> {
+    let mut content = String::from(CACHE_HEADER) + "\n";
+    for bar in bars {
+        content += &to_csv_line(bar);
+    }
+    // Written to a temporary file first, so that an interrupted write doesn't leave a partial day
+    let temp_path = path.with_extension("csv.tmp");
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| Error::Other(err.into()))?;
+    }
+    fs::write(&temp_path, content).map_err(|err| Error::Other(err.into()))?;
+    fs::rename(&temp_path, path).map_err(|err| Error::Other(err.into()))
+}
+
+pub(super) fn to_csv_line(bar: &Bar) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{}\n",
+        bar.date_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        bar.open,
+        bar.high,
+        bar.low,
+        bar.close,
+        bar.volume,
+        bar.trade_count,
+        bar.vwap
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use chrono::Duration;
+
+    #[test]
+    fn get_bars_from_cache() -> Result<()> {
+        let cache_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let cache = DayBarCache::new(cache_dir.clone(), "feed");
+        let fetch = |_, _| Err(Error::Provider("Cached days are not fetched".into()));
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bars = vec![
+            create_bar(hour, 10, 12, 8, 11),
+            create_bar(hour + Duration::minutes(1), 11, 15, 9, 14),
+        ];
+        write_cache(
+            &get_day_path(&cache_dir.join("feed"), &crypto_pair, hour.date_naive()),
+            &bars,
+        )?;
+
+        let minute_bar = cache.get_bar(
+            &crypto_pair,
+            &(hour + Duration::seconds(90)),
+            Duration::minutes(1),
+            fetch,
+        )?;
+        let hour_bar = cache.get_bar(
+            &crypto_pair,
+            &(hour + Duration::minutes(30)),
+            Duration::hours(1),
+            fetch,
+        )?;
+
+        assert_eq!(minute_bar, Some(bars[1].clone()));
+        assert_eq!(hour_bar, Some(create_bar(hour, 10, 15, 8, 14)));
+        fs::remove_dir_all(cache_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn reuse_bars_of_current_day() -> Result<()> {
+        let cache_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let cache = DayBarCache::new(cache_dir.clone(), "feed");
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let today = Utc::now().date_naive().and_time(NaiveTime::MIN).and_utc();
+        let fetches = Mutex::new(0);
+        let fetch = |_, _| {
+            *fetches.lock().unwrap() += 1;
+            Ok(vec![create_bar(today, 10, 12, 8, 11)])
+        };
+
+        for _ in 0..2 {
+            let bars =
+                cache.get_minute_bars(&crypto_pair, today, today + Duration::minutes(1), fetch)?;
+            assert_eq!(bars, vec![create_bar(today, 10, 12, 8, 11)]);
+        }
+
+        assert_eq!(*fetches.lock().unwrap(), 1);
+        // The day isn't over, so it isn't written yet
+        assert!(!cache_dir.exists());
+        Ok(())
+    }
+
+    fn create_bar(date_time: DateTime<Utc>, open: i32, high: i32, low: i32, close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(open),
+            close: BigDecimal::from(close),
+            date_time,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+}
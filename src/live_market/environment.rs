@@ -0,0 +1,177 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::Result;
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use crate::live_market::LiveMarket;
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+struct LiveEnvironment {
+    client: Box<dyn Client + Send + Sync>,
+    market: LiveMarket,
+}
+
+pub fn create_env<T>(client: T) -> impl Environment
+where
+    T: Client + Send + Sync + 'static,
+{
+    create_env_with_market(client, LiveMarket::default())
+}
+
+/// Creates an environment reading market data with `market`, e.g. one of another region or
+/// authenticated for higher rate limits.
+pub fn create_env_with_market<T>(client: T, market: LiveMarket) -> impl Environment
+where
+    T: Client + Send + Sync + 'static,
+{
+    LiveEnvironment {
+        client: Box::new(client),
+        market,
+    }
+}
+
+#[async_trait]
+impl Client for LiveEnvironment {
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        self.client.place_order(req).await
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        self.client.place_orders(reqs).await
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        self.client.validate_order(req).await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.client.get_orders(filter).await
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.client.get_open_orders(filter).await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        self.client.get_order(order_id).await
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.client.get_orders_by_ids(order_ids).await
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        self.client.get_order_by_client_id(client_order_id).await
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        self.client.get_trades(filter).await
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.client.get_order_fills(order_id).await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        self.client.get_account_activities(range).await
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        self.client.get_account().await
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        self.client.get_equity().await
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.client.get_asset(crypto_pair).await
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        self.client.list_asset_pairs().await
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.client.get_position(asset_symbol).await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        self.client.cancel_order(order_id).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.client.cancel_orders(order_ids).await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.client.cancel_all_orders().await
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        self.client.close_all_positions().await
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        self.client.subscribe_order_updates().await
+    }
+}
+
+#[async_trait]
+impl Market for LiveEnvironment {
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        self.market.get_latest_minute_bar(crypto_pair).await
+    }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        self.market.get_latest_minute_bars(crypto_pairs).await
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        self.market
+            .get_timeframe_bars(crypto_pair, timeframes)
+            .await
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        self.market
+            .get_bars(crypto_pair, start, end, timeframe)
+            .await
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        self.market.get_calendar().await
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        self.market.get_clock().await
+    }
+}
+
+impl Environment for LiveEnvironment {}
@@ -0,0 +1,161 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::rate_limit::RateLimiter;
+use crate::api::retry::RetryPolicy;
+use crate::{Error, Result};
+use reqwest::StatusCode;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::de::DeserializeOwned;
+use std::thread;
+use std::time::Duration;
+use tokio::runtime::Builder;
+
+pub(super) async fn execute_request<T>(
+    url: &str,
+    header_map: &HeaderMap,
+    retry_policy: &RetryPolicy,
+    rate_limiter: Option<&RateLimiter>,
+) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let client = reqwest::ClientBuilder::new()
+        .default_headers(header_map.clone())
+        .build()
+        .map_err(provider_error)?;
+    let mut attempt = 1;
+    loop {
+        if let Some(rate_limiter) = rate_limiter {
+            rate_limiter.acquire().await;
+        }
+        let (err, retry_after) = match client.get(url).send().await {
+            Ok(response) if response.status().is_success() => {
+                return response.json().await.map_err(provider_error);
+            }
+            Ok(response) if is_retryable(response.status()) => {
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok()?.parse().ok())
+                    .map(Duration::from_secs);
+                (status_error(response.status()), retry_after)
+            }
+            Ok(response) => return Err(status_error(response.status())),
+            Err(err) => match provider_error(err) {
+                err @ Error::ProviderUnavailable(_) => (err, None),
+                err => return Err(err),
+            },
+        };
+        if attempt >= retry_policy.get_max_attempts() {
+            return Err(err);
+        }
+        let Some(delay) = get_retry_delay(retry_policy, attempt, retry_after) else {
+            return Err(err);
+        };
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Time to wait before retrying the failed attempt number `attempt`, at least as long as the
+/// provider asked for, or `None` when it asked for longer than the policy allows.
+fn get_retry_delay(
+    retry_policy: &RetryPolicy,
+    attempt: u32,
+    retry_after: Option<Duration>,
+) -> Option<Duration> {
+    let backoff = retry_policy.get_backoff(attempt);
+    match retry_after {
+        Some(retry_after) if retry_after > retry_policy.get_max_retry_after() => None,
+        Some(retry_after) => Some(retry_after.max(backoff)),
+        None => Some(backoff),
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn status_error(status: StatusCode) -> Error {
+    let message = format!("Request failed with status {}", status);
+    if is_retryable(status) {
+        Error::ProviderUnavailable(message)
+    } else {
+        Error::Provider(message)
+    }
+}
+
+/// Timeouts and failures to send the request are worth retrying, unlike unexpected responses.
+fn provider_error(err: reqwest::Error) -> Error {
+    if err.is_timeout() || err.is_connect() || err.is_request() {
+        return Error::ProviderUnavailable(err.to_string());
+    }
+    Error::Provider(err.to_string())
+}
+
+/// Runs `future` to completion on a thread of its own, as bars are requested from backtests
+/// running on an async runtime, which can't be blocked on.
+pub(super) fn block_on_thread<F, T>(future: F) -> Result<T>
+where
+    F: Future<Output = Result<T>> + Send,
+    T: Send,
+{
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|err| Error::Other(err.into()))?
+                    .block_on(future)
+            })
+            .join()
+            .map_err(|_| Error::Provider("Fetching bars panicked".into()))?
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::retry::RetryPolicyBuilder;
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable(StatusCode::UNAUTHORIZED));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(matches!(
+            status_error(StatusCode::BAD_GATEWAY),
+            Error::ProviderUnavailable(_)
+        ));
+        assert!(matches!(
+            status_error(StatusCode::FORBIDDEN),
+            Error::Provider(_)
+        ));
+    }
+
+    #[test]
+    fn bounded_retry_after() -> anyhow::Result<()> {
+        let retry_policy = RetryPolicyBuilder::new()
+            .set_initial_backoff(Duration::from_secs(1))
+            .set_jitter(false)
+            .set_max_retry_after(Duration::from_secs(30))
+            .build()?;
+
+        assert_eq!(
+            get_retry_delay(&retry_policy, 1, None),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            get_retry_delay(&retry_policy, 1, Some(Duration::from_secs(30))),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            get_retry_delay(&retry_policy, 1, Some(Duration::from_secs(31))),
+            None
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::calendar::{MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::Timeframe;
+use crate::api::{Client, Environment, Market};
+use crate::simulated::SimulatedClient;
+use crate::simulated::time::SystemClock;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Mutex;
+
+/// [Environment] forward testing a strategy on live prices without a paper trading account:
+/// market data is read from a live [Market], e.g. a [LiveMarket](crate::live_market::LiveMarket),
+/// while orders are executed by a [SimulatedClient] trading at the close of the latest minute bar
+/// of each pair and timestamping orders with the system time.
+///
+/// Prices are refreshed before a [Client] call once the refresh duration elapsed since they were
+/// last refreshed, or at any time with [PaperEnvironment::refresh_prices].
+pub struct PaperEnvironment<M> {
+    market: M,
+    client: SimulatedClient,
+    crypto_pairs_to_trade: Vec<Instrument>,
+    refresh_duration: Duration,
+    refreshed_at: Mutex<Option<DateTime<Utc>>>,
+}
+
+pub struct PaperEnvironmentBuilder<M> {
+    market: M,
+    client: SimulatedClient,
+    crypto_pairs_to_trade: HashSet<Instrument>,
+    refresh_duration: Duration,
+}
+
+impl<M> PaperEnvironmentBuilder<M>
+where
+    M: Market + Clone,
+{
+    pub fn new(market: M, client: SimulatedClient) -> Self {
+        Self {
+            market,
+            client,
+            crypto_pairs_to_trade: HashSet::new(),
+            refresh_duration: Duration::seconds(30),
+        }
+    }
+
+    /// Pairs whose prices are fed from the market to the client.
+    pub fn set_crypto_pairs_to_trade(
+        &mut self,
+        crypto_pairs_to_trade: HashSet<Instrument>,
+    ) -> &mut Self {
+        self.crypto_pairs_to_trade = crypto_pairs_to_trade;
+        self
+    }
+
+    /// Least time between two refreshes of the prices, 30 seconds by default.
+    pub fn set_refresh_duration(&mut self, refresh_duration: Duration) -> &mut Self {
+        self.refresh_duration = refresh_duration;
+        self
+    }
+
+    pub fn build(&self) -> Result<PaperEnvironment<M>> {
+        if self.refresh_duration <= Duration::zero() {
+            return Err(Error::InvalidArgument(
+                "Refresh duration must be positive".into(),
+            ));
+        }
+        self.client.set_clock(SystemClock);
+        Ok(PaperEnvironment {
+            market: self.market.clone(),
+            client: self.client.clone(),
+            crypto_pairs_to_trade: self.crypto_pairs_to_trade.iter().cloned().collect(),
+            refresh_duration: self.refresh_duration,
+            refreshed_at: Mutex::new(None),
+        })
+    }
+}
+
+impl<M> PaperEnvironment<M>
+where
+    M: Market + Send + Sync,
+{
+    /// Sets the price of every pair to trade to the close of its latest minute bar, leaving the
+    /// price of pairs without a bar unchanged.
+    pub async fn refresh_prices(&self) -> Result<()> {
+        let mut refreshed_at = self.refreshed_at.lock().await;
+        self.update_prices().await?;
+        *refreshed_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn refresh_stale_prices(&self) -> Result<()> {
+        let mut refreshed_at = self.refreshed_at.lock().await;
+        let now = Utc::now();
+        if refreshed_at.is_some_and(|refreshed_at| now - refreshed_at < self.refresh_duration) {
+            return Ok(());
+        }
+        self.update_prices().await?;
+        *refreshed_at = Some(now);
+        Ok(())
+    }
+
+    async fn update_prices(&self) -> Result<()> {
+        let bars = self
+            .market
+            .get_latest_minute_bars(&self.crypto_pairs_to_trade)
+            .await?;
+        for (crypto_pair, bar) in bars {
+            self.client.set_notional_per_unit(crypto_pair, bar.close)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<M> Client for PaperEnvironment<M>
+where
+    M: Market + Send + Sync,
+{
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        self.refresh_stale_prices().await?;
+        self.client.place_order(req).await
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        self.refresh_stale_prices().await?;
+        self.client.place_orders(reqs).await
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        self.refresh_stale_prices().await?;
+        self.client.validate_order(req).await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.refresh_stale_prices().await?;
+        self.client.get_orders(filter).await
+    }
+
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        self.refresh_stale_prices().await?;
+        self.client.get_open_orders(filter).await
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        self.refresh_stale_prices().await?;
+        self.client.get_order(order_id).await
+    }
+
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.refresh_stale_prices().await?;
+        self.client.get_orders_by_ids(order_ids).await
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        self.refresh_stale_prices().await?;
+        self.client.get_order_by_client_id(client_order_id).await
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        self.refresh_stale_prices().await?;
+        self.client.get_trades(filter).await
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.refresh_stale_prices().await?;
+        self.client.get_order_fills(order_id).await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        self.refresh_stale_prices().await?;
+        self.client.get_account_activities(range).await
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        self.refresh_stale_prices().await?;
+        self.client.get_account().await
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        self.refresh_stale_prices().await?;
+        self.client.get_equity().await
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.refresh_stale_prices().await?;
+        self.client.get_asset(crypto_pair).await
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        self.refresh_stale_prices().await?;
+        self.client.list_asset_pairs().await
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.refresh_stale_prices().await?;
+        self.client.get_position(asset_symbol).await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        self.refresh_stale_prices().await?;
+        self.client.cancel_order(order_id).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.refresh_stale_prices().await?;
+        self.client.cancel_orders(order_ids).await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.refresh_stale_prices().await?;
+        self.client.cancel_all_orders().await
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        self.refresh_stale_prices().await?;
+        self.client.close_all_positions().await
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        self.client.subscribe_order_updates().await
+    }
+}
+
+#[async_trait]
+impl<M> Market for PaperEnvironment<M>
+where
+    M: Market + Send + Sync,
+{
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
+        self.market.get_latest_minute_bar(crypto_pair).await
+    }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        self.market.get_latest_minute_bars(crypto_pairs).await
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        self.market
+            .get_timeframe_bars(crypto_pair, timeframes)
+            .await
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        self.market
+            .get_bars(crypto_pair, start, end, timeframe)
+            .await
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        self.market.get_calendar().await
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        self.market.get_clock().await
+    }
+}
+
+impl<M> Environment for PaperEnvironment<M> where M: Market + Send + Sync {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::calendar::AlwaysOpenCalendar;
+    use crate::api::common::Amount;
+    use crate::simulated::SimulatedBrokerBuilder;
+    use anyhow::Result;
+    use std::str::FromStr;
+    use std::sync::{Arc, RwLock};
+
+    /// Market whose latest minute bars close at the prices set by the test.
+    #[derive(Clone, Default)]
+    struct TickerMarket {
+        closes: Arc<RwLock<HashMap<Instrument, BigDecimal>>>,
+    }
+
+    impl TickerMarket {
+        fn set_close(&self, crypto_pair: &Instrument, close: i32) {
+            self.closes
+                .write()
+                .unwrap()
+                .insert(crypto_pair.clone(), BigDecimal::from(close));
+        }
+    }
+
+    #[async_trait]
+    impl Market for TickerMarket {
+        async fn get_latest_minute_bar(
+            &self,
+            crypto_pair: &Instrument,
+        ) -> crate::Result<Option<Bar>> {
+            let closes = self.closes.read().unwrap();
+            Ok(closes.get(crypto_pair).map(|close| Bar {
+                low: close.clone(),
+                high: close.clone(),
+                open: close.clone(),
+                close: close.clone(),
+                date_time: Utc::now(),
+                volume: BigDecimal::from(1),
+                trade_count: 1,
+                vwap: None,
+            }))
+        }
+
+        async fn get_latest_minute_bars(
+            &self,
+            crypto_pairs: &[Instrument],
+        ) -> crate::Result<HashMap<Instrument, Bar>> {
+            let mut bars = HashMap::new();
+            for crypto_pair in crypto_pairs {
+                if let Some(bar) = self.get_latest_minute_bar(crypto_pair).await? {
+                    bars.insert(crypto_pair.clone(), bar);
+                }
+            }
+            Ok(bars)
+        }
+
+        async fn get_timeframe_bars(
+            &self,
+            _crypto_pair: &Instrument,
+            _timeframes: &[Timeframe],
+        ) -> crate::Result<Vec<TimeframeBars>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_bars(
+            &self,
+            _crypto_pair: &Instrument,
+            _start: DateTime<Utc>,
+            _end: DateTime<Utc>,
+            _timeframe: Timeframe,
+        ) -> crate::Result<Vec<Bar>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_calendar(&self) -> crate::Result<Box<dyn MarketCalendar + Send + Sync>> {
+            Ok(Box::new(AlwaysOpenCalendar))
+        }
+    }
+
+    #[tokio::test]
+    async fn trade_at_live_prices() -> Result<()> {
+        let market = TickerMarket::default();
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        market.set_close(&crypto_pair, 10);
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let environment =
+            PaperEnvironmentBuilder::new(market.clone(), SimulatedClient::new(broker))
+                .set_crypto_pairs_to_trade(HashSet::from([crypto_pair.clone()]))
+                .build()?;
+
+        let order_id = environment
+            .place_order(OrderRequest::market_buy(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(2),
+                },
+            ))
+            .await?;
+        assert_eq!(
+            environment.get_order(&order_id).await?.average_fill_price,
+            Some(BigDecimal::from(10))
+        );
+
+        // Prices are only refreshed once the refresh duration elapsed
+        market.set_close(&crypto_pair, 15);
+        assert_eq!(environment.get_equity().await?, BigDecimal::from(100));
+        environment.refresh_prices().await?;
+        assert_eq!(environment.get_equity().await?, BigDecimal::from(110));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn timestamp_orders() -> Result<()> {
+        let market = TickerMarket::default();
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        market.set_close(&crypto_pair, 10);
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let environment = PaperEnvironmentBuilder::new(market, SimulatedClient::new(broker))
+            .set_crypto_pairs_to_trade(HashSet::from([crypto_pair.clone()]))
+            .build()?;
+        let before = Utc::now();
+
+        let order_id = environment
+            .place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            ))
+            .await?;
+
+        let created_at = environment.get_order(&order_id).await?.created_at.unwrap();
+        assert!(created_at >= before && created_at <= Utc::now());
+        Ok(())
+    }
+
+    #[test]
+    fn build_invalid_environment() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD").build()?;
+        let err =
+            PaperEnvironmentBuilder::new(TickerMarket::default(), SimulatedClient::new(broker))
+                .set_refresh_duration(Duration::zero())
+                .build()
+                .err()
+                .unwrap();
+        assert_eq!(err.to_string(), "Refresh duration must be positive");
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::Market;
+use crate::api::common::{Bar, Instrument};
+#[cfg(feature = "market_stream")]
+use crate::live_market::MarketEvent;
+#[cfg(feature = "market_stream")]
+use crate::live_market::MarketStream;
+use crate::live_market::data::{CACHE_HEADER, DayBarCache, get_day_path, read_cache, to_csv_line};
+use crate::simulated::data::BarDataSource;
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Appends live minute bars to CSV files of `record_dir`, one per pair and day, in the layout of
+/// the [AlpacaBarDataSource](crate::live_market::AlpacaBarDataSource) cache, so that a session
+/// can be replayed with [RecordedBarDataSource].
+#[derive(Debug, Clone)]
+pub struct BarRecorder {
+    record_dir: PathBuf,
+    last_recorded: Arc<Mutex<HashMap<Instrument, DateTime<Utc>>>>,
+}
+
+impl BarRecorder {
+    pub fn new(record_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            record_dir: record_dir.into(),
+            last_recorded: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Appends `bar` unless a bar as recent was already recorded for the pair, as polling returns
+    /// the same latest bar until the next minute closes. Returns whether the bar was appended.
+    pub fn record(&self, crypto_pair: &Instrument, bar: &Bar) -> Result<bool> {
+        let path = get_day_path(&self.record_dir, crypto_pair, bar.date_time.date_naive());
+        let mut last_recorded = self.last_recorded.lock().unwrap();
+        let last_date_time = match last_recorded.get(crypto_pair) {
+            Some(date_time) => Some(*date_time),
+            // Carries on from a previous recording of the same day
+            None if path.exists() => read_cache(&path)?.iter().map(|bar| bar.date_time).max(),
+            None => None,
+        };
+        if last_date_time.is_some_and(|date_time| bar.date_time <= date_time) {
+            return Ok(false);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| Error::Other(err.into()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| Error::Other(err.into()))?;
+        let is_empty = file
+            .metadata()
+            .map_err(|err| Error::Other(err.into()))?
+            .len()
+            == 0;
+        let mut content = String::new();
+        if is_empty {
+            content = String::from(CACHE_HEADER) + "\n";
+        }
+        content += &to_csv_line(bar);
+        file.write_all(content.as_bytes())
+            .map_err(|err| Error::Other(err.into()))?;
+        last_recorded.insert(crypto_pair.clone(), bar.date_time);
+        Ok(true)
+    }
+
+    /// Records the latest minute bars of `crypto_pairs` once, returning how many were new.
+    pub async fn record_latest<M>(&self, market: &M, crypto_pairs: &[Instrument]) -> Result<usize>
+    where
+        M: Market,
+    {
+        let bars = market.get_latest_minute_bars(crypto_pairs).await?;
+        let mut recorded = 0;
+        for (crypto_pair, bar) in &bars {
+            if self.record(crypto_pair, bar)? {
+                recorded += 1;
+            }
+        }
+        Ok(recorded)
+    }
+
+    /// Polls the latest minute bars of `crypto_pairs` every `interval`, until a poll fails.
+    pub async fn record_polling<M>(
+        &self,
+        market: &M,
+        crypto_pairs: &[Instrument],
+        interval: std::time::Duration,
+    ) -> Result<()>
+    where
+        M: Market,
+    {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            self.record_latest(market, crypto_pairs).await?;
+        }
+    }
+
+    /// Records the bars pushed by `stream` until it ends, ignoring its trades and quotes.
+    #[cfg(feature = "market_stream")]
+    pub async fn record_stream(&self, stream: &mut MarketStream) -> Result<()> {
+        while let Some(event) = stream.next().await {
+            if let MarketEvent::Bar(bar_update) = event? {
+                self.record(&bar_update.crypto_pair, &bar_update.bar)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replays the bars recorded by a [BarRecorder], or cached by an
+/// [AlpacaBarDataSource](crate::live_market::AlpacaBarDataSource) when given the directory of its
+/// feed, e.g. `cache_dir/alpaca-eu-1`, without any network access.
+///
+/// Each bar returned covers the period of the requested duration containing the requested time,
+/// resampled from the recorded minute bars.
+#[derive(Debug, Clone)]
+pub struct RecordedBarDataSource {
+    cache: DayBarCache,
+}
+
+impl RecordedBarDataSource {
+    pub fn new(record_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache: DayBarCache::read_only(record_dir.into()),
+        }
+    }
+}
+
+impl BarDataSource for RecordedBarDataSource {
+    fn get_bar(
+        &self,
+        crypto_pair: &Instrument,
+        date_time: &DateTime<Utc>,
+        bar_duration: chrono::Duration,
+    ) -> anyhow::Result<Option<Bar>> {
+        Ok(self
+            .cache
+            .get_bar(crypto_pair, date_time, bar_duration, |_, _| Ok(Vec::new()))?)
+    }
+
+    fn get_minute_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<Bar>> {
+        Ok(self
+            .cache
+            .get_minute_bars(crypto_pair, start, end, |_, _| Ok(Vec::new()))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    #[test]
+    fn replay_recorded_bars() -> Result<()> {
+        let record_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let recorder = BarRecorder::new(&record_dir);
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+
+        assert!(recorder.record(&crypto_pair, &create_bar(hour, 10))?);
+        // Polled again before the next minute closed
+        assert!(!recorder.record(&crypto_pair, &create_bar(hour, 10))?);
+        // A new recorder carries on from the recorded file
+        let recorder = BarRecorder::new(&record_dir);
+        assert!(!recorder.record(&crypto_pair, &create_bar(hour, 10))?);
+        assert!(recorder.record(&crypto_pair, &create_bar(hour + Duration::minutes(1), 12))?);
+
+        let data_source = RecordedBarDataSource::new(&record_dir);
+        assert_eq!(
+            data_source.get_bar(
+                &crypto_pair,
+                &(hour + Duration::seconds(90)),
+                Duration::minutes(1)
+            )?,
+            Some(create_bar(hour + Duration::minutes(1), 12))
+        );
+        assert_eq!(
+            data_source.get_bar(
+                &crypto_pair,
+                &(hour + Duration::days(1)),
+                Duration::minutes(1)
+            )?,
+            None
+        );
+        fs::remove_dir_all(record_dir)?;
+        Ok(())
+    }
+
+    fn create_bar(date_time: DateTime<Utc>, close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(close),
+            high: BigDecimal::from(close),
+            open: BigDecimal::from(close),
+            close: BigDecimal::from(close),
+            date_time,
+            volume: BigDecimal::from(1),
+            trade_count: 1,
+            vwap: Some(BigDecimal::from(close)),
+        }
+    }
+}
@@ -0,0 +1,484 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use super::market::BarResponse;
+use crate::api::common::{Bar, Instrument, OrderSide};
+use crate::api::credentials::Credentials;
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use serde_this_or_that::as_string;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
+
+const STREAM_BASE_URL: &str = "wss://stream.data.alpaca.markets";
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Minute bar of a subscribed pair, received as soon as the minute closes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BarUpdate {
+    pub crypto_pair: Instrument,
+    pub bar: Bar,
+}
+
+/// Trade of a subscribed pair on the provider's exchange.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeUpdate {
+    pub crypto_pair: Instrument,
+    pub price: BigDecimal,
+    pub quantity: BigDecimal,
+    /// Side of the order that took liquidity, when known.
+    pub taker_side: Option<OrderSide>,
+    pub date_time: DateTime<Utc>,
+}
+
+/// Best bid and ask of a subscribed pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteUpdate {
+    pub crypto_pair: Instrument,
+    pub bid_price: BigDecimal,
+    pub bid_quantity: BigDecimal,
+    pub ask_price: BigDecimal,
+    pub ask_quantity: BigDecimal,
+    pub date_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketEvent {
+    Bar(BarUpdate),
+    Trade(TradeUpdate),
+    Quote(QuoteUpdate),
+}
+
+/// Kind of [MarketEvent] a pair is subscribed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketChannel {
+    Bars,
+    Trades,
+    Quotes,
+}
+
+/// Live bars, trades and quotes of the pairs subscribed to, pushed by the Alpaca crypto data
+/// websocket over a single connection.
+///
+/// The connection is kept by a background task until the stream is dropped. Errors reported by
+/// the websocket are yielded as items, the stream ending once the connection is closed.
+#[derive(Debug)]
+pub struct MarketStream {
+    commands: UnboundedSender<Command>,
+    events: UnboundedReceiver<Result<MarketEvent>>,
+}
+
+#[derive(Debug)]
+enum Command {
+    Subscribe(MarketChannel, Vec<Instrument>),
+    Unsubscribe(MarketChannel, Vec<Instrument>),
+}
+
+impl MarketStream {
+    /// Connects and authenticates to the websocket of the crypto exchange in `region`, e.g. `eu-1`
+    /// as set on the `LiveMarket` the bars are read from, without any subscription yet.
+    pub async fn connect(credentials: &Credentials, region: &str) -> Result<Self> {
+        let (mut socket, _) = connect_async(get_stream_url(region))
+            .await
+            .map_err(provider_error)?;
+        expect_success(&mut socket, "connected").await?;
+        send(
+            &mut socket,
+            json!({
+                "action": "auth",
+                "key": credentials.api_key,
+                "secret": credentials.api_secret,
+            }),
+        )
+        .await?;
+        expect_success(&mut socket, "authenticated").await?;
+
+        let (command_sender, command_receiver) = unbounded_channel();
+        let (event_sender, event_receiver) = unbounded_channel();
+        tokio::spawn(run(socket, command_receiver, event_sender));
+        Ok(Self {
+            commands: command_sender,
+            events: event_receiver,
+        })
+    }
+
+    pub fn subscribe(&self, channel: MarketChannel, crypto_pairs: &[Instrument]) -> Result<()> {
+        self.send_command(Command::Subscribe(channel, crypto_pairs.to_vec()))
+    }
+
+    pub fn unsubscribe(&self, channel: MarketChannel, crypto_pairs: &[Instrument]) -> Result<()> {
+        self.send_command(Command::Unsubscribe(channel, crypto_pairs.to_vec()))
+    }
+
+    /// Waits for the next event, `None` once the connection is closed.
+    pub async fn next(&mut self) -> Option<Result<MarketEvent>> {
+        self.events.recv().await
+    }
+
+    fn send_command(&self, command: Command) -> Result<()> {
+        self.commands
+            .send(command)
+            .map_err(|_| Error::Provider("Market stream connection is closed".into()))
+    }
+}
+
+impl Stream for MarketStream {
+    type Item = Result<MarketEvent>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.events.poll_recv(cx)
+    }
+}
+
+/// Forwards the commands to the websocket and its messages to the stream, until either side is
+/// closed.
+async fn run(
+    mut socket: Socket,
+    mut commands: UnboundedReceiver<Command>,
+    events: UnboundedSender<Result<MarketEvent>>,
+) {
+    loop {
+        tokio::select! {
+            command = commands.recv() => {
+                let Some(command) = command else {
+                    let _ = socket.close(None).await;
+                    return;
+                };
+                if let Err(err) = send(&mut socket, command_message(&command)).await {
+                    let _ = events.send(Err(err));
+                    return;
+                }
+            }
+            message = socket.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return,
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => {
+                        let _ = events.send(Err(provider_error(err)));
+                        return;
+                    }
+                };
+                let stream_messages = match parse_messages(&text) {
+                    Ok(stream_messages) => stream_messages,
+                    Err(err) => {
+                        let _ = events.send(Err(err));
+                        continue;
+                    }
+                };
+                for stream_message in stream_messages {
+                    let event = match stream_message {
+                        StreamMessage::Bar(bar) => bar.to_bar_update().map(MarketEvent::Bar),
+                        StreamMessage::Trade(trade) => {
+                            trade.to_trade_update().map(MarketEvent::Trade)
+                        }
+                        StreamMessage::Quote(quote) => {
+                            quote.to_quote_update().map(MarketEvent::Quote)
+                        }
+                        StreamMessage::Error { code, msg } => Err(stream_error(code, &msg)),
+                        _ => continue,
+                    };
+                    if events.send(event).is_err() {
+                        let _ = socket.close(None).await;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn get_stream_url(region: &str) -> String {
+    format!("{}/v1beta3/crypto/{}", STREAM_BASE_URL, region)
+}
+
+async fn send(socket: &mut Socket, message: serde_json::Value) -> Result<()> {
+    socket
+        .send(Message::text(message.to_string()))
+        .await
+        .map_err(provider_error)
+}
+
+/// Reads the next message, failing unless it is a success message with `expected_msg`.
+async fn expect_success(socket: &mut Socket, expected_msg: &str) -> Result<()> {
+    loop {
+        let text = match socket.next().await {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(_))) | None => {
+                return Err(Error::Provider(
+                    "Market stream connection was closed".into(),
+                ));
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(err)) => return Err(provider_error(err)),
+        };
+        return match parse_messages(&text)?.into_iter().next() {
+            Some(StreamMessage::Success { msg }) if msg == expected_msg => Ok(()),
+            Some(StreamMessage::Error { code, msg }) => Err(stream_error(code, &msg)),
+            _ => Err(Error::Provider(format!(
+                "Unexpected market stream message: {}",
+                text
+            ))),
+        };
+    }
+}
+
+fn command_message(command: &Command) -> serde_json::Value {
+    let (action, channel, crypto_pairs) = match command {
+        Command::Subscribe(channel, crypto_pairs) => ("subscribe", channel, crypto_pairs),
+        Command::Unsubscribe(channel, crypto_pairs) => ("unsubscribe", channel, crypto_pairs),
+    };
+    let channel = match channel {
+        MarketChannel::Bars => "bars",
+        MarketChannel::Trades => "trades",
+        MarketChannel::Quotes => "quotes",
+    };
+    let symbols: Vec<String> = crypto_pairs.iter().map(Instrument::to_string).collect();
+    json!({ "action": action, channel: symbols })
+}
+
+fn parse_messages(text: &str) -> Result<Vec<StreamMessage>> {
+    serde_json::from_str(text)
+        .map_err(|err| Error::Provider(format!("Invalid market stream message {}: {}", text, err)))
+}
+
+fn provider_error(err: tokio_tungstenite::tungstenite::Error) -> Error {
+    Error::Provider(err.to_string())
+}
+
+fn stream_error(code: i64, msg: &str) -> Error {
+    Error::Provider(format!("Market stream error {}: {}", code, msg))
+}
+
+fn parse_decimal(value: &str) -> Result<BigDecimal> {
+    BigDecimal::from_str(value)
+        .map_err(|err| Error::Provider(format!("Invalid market stream value {}: {}", value, err)))
+}
+
+fn parse_date_time(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::<Utc>::from_str(value)
+        .map_err(|err| Error::Provider(format!("Invalid market stream timestamp: {}", err)))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "T")]
+enum StreamMessage {
+    #[serde(rename = "success")]
+    Success { msg: String },
+    #[serde(rename = "error")]
+    Error { code: i64, msg: String },
+    #[serde(rename = "b")]
+    Bar(StreamBar),
+    #[serde(rename = "t")]
+    Trade(StreamTrade),
+    #[serde(rename = "q")]
+    Quote(StreamQuote),
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamBar {
+    #[serde(rename = "S")]
+    symbol: String,
+
+    #[serde(flatten)]
+    bar: BarResponse,
+}
+
+impl StreamBar {
+    fn to_bar_update(&self) -> Result<BarUpdate> {
+        Ok(BarUpdate {
+            crypto_pair: Instrument::from_str(&self.symbol)?,
+            bar: self.bar.to_bar()?,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamTrade {
+    #[serde(rename = "S")]
+    symbol: String,
+
+    #[serde(rename = "p", deserialize_with = "as_string")]
+    price: String,
+
+    #[serde(rename = "s", deserialize_with = "as_string")]
+    size: String,
+
+    #[serde(rename = "tks")]
+    taker_side: Option<String>,
+
+    #[serde(rename = "t")]
+    timestamp: String,
+}
+
+impl StreamTrade {
+    fn to_trade_update(&self) -> Result<TradeUpdate> {
+        Ok(TradeUpdate {
+            crypto_pair: Instrument::from_str(&self.symbol)?,
+            price: parse_decimal(&self.price)?,
+            quantity: parse_decimal(&self.size)?,
+            taker_side: match self.taker_side.as_deref() {
+                Some("B") => Some(OrderSide::Buy),
+                Some("S") => Some(OrderSide::Sell),
+                _ => None,
+            },
+            date_time: parse_date_time(&self.timestamp)?,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamQuote {
+    #[serde(rename = "S")]
+    symbol: String,
+
+    #[serde(rename = "bp", deserialize_with = "as_string")]
+    bid_price: String,
+
+    #[serde(rename = "bs", deserialize_with = "as_string")]
+    bid_size: String,
+
+    #[serde(rename = "ap", deserialize_with = "as_string")]
+    ask_price: String,
+
+    #[serde(rename = "as", deserialize_with = "as_string")]
+    ask_size: String,
+
+    #[serde(rename = "t")]
+    timestamp: String,
+}
+
+impl StreamQuote {
+    fn to_quote_update(&self) -> Result<QuoteUpdate> {
+        Ok(QuoteUpdate {
+            crypto_pair: Instrument::from_str(&self.symbol)?,
+            bid_price: parse_decimal(&self.bid_price)?,
+            bid_quantity: parse_decimal(&self.bid_size)?,
+            ask_price: parse_decimal(&self.ask_price)?,
+            ask_quantity: parse_decimal(&self.ask_size)?,
+            date_time: parse_date_time(&self.timestamp)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bar_and_control_messages() -> anyhow::Result<()> {
+        let messages = parse_messages(
+            r#"[
+                {"T": "subscription", "trades": [], "quotes": [], "bars": ["BTC/USD"]},
+                {"T": "b", "S": "BTC/USD", "o": 100.5, "h": 101, "l": 99, "c": 100, "v": 2.5,
+                 "t": "2025-12-17T18:30:00Z", "n": 12, "vw": 100.2},
+                {"T": "error", "code": 405, "msg": "symbol limit exceeded"}
+            ]"#,
+        )?;
+
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(messages[0], StreamMessage::Other));
+        let StreamMessage::Bar(bar) = &messages[1] else {
+            panic!("Expected a bar message");
+        };
+        assert_eq!(
+            bar.to_bar_update()?,
+            BarUpdate {
+                crypto_pair: Instrument::from_str("BTC/USD")?,
+                bar: Bar {
+                    low: BigDecimal::from(99),
+                    high: BigDecimal::from(101),
+                    open: BigDecimal::from_str("100.5")?,
+                    close: BigDecimal::from(100),
+                    date_time: DateTime::<Utc>::from_str("2025-12-17T18:30:00Z")?,
+                    volume: BigDecimal::from_str("2.5")?,
+                    trade_count: 12,
+                    vwap: Some(BigDecimal::from_str("100.2")?),
+                },
+            }
+        );
+        assert!(
+            matches!(&messages[2], StreamMessage::Error { code: 405, msg } if msg == "symbol limit exceeded")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_trade_and_quote_messages() -> anyhow::Result<()> {
+        let messages = parse_messages(
+            r#"[
+                {"T": "t", "S": "BTC/USD", "p": 100.5, "s": 0.25, "t": "2025-12-17T18:30:01Z",
+                 "i": 42, "tks": "S"},
+                {"T": "q", "S": "BTC/USD", "bp": 100, "bs": 1.5, "ap": 101, "as": 2,
+                 "t": "2025-12-17T18:30:02Z"}
+            ]"#,
+        )?;
+
+        let [StreamMessage::Trade(trade), StreamMessage::Quote(quote)] = &messages[..] else {
+            panic!("Expected a trade and a quote message");
+        };
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        assert_eq!(
+            trade.to_trade_update()?,
+            TradeUpdate {
+                crypto_pair: crypto_pair.clone(),
+                price: BigDecimal::from_str("100.5")?,
+                quantity: BigDecimal::from_str("0.25")?,
+                taker_side: Some(OrderSide::Sell),
+                date_time: DateTime::<Utc>::from_str("2025-12-17T18:30:01Z")?,
+            }
+        );
+        assert_eq!(
+            quote.to_quote_update()?,
+            QuoteUpdate {
+                crypto_pair,
+                bid_price: BigDecimal::from(100),
+                bid_quantity: BigDecimal::from_str("1.5")?,
+                ask_price: BigDecimal::from(101),
+                ask_quantity: BigDecimal::from(2),
+                date_time: DateTime::<Utc>::from_str("2025-12-17T18:30:02Z")?,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn subscription_messages() -> anyhow::Result<()> {
+        let crypto_pairs = vec![
+            Instrument::from_str("BTC/USD")?,
+            Instrument::from_str("ETH/USD")?,
+        ];
+        assert_eq!(
+            command_message(&Command::Subscribe(
+                MarketChannel::Bars,
+                crypto_pairs.clone()
+            )),
+            json!({ "action": "subscribe", "bars": ["BTC/USD", "ETH/USD"] })
+        );
+        assert_eq!(
+            command_message(&Command::Unsubscribe(MarketChannel::Quotes, crypto_pairs)),
+            json!({ "action": "unsubscribe", "quotes": ["BTC/USD", "ETH/USD"] })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn stream_url_of_region() {
+        assert_eq!(
+            get_stream_url("eu-1"),
+            "wss://stream.data.alpaca.markets/v1beta3/crypto/eu-1"
+        );
+    }
+}
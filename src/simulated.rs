@@ -3,6 +3,10 @@
 
 pub use broker::SimulatedBrokerBuilder;
 pub use broker::SimulatedBroker;
+pub use broker::MarginCall;
+pub use broker::BuyingPowerReport;
+pub use broker::MAIN_ACCOUNT;
+pub use broker::CashReserve;
 mod broker;
 
 pub use client::SimulatedClient;
@@ -12,8 +16,48 @@ pub use environment::SimulatedEnvironment;
 pub use environment::SimulatedEnvironmentBuilder;
 mod environment;
 
+pub use progress::BacktestProgress;
+pub use progress::BacktestProgressStream;
+mod progress;
+
 pub mod time;
 pub mod data;
+pub mod fill;
+pub mod matching;
+pub mod price;
+pub mod quality;
+pub mod synthetic;
 
 pub use context::SimulatedContext; 
 mod context;
+
+pub use event::EventBus;
+pub use event::OrderListenerGuard;
+pub use event::OrderEvent;
+pub use event::RiskAlert;
+pub use event::SimulationEvent;
+mod event;
+
+pub use exchange::SimulatedExchange;
+mod exchange;
+
+pub use scenario::Scenario;
+mod scenario;
+
+pub use seed::RandomSource;
+pub use seed::RngState;
+pub use seed::SimulationConfig;
+mod seed;
+
+pub use shard::BacktestShard;
+pub use shard::ShardedBacktest;
+pub use shard::ShardedBacktestBuilder;
+pub use shard::ShardedBacktestResult;
+mod shard;
+
+pub use sweep::SensitivityResult;
+pub use sweep::SensitivitySweep;
+pub use sweep::SensitivitySweepBuilder;
+mod sweep;
+
+mod book;
@@ -0,0 +1,179 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Order, OrderId, OrderSide};
+use bigdecimal::BigDecimal;
+use std::collections::{BTreeMap, HashMap};
+
+/// Open orders of the simulated broker indexed by pair, side and limit price, so that a price
+/// update only has to look at the orders whose limit price it crosses.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PendingOrderBook {
+    books: HashMap<String, PairBook>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PairBook {
+    /// Market orders left unfilled, e.g. while trading is halted.
+    market: Vec<OrderId>,
+    buys: BTreeMap<BigDecimal, Vec<OrderId>>,
+    sells: BTreeMap<BigDecimal, Vec<OrderId>>,
+}
+
+impl PendingOrderBook {
+    pub(crate) fn insert(&mut self, order: &Order) {
+        let book = self.books.entry(order.asset_symbol.clone()).or_default();
+        let order_ids = match (&order.limit_price, &order.side) {
+            (None, _) => &mut book.market,
+            (Some(limit_price), OrderSide::Buy) => {
+                book.buys.entry(limit_price.clone()).or_default()
+            }
+            (Some(limit_price), OrderSide::Sell) => {
+                book.sells.entry(limit_price.clone()).or_default()
+            }
+        };
+        order_ids.push(order.order_id.clone());
+    }
+
+    pub(crate) fn remove(&mut self, order: &Order) {
+        let Some(book) = self.books.get_mut(&order.asset_symbol) else {
+            return;
+        };
+        match (&order.limit_price, &order.side) {
+            (None, _) => book.market.retain(|order_id| *order_id != order.order_id),
+            (Some(limit_price), OrderSide::Buy) => {
+                remove_from_level(&mut book.buys, limit_price, &order.order_id)
+            }
+            (Some(limit_price), OrderSide::Sell) => {
+                remove_from_level(&mut book.sells, limit_price, &order.order_id)
+            }
+        }
+        if book.market.is_empty() && book.buys.is_empty() && book.sells.is_empty() {
+            self.books.remove(&order.asset_symbol);
+        }
+    }
+
+    /// Ids of the pending market orders of a pair and of its limit orders crossed by
+    /// `notional_per_unit`, in time priority within each price level.
+    pub(crate) fn get_crossed_order_ids(
+        &self,
+        asset_symbol: &str,
+        notional_per_unit: &BigDecimal,
+    ) -> Vec<OrderId> {
+        let Some(book) = self.books.get(asset_symbol) else {
+            return Vec::new();
+        };
+        let mut order_ids = book.market.clone();
+        order_ids.extend(
+            book.buys
+                .range(notional_per_unit..)
+                .rev()
+                .flat_map(|(_, order_ids)| order_ids.iter().cloned()),
+        );
+        order_ids.extend(
+            book.sells
+                .range(..=notional_per_unit)
+                .flat_map(|(_, order_ids)| order_ids.iter().cloned()),
+        );
+        order_ids
+    }
+
+    pub(crate) fn get_order_ids(&self) -> Vec<OrderId> {
+        self.books
+            .values()
+            .flat_map(|book| {
+                book.market
+                    .iter()
+                    .chain(book.buys.values().flatten())
+                    .chain(book.sells.values().flatten())
+                    .cloned()
+            })
+            .collect()
+    }
+}
+
+fn remove_from_level(
+    levels: &mut BTreeMap<BigDecimal, Vec<OrderId>>,
+    limit_price: &BigDecimal,
+    order_id: &OrderId,
+) {
+    if let Some(order_ids) = levels.get_mut(limit_price) {
+        order_ids.retain(|level_order_id| level_order_id != order_id);
+        if order_ids.is_empty() {
+            levels.remove(limit_price);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, OrderStatus, OrderType};
+
+    #[test]
+    fn get_crossed_order_ids() {
+        let mut book = PendingOrderBook::default();
+        book.insert(&create_order("buy_9", OrderSide::Buy, Some(9)));
+        book.insert(&create_order("buy_11", OrderSide::Buy, Some(11)));
+        book.insert(&create_order("sell_9", OrderSide::Sell, Some(9)));
+        book.insert(&create_order("sell_11", OrderSide::Sell, Some(11)));
+        book.insert(&create_order("market", OrderSide::Buy, None));
+
+        assert_eq!(
+            book.get_crossed_order_ids("BTC/USD", &BigDecimal::from(10)),
+            create_order_ids(&["market", "buy_11", "sell_9"])
+        );
+        assert_eq!(
+            book.get_crossed_order_ids("BTC/USD", &BigDecimal::from(9)),
+            create_order_ids(&["market", "buy_11", "buy_9", "sell_9"])
+        );
+        assert!(
+            book.get_crossed_order_ids("ETH/USD", &BigDecimal::from(10))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn remove() {
+        let mut book = PendingOrderBook::default();
+        let order = create_order("buy", OrderSide::Buy, Some(9));
+        book.insert(&order);
+        book.insert(&create_order("market", OrderSide::Sell, None));
+
+        book.remove(&order);
+
+        assert_eq!(book.get_order_ids(), create_order_ids(&["market"]));
+        book.remove(&create_order("market", OrderSide::Sell, None));
+        assert!(book.books.is_empty());
+    }
+
+    fn create_order_ids(order_ids: &[&str]) -> Vec<OrderId> {
+        order_ids.iter().map(|order_id| OrderId::new(*order_id)).collect()
+    }
+
+    fn create_order(order_id: &str, side: OrderSide, limit_price: Option<i32>) -> Order {
+        Order {
+            order_id: OrderId::new(order_id),
+            asset_symbol: "BTC/USD".into(),
+            amount: Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+            limit_price: limit_price.map(BigDecimal::from),
+            filled_quantity: BigDecimal::from(0),
+            average_fill_price: None,
+            status: OrderStatus::New,
+            type_: match limit_price {
+                Some(_) => OrderType::Limit,
+                None => OrderType::Market,
+            },
+            side,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
+        }
+    }
+}
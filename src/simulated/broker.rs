@@ -1,31 +1,208 @@
 // Copyright (C) 2025 Agostinho Junior
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::api::common::{Amount, CryptoPair, Order, OrderSide, OrderStatus, OrderType};
-use crate::api::request::OrderRequest;
-use anyhow::{Result, anyhow};
+use crate::api::common::{
+    AccountActivity, ActivityKind, Amount, Asset, AssetStatus, FlattenSummary, Instrument, Order,
+    OrderId, OrderSide, OrderStatus, OrderType, OrderValidation, TimeInForce, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::validation::OrderValidator;
+use crate::backtest::CheckpointOrder;
+use crate::simulated::book::PendingOrderBook;
+use crate::simulated::event::{
+    EventBus, OrderEvent, OrderListenerGuard, OrderListeners, RiskAlert, SimulationEvent,
+};
+use crate::simulated::fill::FillPolicy;
+use crate::simulated::matching::{
+    Execution, FillPolicyMatchingEngine, MarketState, MatchingEngine, get_remaining_notional,
+    get_remaining_quantity,
+};
+use crate::simulated::seed::{RandomSource, RngState};
+use crate::simulated::time::Clock;
+use crate::{Error, Result};
 use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 use std::collections::{HashMap, HashSet};
-use std::str::FromStr;
+use std::sync::Mutex;
 use uuid::Uuid;
 
+/// Decimal places of the prices of pairs derived from the price of their inverse pair.
+const INVERSE_PRICE_SCALE: i64 = 12;
+
 #[derive(Debug, Clone)]
 pub struct SimulatedBroker {
+    /// Account the broker publishes its events as, [MAIN_ACCOUNT] unless it's a sub-account or
+    /// an exchange account.
+    account: String,
     currency: String,
     notional_assets: HashSet<String>,
     buying_power_balances: HashMap<String, BigDecimal>,
-    orders: HashMap<String, Order>,
-    notional_per_unit: HashMap<CryptoPair, BigDecimal>,
+    orders: HashMap<OrderId, Order>,
+    /// Ids of the orders in the order they were placed.
+    order_ids: Vec<OrderId>,
+    /// Position of each order in `order_ids`.
+    order_indices: HashMap<OrderId, usize>,
+    /// Ids of the orders by client order id.
+    client_order_ids: HashMap<String, OrderId>,
+    trades: Vec<Trade>,
+    /// Positions in `trades` of the fills of each order.
+    trade_indices: HashMap<OrderId, Vec<usize>>,
+    activities: Vec<AccountActivity>,
+    notional_per_unit: HashMap<Instrument, BigDecimal>,
     balances: HashMap<String, BigDecimal>,
+    /// Average price paid in the currency per unit of each held asset, fees included.
+    average_entry_prices: HashMap<String, BigDecimal>,
+    /// Balances and average entry prices the account is reset to.
+    starting_balances: HashMap<String, BigDecimal>,
+    starting_average_entry_prices: HashMap<String, BigDecimal>,
     fee_multiplier: BigDecimal,
+    margin: Option<Margin>,
+    margin_calls: Vec<MarginCall>,
+    event_bus: Option<EventBus>,
+    order_listeners: OrderListeners,
+    matching_engine: Box<dyn MatchingEngine + Send + Sync>,
+    order_validator: OrderValidator,
+    cash_reserve: Option<CashReserve>,
+    max_price_age: Option<Duration>,
+    clock: Option<Box<dyn Clock + Send + Sync>>,
+    price_updated_at: HashMap<Instrument, DateTime<Utc>>,
+    trading_halted: bool,
+    reservations: HashMap<OrderId, Reservation>,
+    /// Instrument of each order, as its asset symbol doesn't tell the asset class.
+    instruments: HashMap<OrderId, Instrument>,
+    disabled_pairs: HashSet<Instrument>,
+    max_consecutive_rejections: Option<u32>,
+    consecutive_rejections: HashMap<Instrument, u32>,
+    slippage_multiplier: BigDecimal,
+    sub_accounts: HashMap<String, SimulatedBroker>,
+    pending_orders: PendingOrderBook,
+    id_generator: IdGenerator,
 }
 
-#[derive(Debug)]
+/// Name of the account of the broker itself, as opposed to its sub-accounts, in
+/// [SimulatedBroker::transfer].
+pub const MAIN_ACCOUNT: &str = "main";
+
+#[derive(Debug, Clone)]
 pub struct SimulatedBrokerBuilder {
     currency: String,
     notional_assets: HashSet<String>,
     balances: HashMap<String, BigDecimal>,
+    average_entry_prices: HashMap<String, BigDecimal>,
     fee_multiplier: BigDecimal,
+    margin: Option<Margin>,
+    matching_engine: Box<dyn MatchingEngine + Send + Sync>,
+    order_validator: OrderValidator,
+    cash_reserve: Option<CashReserve>,
+    max_price_age: Option<Duration>,
+    max_consecutive_rejections: Option<u32>,
+    slippage_multiplier: BigDecimal,
+    id_seed: Option<u64>,
+    fill_seed: Option<u64>,
+}
+
+/// Generates order and trade ids, random unless seeded.
+#[derive(Debug, Default)]
+struct IdGenerator {
+    rng: Option<Mutex<ChaCha12Rng>>,
+}
+
+impl IdGenerator {
+    fn seeded(seed: u64) -> Self {
+        Self {
+            rng: Some(Mutex::new(ChaCha12Rng::seed_from_u64(seed))),
+        }
+    }
+
+    /// State of the random number generator, `None` for random ids.
+    fn get_rng_state(&self) -> Option<RngState> {
+        self.rng
+            .as_ref()
+            .map(|rng| RngState::of(&rng.lock().unwrap()))
+    }
+
+    fn set_rng_state(&mut self, rng_state: &RngState) {
+        self.rng = Some(Mutex::new(rng_state.to_rng()));
+    }
+
+    fn next_id(&self) -> String {
+        match &self.rng {
+            Some(rng) => uuid::Builder::from_random_bytes(rng.lock().unwrap().random())
+                .into_uuid()
+                .to_string(),
+            None => Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// Generator of other ids than this one, e.g. for a sub-account.
+    fn fork(&self) -> Self {
+        match &self.rng {
+            Some(rng) => Self::seeded(rng.lock().unwrap().random()),
+            None => Self::default(),
+        }
+    }
+}
+
+impl Clone for IdGenerator {
+    fn clone(&self) -> Self {
+        Self {
+            rng: self
+                .rng
+                .as_ref()
+                .map(|rng| Mutex::new(rng.lock().unwrap().clone())),
+        }
+    }
+}
+
+/// Buying power held for an open order, released as the order gets filled, cancelled or expires.
+#[derive(Debug, Clone)]
+struct Reservation {
+    asset: String,
+    amount: BigDecimal,
+    /// Price the reservation of a buy order was computed with, `None` when it was reserved by
+    /// notional.
+    price: Option<BigDecimal>,
+}
+
+#[derive(Debug, Clone)]
+struct Margin {
+    leverage: BigDecimal,
+    maintenance_margin_multiplier: BigDecimal,
+}
+
+/// Part of the currency balance that is kept out of the buying power, as a buffer for fees and
+/// withdrawals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CashReserve {
+    Absolute(BigDecimal),
+    /// Percentage of the currency balance, up to one hundred.
+    Percentage(BigDecimal),
+}
+
+/// Breakdown of the buying power of an asset, see [SimulatedBroker::get_buying_power_report].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuyingPowerReport {
+    pub balance: BigDecimal,
+    /// Buying power reserved by each open order, keyed by order id.
+    pub reserved: HashMap<OrderId, BigDecimal>,
+    /// Buying power left to place new orders with.
+    pub buying_power: BigDecimal,
+}
+
+/// Recorded every time the broker's equity falls below its maintenance margin while margin mode
+/// is enabled, along with the market orders placed to close the open positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarginCall {
+    pub equity: BigDecimal,
+    pub maintenance_margin: BigDecimal,
+    pub liquidation_order_ids: Vec<OrderId>,
 }
 
 impl SimulatedBrokerBuilder {
@@ -39,7 +216,17 @@ impl SimulatedBrokerBuilder {
             currency,
             notional_assets,
             balances,
+            average_entry_prices: HashMap::new(),
             fee_multiplier: BigDecimal::from(0),
+            margin: None,
+            matching_engine: Box::new(FillPolicyMatchingEngine::default()),
+            order_validator: OrderValidator::default(),
+            cash_reserve: None,
+            max_price_age: None,
+            max_consecutive_rejections: None,
+            slippage_multiplier: BigDecimal::from(0),
+            id_seed: None,
+            fill_seed: None,
         }
     }
 
@@ -48,6 +235,49 @@ impl SimulatedBrokerBuilder {
         self
     }
 
+    /// Starts the broker with an open position of `quantity` units of `asset`, bought at
+    /// `average_entry_price` in the currency.
+    pub fn add_position(
+        &mut self,
+        asset: &str,
+        quantity: BigDecimal,
+        average_entry_price: BigDecimal,
+    ) -> Result<&mut Self> {
+        if self.notional_assets.contains(asset) {
+            return Err(Error::InvalidArgument(format!(
+                "Cannot add a position of notional asset {}",
+                asset
+            )));
+        }
+        if quantity <= 0 {
+            return Err(Error::InvalidArgument(
+                "Position quantity must be positive".into(),
+            ));
+        }
+        if average_entry_price < 0 {
+            return Err(Error::InvalidArgument(
+                "Average entry price must not be negative".into(),
+            ));
+        }
+        self.balances.insert(asset.into(), quantity);
+        self.average_entry_prices
+            .insert(asset.into(), average_entry_price);
+        Ok(self)
+    }
+
+    /// Replaces the balances and average entry prices of the given assets, e.g. to resume from a
+    /// checkpoint.
+    pub(crate) fn restore_balances(
+        &mut self,
+        balances: &HashMap<String, BigDecimal>,
+        average_entry_prices: &HashMap<String, BigDecimal>,
+    ) -> &mut Self {
+        self.balances.extend(balances.clone());
+        self.average_entry_prices
+            .extend(average_entry_prices.clone());
+        self
+    }
+
     pub fn add_notional_asset(
         &mut self,
         notional_asset: &str,
@@ -64,21 +294,168 @@ impl SimulatedBrokerBuilder {
         &mut self,
         fee_percentage: BigDecimal,
     ) -> Result<&mut Self> {
-        if fee_percentage < BigDecimal::from(0) || fee_percentage > BigDecimal::from(100) {
-            return Err(anyhow!("Fee percentage must be between 0 and 100"));
+        if !(BigDecimal::from(0)..=BigDecimal::from(100)).contains(&fee_percentage) {
+            return Err(Error::InvalidArgument(
+                "Fee percentage must be between 0 and 100".into(),
+            ));
         }
         self.fee_multiplier = fee_percentage / BigDecimal::from(100);
         Ok(self)
     }
 
-    pub fn build(&self) -> SimulatedBroker {
-        SimulatedBroker::new(
+    /// Worsens the price of every execution by `slippage_percentage`, without going past the limit
+    /// price of limit orders.
+    pub fn set_slippage_percentage(
+        &mut self,
+        slippage_percentage: BigDecimal,
+    ) -> Result<&mut Self> {
+        if !(BigDecimal::from(0)..=BigDecimal::from(100)).contains(&slippage_percentage) {
+            return Err(Error::InvalidArgument(
+                "Slippage percentage must be between 0 and 100".into(),
+            ));
+        }
+        self.slippage_multiplier = slippage_percentage / BigDecimal::from(100);
+        Ok(self)
+    }
+
+    /// Enables margin mode, allowing the currency balance to be borrowed against up to `leverage`
+    /// times the account equity. Whenever a price update leaves the equity below the maintenance
+    /// margin, the open orders are cancelled and all open positions, long or short, are closed
+    /// with market orders, even on disabled pairs or while trading is halted.
+    pub fn set_margin(
+        &mut self,
+        leverage: BigDecimal,
+        maintenance_margin_percentage: BigDecimal,
+    ) -> Result<&mut Self> {
+        if leverage < 1 {
+            return Err(Error::InvalidArgument("Leverage must be at least 1".into()));
+        }
+        if maintenance_margin_percentage <= 0 || maintenance_margin_percentage > 100 {
+            return Err(Error::InvalidArgument(
+                "Maintenance margin percentage must be greater than 0 and up to 100".into(),
+            ));
+        }
+        self.margin = Some(Margin {
+            leverage,
+            maintenance_margin_multiplier: maintenance_margin_percentage / BigDecimal::from(100),
+        });
+        Ok(self)
+    }
+
+    pub fn set_cash_reserve(&mut self, cash_reserve: CashReserve) -> Result<&mut Self> {
+        match &cash_reserve {
+            CashReserve::Absolute(reserve) if *reserve < 0 => {
+                return Err(Error::InvalidArgument(
+                    "Cash reserve must not be negative".into(),
+                ));
+            }
+            CashReserve::Percentage(percentage) if *percentage < 0 || *percentage > 100 => {
+                return Err(Error::InvalidArgument(
+                    "Cash reserve percentage must be between 0 and 100".into(),
+                ));
+            }
+            _ => {}
+        }
+        self.cash_reserve = Some(cash_reserve);
+        Ok(self)
+    }
+
+    /// Rejects market orders whose pair price was set longer than `max_price_age` ago, according
+    /// to the clock given to [SimulatedBroker::set_clock].
+    pub fn set_max_price_age(&mut self, max_price_age: Duration) -> &mut Self {
+        self.max_price_age = Some(max_price_age);
+        self
+    }
+
+    /// Disables trading a pair after `max_consecutive_rejections` of its orders in a row are
+    /// rejected, until it is enabled again with [SimulatedBroker::set_pair_trading_enabled].
+    pub fn set_max_consecutive_rejections(
+        &mut self,
+        max_consecutive_rejections: u32,
+    ) -> Result<&mut Self> {
+        if max_consecutive_rejections == 0 {
+            return Err(Error::InvalidArgument(
+                "Maximum consecutive rejections must be at least 1".into(),
+            ));
+        }
+        self.max_consecutive_rejections = Some(max_consecutive_rejections);
+        Ok(self)
+    }
+
+    /// Replaces the default [TouchFillPolicy](crate::simulated::fill::TouchFillPolicy) deciding
+    /// when and at which price orders get filled, always filling the whole order at once.
+    pub fn set_fill_policy<F>(&mut self, fill_policy: F) -> &mut Self
+    where
+        F: FillPolicy + Send + Sync + 'static,
+    {
+        self.set_matching_engine(FillPolicyMatchingEngine::new(fill_policy))
+    }
+
+    /// Replaces the default [FillPolicyMatchingEngine] producing the executions of pending orders.
+    pub fn set_matching_engine<M>(&mut self, matching_engine: M) -> &mut Self
+    where
+        M: MatchingEngine + Send + Sync + 'static,
+    {
+        self.matching_engine = Box::new(matching_engine);
+        self
+    }
+
+    /// Replaces the default [OrderValidator] every order request is checked with before placing it.
+    pub fn set_order_validator(&mut self, order_validator: OrderValidator) -> &mut Self {
+        self.order_validator = order_validator;
+        self
+    }
+
+    /// Seeds the generation of order and trade ids, which are random by default, so that
+    /// repeated runs create the same ids.
+    pub fn set_id_seed(&mut self, seed: u64) -> &mut Self {
+        self.id_seed = Some(seed);
+        self
+    }
+
+    /// Seeds the matching engine when building the broker, whichever engine or fill policy is
+    /// set, so that repeated runs with a random fill policy fill orders the same way.
+    pub fn set_fill_seed(&mut self, seed: u64) -> &mut Self {
+        self.fill_seed = Some(seed);
+        self
+    }
+
+    pub fn build(&self) -> Result<SimulatedBroker> {
+        if self.currency.is_empty() {
+            return Err(Error::InvalidArgument("Currency must not be empty".into()));
+        }
+        if let Some(asset) = self
+            .average_entry_prices
+            .keys()
+            .find(|asset| self.notional_assets.contains(*asset))
+        {
+            return Err(Error::InvalidArgument(format!(
+                "Cannot add a position of notional asset {}",
+                asset
+            )));
+        }
+        let mut broker = SimulatedBroker::new(
             &self.currency,
             self.notional_assets.clone(),
             self.balances.clone(),
             self.fee_multiplier.clone(),
-        )
-        .unwrap()
+        )?;
+        broker.average_entry_prices = self.average_entry_prices.clone();
+        broker.starting_average_entry_prices = self.average_entry_prices.clone();
+        broker.margin = self.margin.clone();
+        broker.matching_engine = self.matching_engine.clone();
+        if let Some(seed) = self.fill_seed {
+            broker.matching_engine.reseed(seed);
+        }
+        broker.order_validator = self.order_validator.clone();
+        broker.cash_reserve = self.cash_reserve.clone();
+        broker.max_price_age = self.max_price_age;
+        broker.max_consecutive_rejections = self.max_consecutive_rejections;
+        broker.slippage_multiplier = self.slippage_multiplier.clone();
+        if let Some(seed) = self.id_seed {
+            broker.id_generator = IdGenerator::seeded(seed);
+        }
+        Ok(broker)
     }
 }
 
@@ -90,29 +467,351 @@ impl SimulatedBroker {
         fee_multiplier: BigDecimal,
     ) -> Result<Self> {
         if !notional_assets.contains(currency) {
-            return Err(anyhow!("Missing currency notional asset {}", currency));
+            return Err(Error::InvalidArgument(format!(
+                "Missing currency notional asset {}",
+                currency
+            )));
         }
         Ok(Self {
+            account: MAIN_ACCOUNT.into(),
             currency: currency.into(),
             notional_assets,
             orders: HashMap::new(),
+            order_ids: Vec::new(),
+            order_indices: HashMap::new(),
+            client_order_ids: HashMap::new(),
+            trades: Vec::new(),
+            trade_indices: HashMap::new(),
+            activities: Vec::new(),
             notional_per_unit: HashMap::new(),
             buying_power_balances: starting_balances.clone(),
-            balances: starting_balances,
+            balances: starting_balances.clone(),
+            average_entry_prices: HashMap::new(),
+            starting_balances,
+            starting_average_entry_prices: HashMap::new(),
             fee_multiplier,
+            margin: None,
+            margin_calls: Vec::new(),
+            event_bus: None,
+            order_listeners: OrderListeners::default(),
+            matching_engine: Box::new(FillPolicyMatchingEngine::default()),
+            order_validator: OrderValidator::default(),
+            cash_reserve: None,
+            max_price_age: None,
+            clock: None,
+            price_updated_at: HashMap::new(),
+            trading_halted: false,
+            reservations: HashMap::new(),
+            instruments: HashMap::new(),
+            disabled_pairs: HashSet::new(),
+            max_consecutive_rejections: None,
+            consecutive_rejections: HashMap::new(),
+            slippage_multiplier: BigDecimal::from(0),
+            sub_accounts: HashMap::new(),
+            pending_orders: PendingOrderBook::default(),
+            id_generator: IdGenerator::default(),
+        })
+    }
+
+    pub(crate) fn set_matching_engine<M>(&mut self, matching_engine: M)
+    where
+        M: MatchingEngine + Send + Sync + 'static,
+    {
+        self.matching_engine = Box::new(matching_engine);
+    }
+
+    /// Timestamps every price update, required for the maximum price age to be enforced.
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.clock = Some(Box::new(clock));
+        for sub_account in self.sub_accounts.values_mut() {
+            sub_account.clock = self.clock.clone();
+        }
+    }
+
+    /// Publishes order updates, price updates and risk alerts to the given [EventBus].
+    pub fn set_event_bus(&mut self, event_bus: EventBus) {
+        for sub_account in self.sub_accounts.values_mut() {
+            sub_account.set_event_bus(event_bus.clone());
+        }
+        self.event_bus = Some(event_bus);
+    }
+
+    pub(crate) fn set_account(&mut self, account: &str) {
+        self.account = account.into();
+    }
+
+    /// Registers a listener invoked every time an order is placed or changes status, until the
+    /// returned guard is dropped. Listeners are shared with the clones and sub-accounts of this
+    /// broker.
+    pub fn add_order_listener<F>(&mut self, listener: F) -> OrderListenerGuard
+    where
+        F: FnMut(&OrderEvent) + Send + 'static,
+    {
+        self.order_listeners.add(listener)
+    }
+
+    fn publish(&self, event: SimulationEvent) {
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(event);
+        }
+    }
+
+    fn publish_risk_alert(&self, risk_alert: RiskAlert) {
+        self.publish(SimulationEvent::RiskAlert {
+            account: self.account.clone(),
+            risk_alert,
+        });
+    }
+
+    fn publish_order_event(&self, order_event: OrderEvent) {
+        self.order_listeners.notify(&order_event);
+        self.publish(SimulationEvent::OrderUpdate(Box::new(order_event)));
+    }
+
+    /// Places an order, or returns the id of the order already placed with the same client order
+    /// id.
+    pub fn place_order(&mut self, order_req: OrderRequest) -> Result<OrderId> {
+        if let Some(client_order_id) = &order_req.client_order_id
+            && let Some(order_id) = self.client_order_ids.get(client_order_id)
+        {
+            return Ok(order_id.clone());
+        }
+        let crypto_pair = order_req.crypto_pair.clone();
+        if self.disabled_pairs.contains(&crypto_pair) {
+            return Err(Error::TradingDisabled(crypto_pair.clone()));
+        }
+        let result = self.try_place_order(order_req);
+        if result.is_ok() {
+            self.consecutive_rejections.remove(&crypto_pair);
+            return result;
+        }
+        let consecutive_rejections = self
+            .consecutive_rejections
+            .entry(crypto_pair.clone())
+            .or_insert(0);
+        *consecutive_rejections += 1;
+        let consecutive_rejections = *consecutive_rejections;
+        if self
+            .max_consecutive_rejections
+            .is_some_and(|max_consecutive_rejections| {
+                consecutive_rejections >= max_consecutive_rejections
+            })
+        {
+            self.disable_pair(
+                crypto_pair,
+                format!("{} consecutive order rejections", consecutive_rejections),
+            );
+        }
+        result
+    }
+
+    /// Disabled pairs reject every new order, while their open orders keep being processed.
+    /// Enabling or disabling a pair applies to the sub-accounts too, while a sub-account
+    /// disabling a pair after its own rejections only disables it for that sub-account.
+    pub fn set_pair_trading_enabled(&mut self, crypto_pair: Instrument, enabled: bool) {
+        if enabled {
+            for sub_account in self.sub_accounts.values_mut() {
+                sub_account.consecutive_rejections.remove(&crypto_pair);
+                sub_account.disabled_pairs.remove(&crypto_pair);
+            }
+            self.consecutive_rejections.remove(&crypto_pair);
+            if self.disabled_pairs.remove(&crypto_pair) {
+                self.publish_risk_alert(RiskAlert::PairEnabled(crypto_pair));
+            }
+        } else {
+            self.disable_pair(crypto_pair, "Disabled manually".into());
+        }
+    }
+
+    pub fn is_pair_trading_enabled(&self, crypto_pair: &Instrument) -> bool {
+        !self.disabled_pairs.contains(crypto_pair)
+    }
+
+    /// Pairs with a known price whose trading is enabled, sorted by symbol.
+    pub fn list_asset_pairs(&self) -> Vec<Instrument> {
+        let mut crypto_pairs: Vec<Instrument> = self
+            .notional_per_unit
+            .keys()
+            .filter(|crypto_pair| self.is_pair_trading_enabled(crypto_pair))
+            .cloned()
+            .collect();
+        crypto_pairs.sort_by_key(|crypto_pair| crypto_pair.to_string());
+        crypto_pairs
+    }
+
+    /// Trading rules of a pair with a known price, from the order validator and the trading
+    /// status of the pair.
+    pub fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.get_notional_per_unit(crypto_pair)?;
+        let status = if !self.is_pair_trading_enabled(crypto_pair) {
+            AssetStatus::Disabled
+        } else if self.trading_halted {
+            AssetStatus::Halted
+        } else {
+            AssetStatus::Active
+        };
+        Ok(Asset {
+            crypto_pair: crypto_pair.clone(),
+            tradable: status != AssetStatus::Disabled,
+            status,
+            min_notional: self.order_validator.get_min_notional().cloned(),
+            max_notional: self.order_validator.get_max_notional().cloned(),
+            quantity_scale: self.order_validator.get_max_quantity_scale(),
+            price_scale: self.order_validator.get_max_limit_price_scale(),
+        })
+    }
+
+    fn disable_pair(&mut self, crypto_pair: Instrument, reason: String) {
+        for sub_account in self.sub_accounts.values_mut() {
+            sub_account.disabled_pairs.insert(crypto_pair.clone());
+        }
+        if self.disabled_pairs.insert(crypto_pair.clone()) {
+            self.publish_risk_alert(RiskAlert::PairDisabled {
+                crypto_pair,
+                reason,
+            });
+        }
+    }
+
+    /// Runs every check of [SimulatedBroker::place_order] without placing the order, reporting
+    /// the buying power it would reserve.
+    pub fn validate_order(&self, order_req: &OrderRequest) -> Result<OrderValidation> {
+        if self.disabled_pairs.contains(&order_req.crypto_pair) {
+            return Err(Error::TradingDisabled(order_req.crypto_pair.clone()));
+        }
+        self.check_order_request(order_req)?;
+        let order = self.new_unplaced_order(order_req.clone());
+        let (asset, buying_power_needed, buying_power) =
+            self.check_buying_power(&order, &order_req.crypto_pair)?;
+        Ok(OrderValidation {
+            reserved_asset: asset,
+            remaining_buying_power: buying_power - &buying_power_needed,
+            reserved_amount: buying_power_needed,
+            trading_halted: self.trading_halted,
         })
     }
 
-    pub fn place_order(&mut self, order_req: OrderRequest) -> Result<String> {
-        let order_id = Uuid::new_v4().to_string();
+    /// Places the orders in turn, placing none of them when any would be rejected.
+    ///
+    /// The batch is checked up front, each order against the buying power left by the previous
+    /// ones, without counting on what their fills bring in. A batch selling what it buys is
+    /// therefore rejected, while fees may still reject an order once the previous ones have filled.
+    pub fn place_orders(&mut self, order_reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        self.check_orders(&order_reqs)?;
+        order_reqs
+            .into_iter()
+            .map(|order_req| self.place_order(order_req))
+            .collect()
+    }
+
+    /// Runs the checks of [SimulatedBroker::place_orders] on a batch without placing it.
+    pub(crate) fn check_orders(&self, order_reqs: &[OrderRequest]) -> Result<()> {
+        let mut reserved_amounts: HashMap<String, BigDecimal> = HashMap::new();
+        let mut client_order_ids = HashSet::new();
+        for order_req in order_reqs {
+            // Orders already placed, in the account or earlier in the batch, are returned as is
+            if let Some(client_order_id) = &order_req.client_order_id
+                && (self.client_order_ids.contains_key(client_order_id)
+                    || !client_order_ids.insert(client_order_id))
+            {
+                continue;
+            }
+            if self.disabled_pairs.contains(&order_req.crypto_pair) {
+                return Err(Error::TradingDisabled(order_req.crypto_pair.clone()));
+            }
+            self.check_order_request(order_req)?;
+            let order = self.new_unplaced_order(order_req.clone());
+            let (asset, buying_power_needed) =
+                self.get_asset_and_buying_power_needed(&order, &order_req.crypto_pair)?;
+            let reserved_amount = reserved_amounts.entry(asset.clone()).or_default();
+            let buying_power = self.get_buying_power(&asset) - &*reserved_amount;
+            if buying_power < buying_power_needed {
+                return Err(Error::InsufficientBuyingPower {
+                    asset,
+                    needed: buying_power_needed,
+                    available: buying_power,
+                });
+            }
+            *reserved_amount += buying_power_needed;
+        }
+        Ok(())
+    }
+
+    fn try_place_order(&mut self, order_req: OrderRequest) -> Result<OrderId> {
+        let time_in_force = order_req.time_in_force.clone();
+        let crypto_pair = order_req.crypto_pair.clone();
+        let order = self.create_order(order_req)?;
+        let order_id = order.order_id.clone();
+        self.queue_order(order, crypto_pair)?;
+        if !self.trading_halted {
+            self.maybe_update_order(&order_id)?;
+        }
+        if time_in_force == TimeInForce::ImmediateOrCancel
+            && matches!(
+                self.get_order(&order_id)?.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            )
+        {
+            self.cancel_order(&order_id)?;
+        }
+
+        Ok(order_id)
+    }
+
+    fn create_order(&self, order_req: OrderRequest) -> Result<Order> {
+        self.check_order_request(&order_req)?;
+        Ok(self.new_order(self.next_order_id(), order_req))
+    }
+
+    fn check_order_request(&self, order_req: &OrderRequest) -> Result<()> {
+        if order_req.stop_price.is_some() {
+            return Err(Error::InvalidArgument(
+                "Stop orders are not supported by the simulated broker".into(),
+            ));
+        }
+        self.check_notional(&order_req.crypto_pair)?;
+        self.order_validator.validate(
+            order_req,
+            self.find_notional_per_unit(&order_req.crypto_pair).as_ref(),
+        )?;
+        if order_req.limit_price.is_none() {
+            self.check_price_age(&order_req.crypto_pair)?;
+        }
+        Ok(())
+    }
 
+    /// Places an order of the broker's own risk management, e.g. to liquidate a position, which
+    /// disabled pairs, the order validator, stale prices and trading halts don't hold back.
+    fn place_risk_order(&mut self, order_req: OrderRequest) -> Result<OrderId> {
+        self.check_notional(&order_req.crypto_pair)?;
+        let crypto_pair = order_req.crypto_pair.clone();
+        let order = self.new_order(self.next_order_id(), order_req);
+        let order_id = order.order_id.clone();
+        self.queue_order(order, crypto_pair)?;
+        self.maybe_update_order(&order_id)?;
+        Ok(order_id)
+    }
+
+    fn next_order_id(&self) -> OrderId {
+        OrderId::from(self.id_generator.next_id())
+    }
+
+    /// Order yet to be given an id, to work out the buying power it needs.
+    fn new_unplaced_order(&self, order_req: OrderRequest) -> Order {
+        self.new_order(OrderId::new(""), order_req)
+    }
+
+    fn new_order(&self, order_id: OrderId, order_req: OrderRequest) -> Order {
         let type_ = match order_req.limit_price {
             None => OrderType::Market,
             Some(_) => OrderType::Limit,
         };
 
-        let order = Order {
-            order_id: order_id.clone(),
+        Order {
+            order_id,
             asset_symbol: order_req.crypto_pair.to_string(),
             amount: order_req.amount,
             limit_price: order_req.limit_price,
@@ -121,35 +820,171 @@ impl SimulatedBroker {
             status: OrderStatus::New,
             type_,
             side: order_req.side,
-        };
-
-        self.queue_order(order.clone())?;
-
-        if order.limit_price.is_some() {
-            self.maybe_update_order(&order_id)?
-        } else {
-            self.fill_order_immediately(&order_id)?
+            display_quantity: order_req.display_quantity,
+            run_id: order_req.run_id,
+            strategy_id: order_req.strategy_id,
+            created_at: self.now(),
+            tag: order_req.tag,
+            metadata: order_req.metadata,
+            client_order_id: order_req.client_order_id,
         }
+    }
 
-        Ok(order_id)
+    fn check_price_age(&self, crypto_pair: &Instrument) -> Result<()> {
+        if let (Some(max_price_age), Some(clock), Some(price_updated_at)) = (
+            &self.max_price_age,
+            &self.clock,
+            self.price_updated_at
+                .get(crypto_pair)
+                .or_else(|| self.price_updated_at.get(&crypto_pair.inverse())),
+        ) && clock.now() - *price_updated_at > *max_price_age
+        {
+            return Err(Error::StalePrice {
+                crypto_pair: crypto_pair.clone(),
+                updated_at: *price_updated_at,
+            });
+        }
+        Ok(())
     }
 
-    fn queue_order(&mut self, order: Order) -> Result<()> {
-        let (asset, buying_power_needed) = self.get_asset_and_buying_power_needed(&order)?;
+    /// Returns the asset the order reserves buying power of, the buying power needed and the
+    /// buying power available.
+    fn check_buying_power(
+        &self,
+        order: &Order,
+        crypto_pair: &Instrument,
+    ) -> Result<(String, BigDecimal, BigDecimal)> {
+        let (asset, buying_power_needed) =
+            self.get_asset_and_buying_power_needed(order, crypto_pair)?;
         let buying_power = self.get_buying_power(&asset);
         if buying_power < buying_power_needed {
-            return Err(anyhow!("Not enough {} buying power", asset));
+            return Err(Error::InsufficientBuyingPower {
+                asset,
+                needed: buying_power_needed,
+                available: buying_power,
+            });
         }
-        self.update_buying_power(&asset, -buying_power_needed);
-        self.orders.insert(order.order_id.clone(), order);
+        Ok((asset, buying_power_needed, buying_power))
+    }
+
+    fn queue_order(&mut self, order: Order, crypto_pair: Instrument) -> Result<()> {
+        let (asset, buying_power_needed, _) = self.check_buying_power(&order, &crypto_pair)?;
+        self.update_buying_power(&asset, -&buying_power_needed);
+        // Buys of a quantity reserve buying power at the limit price or the current slipped price,
+        // which may differ from the fill price
+        let price = match (&order.side, &order.limit_price, &order.amount) {
+            (OrderSide::Buy, Some(limit_price), _) => Some(limit_price.clone()),
+            (OrderSide::Buy, None, Amount::Quantity { .. }) => {
+                Some(self.get_slipped_price(&order, &self.get_notional_per_unit(&crypto_pair)?))
+            }
+            _ => None,
+        };
+        self.insert_order(
+            order.clone(),
+            crypto_pair,
+            Reservation {
+                asset,
+                amount: buying_power_needed,
+                price,
+            },
+        );
+        self.publish_order_event(OrderEvent {
+            account: self.account.clone(),
+            previous_status: None,
+            order,
+        });
         Ok(())
     }
 
-    fn get_asset_and_buying_power_needed(&self, order: &Order) -> Result<(String, BigDecimal)> {
-        let asset_pair = &CryptoPair::from_str(&order.asset_symbol)?;
+    fn insert_order(&mut self, order: Order, crypto_pair: Instrument, reservation: Reservation) {
+        self.reservations
+            .insert(order.order_id.clone(), reservation);
+        self.pending_orders.insert(&order);
+        self.instruments.insert(order.order_id.clone(), crypto_pair);
+        self.order_indices
+            .insert(order.order_id.clone(), self.order_ids.len());
+        self.order_ids.push(order.order_id.clone());
+        if let Some(client_order_id) = &order.client_order_id {
+            self.client_order_ids
+                .insert(client_order_id.clone(), order.order_id.clone());
+        }
+        self.orders.insert(order.order_id.clone(), order);
+    }
+
+    /// Open orders in the order they were placed, with the buying power they still reserve, e.g.
+    /// to take a checkpoint.
+    pub(crate) fn get_checkpoint_orders(&self) -> Vec<CheckpointOrder> {
+        self.get_open_orders(&OrderFilter::default())
+            .into_iter()
+            .map(|order| {
+                let reservation = self.reservations.get(&order.order_id);
+                CheckpointOrder {
+                    instrument: self.instruments[&order.order_id].clone(),
+                    reserved: reservation.map_or(BigDecimal::from(0), |reservation| {
+                        reservation.amount.clone()
+                    }),
+                    reservation_price: reservation
+                        .and_then(|reservation| reservation.price.clone()),
+                    order,
+                }
+            })
+            .collect()
+    }
+
+    /// Puts back an open order of a checkpoint, reserving its buying power again out of the
+    /// balances restored along with it.
+    pub(crate) fn restore_order(&mut self, checkpoint_order: CheckpointOrder) {
+        let CheckpointOrder {
+            order,
+            instrument,
+            reserved,
+            reservation_price,
+        } = checkpoint_order;
+        let asset = match order.side {
+            OrderSide::Buy => instrument.notional_coin.clone(),
+            OrderSide::Sell => instrument.quantity_coin.clone(),
+        };
+        self.update_buying_power(&asset, -&reserved);
+        self.insert_order(
+            order,
+            instrument,
+            Reservation {
+                asset,
+                amount: reserved,
+                price: reservation_price,
+            },
+        );
+    }
+
+    /// States of the random number generators of the ids and of the matching engine, kept in
+    /// backtest checkpoints.
+    pub(crate) fn get_rng_states(&self) -> HashMap<RandomSource, RngState> {
+        let mut rng_states = HashMap::new();
+        if let Some(rng_state) = self.id_generator.get_rng_state() {
+            rng_states.insert(RandomSource::Ids, rng_state);
+        }
+        if let Some(rng_state) = self.matching_engine.get_rng_state() {
+            rng_states.insert(RandomSource::Fills, rng_state);
+        }
+        rng_states
+    }
+
+    pub(crate) fn set_rng_states(&mut self, rng_states: &HashMap<RandomSource, RngState>) {
+        if let Some(rng_state) = rng_states.get(&RandomSource::Ids) {
+            self.id_generator.set_rng_state(rng_state);
+        }
+        if let Some(rng_state) = rng_states.get(&RandomSource::Fills) {
+            self.matching_engine.set_rng_state(rng_state);
+        }
+    }
 
+    fn get_asset_and_buying_power_needed(
+        &self,
+        order: &Order,
+        asset_pair: &Instrument,
+    ) -> Result<(String, BigDecimal)> {
         let (quantity, notional) =
-            self.get_current_quantity_and_notional(&order.asset_symbol, &order.amount)?;
+            self.get_current_quantity_and_notional(asset_pair, &order.amount)?;
 
         let asset: &str;
         let buying_power_needed: BigDecimal;
@@ -158,6 +993,10 @@ impl SimulatedBroker {
             asset = &asset_pair.notional_coin;
             if let Some(limit_price) = &order.limit_price {
                 buying_power_needed = limit_price * quantity;
+            } else if let Amount::Quantity { .. } = order.amount {
+                // Market buys of a quantity fill at the slipped price
+                let notional_per_unit = self.get_notional_per_unit(asset_pair)?;
+                buying_power_needed = self.get_slipped_price(order, &notional_per_unit) * quantity;
             } else {
                 buying_power_needed = notional;
             }
@@ -169,30 +1008,90 @@ impl SimulatedBroker {
         Ok((asset.to_string(), buying_power_needed))
     }
 
-    fn maybe_update_order(&mut self, order_id: &String) -> Result<()> {
+    fn maybe_update_order(&mut self, order_id: &OrderId) -> Result<()> {
         let order = self.orders.get(order_id).unwrap().clone();
-        let asset_pair = &CryptoPair::from_str(&order.asset_symbol)?;
-        let current_price = &self.get_notional_per_unit(asset_pair)?;
-        let limit_price = &order.limit_price.clone().unwrap();
+        let crypto_pair = self.instruments[order_id].clone();
+        let notional_per_unit = self.get_notional_per_unit(&crypto_pair)?;
+        let market_state = MarketState {
+            crypto_pair,
+            notional_per_unit,
+        };
 
-        if current_price == limit_price
-            || ((order.side == OrderSide::Buy) == (current_price < limit_price))
-        {
-            self.fill_order_immediately(&order.order_id)?;
+        // Iceberg orders only expose their current slice to the matching engine
+        let mut displayed_quantity = order.display_quantity.clone().map(|display_quantity| {
+            display_quantity.min(get_remaining_quantity(
+                &order,
+                &market_state.notional_per_unit,
+            ))
+        });
+
+        for mut execution in self.matching_engine.match_order(&order, &market_state) {
+            if self.orders[order_id].status == OrderStatus::Filled {
+                break;
+            }
+            if let Some(displayed_quantity) = &mut displayed_quantity {
+                if *displayed_quantity <= 0 {
+                    break;
+                }
+                execution.quantity = execution.quantity.min(displayed_quantity.clone());
+                *displayed_quantity -= &execution.quantity;
+            }
+            execution.price = self.get_slipped_price(&order, &execution.price);
+            self.execute_order(order_id, &execution)?;
         }
 
         Ok(())
     }
 
-    fn fill_order_immediately(&mut self, order_id: &String) -> Result<()> {
+    fn get_slipped_price(&self, order: &Order, price: &BigDecimal) -> BigDecimal {
+        if self.slippage_multiplier == 0 {
+            return price.clone();
+        }
+        let slipped_price: BigDecimal = match order.side {
+            OrderSide::Buy => price * (1 + &self.slippage_multiplier),
+            OrderSide::Sell => price * (1 - &self.slippage_multiplier),
+        };
+        match (&order.side, &order.limit_price) {
+            (OrderSide::Buy, Some(limit_price)) => {
+                slipped_price.min(limit_price.max(price).clone())
+            }
+            (OrderSide::Sell, Some(limit_price)) => {
+                slipped_price.max(limit_price.min(price).clone())
+            }
+            _ => slipped_price,
+        }
+    }
+
+    pub(crate) fn execute_order(
+        &mut self,
+        order_id: &OrderId,
+        execution: &Execution,
+    ) -> Result<()> {
         let order = &self.orders.get(order_id).unwrap().clone();
-        let (quantity, notional) =
-            &self.get_current_quantity_and_notional(&order.asset_symbol, &order.amount)?;
-        let asset_pair = &CryptoPair::from_str(&order.asset_symbol)?;
+        let remaining_quantity = get_remaining_quantity(order, &execution.price);
+        if execution.quantity <= 0 {
+            return Ok(());
+        }
+        let is_filled = execution.quantity >= remaining_quantity;
+        let quantity = &if is_filled {
+            remaining_quantity
+        } else {
+            execution.quantity.clone()
+        };
+        let notional = &match &order.amount {
+            Amount::Notional { notional } if is_filled => get_remaining_notional(order, notional),
+            _ => quantity * &execution.price,
+        };
+        let asset_pair = &self.instruments[order_id].clone();
         let notional_asset = &asset_pair.notional_coin;
         let quantity_asset = &asset_pair.quantity_coin;
 
         if order.side == OrderSide::Buy {
+            self.update_average_entry_price(
+                asset_pair,
+                &(quantity.clone() * (1 - &self.fee_multiplier)),
+                notional,
+            );
             self.update_balance(notional_asset, -notional);
             self.update_balance(
                 quantity_asset,
@@ -202,9 +1101,9 @@ impl SimulatedBroker {
                 quantity_asset,
                 quantity.clone() * (1 - &self.fee_multiplier),
             );
-            if let Some(limit_price) = order.limit_price.clone() {
-                self.update_buying_power(notional_asset, limit_price * quantity - notional);
-            }
+            // The reserved buying power is released and the actual notional spent instead
+            let released = self.consume_reservation(order, quantity, notional);
+            self.update_buying_power(notional_asset, released - notional);
         } else {
             self.update_balance(
                 notional_asset,
@@ -215,38 +1114,156 @@ impl SimulatedBroker {
                 notional.clone() * (1 - &self.fee_multiplier),
             );
             self.update_balance(quantity_asset, -quantity);
+            if self.get_balance(quantity_asset) <= 0 {
+                self.average_entry_prices.remove(quantity_asset);
+            }
+            self.consume_reservation(order, quantity, notional);
         }
-
-        let adjusted_amount = match &order.amount {
-            Amount::Quantity { quantity } => Amount::Quantity {
-                quantity: quantity * (1 - &self.fee_multiplier),
-            },
-            Amount::Notional { notional } => Amount::Notional {
-                notional: notional * (1 - &self.fee_multiplier),
-            },
+        if is_filled {
+            self.release_reservation(order_id);
+            self.pending_orders.remove(order);
+        }
+        let (fee, fee_asset) = match order.side {
+            OrderSide::Buy => (quantity * &self.fee_multiplier, quantity_asset),
+            OrderSide::Sell => (notional * &self.fee_multiplier, notional_asset),
+        };
+        self.trade_indices
+            .entry(order_id.clone())
+            .or_default()
+            .push(self.trades.len());
+        let trade = Trade {
+            trade_id: self.id_generator.next_id(),
+            order_id: order_id.clone(),
+            asset_symbol: order.asset_symbol.clone(),
+            side: order.side.clone(),
+            price: execution.price.clone(),
+            quantity: quantity.clone(),
+            fee,
+            fee_asset: fee_asset.clone(),
+            date_time: self.now(),
+        };
+        self.record_activity(ActivityKind::Fill(trade.clone()));
+        if trade.fee != 0 {
+            self.record_activity(ActivityKind::Fee {
+                order_id: order_id.clone(),
+                asset: fee_asset.clone(),
+                amount: trade.fee.clone(),
+            });
+        }
+        self.trades.push(trade);
+
+        let filled_quantity = &order.filled_quantity + quantity;
+        let filled_notional = match &order.average_fill_price {
+            Some(average_fill_price) => &order.filled_quantity * average_fill_price + notional,
+            None => notional.clone(),
         };
+        let average_fill_price = Some(filled_notional / &filled_quantity);
 
-        self.orders.insert(
-            order_id.clone(),
+        let executed_order = if is_filled {
+            let adjusted_amount = match &order.amount {
+                Amount::Quantity { quantity } => Amount::Quantity {
+                    quantity: quantity * (1 - &self.fee_multiplier),
+                },
+                Amount::Notional { notional } => Amount::Notional {
+                    notional: notional * (1 - &self.fee_multiplier),
+                },
+            };
             Order {
-                filled_quantity: quantity.clone(),
-                average_fill_price: Some(notional / quantity),
+                filled_quantity,
+                average_fill_price,
                 status: OrderStatus::Filled,
                 amount: adjusted_amount,
                 ..order.clone()
-            },
-        );
+            }
+        } else {
+            Order {
+                filled_quantity,
+                average_fill_price,
+                status: OrderStatus::PartiallyFilled,
+                ..order.clone()
+            }
+        };
+        self.orders.insert(order_id.clone(), executed_order.clone());
+        self.publish_order_event(OrderEvent {
+            account: self.account.clone(),
+            previous_status: Some(order.status.clone()),
+            order: executed_order,
+        });
 
         Ok(())
     }
 
+    /// Adds a bought quantity to the average entry price of an asset, only known for assets bought
+    /// with the currency from no position or a position of known entry price.
+    fn update_average_entry_price(
+        &mut self,
+        crypto_pair: &Instrument,
+        quantity: &BigDecimal,
+        notional: &BigDecimal,
+    ) {
+        let asset = &crypto_pair.quantity_coin;
+        let held_quantity = self.get_balance(asset).max(BigDecimal::from(0));
+        let held_cost = match self.average_entry_prices.get(asset) {
+            Some(average_entry_price) => &held_quantity * average_entry_price,
+            None if held_quantity == 0 => BigDecimal::from(0),
+            None => return,
+        };
+        let total_quantity = held_quantity + quantity;
+        if crypto_pair.notional_coin != self.currency || total_quantity <= 0 {
+            self.average_entry_prices.remove(asset);
+            return;
+        }
+        self.average_entry_prices
+            .insert(asset.clone(), (held_cost + notional) / total_quantity);
+    }
+
+    /// Average price paid in the currency per unit of an open position, fees included. `None`
+    /// when the asset isn't held or was bought with another notional asset.
+    pub fn get_average_entry_price(&self, asset: &str) -> Option<BigDecimal> {
+        self.average_entry_prices.get(asset).cloned()
+    }
+
+    /// Reduces the reservation of an order by the part covering an execution, returning how much
+    /// was taken from it.
+    fn consume_reservation(
+        &mut self,
+        order: &Order,
+        quantity: &BigDecimal,
+        notional: &BigDecimal,
+    ) -> BigDecimal {
+        let Some(reservation) = self.reservations.get_mut(&order.order_id) else {
+            return BigDecimal::from(0);
+        };
+        let consumed = match (&order.side, &reservation.price) {
+            (OrderSide::Sell, _) => quantity.clone(),
+            (OrderSide::Buy, Some(price)) => price * quantity,
+            (OrderSide::Buy, None) => notional.clone(),
+        };
+        let consumed = consumed.min(reservation.amount.clone());
+        reservation.amount -= &consumed;
+        consumed
+    }
+
+    /// Returns the buying power still reserved by an order.
+    fn release_reservation(&mut self, order_id: &OrderId) {
+        if let Some(reservation) = self.reservations.remove(order_id) {
+            self.update_buying_power(&reservation.asset, reservation.amount);
+        }
+    }
+
     fn get_current_quantity_and_notional(
         &self,
-        asset_symbol: &str,
+        asset_pair: &Instrument,
         amount: &Amount,
     ) -> Result<(BigDecimal, BigDecimal)> {
-        let asset_pair = &CryptoPair::from_str(&asset_symbol)?;
         let notional_per_unit = &self.get_notional_per_unit(asset_pair)?;
+        Ok(Self::get_quantity_and_notional(amount, notional_per_unit))
+    }
+
+    fn get_quantity_and_notional(
+        amount: &Amount,
+        notional_per_unit: &BigDecimal,
+    ) -> (BigDecimal, BigDecimal) {
         let quantity: BigDecimal = match amount {
             Amount::Quantity { quantity } => quantity.clone(),
             Amount::Notional { notional } => notional / notional_per_unit,
@@ -255,18 +1272,289 @@ impl SimulatedBroker {
             Amount::Quantity { quantity } => quantity * notional_per_unit,
             Amount::Notional { notional } => notional.clone(),
         };
-        Ok((quantity, notional))
+        (quantity, notional)
     }
 
-    pub fn get_orders(&self) -> Vec<Order> {
-        self.orders.values().cloned().collect()
+    /// Orders matching the filter, in the order they were placed.
+    pub fn get_orders(&self, filter: &OrderFilter) -> Vec<Order> {
+        filter.apply(self.order_ids.iter().map(|order_id| &self.orders[order_id]))
     }
 
-    pub fn get_order(&self, order_id: &str) -> Result<Order> {
+    /// New and partially filled orders matching the filter, in the order they were placed. Only
+    /// the pending orders are looked at, however long the order history is.
+    pub fn get_open_orders(&self, filter: &OrderFilter) -> Vec<Order> {
+        let mut order_ids = self.pending_orders.get_order_ids();
+        order_ids.sort_by_key(|order_id| self.order_indices[order_id]);
+        filter.apply(order_ids.iter().map(|order_id| &self.orders[order_id]))
+    }
+
+    pub fn get_order(&self, order_id: &OrderId) -> Result<Order> {
         self.orders
             .get(order_id)
-            .map(Order::clone)
-            .ok_or(anyhow!("Order with id {} doesn't exist", order_id))
+            .cloned()
+            .ok_or(Error::UnknownOrder(order_id.clone()))
+    }
+
+    /// Instrument the order was placed for, with its asset class.
+    pub(crate) fn get_order_instrument(&self, order_id: &OrderId) -> Result<Instrument> {
+        self.instruments
+            .get(order_id)
+            .cloned()
+            .ok_or(Error::UnknownOrder(order_id.clone()))
+    }
+
+    /// Orders with the given ids, in the same order as the ids.
+    pub fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        order_ids
+            .iter()
+            .map(|order_id| self.get_order(order_id))
+            .collect()
+    }
+
+    /// Fills of all orders within the filter's time range, in the order they happened.
+    pub fn get_trades(&self, filter: &TradeFilter) -> Vec<Trade> {
+        self.trades
+            .iter()
+            .filter(|trade| filter.matches(trade))
+            .cloned()
+            .collect()
+    }
+
+    /// Ledger of the fills, fees, deposits and withdrawals of the account within the range, in
+    /// the order they happened. The simulated broker never pays interest.
+    pub fn get_account_activities(&self, range: &TimeRange) -> Vec<AccountActivity> {
+        self.activities
+            .iter()
+            .filter(|activity| range.contains(activity.date_time))
+            .cloned()
+            .collect()
+    }
+
+    /// Adds `amount` of a notional asset to the account.
+    pub fn deposit(&mut self, asset: &str, amount: BigDecimal) -> Result<()> {
+        self.check_cash_movement(asset, &amount)?;
+        self.update_balance(asset, amount.clone());
+        self.update_buying_power(asset, amount.clone());
+        self.record_activity(ActivityKind::Deposit {
+            asset: asset.into(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Removes `amount` of a notional asset from the account, up to its buying power.
+    pub fn withdraw(&mut self, asset: &str, amount: BigDecimal) -> Result<()> {
+        self.check_cash_movement(asset, &amount)?;
+        let buying_power = self.get_buying_power(asset);
+        if buying_power < amount {
+            return Err(Error::InsufficientBuyingPower {
+                asset: asset.into(),
+                needed: amount,
+                available: buying_power,
+            });
+        }
+        self.update_balance(asset, -&amount);
+        self.update_buying_power(asset, -&amount);
+        self.record_activity(ActivityKind::Withdrawal {
+            asset: asset.into(),
+            amount,
+        });
+        Ok(())
+    }
+
+    fn check_cash_movement(&self, asset: &str, amount: &BigDecimal) -> Result<()> {
+        if !self.notional_assets.contains(asset) {
+            return Err(Error::InvalidNotionalAsset(asset.into()));
+        }
+        if *amount <= 0 {
+            return Err(Error::InvalidArgument("Amount must be positive".into()));
+        }
+        Ok(())
+    }
+
+    fn record_activity(&mut self, kind: ActivityKind) {
+        self.activities.push(AccountActivity {
+            date_time: self.now(),
+            kind,
+        });
+    }
+
+    pub(crate) fn now(&self) -> Option<DateTime<Utc>> {
+        self.clock.as_ref().map(|clock| clock.now())
+    }
+
+    /// Fills of a single order, in the order they happened.
+    pub fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.get_order(order_id)?;
+        Ok(self
+            .trade_indices
+            .get(order_id)
+            .into_iter()
+            .flatten()
+            .map(|index| self.trades[*index].clone())
+            .collect())
+    }
+
+    pub fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        let order_id = self
+            .client_order_ids
+            .get(client_order_id)
+            .ok_or(Error::UnknownClientOrderId(client_order_id.into()))?;
+        self.get_order(order_id)
+    }
+
+    /// Cancels an open order, releasing the buying power it still reserves.
+    pub fn cancel_order(&mut self, order_id: &OrderId) -> Result<()> {
+        self.close_order(order_id, OrderStatus::Cancelled)
+    }
+
+    /// Cancels several open orders, cancelling none of them when any isn't open.
+    pub fn cancel_orders(&mut self, order_ids: &[OrderId]) -> Result<()> {
+        for order_id in order_ids {
+            if !matches!(
+                self.get_order(order_id)?.status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            ) {
+                return Err(Error::OrderNotOpen(order_id.clone()));
+            }
+        }
+        for order_id in order_ids {
+            self.cancel_order(order_id)?;
+        }
+        Ok(())
+    }
+
+    /// Expires an open order, releasing the buying power it still reserves.
+    pub fn expire_order(&mut self, order_id: &OrderId) -> Result<()> {
+        self.close_order(order_id, OrderStatus::Expired)
+    }
+
+    /// Cancels every open order, returning the ids of the cancelled orders.
+    pub fn cancel_all_orders(&mut self) -> Result<Vec<OrderId>> {
+        let order_ids: Vec<OrderId> = self
+            .get_open_orders(&OrderFilter::default())
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect();
+        for order_id in &order_ids {
+            self.cancel_order(order_id)?;
+        }
+        Ok(order_ids)
+    }
+
+    /// Cancels every open order, then sells every priced position for the currency with market
+    /// orders, even on disabled pairs or while trading is halted. A position that can't be sold
+    /// is reported in the failures of the summary, without holding back the other positions.
+    pub fn close_all_positions(&mut self) -> Result<FlattenSummary> {
+        let cancelled_order_ids = self.cancel_all_orders()?;
+        let mut assets: Vec<String> = self.get_position_values().into_keys().collect();
+        assets.sort();
+        let mut closing_order_ids = Vec::new();
+        let mut failures = HashMap::new();
+        for asset in assets {
+            let quantity = self.get_balance(&asset);
+            if quantity <= 0 {
+                continue;
+            }
+            match self.place_risk_order(OrderRequest::market_sell(
+                self.get_currency_pair(&asset),
+                Amount::Quantity { quantity },
+            )) {
+                Ok(order_id) => closing_order_ids.push(order_id),
+                Err(err) => {
+                    failures.insert(asset, err.to_string());
+                }
+            }
+        }
+        Ok(FlattenSummary {
+            cancelled_order_ids,
+            closing_order_ids,
+            failures,
+        })
+    }
+
+    /// Returns the account to the balances and positions it was built with, forgetting its
+    /// orders, trades, activities, margin calls and sub-accounts and enabling trading every pair
+    /// again, e.g. so that every test of a suite starts from a clean slate. Prices and settings
+    /// are kept.
+    pub fn reset_account(&mut self) {
+        self.balances = self.starting_balances.clone();
+        self.buying_power_balances = self.starting_balances.clone();
+        self.average_entry_prices = self.starting_average_entry_prices.clone();
+        self.orders = HashMap::new();
+        self.order_ids = Vec::new();
+        self.order_indices = HashMap::new();
+        self.client_order_ids = HashMap::new();
+        self.trades = Vec::new();
+        self.trade_indices = HashMap::new();
+        self.activities = Vec::new();
+        self.reservations = HashMap::new();
+        self.instruments = HashMap::new();
+        self.margin_calls = Vec::new();
+        self.disabled_pairs = HashSet::new();
+        self.consecutive_rejections = HashMap::new();
+        self.sub_accounts = HashMap::new();
+        self.pending_orders = PendingOrderBook::default();
+    }
+
+    fn close_order(&mut self, order_id: &OrderId, status: OrderStatus) -> Result<()> {
+        let order = self.get_order(order_id)?;
+        if !matches!(
+            order.status,
+            OrderStatus::New | OrderStatus::PartiallyFilled
+        ) {
+            return Err(Error::OrderNotOpen(order_id.clone()));
+        }
+        self.release_reservation(order_id);
+        self.pending_orders.remove(&order);
+        let closed_order = Order {
+            status,
+            ..order.clone()
+        };
+        self.orders.insert(order_id.clone(), closed_order.clone());
+        self.publish_order_event(OrderEvent {
+            account: self.account.clone(),
+            previous_status: Some(order.status),
+            order: closed_order,
+        });
+        Ok(())
+    }
+
+    /// Buying power of `asset` held by open orders.
+    pub fn get_reserved(&self, asset: &str) -> BigDecimal {
+        self.reservations
+            .values()
+            .filter(|reservation| reservation.asset == asset)
+            .map(|reservation| &reservation.amount)
+            .sum()
+    }
+
+    /// Balance, reservations of the open orders and free buying power of every held or reserved
+    /// asset, keyed by asset.
+    pub fn get_buying_power_report(&self) -> HashMap<String, BuyingPowerReport> {
+        let mut assets: HashSet<String> = self.balances.keys().cloned().collect();
+        assets.extend(
+            self.reservations
+                .values()
+                .map(|reservation| reservation.asset.clone()),
+        );
+        assets
+            .into_iter()
+            .map(|asset| {
+                let reserved = self
+                    .reservations
+                    .iter()
+                    .filter(|(_, reservation)| reservation.asset == asset)
+                    .map(|(order_id, reservation)| (order_id.clone(), reservation.amount.clone()))
+                    .collect();
+                let report = BuyingPowerReport {
+                    balance: self.get_balance(&asset),
+                    reserved,
+                    buying_power: self.get_buying_power(&asset),
+                };
+                (asset, report)
+            })
+            .collect()
     }
 
     pub fn get_currency(&self) -> String {
@@ -274,7 +1562,104 @@ impl SimulatedBroker {
     }
 
     pub fn get_buying_power(&self, asset: &str) -> BigDecimal {
-        Self::get_asset_value(&self.buying_power_balances, asset)
+        let buying_power = Self::get_asset_value(&self.buying_power_balances, asset);
+        if asset != self.currency {
+            return buying_power;
+        }
+        let buying_power = match &self.margin {
+            Some(margin) => {
+                let borrowing_capacity: BigDecimal = self.get_equity() * (&margin.leverage - 1);
+                buying_power + borrowing_capacity.max(BigDecimal::from(0))
+            }
+            None => buying_power,
+        };
+        let cash_reserve = self
+            .get_cash_reserve()
+            .min(buying_power.clone().max(BigDecimal::from(0)));
+        buying_power - cash_reserve
+    }
+
+    /// Amount of the currency balance excluded from the buying power.
+    pub fn get_cash_reserve(&self) -> BigDecimal {
+        match &self.cash_reserve {
+            Some(CashReserve::Absolute(reserve)) => reserve.clone(),
+            Some(CashReserve::Percentage(percentage)) => {
+                self.get_balance(&self.currency) * percentage / BigDecimal::from(100)
+            }
+            None => BigDecimal::from(0),
+        }
+    }
+
+    /// Total account value in the broker's currency. Assets without a known notional per unit
+    /// against the currency are not accounted for.
+    pub fn get_equity(&self) -> BigDecimal {
+        let mut equity = self.get_balance(&self.currency);
+        for (asset, position_value) in self.get_position_values() {
+            if asset != self.currency {
+                equity += position_value;
+            }
+        }
+        equity
+    }
+
+    /// Equity required to open the current positions when margin mode is enabled, their gross
+    /// value divided by the leverage, zero otherwise.
+    pub fn get_initial_margin(&self) -> BigDecimal {
+        match &self.margin {
+            Some(margin) => self.get_gross_position_value() / &margin.leverage,
+            None => BigDecimal::from(0),
+        }
+    }
+
+    /// Equity required to keep the open positions when margin mode is enabled, zero otherwise.
+    pub fn get_maintenance_margin(&self) -> BigDecimal {
+        match &self.margin {
+            Some(margin) => self.get_gross_position_value() * &margin.maintenance_margin_multiplier,
+            None => BigDecimal::from(0),
+        }
+    }
+
+    fn get_gross_position_value(&self) -> BigDecimal {
+        self.get_position_values()
+            .values()
+            .map(BigDecimal::abs)
+            .sum()
+    }
+
+    /// Fees of every trade valued in the currency at the price of the trade. Fees of trades on
+    /// pairs not priced in the currency are left out.
+    pub fn get_fees_paid(&self) -> BigDecimal {
+        let mut fees_paid = BigDecimal::from(0);
+        for trade in &self.trades {
+            if trade.fee_asset == self.currency {
+                fees_paid += &trade.fee;
+            } else if let Some(crypto_pair) = self.instruments.get(&trade.order_id)
+                && crypto_pair.notional_coin == self.currency
+                && crypto_pair.quantity_coin == trade.fee_asset
+            {
+                fees_paid += &trade.fee * &trade.price;
+            }
+        }
+        fees_paid
+    }
+
+    pub fn get_margin_calls(&self) -> Vec<MarginCall> {
+        self.margin_calls.clone()
+    }
+
+    fn get_position_values(&self) -> HashMap<String, BigDecimal> {
+        let mut position_values = HashMap::new();
+        for asset in self.get_purchased_asset_symbols() {
+            let crypto_pair = self.get_currency_pair(&asset);
+            if let Some(notional_per_unit) = self.find_notional_per_unit(&crypto_pair) {
+                position_values.insert(asset.clone(), self.get_balance(&asset) * notional_per_unit);
+            }
+        }
+        position_values
+    }
+
+    fn get_currency_pair(&self, asset: &str) -> Instrument {
+        Instrument::crypto(asset, &self.currency)
     }
 
     pub fn get_balance(&self, asset: &str) -> BigDecimal {
@@ -282,34 +1667,307 @@ impl SimulatedBroker {
     }
 
     fn get_asset_value(values: &HashMap<String, BigDecimal>, asset: &str) -> BigDecimal {
-        values
-            .get(asset)
-            .map(BigDecimal::clone)
-            .unwrap_or(BigDecimal::from(0))
+        values.get(asset).cloned().unwrap_or(BigDecimal::from(0))
     }
 
-    pub fn get_notional_per_unit(&self, asset_pair: &CryptoPair) -> Result<BigDecimal> {
+    /// Price of the pair, or the inverse of the price of its inverse pair when only that one is
+    /// known, e.g. `0.8` for `USD/GBP` when `GBP/USD` is `1.25`.
+    pub fn get_notional_per_unit(&self, asset_pair: &Instrument) -> Result<BigDecimal> {
         self.check_notional(asset_pair)?;
+        self.find_notional_per_unit(asset_pair)
+            .ok_or(Error::InvalidAssetPair(asset_pair.clone()))
+    }
+
+    fn find_notional_per_unit(&self, asset_pair: &Instrument) -> Option<BigDecimal> {
+        if let Some(notional_per_unit) = self.notional_per_unit.get(asset_pair) {
+            return Some(notional_per_unit.clone());
+        }
         self.notional_per_unit
-            .get(&asset_pair)
-            .map(BigDecimal::clone)
-            .ok_or(anyhow!("{} does not have notional per unit", asset_pair))
+            .get(&asset_pair.inverse())
+            .filter(|inverse_notional_per_unit| **inverse_notional_per_unit != 0)
+            .map(|inverse_notional_per_unit| {
+                inverse_notional_per_unit
+                    .inverse()
+                    .round(INVERSE_PRICE_SCALE)
+            })
     }
 
+    /// Sets the price of a pair as of the current time of the clock, if any.
     pub fn set_notional_value_per_unit(
         &mut self,
-        crypto_pair: CryptoPair,
+        crypto_pair: Instrument,
+        notional_per_unit: BigDecimal,
+    ) -> Result<()> {
+        let updated_at = self.clock.as_ref().map(|clock| clock.now());
+        self.set_price(crypto_pair, notional_per_unit, updated_at)
+    }
+
+    /// Sets the price of a pair as of `updated_at`, e.g. the time of the bar it comes from, which
+    /// is what the maximum price age is measured from.
+    pub fn set_notional_value_per_unit_at(
+        &mut self,
+        crypto_pair: Instrument,
+        notional_per_unit: BigDecimal,
+        updated_at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.set_price(crypto_pair, notional_per_unit, Some(updated_at))
+    }
+
+    fn set_price(
+        &mut self,
+        crypto_pair: Instrument,
         notional_per_unit: BigDecimal,
+        updated_at: Option<DateTime<Utc>>,
     ) -> Result<()> {
         self.check_notional(&crypto_pair)?;
+        self.publish(SimulationEvent::PriceUpdate {
+            crypto_pair: crypto_pair.clone(),
+            notional_per_unit: notional_per_unit.clone(),
+        });
+        self.update_notional_value_per_unit(&crypto_pair, &notional_per_unit, updated_at)
+    }
+
+    fn update_notional_value_per_unit(
+        &mut self,
+        crypto_pair: &Instrument,
+        notional_per_unit: &BigDecimal,
+        updated_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
         self.notional_per_unit
             .insert(crypto_pair.clone(), notional_per_unit.clone());
+        if let Some(updated_at) = updated_at {
+            self.price_updated_at
+                .insert(crypto_pair.clone(), updated_at);
+        }
+
+        if !self.trading_halted {
+            self.update_open_orders(Some(crypto_pair))?;
+            // Orders on the inverse pair are priced from this pair unless it has its own price
+            let inverse_pair = crypto_pair.inverse();
+            if !self.notional_per_unit.contains_key(&inverse_pair)
+                && self.check_notional(&inverse_pair).is_ok()
+            {
+                self.update_open_orders(Some(&inverse_pair))?;
+            }
+        }
+
+        for sub_account in self.sub_accounts.values_mut() {
+            sub_account.update_notional_value_per_unit(
+                crypto_pair,
+                notional_per_unit,
+                updated_at,
+            )?;
+        }
+
+        self.check_margin()
+    }
+
+    /// While trading is halted orders are still accepted, but none of them is filled until
+    /// trading resumes.
+    pub fn set_trading_halted(&mut self, trading_halted: bool) -> Result<()> {
+        let was_halted = self.trading_halted;
+        self.trading_halted = trading_halted;
+        if was_halted && !trading_halted {
+            self.update_open_orders(None)?;
+        }
+        for sub_account in self.sub_accounts.values_mut() {
+            sub_account.set_trading_halted(trading_halted)?;
+        }
+        Ok(())
+    }
+
+    /// Creates an empty sub-account with its own balances and orders, sharing the settings,
+    /// prices, trading status and disabled pairs of this broker, which keep applying to it. The
+    /// events of a sub-account are published as its name. Sub-accounts are funded with
+    /// [SimulatedBroker::transfer] and are not part of this broker's equity.
+    pub fn add_sub_account(&mut self, name: &str) -> Result<()> {
+        if name == MAIN_ACCOUNT || self.sub_accounts.contains_key(name) {
+            return Err(Error::AccountAlreadyExists(name.into()));
+        }
+        let sub_account = self.new_sub_account(name)?;
+        self.sub_accounts.insert(name.into(), sub_account);
+        Ok(())
+    }
+
+    fn new_sub_account(&self, name: &str) -> Result<SimulatedBroker> {
+        let mut sub_account = SimulatedBroker::new(
+            &self.currency,
+            self.notional_assets.clone(),
+            HashMap::from([(self.currency.clone(), BigDecimal::from(0))]),
+            self.fee_multiplier.clone(),
+        )?;
+        sub_account.account = name.into();
+        sub_account.notional_per_unit = self.notional_per_unit.clone();
+        sub_account.price_updated_at = self.price_updated_at.clone();
+        sub_account.margin = self.margin.clone();
+        sub_account.event_bus = self.event_bus.clone();
+        sub_account.order_listeners = self.order_listeners.clone();
+        sub_account.matching_engine = self.matching_engine.clone();
+        sub_account.order_validator = self.order_validator.clone();
+        sub_account.cash_reserve = self.cash_reserve.clone();
+        sub_account.max_price_age = self.max_price_age;
+        sub_account.clock = self.clock.clone();
+        sub_account.trading_halted = self.trading_halted;
+        sub_account.disabled_pairs = self.disabled_pairs.clone();
+        sub_account.max_consecutive_rejections = self.max_consecutive_rejections;
+        sub_account.slippage_multiplier = self.slippage_multiplier.clone();
+        sub_account.id_generator = self.id_generator.fork();
+        Ok(sub_account)
+    }
+
+    pub fn get_sub_account(&self, name: &str) -> Result<&SimulatedBroker> {
+        self.sub_accounts
+            .get(name)
+            .ok_or(Error::UnknownAccount(name.into()))
+    }
+
+    pub fn get_sub_account_mut(&mut self, name: &str) -> Result<&mut SimulatedBroker> {
+        self.sub_accounts
+            .get_mut(name)
+            .ok_or(Error::UnknownAccount(name.into()))
+    }
+
+    pub fn get_sub_account_names(&self) -> HashSet<String> {
+        self.sub_accounts.keys().cloned().collect()
+    }
+
+    /// Moves `amount` of `asset` between two accounts, either sub-accounts or [MAIN_ACCOUNT]. Only
+    /// the free buying power of the source account can be transferred.
+    pub fn transfer(
+        &mut self,
+        from: &str,
+        to: &str,
+        asset: &str,
+        amount: BigDecimal,
+    ) -> Result<()> {
+        if amount <= 0 {
+            return Err(Error::InvalidArgument(
+                "Transfer amount must be positive".into(),
+            ));
+        }
+        if from == to {
+            return Err(Error::InvalidArgument(
+                "Cannot transfer from an account to itself".into(),
+            ));
+        }
+        self.get_account(to)?;
+        let source = self.get_account_mut(from)?;
+        let buying_power = source.get_buying_power(asset);
+        if buying_power < amount {
+            return Err(Error::InsufficientBuyingPower {
+                asset: asset.into(),
+                needed: amount,
+                available: buying_power,
+            });
+        }
+        let average_entry_price = source.get_average_entry_price(asset);
+        source.update_balance(asset, -&amount);
+        source.update_buying_power(asset, -&amount);
+        if source.get_balance(asset) <= 0 {
+            source.average_entry_prices.remove(asset);
+        }
+        source.record_activity(ActivityKind::Withdrawal {
+            asset: asset.into(),
+            amount: amount.clone(),
+        });
+        let destination = self.get_account_mut(to)?;
+        destination.record_activity(ActivityKind::Deposit {
+            asset: asset.into(),
+            amount: amount.clone(),
+        });
+        // The transferred units keep the entry price they had in the source account
+        match average_entry_price {
+            Some(average_entry_price) => destination.update_average_entry_price(
+                &Instrument::crypto(asset, &destination.currency),
+                &amount,
+                &(&amount * average_entry_price),
+            ),
+            None => {
+                destination.average_entry_prices.remove(asset);
+            }
+        }
+        destination.update_balance(asset, amount.clone());
+        destination.update_buying_power(asset, amount);
+        Ok(())
+    }
+
+    fn get_account(&self, name: &str) -> Result<&SimulatedBroker> {
+        if name == MAIN_ACCOUNT {
+            return Ok(self);
+        }
+        self.get_sub_account(name)
+    }
+
+    fn get_account_mut(&mut self, name: &str) -> Result<&mut SimulatedBroker> {
+        if name == MAIN_ACCOUNT {
+            return Ok(self);
+        }
+        self.get_sub_account_mut(name)
+    }
+
+    pub fn is_trading_halted(&self) -> bool {
+        self.trading_halted
+    }
 
-        let order_ids: HashSet<String> = self.orders.keys().cloned().collect();
+    /// Updates the pending market orders and the limit orders crossed by the price of
+    /// `crypto_pair`, or every open order when no pair is given.
+    fn update_open_orders(&mut self, crypto_pair: Option<&Instrument>) -> Result<()> {
+        let order_ids = match crypto_pair {
+            Some(crypto_pair) => self.pending_orders.get_crossed_order_ids(
+                &crypto_pair.to_string(),
+                &self.get_notional_per_unit(crypto_pair)?,
+            ),
+            None => self.pending_orders.get_order_ids(),
+        };
         for order_id in order_ids {
-            self.maybe_update_order(&order_id)?
+            if matches!(
+                self.orders[&order_id].status,
+                OrderStatus::New | OrderStatus::PartiallyFilled
+            ) {
+                self.maybe_update_order(&order_id)?
+            }
         }
+        Ok(())
+    }
 
+    fn check_margin(&mut self) -> Result<()> {
+        if self.margin.is_none() {
+            return Ok(());
+        }
+        let equity = self.get_equity();
+        let maintenance_margin = self.get_maintenance_margin();
+        if maintenance_margin == 0 || equity >= maintenance_margin {
+            return Ok(());
+        }
+        // Open orders hold part of the positions, which must be free to be closed
+        self.cancel_all_orders()?;
+        let mut assets: Vec<String> = self.get_position_values().into_keys().collect();
+        assets.sort();
+        let mut liquidation_order_ids = Vec::new();
+        for asset in assets {
+            let quantity = self.get_balance(&asset);
+            let crypto_pair = self.get_currency_pair(&asset);
+            let order_req = if quantity > 0 {
+                OrderRequest::market_sell(crypto_pair, Amount::Quantity { quantity })
+            } else if quantity < 0 {
+                OrderRequest::market_buy(
+                    crypto_pair,
+                    Amount::Quantity {
+                        quantity: -quantity,
+                    },
+                )
+            } else {
+                continue;
+            };
+            liquidation_order_ids.push(self.place_risk_order(order_req)?);
+        }
+        let margin_call = MarginCall {
+            equity,
+            maintenance_margin,
+            liquidation_order_ids,
+        };
+        self.margin_calls.push(margin_call.clone());
+        self.publish_risk_alert(RiskAlert::MarginCall(margin_call));
         Ok(())
     }
 
@@ -321,11 +1979,10 @@ impl SimulatedBroker {
             .collect()
     }
 
-    fn check_notional(&self, asset_pair: &CryptoPair) -> Result<()> {
+    fn check_notional(&self, asset_pair: &Instrument) -> Result<()> {
         if !self.notional_assets.contains(&asset_pair.notional_coin) {
-            return Err(anyhow!(
-                "{} is not a valid notional asset",
-                asset_pair.notional_coin,
+            return Err(Error::InvalidNotionalAsset(
+                asset_pair.notional_coin.clone(),
             ));
         }
         Ok(())
@@ -340,10 +1997,7 @@ impl SimulatedBroker {
     }
 
     fn update_value(values: &mut HashMap<String, BigDecimal>, asset: &str, delta: BigDecimal) {
-        let previous_balance = values
-            .get(asset)
-            .map(BigDecimal::clone)
-            .unwrap_or(BigDecimal::from(0));
+        let previous_balance = values.get(asset).cloned().unwrap_or(BigDecimal::from(0));
         values.insert(asset.into(), previous_balance + delta);
     }
 }
@@ -351,18 +2005,25 @@ impl SimulatedBroker {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::common::CryptoPair;
+    use crate::api::common::Instrument;
+    use crate::api::request::OrderRequestBuilder;
+    use crate::api::validation::{OrderValidatorBuilder, Violation};
+    use crate::simulated::fill::ConservativeFillPolicy;
+    use crate::simulated::scenario::Scenario;
+    use crate::simulated::time::ManualClock;
+    use anyhow::Result;
     use std::collections::HashMap;
     use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn place_order_invalid_asset_pair() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("AAPL/USD")?,
+            Instrument::from_str("AAPL/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -375,17 +2036,97 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn place_and_fill_equity_orders() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(1000))
+            .set_fee_percentage_up_to_one_hundred(BigDecimal::from(1))?
+            .build()?;
+        let aapl = Instrument::equity("AAPL", "USD");
+        broker.set_notional_value_per_unit(aapl.clone(), BigDecimal::from(100))?;
+
+        let buy_order_id = broker.place_order(OrderRequest::market_buy(
+            aapl.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(2),
+            },
+        ))?;
+        let sell_order_id = broker.place_order(OrderRequest::limit_sell(
+            aapl.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+            BigDecimal::from(110),
+        ))?;
+        assert_eq!(broker.get_order(&sell_order_id)?.status, OrderStatus::New);
+        broker.set_notional_value_per_unit(aapl, BigDecimal::from(110))?;
+
+        assert_eq!(broker.get_order(&buy_order_id)?.status, OrderStatus::Filled);
+        assert_eq!(
+            broker.get_order(&sell_order_id)?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(broker.get_order(&sell_order_id)?.asset_symbol, "AAPL");
+        assert_eq!(broker.get_balance("AAPL"), BigDecimal::from_str("0.98")?);
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from_str("908.9")?);
+        assert_eq!(broker.get_fees_paid(), BigDecimal::from_str("3.1")?);
+        Ok(())
+    }
+
+    #[test]
+    fn place_order_validation_error() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_order_validator(
+                OrderValidatorBuilder::new()
+                    .set_max_quantity_scale(2)
+                    .set_min_notional(BigDecimal::from(1))
+                    .build(),
+            )
+            .build()?;
+        broker.set_notional_value_per_unit(
+            Instrument::from_str("GBP/USD")?,
+            BigDecimal::from_str("1.31")?,
+        )?;
+
+        let err = broker
+            .place_order(OrderRequest::market_buy(
+                Instrument::from_str("GBP/USD")?,
+                Amount::Quantity {
+                    quantity: BigDecimal::from_str("0.001")?,
+                },
+            ))
+            .unwrap_err();
+
+        let Error::Validation(validation_error) = err else {
+            panic!("Expected a validation error, got {}", err);
+        };
+        assert_eq!(
+            validation_error.violations,
+            vec![
+                Violation::QuantityTooPrecise { max_scale: 2 },
+                Violation::NotionalBelowMinimum {
+                    notional: BigDecimal::from_str("0.00131")?,
+                    min_notional: BigDecimal::from(1),
+                },
+            ]
+        );
+        assert!(broker.get_orders(&OrderFilter::default()).is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn place_order_no_balance() -> Result<()> {
-        let mut broker = SimulatedBrokerBuilder::new("USD").build();
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -394,22 +2135,27 @@ mod tests {
         let err = broker.place_order(order_request).unwrap_err();
 
         assert_eq!(err.to_string(), "Not enough USD buying power");
+        assert!(matches!(
+            err,
+            Error::InsufficientBuyingPower { needed, available, .. }
+                if needed == BigDecimal::from_str("13.1")? && available == 0
+        ));
         Ok(())
     }
 
     #[test]
     fn place_order_close_but_not_enough_balance() -> Result<()> {
-        let mut broker = SimulatedBrokerBuilder::new("USD").build();
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
         broker.update_balance("USD", BigDecimal::from_str("13.09")?);
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -424,57 +2170,35 @@ mod tests {
 
     #[test]
     fn place_order_updates_balances() -> Result<()> {
-        let mut broker = SimulatedBrokerBuilder::new("USD")
+        let broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
-        let _ = broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
-            BigDecimal::from_str("1.31")?,
-        );
-
-        let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("GBP/USD")?,
-            Amount::Quantity {
-                quantity: BigDecimal::from(10),
-            },
-        );
-
-        broker.place_order(order_request)?;
-
-        assert_eq!(broker.get_balance("USD"), BigDecimal::from(1));
-        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(1));
-        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(10));
-        assert_eq!(broker.get_buying_power("GBP"), BigDecimal::from(10));
+        Scenario::new(broker)
+            .set_price("GBP/USD", "1.31")?
+            .place_order("buy", create_market_buy_gbp(10)?)?
+            .expect_balance("USD", "1")?
+            .expect_buying_power("USD", "1")?
+            .expect_balance("GBP", "10")?
+            .expect_buying_power("GBP", "10")?;
 
         Ok(())
     }
 
     #[test]
     fn place_order_with_fee_updates_balances() -> Result<()> {
-        let mut broker = SimulatedBrokerBuilder::new("USD")
+        let broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
             .set_fee_percentage_up_to_one_hundred(BigDecimal::from(1))?
-            .build();
-
-        let _ = broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
-            BigDecimal::from_str("1.31")?,
-        );
-
-        let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("GBP/USD")?,
-            Amount::Quantity {
-                quantity: BigDecimal::from(10),
-            },
-        );
+            .build()?;
 
-        broker.place_order(order_request)?;
-
-        assert_eq!(broker.get_balance("USD"), BigDecimal::from(1));
-        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(1));
-        assert_eq!(broker.get_balance("GBP"), BigDecimal::from_str("9.9")?);
-        assert_eq!(broker.get_buying_power("GBP"), BigDecimal::from_str("9.9")?);
+        Scenario::new(broker)
+            .set_price("GBP/USD", "1.31")?
+            .place_order("buy", create_market_buy_gbp(10)?)?
+            .expect_balance("USD", "1")?
+            .expect_buying_power("USD", "1")?
+            .expect_balance("GBP", "9.9")?
+            .expect_buying_power("GBP", "9.9")?;
 
         Ok(())
     }
@@ -483,15 +2207,15 @@ mod tests {
     fn place_order_returns_valid_order_id() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -509,15 +2233,15 @@ mod tests {
     fn get_market_buy_order() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.32")?,
         )?;
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -539,6 +2263,13 @@ mod tests {
             status: OrderStatus::Filled,
             type_: OrderType::Market,
             side: OrderSide::Buy,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         };
 
         assert_eq!(actual_order, expected_order);
@@ -556,15 +2287,15 @@ mod tests {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
             .set_fee_percentage_up_to_one_hundred(BigDecimal::from(10))?
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.32")?,
         )?;
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -586,6 +2317,13 @@ mod tests {
             status: OrderStatus::Filled,
             type_: OrderType::Market,
             side: OrderSide::Buy,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         };
 
         assert_eq!(actual_order, expected_order);
@@ -600,10 +2338,10 @@ mod tests {
 
     #[test]
     fn get_market_sell_order() -> Result<()> {
-        let mut broker = SimulatedBrokerBuilder::new("USD").build();
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
@@ -611,7 +2349,7 @@ mod tests {
         broker.update_buying_power("GBP", BigDecimal::from(11));
 
         let order_request = OrderRequest::market_sell(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -633,6 +2371,13 @@ mod tests {
             status: OrderStatus::Filled,
             type_: OrderType::Market,
             side: OrderSide::Sell,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         };
 
         assert_eq!(actual_order, expected_order);
@@ -652,10 +2397,10 @@ mod tests {
     fn get_market_sell_order_with_fee() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_fee_percentage_up_to_one_hundred(BigDecimal::from(10))?
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
@@ -663,7 +2408,7 @@ mod tests {
         broker.update_buying_power("GBP", BigDecimal::from(11));
 
         let order_request = OrderRequest::market_sell(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -685,6 +2430,13 @@ mod tests {
             status: OrderStatus::Filled,
             type_: OrderType::Market,
             side: OrderSide::Sell,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         };
 
         assert_eq!(actual_order, expected_order);
@@ -704,15 +2456,15 @@ mod tests {
     fn get_updated_limit_buy_order() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
         let order_request = OrderRequest::limit_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -736,6 +2488,13 @@ mod tests {
                 status: OrderStatus::New,
                 type_: OrderType::Limit,
                 side: OrderSide::Buy,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -745,7 +2504,7 @@ mod tests {
         assert_eq!(broker.get_buying_power("GBP"), BigDecimal::from(0));
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.29")?,
         )?;
 
@@ -764,6 +2523,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Buy,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -780,15 +2546,15 @@ mod tests {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
             .set_fee_percentage_up_to_one_hundred(BigDecimal::from(50))?
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
         let order_request = OrderRequest::limit_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -812,6 +2578,13 @@ mod tests {
                 status: OrderStatus::New,
                 type_: OrderType::Limit,
                 side: OrderSide::Buy,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -821,7 +2594,7 @@ mod tests {
         assert_eq!(broker.get_buying_power("GBP"), BigDecimal::from(0));
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.29")?,
         )?;
 
@@ -840,6 +2613,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Buy,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -853,10 +2633,10 @@ mod tests {
 
     #[test]
     fn get_updated_limit_sell_order() -> Result<()> {
-        let mut broker = SimulatedBrokerBuilder::new("USD").build();
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
@@ -864,7 +2644,7 @@ mod tests {
         broker.update_buying_power("GBP", BigDecimal::from(12));
 
         let order_request = OrderRequest::limit_sell(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -888,6 +2668,13 @@ mod tests {
                 status: OrderStatus::New,
                 type_: OrderType::Limit,
                 side: OrderSide::Sell,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -897,7 +2684,7 @@ mod tests {
         assert_eq!(broker.get_buying_power("GBP"), BigDecimal::from(2));
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.33")?,
         )?;
 
@@ -916,6 +2703,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Sell,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -934,10 +2728,10 @@ mod tests {
     fn get_updated_limit_sell_order_with_fee() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_fee_percentage_up_to_one_hundred(BigDecimal::from(50))?
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
@@ -945,7 +2739,7 @@ mod tests {
         broker.update_buying_power("GBP", BigDecimal::from(12));
 
         let order_request = OrderRequest::limit_sell(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -969,6 +2763,13 @@ mod tests {
                 status: OrderStatus::New,
                 type_: OrderType::Limit,
                 side: OrderSide::Sell,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -978,7 +2779,7 @@ mod tests {
         assert_eq!(broker.get_buying_power("GBP"), BigDecimal::from(2));
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.33")?,
         )?;
 
@@ -997,6 +2798,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Sell,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -1015,15 +2823,15 @@ mod tests {
     fn get_immediately_filled_limit_buy_order() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
         let order_request = OrderRequest::limit_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -1047,6 +2855,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Buy,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -1063,15 +2878,15 @@ mod tests {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
             .set_fee_percentage_up_to_one_hundred(BigDecimal::from(25))?
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
         let order_request = OrderRequest::limit_buy(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -1095,6 +2910,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Buy,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -1108,10 +2930,10 @@ mod tests {
 
     #[test]
     fn get_immediately_filled_limit_sell_order() -> Result<()> {
-        let mut broker = SimulatedBrokerBuilder::new("USD").build();
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
@@ -1119,7 +2941,7 @@ mod tests {
         broker.update_buying_power("GBP", BigDecimal::from_str("10.5")?);
 
         let order_request = OrderRequest::limit_sell(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -1143,6 +2965,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Sell,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -1161,10 +2990,10 @@ mod tests {
     fn get_immediately_filled_limit_sell_order_with_fee() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_fee_percentage_up_to_one_hundred(BigDecimal::from(50))?
-            .build();
+            .build()?;
 
         broker.set_notional_value_per_unit(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             BigDecimal::from_str("1.31")?,
         )?;
 
@@ -1172,7 +3001,7 @@ mod tests {
         broker.update_buying_power("GBP", BigDecimal::from_str("10.5")?);
 
         let order_request = OrderRequest::limit_sell(
-            CryptoPair::from_str("GBP/USD")?,
+            Instrument::from_str("GBP/USD")?,
             Amount::Quantity {
                 quantity: BigDecimal::from(10),
             },
@@ -1196,6 +3025,13 @@ mod tests {
                 status: OrderStatus::Filled,
                 type_: OrderType::Limit,
                 side: OrderSide::Sell,
+                display_quantity: None,
+                run_id: None,
+                strategy_id: None,
+                created_at: None,
+                tag: None,
+                metadata: HashMap::new(),
+                client_order_id: None,
             }
         );
 
@@ -1210,15 +3046,219 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn get_updated_limit_buy_order_with_conservative_fill_policy() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from_str("14.1")?)
+            .set_fill_policy(ConservativeFillPolicy)
+            .build()?;
+
+        Scenario::new(broker)
+            .set_price("GBP/USD", "1.31")?
+            .place_order(
+                "buy",
+                OrderRequest::limit_buy(
+                    Instrument::from_str("GBP/USD")?,
+                    Amount::Quantity {
+                        quantity: BigDecimal::from(10),
+                    },
+                    BigDecimal::from_str("1.3")?,
+                ),
+            )?
+            .set_price("GBP/USD", "1.3")?
+            .expect_order_status("buy", OrderStatus::New)?
+            .set_price("GBP/USD", "1.29")?
+            .expect_order_status("buy", OrderStatus::Filled)?
+            .expect_order("buy", |order| {
+                order.average_fill_price == Some(BigDecimal::from_str("1.29").unwrap())
+            })?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_partially_filled_limit_buy_order() -> Result<()> {
+        #[derive(Clone)]
+        struct HalfMatchingEngine;
+
+        impl MatchingEngine for HalfMatchingEngine {
+            fn match_order(&self, _order: &Order, market_state: &MarketState) -> Vec<Execution> {
+                vec![Execution {
+                    price: market_state.notional_per_unit.clone(),
+                    quantity: BigDecimal::from(5),
+                }]
+            }
+        }
+
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(20))
+            .set_matching_engine(HalfMatchingEngine)
+            .build()?;
+
+        broker.set_notional_value_per_unit(
+            Instrument::from_str("GBP/USD")?,
+            BigDecimal::from_str("1.2")?,
+        )?;
+
+        let order_id = broker.place_order(OrderRequest::limit_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from_str("1.3")?,
+        ))?;
+
+        let order = broker.get_order(&order_id)?;
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.filled_quantity, BigDecimal::from(5));
+        assert_eq!(order.average_fill_price, Some(BigDecimal::from_str("1.2")?));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(14));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from_str("7.5")?);
+
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(1))?;
+
+        let order = broker.get_order(&order_id)?;
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.filled_quantity, BigDecimal::from(10));
+        assert_eq!(order.average_fill_price, Some(BigDecimal::from_str("1.1")?));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(9));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(9));
+        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(10));
+
+        Ok(())
+    }
+
+    #[test]
+    fn order_listeners_notified_on_status_transitions() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from_str("14.1")?)
+            .build()?;
+
+        let order_events = Arc::new(Mutex::new(Vec::new()));
+        let listener_order_events = order_events.clone();
+        let _listener = broker.add_order_listener(move |order_event| {
+            listener_order_events
+                .lock()
+                .unwrap()
+                .push(order_event.clone())
+        });
+
+        broker.set_notional_value_per_unit(
+            Instrument::from_str("GBP/USD")?,
+            BigDecimal::from_str("1.31")?,
+        )?;
+
+        let order_id = broker.place_order(OrderRequest::limit_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from_str("1.3")?,
+        ))?;
+
+        broker.set_notional_value_per_unit(
+            Instrument::from_str("GBP/USD")?,
+            BigDecimal::from_str("1.29")?,
+        )?;
+
+        let order_events = order_events.lock().unwrap();
+        assert_eq!(order_events.len(), 2);
+        assert_eq!(order_events[0].previous_status, None);
+        assert_eq!(order_events[0].order.order_id, order_id);
+        assert_eq!(order_events[0].order.status, OrderStatus::New);
+        assert_eq!(order_events[1].previous_status, Some(OrderStatus::New));
+        assert_eq!(order_events[1].order.status, OrderStatus::Filled);
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_iceberg_limit_sell_order() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
+        broker.update_balance("GBP", BigDecimal::from(10));
+        broker.update_buying_power("GBP", BigDecimal::from(10));
+
+        Scenario::new(broker)
+            .set_price("GBP/USD", "1.31")?
+            .place_order(
+                "sell",
+                OrderRequest::iceberg_limit_sell(
+                    Instrument::from_str("GBP/USD")?,
+                    BigDecimal::from(10),
+                    BigDecimal::from_str("1.32")?,
+                    BigDecimal::from(4),
+                ),
+            )?
+            .expect_order_status("sell", OrderStatus::New)?
+            .expect_order("sell", |order| {
+                order.display_quantity == Some(BigDecimal::from(4))
+                    && order.amount
+                        == Amount::Quantity {
+                            quantity: BigDecimal::from(10),
+                        }
+            })?
+            .set_price("GBP/USD", "1.32")?
+            .expect_order_status("sell", OrderStatus::PartiallyFilled)?
+            .expect_order("sell", |order| order.filled_quantity == 4)?
+            .expect_balance("GBP", "6")?
+            .set_price("GBP/USD", "1.33")?
+            .expect_order("sell", |order| order.filled_quantity == 8)?
+            .set_price("GBP/USD", "1.33")?
+            .expect_order_status("sell", OrderStatus::Filled)?
+            .expect_balance("GBP", "0")?
+            .expect_balance("USD", "13.26")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn place_iceberg_order_invalid_display_quantity() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+
+        broker.set_notional_value_per_unit(
+            Instrument::from_str("GBP/USD")?,
+            BigDecimal::from_str("1.31")?,
+        )?;
+
+        let err = broker
+            .place_order(OrderRequest::iceberg_limit_buy(
+                Instrument::from_str("GBP/USD")?,
+                BigDecimal::from(10),
+                BigDecimal::from_str("1.3")?,
+                BigDecimal::from(11),
+            ))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Display quantity must be positive and up to the order quantity"
+        );
+
+        let err = broker
+            .place_order(OrderRequest {
+                display_quantity: Some(BigDecimal::from(1)),
+                ..create_market_buy_gbp(10)?
+            })
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Iceberg orders must be limit orders with a quantity amount"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn set_notional_per_unit_invalid_notional_asset() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
         let err = broker
             .set_notional_value_per_unit(
-                CryptoPair::from_str("GBP/USDT")?,
+                Instrument::from_str("GBP/USDT")?,
                 BigDecimal::from_str("1.31")?,
             )
             .unwrap_err();
@@ -1232,11 +3272,11 @@ mod tests {
     fn set_notional_per_unit_inverted_notional_asset() -> Result<()> {
         let mut broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from_str("14.1")?)
-            .build();
+            .build()?;
 
         let err = broker
             .set_notional_value_per_unit(
-                CryptoPair::from_str("USD/GBP")?,
+                Instrument::from_str("USD/GBP")?,
                 BigDecimal::from_str("1.31")?,
             )
             .unwrap_err();
@@ -1256,19 +3296,64 @@ mod tests {
     }
 
     #[test]
-    fn build_no_balance() {
-        let broker = SimulatedBrokerBuilder::new("USD").build();
+    fn build_without_currency() {
+        let err = SimulatedBrokerBuilder::new("").build().unwrap_err();
+        assert_eq!(err.to_string(), "Currency must not be empty");
+    }
+
+    #[test]
+    fn build_no_balance() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD").build()?;
         assert_eq!(broker.get_balance("USD"), BigDecimal::from(0));
         assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(0));
+        Ok(())
     }
 
     #[test]
-    fn build_negative_balance() {
+    fn build_negative_balance() -> Result<()> {
         let broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from(-10))
-            .build();
+            .build()?;
         assert_eq!(broker.get_balance("USD"), BigDecimal::from(-10));
         assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(-10));
+        Ok(())
+    }
+
+    #[test]
+    fn trade_inverse_pair() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .add_notional_asset("GBP", Some(BigDecimal::from(100)))
+            .build()?;
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        let usd_gbp = gbp_usd.inverse();
+        broker.set_notional_value_per_unit(gbp_usd, BigDecimal::from_str("1.25")?)?;
+
+        assert_eq!(
+            broker.get_notional_per_unit(&usd_gbp)?,
+            BigDecimal::from_str("0.8")?
+        );
+        broker.place_order(OrderRequest::market_buy(
+            usd_gbp.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+        ))?;
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(10));
+        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(92));
+
+        let order_id = broker.place_order(OrderRequest::limit_buy(
+            usd_gbp,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from_str("0.7")?,
+        ))?;
+        assert_eq!(broker.get_order(&order_id)?.status, OrderStatus::New);
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+        assert_eq!(broker.get_order(&order_id)?.status, OrderStatus::Filled);
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(20));
+        Ok(())
     }
 
     #[test]
@@ -1277,7 +3362,7 @@ mod tests {
             .set_balance(BigDecimal::from_str("14.1")?)
             .add_notional_asset("BTC", None)
             .add_notional_asset("USDT", Some(BigDecimal::from(-10)))
-            .build();
+            .build()?;
 
         assert_eq!(
             broker.get_balance(&broker.get_currency()),
@@ -1320,6 +3405,301 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn build_set_leverage_below_one() -> Result<()> {
+        let err = SimulatedBrokerBuilder::new("GBP")
+            .set_margin(BigDecimal::from_str("0.5")?, BigDecimal::from(25))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Leverage must be at least 1");
+        Ok(())
+    }
+
+    #[test]
+    fn build_set_zero_maintenance_margin() -> Result<()> {
+        let err = SimulatedBrokerBuilder::new("GBP")
+            .set_margin(BigDecimal::from(2), BigDecimal::from(0))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Maintenance margin percentage must be greater than 0 and up to 100"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn place_order_with_cash_reserve() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(20))
+            .set_cash_reserve(CashReserve::Absolute(BigDecimal::from(5)))?
+            .build()?;
+
+        Scenario::new(broker)
+            .set_price("GBP/USD", "1")?
+            .expect_buying_power("USD", "15")?
+            .expect_rejection(create_market_buy_gbp(16)?, "Not enough USD buying power")?
+            .place_order("buy", create_market_buy_gbp(15)?)?
+            .expect_order_status("buy", OrderStatus::Filled)?
+            .expect_balance("USD", "5")?
+            .expect_buying_power("USD", "0")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_buying_power_with_cash_reserve_percentage() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(200))
+            .set_cash_reserve(CashReserve::Percentage(BigDecimal::from(10)))?
+            .build()?;
+
+        assert_eq!(broker.get_cash_reserve(), BigDecimal::from(20));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(180));
+
+        broker.update_balance("USD", BigDecimal::from(-100));
+        broker.update_buying_power("USD", BigDecimal::from(-100));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(90));
+
+        Ok(())
+    }
+
+    #[test]
+    fn build_set_invalid_cash_reserve() {
+        let err = SimulatedBrokerBuilder::new("USD")
+            .set_cash_reserve(CashReserve::Absolute(BigDecimal::from(-1)))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Cash reserve must not be negative");
+
+        let err = SimulatedBrokerBuilder::new("USD")
+            .set_cash_reserve(CashReserve::Percentage(BigDecimal::from(101)))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cash reserve percentage must be between 0 and 100"
+        );
+    }
+
+    #[test]
+    fn place_market_order_with_stale_price() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_max_price_age(Duration::minutes(5))
+            .build()?;
+
+        let mut scenario = Scenario::new(broker);
+        scenario
+            .set_price("GBP/USD", "1.31")?
+            .advance_time(Duration::minutes(5))?
+            .place_order("fresh", create_market_buy_gbp(1)?)?
+            .advance_time(Duration::seconds(1))?
+            .expect_rejection(
+                create_market_buy_gbp(1)?,
+                "GBP/USD price was last set at 1970-01-01 00:00:00 UTC and is stale",
+            )?
+            .place_order(
+                "limit",
+                OrderRequest::limit_buy(
+                    Instrument::from_str("GBP/USD")?,
+                    Amount::Quantity {
+                        quantity: BigDecimal::from(1),
+                    },
+                    BigDecimal::from_str("1.2")?,
+                ),
+            )?
+            .set_price("GBP/USD", "1.32")?
+            .place_order("refreshed", create_market_buy_gbp(1)?)?
+            .expect_order_status("refreshed", OrderStatus::Filled)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn place_order_while_trading_halted() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        broker.set_trading_halted(true)?;
+
+        let mut scenario = Scenario::new(broker);
+        scenario
+            .set_price("GBP/USD", "1.3")?
+            .place_order("buy", create_market_buy_gbp(10)?)?
+            .expect_order_status("buy", OrderStatus::New)?
+            .set_price("GBP/USD", "1.4")?
+            .expect_order_status("buy", OrderStatus::New)?
+            .expect_buying_power("USD", "87")?;
+
+        scenario.with_broker(|broker| broker.set_trading_halted(false))?;
+
+        scenario
+            .expect_order_status("buy", OrderStatus::Filled)?
+            .expect_balance("USD", "86")?
+            .expect_buying_power("USD", "86")?
+            .expect_balance("GBP", "10")?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn place_order_with_margin() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_margin(BigDecimal::from(2), BigDecimal::from(25))?
+            .build()?;
+
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(10))?;
+
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(200));
+
+        broker.place_order(OrderRequest::market_buy(
+            Instrument::from_str("BTC/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(20),
+            },
+        ))?;
+
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(-100));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(0));
+        assert_eq!(broker.get_equity(), BigDecimal::from(100));
+        assert_eq!(broker.get_maintenance_margin(), BigDecimal::from(50));
+
+        let err = broker
+            .place_order(OrderRequest::market_buy(
+                Instrument::from_str("BTC/USD")?,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            ))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Not enough USD buying power");
+
+        Ok(())
+    }
+
+    #[test]
+    fn margin_call_liquidates_positions() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_margin(BigDecimal::from(2), BigDecimal::from(25))?
+            .build()?;
+
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(10))?;
+
+        broker.place_order(OrderRequest::market_buy(
+            Instrument::from_str("BTC/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(20),
+            },
+        ))?;
+
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(8))?;
+
+        assert!(broker.get_margin_calls().is_empty());
+
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(6))?;
+
+        let margin_calls = broker.get_margin_calls();
+        assert_eq!(margin_calls.len(), 1);
+        assert_eq!(margin_calls[0].equity, BigDecimal::from(20));
+        assert_eq!(margin_calls[0].maintenance_margin, BigDecimal::from(30));
+        assert_eq!(margin_calls[0].liquidation_order_ids.len(), 1);
+
+        let liquidation_order = broker.get_order(&margin_calls[0].liquidation_order_ids[0])?;
+        assert_eq!(liquidation_order.side, OrderSide::Sell);
+        assert_eq!(liquidation_order.status, OrderStatus::Filled);
+        assert_eq!(liquidation_order.filled_quantity, BigDecimal::from(20));
+
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(20));
+        assert_eq!(broker.get_balance("BTC"), BigDecimal::from(0));
+
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(5))?;
+
+        assert_eq!(broker.get_margin_calls().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn margin_call_cancels_open_orders_and_closes_short_positions() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_margin(BigDecimal::from(2), BigDecimal::from(25))?
+            .build()?;
+        let btc_usd = Instrument::from_str("BTC/USD")?;
+        broker.set_notional_value_per_unit(btc_usd.clone(), BigDecimal::from(10))?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("ETH/USD")?, BigDecimal::from(10))?;
+        broker.place_order(OrderRequest::market_buy(
+            btc_usd.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(20),
+            },
+        ))?;
+        let limit_sell_order_id = broker.place_order(OrderRequest::limit_sell(
+            btc_usd.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(5),
+            },
+            BigDecimal::from(20),
+        ))?;
+        // The broker doesn't open short positions itself
+        broker.update_balance("ETH", BigDecimal::from(-1));
+
+        broker.set_notional_value_per_unit(btc_usd, BigDecimal::from(6))?;
+
+        let margin_calls = broker.get_margin_calls();
+        assert_eq!(margin_calls.len(), 1);
+        assert_eq!(
+            broker.get_order(&limit_sell_order_id)?.status,
+            OrderStatus::Cancelled
+        );
+        let liquidation_orders =
+            broker.get_orders_by_ids(&margin_calls[0].liquidation_order_ids)?;
+        assert_eq!(liquidation_orders.len(), 2);
+        assert_eq!(liquidation_orders[0].side, OrderSide::Sell);
+        assert_eq!(liquidation_orders[0].filled_quantity, BigDecimal::from(20));
+        assert_eq!(liquidation_orders[1].side, OrderSide::Buy);
+        assert_eq!(liquidation_orders[1].filled_quantity, BigDecimal::from(1));
+        assert_eq!(broker.get_balance("BTC"), BigDecimal::from(0));
+        assert_eq!(broker.get_balance("ETH"), BigDecimal::from(0));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(10));
+        Ok(())
+    }
+
+    #[test]
+    fn margin_call_liquidates_disabled_pair() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_margin(BigDecimal::from(2), BigDecimal::from(25))?
+            .build()?;
+        let btc_usd = Instrument::from_str("BTC/USD")?;
+        broker.set_notional_value_per_unit(btc_usd.clone(), BigDecimal::from(10))?;
+        broker.place_order(OrderRequest::market_buy(
+            btc_usd.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(20),
+            },
+        ))?;
+        broker.set_pair_trading_enabled(btc_usd.clone(), false);
+        broker.set_trading_halted(true)?;
+
+        broker.set_notional_value_per_unit(btc_usd, BigDecimal::from(6))?;
+
+        let margin_calls = broker.get_margin_calls();
+        assert_eq!(margin_calls.len(), 1);
+        let liquidation_orders =
+            broker.get_orders_by_ids(&margin_calls[0].liquidation_order_ids)?;
+        assert_eq!(liquidation_orders.len(), 1);
+        assert_eq!(liquidation_orders[0].status, OrderStatus::Filled);
+        assert_eq!(broker.get_balance("BTC"), BigDecimal::from(0));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(20));
+        Ok(())
+    }
+
     #[test]
     fn get_purchased_asset_symbols() -> Result<()> {
         let broker = SimulatedBrokerBuilder::new("USD")
@@ -1327,11 +3707,1065 @@ mod tests {
             .add_notional_asset("BTC", None)
             .add_notional_asset("ETH", Some(BigDecimal::from(20)))
             .add_notional_asset("USDT", Some(BigDecimal::from(-10)))
-            .build();
+            .build()?;
         let symbols = broker.get_purchased_asset_symbols();
         assert_eq!(symbols.len(), 2);
         assert!(symbols.contains("ETH"));
         assert!(symbols.contains("USDT"));
         Ok(())
     }
+
+    #[test]
+    fn cancel_limit_buy_order_releases_buying_power() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(20))
+            .build()?;
+        broker.set_notional_value_per_unit(
+            Instrument::from_str("GBP/USD")?,
+            BigDecimal::from_str("1.3")?,
+        )?;
+
+        let order_id = broker.place_order(OrderRequest::limit_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from_str("1.2")?,
+        ))?;
+        assert_eq!(broker.get_reserved("USD"), BigDecimal::from(12));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(8));
+
+        broker.cancel_order(&order_id)?;
+
+        assert_eq!(broker.get_order(&order_id)?.status, OrderStatus::Cancelled);
+        assert_eq!(broker.get_reserved("USD"), BigDecimal::from(0));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(20));
+        let err = broker.cancel_order(&order_id).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Order with id {} is not open", order_id)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expire_partially_filled_limit_buy_order() -> Result<()> {
+        #[derive(Clone)]
+        struct HalfMatchingEngine;
+
+        impl MatchingEngine for HalfMatchingEngine {
+            fn match_order(&self, _order: &Order, market_state: &MarketState) -> Vec<Execution> {
+                vec![Execution {
+                    price: market_state.notional_per_unit.clone(),
+                    quantity: BigDecimal::from(5),
+                }]
+            }
+        }
+
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(20))
+            .set_matching_engine(HalfMatchingEngine)
+            .build()?;
+        broker.set_notional_value_per_unit(
+            Instrument::from_str("GBP/USD")?,
+            BigDecimal::from_str("1.2")?,
+        )?;
+
+        let order_id = broker.place_order(OrderRequest::limit_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from_str("1.3")?,
+        ))?;
+        assert_eq!(broker.get_reserved("USD"), BigDecimal::from_str("6.5")?);
+
+        broker.expire_order(&order_id)?;
+
+        assert_eq!(broker.get_order(&order_id)?.status, OrderStatus::Expired);
+        assert_eq!(broker.get_reserved("USD"), BigDecimal::from(0));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(14));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(14));
+        Ok(())
+    }
+
+    #[test]
+    fn limit_buy_notional_order_releases_reservation_on_fill() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(20))
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+
+        broker.place_order(OrderRequest::limit_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Notional {
+                notional: BigDecimal::from(10),
+            },
+            BigDecimal::from_str("1.5")?,
+        ))?;
+        assert_eq!(broker.get_reserved("USD"), BigDecimal::from_str("7.5")?);
+
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(1))?;
+
+        assert_eq!(broker.get_reserved("USD"), BigDecimal::from(0));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(10));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(10));
+        Ok(())
+    }
+
+    #[test]
+    fn get_buying_power_report() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(20))
+            .set_cash_reserve(CashReserve::Absolute(BigDecimal::from(2)))?
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+        broker.place_order(create_market_buy_gbp(5)?)?;
+        let buy_order_id = broker.place_order(OrderRequest::limit_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(4),
+            },
+            BigDecimal::from(1),
+        ))?;
+        let sell_order_id = broker.place_order(OrderRequest::limit_sell(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(3),
+            },
+            BigDecimal::from(3),
+        ))?;
+
+        let report = broker.get_buying_power_report();
+
+        assert_eq!(
+            report["USD"],
+            BuyingPowerReport {
+                balance: BigDecimal::from(10),
+                reserved: HashMap::from([(buy_order_id, BigDecimal::from(4))]),
+                buying_power: BigDecimal::from(4),
+            }
+        );
+        assert_eq!(
+            report["GBP"],
+            BuyingPowerReport {
+                balance: BigDecimal::from(5),
+                reserved: HashMap::from([(sell_order_id, BigDecimal::from(3))]),
+                buying_power: BigDecimal::from(2),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn disable_pair_after_consecutive_rejections() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(10))
+            .set_max_consecutive_rejections(2)?
+            .build()?;
+        let event_bus = EventBus::new();
+        let receiver = event_bus.subscribe();
+        broker.set_event_bus(event_bus);
+        let crypto_pair = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(crypto_pair.clone(), BigDecimal::from(1))?;
+
+        assert!(broker.place_order(create_market_buy_gbp(20)?).is_err());
+        broker.place_order(create_market_buy_gbp(1)?)?;
+        assert!(broker.place_order(create_market_buy_gbp(20)?).is_err());
+        assert!(broker.is_pair_trading_enabled(&crypto_pair));
+        assert!(broker.place_order(create_market_buy_gbp(20)?).is_err());
+        assert!(!broker.is_pair_trading_enabled(&crypto_pair));
+
+        let err = broker.place_order(create_market_buy_gbp(1)?).unwrap_err();
+        assert_eq!(err.to_string(), "Trading GBP/USD is disabled");
+        let risk_alerts: Vec<RiskAlert> = receiver
+            .try_iter()
+            .filter_map(|event| match event {
+                SimulationEvent::RiskAlert {
+                    account,
+                    risk_alert,
+                } if account == MAIN_ACCOUNT => Some(risk_alert),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            risk_alerts,
+            vec![RiskAlert::PairDisabled {
+                crypto_pair: crypto_pair.clone(),
+                reason: "2 consecutive order rejections".into(),
+            }]
+        );
+
+        broker.set_pair_trading_enabled(crypto_pair.clone(), true);
+        broker.place_order(create_market_buy_gbp(1)?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn disable_pair_manually() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(10))
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(1))?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("EUR/USD")?, BigDecimal::from(1))?;
+
+        broker.set_pair_trading_enabled(Instrument::from_str("GBP/USD")?, false);
+
+        assert!(broker.place_order(create_market_buy_gbp(1)?).is_err());
+        broker.place_order(OrderRequest::market_buy(
+            Instrument::from_str("EUR/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+        ))?;
+        Ok(())
+    }
+
+    #[test]
+    fn validate_order() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(10))
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+
+        let validation = broker.validate_order(&create_market_buy_gbp(3)?)?;
+
+        assert_eq!(
+            validation,
+            OrderValidation {
+                reserved_asset: "USD".into(),
+                reserved_amount: BigDecimal::from(6),
+                remaining_buying_power: BigDecimal::from(4),
+                trading_halted: false,
+            }
+        );
+        assert!(broker.get_orders(&OrderFilter::default()).is_empty());
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(10));
+        let err = broker
+            .validate_order(&create_market_buy_gbp(6)?)
+            .unwrap_err();
+        assert!(matches!(err, Error::InsufficientBuyingPower { .. }));
+        broker.set_pair_trading_enabled(Instrument::from_str("GBP/USD")?, false);
+        let err = broker
+            .validate_order(&create_market_buy_gbp(1)?)
+            .unwrap_err();
+        assert!(matches!(err, Error::TradingDisabled(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn get_asset() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_order_validator(
+                OrderValidatorBuilder::new()
+                    .set_max_quantity_scale(2)
+                    .set_min_notional(BigDecimal::from(1))
+                    .build(),
+            )
+            .build()?;
+        let crypto_pair = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(crypto_pair.clone(), BigDecimal::from(1))?;
+
+        assert_eq!(
+            broker.get_asset(&crypto_pair)?,
+            Asset {
+                crypto_pair: crypto_pair.clone(),
+                status: AssetStatus::Active,
+                tradable: true,
+                min_notional: Some(BigDecimal::from(1)),
+                max_notional: None,
+                quantity_scale: Some(2),
+                price_scale: None,
+            }
+        );
+        broker.set_pair_trading_enabled(crypto_pair.clone(), false);
+        let asset = broker.get_asset(&crypto_pair)?;
+        assert_eq!(asset.status, AssetStatus::Disabled);
+        assert!(!asset.tradable);
+        let err = broker
+            .get_asset(&Instrument::from_str("EUR/USD")?)
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidAssetPair(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn list_asset_pairs() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(1))?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("EUR/USD")?, BigDecimal::from(1))?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("BTC/USD")?, BigDecimal::from(1))?;
+
+        broker.set_pair_trading_enabled(Instrument::from_str("BTC/USD")?, false);
+
+        assert_eq!(
+            broker.list_asset_pairs(),
+            vec![
+                Instrument::from_str("EUR/USD")?,
+                Instrument::from_str("GBP/USD")?
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_orders_with_slippage() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_slippage_percentage(BigDecimal::from(10))?
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+
+        let order_id = broker.place_order(create_market_buy_gbp(10)?)?;
+        assert_eq!(
+            broker.get_order(&order_id)?.average_fill_price,
+            Some(BigDecimal::from_str("2.2")?)
+        );
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(78));
+
+        // Limit orders never fill past their limit price
+        let order_id = broker.place_order(OrderRequest::limit_sell(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from_str("1.9")?,
+        ))?;
+        assert_eq!(
+            broker.get_order(&order_id)?.average_fill_price,
+            Some(BigDecimal::from_str("1.9")?)
+        );
+
+        // Market buys need the buying power of their slipped price
+        let mut small_broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(20))
+            .set_slippage_percentage(BigDecimal::from(10))?
+            .build()?;
+        small_broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+        let err = small_broker
+            .place_order(create_market_buy_gbp(10)?)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InsufficientBuyingPower { asset, needed, available }
+                if asset == "USD" && needed == 22 && available == 20
+        ));
+        assert_eq!(small_broker.get_balance("USD"), BigDecimal::from(20));
+        small_broker.place_order(create_market_buy_gbp(9)?)?;
+        assert_eq!(
+            small_broker.get_balance("USD"),
+            BigDecimal::from_str("0.2")?
+        );
+        assert_eq!(
+            small_broker.get_buying_power("USD"),
+            BigDecimal::from_str("0.2")?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_orders_with_filter() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let clock = ManualClock::new(start);
+        broker.set_clock(clock.clone());
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+
+        let first_order_id = broker.place_order(create_market_buy_gbp(1)?)?;
+        clock.advance(Duration::minutes(1));
+        let mut order_ids = Vec::new();
+        for _ in 0..3 {
+            order_ids.push(broker.place_order(OrderRequest::limit_buy(
+                gbp_usd.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+                BigDecimal::from(1),
+            ))?);
+        }
+
+        let get_order_ids = |filter: &OrderFilter| -> Vec<OrderId> {
+            broker
+                .get_orders(filter)
+                .into_iter()
+                .map(|order| order.order_id)
+                .collect()
+        };
+        assert_eq!(
+            get_order_ids(&OrderFilter::default()),
+            [vec![first_order_id.clone()], order_ids.clone()].concat()
+        );
+        assert_eq!(
+            get_order_ids(&OrderFilter {
+                status: Some(OrderStatus::Filled),
+                ..OrderFilter::default()
+            }),
+            vec![first_order_id]
+        );
+        assert_eq!(
+            get_order_ids(&OrderFilter {
+                created_after: Some(start),
+                offset: 1,
+                limit: Some(1),
+                ..OrderFilter::default()
+            }),
+            vec![order_ids[1].clone()]
+        );
+        assert!(
+            get_order_ids(&OrderFilter {
+                side: Some(OrderSide::Sell),
+                ..OrderFilter::default()
+            })
+            .is_empty()
+        );
+        assert!(
+            get_order_ids(&OrderFilter {
+                crypto_pair: Some(Instrument::from_str("EUR/USD")?),
+                ..OrderFilter::default()
+            })
+            .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_open_orders() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        let eur_usd = Instrument::from_str("EUR/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+        broker.set_notional_value_per_unit(eur_usd.clone(), BigDecimal::from(2))?;
+        let create_limit_buy = |crypto_pair: &Instrument| {
+            OrderRequest::limit_buy(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+                BigDecimal::from(1),
+            )
+        };
+
+        let first_order_id = broker.place_order(create_limit_buy(&eur_usd))?;
+        broker.place_order(create_market_buy_gbp(1)?)?;
+        let second_order_id = broker.place_order(create_limit_buy(&gbp_usd))?;
+        let cancelled_order_id = broker.place_order(create_limit_buy(&eur_usd))?;
+        broker.cancel_order(&cancelled_order_id)?;
+
+        let order_ids: Vec<OrderId> = broker
+            .get_open_orders(&OrderFilter::default())
+            .into_iter()
+            .map(|order| order.order_id)
+            .collect();
+        assert_eq!(order_ids, vec![first_order_id, second_order_id.clone()]);
+        let orders = broker.get_open_orders(&OrderFilter {
+            crypto_pair: Some(gbp_usd),
+            ..OrderFilter::default()
+        });
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_id, second_order_id);
+        Ok(())
+    }
+
+    #[test]
+    fn close_all_positions() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+        broker.place_order(create_market_buy_gbp(10)?)?;
+        let limit_order_id = broker.place_order(OrderRequest::limit_buy(
+            gbp_usd,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from(1),
+        ))?;
+
+        let summary = broker.close_all_positions()?;
+
+        assert_eq!(summary.cancelled_order_ids, vec![limit_order_id.clone()]);
+        assert_eq!(summary.closing_order_ids.len(), 1);
+        assert_eq!(
+            broker.get_order(&summary.closing_order_ids[0])?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(
+            broker.get_order(&limit_order_id)?.status,
+            OrderStatus::Cancelled
+        );
+        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(0));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(100));
+        assert!(broker.cancel_all_orders()?.is_empty());
+        assert!(summary.failures.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn close_all_positions_despite_failures() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .add_position("ETH", BigDecimal::from(1), BigDecimal::from(10))?
+            .add_position("GBP", BigDecimal::from(10), BigDecimal::from(2))?
+            .build()?;
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("ETH/USD")?, BigDecimal::from(10))?;
+        // The ETH position can't be sold without its buying power
+        broker.update_buying_power("ETH", BigDecimal::from(-1));
+        broker.set_pair_trading_enabled(gbp_usd, false);
+        broker.set_trading_halted(true)?;
+
+        let summary = broker.close_all_positions()?;
+
+        assert_eq!(summary.closing_order_ids.len(), 1);
+        assert_eq!(
+            broker.get_order(&summary.closing_order_ids[0])?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(
+            summary.failures,
+            HashMap::from([("ETH".into(), "Not enough ETH buying power".into())])
+        );
+        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(0));
+        assert_eq!(broker.get_balance("ETH"), BigDecimal::from(1));
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(120));
+        Ok(())
+    }
+
+    #[test]
+    fn reset_account() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .add_position("GBP", BigDecimal::from(10), BigDecimal::from(1))?
+            .build()?;
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+        broker.place_order(create_market_buy_gbp(10)?)?;
+        broker.place_order(OrderRequest::limit_buy(
+            gbp_usd,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from(1),
+        ))?;
+        broker.deposit("USD", BigDecimal::from(50))?;
+        broker.add_sub_account("savings")?;
+        broker.set_pair_trading_enabled(Instrument::from_str("BTC/USD")?, false);
+
+        broker.reset_account();
+
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(100));
+        assert_eq!(broker.get_buying_power("USD"), BigDecimal::from(100));
+        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(10));
+        assert_eq!(
+            broker.get_average_entry_price("GBP"),
+            Some(BigDecimal::from(1))
+        );
+        assert_eq!(broker.get_equity(), BigDecimal::from(120));
+        assert!(broker.get_orders(&OrderFilter::default()).is_empty());
+        assert!(
+            broker
+                .get_account_activities(&TimeRange::default())
+                .is_empty()
+        );
+        assert!(broker.get_sub_account_names().is_empty());
+        assert!(broker.is_pair_trading_enabled(&Instrument::from_str("BTC/USD")?));
+        Ok(())
+    }
+
+    #[test]
+    fn get_orders_with_tag_and_metadata() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+        let metadata = HashMap::from([("signal".to_string(), "breakout".to_string())]);
+
+        let order_id = broker.place_order(OrderRequest {
+            tag: Some("entry".into()),
+            metadata: metadata.clone(),
+            ..create_market_buy_gbp(1)?
+        })?;
+        broker.place_order(OrderRequest {
+            tag: Some("hedge".into()),
+            ..create_market_buy_gbp(1)?
+        })?;
+
+        let order = broker.get_order(&order_id)?;
+        assert_eq!(order.tag, Some("entry".into()));
+        assert_eq!(order.metadata, metadata);
+        for filter in [
+            OrderFilter {
+                tag: Some("entry".into()),
+                ..OrderFilter::default()
+            },
+            OrderFilter {
+                metadata,
+                ..OrderFilter::default()
+            },
+        ] {
+            assert_eq!(broker.get_orders(&filter), vec![order.clone()]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn place_order_with_client_order_id() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+        let create_request = || -> Result<OrderRequest> {
+            Ok(OrderRequest {
+                client_order_id: Some("entry-1".into()),
+                ..create_market_buy_gbp(10)?
+            })
+        };
+
+        let order_id = broker.place_order(create_request()?)?;
+        assert_eq!(broker.place_order(create_request()?)?, order_id);
+
+        assert_eq!(broker.get_orders(&OrderFilter::default()).len(), 1);
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(80));
+        assert_eq!(broker.get_order_by_client_id("entry-1")?.order_id, order_id);
+        let err = broker.get_order_by_client_id("entry-2").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Order with client order id entry-2 doesn't exist"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn order_listener_unregistered_when_guard_dropped() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+        let events = Arc::new(Mutex::new(0));
+        let listener_events = events.clone();
+        let listener = broker.add_order_listener(move |_| *listener_events.lock().unwrap() += 1);
+
+        broker.place_order(create_market_buy_gbp(1)?)?;
+        assert_eq!(*events.lock().unwrap(), 2);
+
+        drop(listener);
+        broker.place_order(create_market_buy_gbp(1)?)?;
+        assert_eq!(*events.lock().unwrap(), 2);
+        // The listener itself is gone, along with what it captured
+        assert_eq!(Arc::strong_count(&events), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn place_orders_all_or_none() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+        let events = Arc::new(Mutex::new(0));
+        let listener_events = events.clone();
+        let _listener = broker.add_order_listener(move |_| *listener_events.lock().unwrap() += 1);
+
+        let err = broker
+            .place_orders(vec![create_market_buy_gbp(30)?, create_market_buy_gbp(30)?])
+            .unwrap_err();
+        assert!(matches!(err, Error::InsufficientBuyingPower { .. }));
+        assert!(broker.get_orders(&OrderFilter::default()).is_empty());
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(100));
+        assert_eq!(*events.lock().unwrap(), 0);
+
+        let limit_buy = OrderRequest::limit_buy(
+            gbp_usd,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from(1),
+        );
+        let order_ids = broker.place_orders(vec![
+            create_market_buy_gbp(10)?,
+            limit_buy.clone(),
+            limit_buy,
+        ])?;
+        let orders = broker.get_orders_by_ids(&order_ids)?;
+        assert_eq!(
+            orders
+                .iter()
+                .map(|order| order.status.clone())
+                .collect::<Vec<_>>(),
+            vec![OrderStatus::Filled, OrderStatus::New, OrderStatus::New]
+        );
+
+        let err = broker.cancel_orders(&order_ids).unwrap_err();
+        assert!(matches!(err, Error::OrderNotOpen(order_id) if order_id == order_ids[0]));
+        assert_eq!(broker.get_open_orders(&OrderFilter::default()).len(), 2);
+        broker.cancel_orders(&order_ids[1..])?;
+        assert!(broker.get_open_orders(&OrderFilter::default()).is_empty());
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(80));
+        Ok(())
+    }
+
+    #[test]
+    fn place_immediate_or_cancel_order() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+
+        let order_id = broker.place_order(
+            OrderRequestBuilder::new(gbp_usd.clone())
+                .set_side(OrderSide::Buy)
+                .set_amount(Amount::Quantity {
+                    quantity: BigDecimal::from(10),
+                })
+                .set_limit_price(BigDecimal::from(1))
+                .set_time_in_force(TimeInForce::ImmediateOrCancel)
+                .build()?,
+        )?;
+        assert_eq!(broker.get_order(&order_id)?.status, OrderStatus::Cancelled);
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(100));
+
+        let order_id = broker.place_order(
+            OrderRequestBuilder::new(gbp_usd.clone())
+                .set_side(OrderSide::Buy)
+                .set_amount(Amount::Quantity {
+                    quantity: BigDecimal::from(10),
+                })
+                .set_time_in_force(TimeInForce::ImmediateOrCancel)
+                .build()?,
+        )?;
+        assert_eq!(broker.get_order(&order_id)?.status, OrderStatus::Filled);
+
+        let err = broker
+            .place_order(
+                OrderRequestBuilder::new(gbp_usd)
+                    .set_side(OrderSide::Sell)
+                    .set_amount(Amount::Quantity {
+                        quantity: BigDecimal::from(1),
+                    })
+                    .set_stop_price(BigDecimal::from(1))
+                    .build()?,
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn get_trades() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .set_fee_percentage_up_to_one_hundred(BigDecimal::from(10))?
+            .build()?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let clock = ManualClock::new(start);
+        broker.set_clock(clock.clone());
+        let gbp_usd = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(gbp_usd.clone(), BigDecimal::from(2))?;
+
+        let buy_order_id = broker.place_order(create_market_buy_gbp(10)?)?;
+        clock.advance(Duration::minutes(1));
+        let sell_order_id = broker.place_order(OrderRequest::market_sell(
+            gbp_usd,
+            Amount::Quantity {
+                quantity: BigDecimal::from(5),
+            },
+        ))?;
+
+        let trades = broker.get_trades(&TradeFilter::default());
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].order_id, buy_order_id);
+        assert_eq!(trades[0].quantity, BigDecimal::from(10));
+        assert_eq!(trades[0].fee, BigDecimal::from(1));
+        assert_eq!(trades[0].fee_asset, "GBP");
+        assert_eq!(trades[0].date_time, Some(start));
+        assert_eq!(trades[1].order_id, sell_order_id);
+        assert_eq!(trades[1].price, BigDecimal::from(2));
+        assert_eq!(trades[1].fee, BigDecimal::from(1));
+        assert_eq!(trades[1].fee_asset, "USD");
+
+        assert_eq!(
+            broker.get_order_fills(&buy_order_id)?,
+            vec![trades[0].clone()]
+        );
+        assert_eq!(
+            broker.get_order_fills(&sell_order_id)?,
+            vec![trades[1].clone()]
+        );
+        let err = broker
+            .get_order_fills(&OrderId::new("unknown"))
+            .unwrap_err();
+        assert!(matches!(err, Error::UnknownOrder(order_id) if order_id.as_str() == "unknown"));
+
+        let trades = broker.get_trades(&TradeFilter {
+            start: Some(start + Duration::seconds(1)),
+            end: None,
+        });
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].order_id, sell_order_id);
+        Ok(())
+    }
+
+    #[test]
+    fn build_with_position() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .add_position("GBP", BigDecimal::from(10), BigDecimal::from(1))?
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+
+        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(10));
+        assert_eq!(broker.get_equity(), BigDecimal::from(120));
+        assert_eq!(
+            broker.get_average_entry_price("GBP"),
+            Some(BigDecimal::from(1))
+        );
+
+        broker.place_order(create_market_buy_gbp(10)?)?;
+        assert_eq!(
+            broker.get_average_entry_price("GBP"),
+            Some(BigDecimal::from_str("1.5")?)
+        );
+
+        broker.place_order(OrderRequest::market_sell(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(20),
+            },
+        ))?;
+        assert_eq!(broker.get_average_entry_price("GBP"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn build_with_invalid_position() {
+        let err = SimulatedBrokerBuilder::new("USD")
+            .add_position("GBP", BigDecimal::from(0), BigDecimal::from(1))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Position quantity must be positive");
+        let err = SimulatedBrokerBuilder::new("USD")
+            .add_position("USD", BigDecimal::from(1), BigDecimal::from(1))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot add a position of notional asset USD"
+        );
+    }
+
+    #[test]
+    fn get_account_activities() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_fee_percentage_up_to_one_hundred(BigDecimal::from(10))?
+            .build()?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let clock = ManualClock::new(start);
+        broker.set_clock(clock.clone());
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+
+        broker.deposit("USD", BigDecimal::from(100))?;
+        clock.advance(Duration::minutes(1));
+        let order_id = broker.place_order(create_market_buy_gbp(10)?)?;
+        clock.advance(Duration::minutes(1));
+        broker.withdraw("USD", BigDecimal::from(30))?;
+
+        let activities = broker.get_account_activities(&TimeRange::default());
+        let kinds: Vec<ActivityKind> = activities
+            .iter()
+            .map(|activity| activity.kind.clone())
+            .collect();
+        assert_eq!(kinds.len(), 4);
+        assert_eq!(
+            kinds[0],
+            ActivityKind::Deposit {
+                asset: "USD".into(),
+                amount: BigDecimal::from(100),
+            }
+        );
+        assert!(matches!(&kinds[1], ActivityKind::Fill(trade) if trade.order_id == order_id));
+        assert_eq!(
+            kinds[2],
+            ActivityKind::Fee {
+                order_id,
+                asset: "GBP".into(),
+                amount: BigDecimal::from(1),
+            }
+        );
+        assert_eq!(
+            kinds[3],
+            ActivityKind::Withdrawal {
+                asset: "USD".into(),
+                amount: BigDecimal::from(30),
+            }
+        );
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(50));
+
+        let activities = broker.get_account_activities(&TimeRange {
+            start: Some(start + Duration::minutes(1)),
+            end: Some(start + Duration::minutes(2)),
+        });
+        assert_eq!(activities.len(), 2);
+
+        let err = broker.withdraw("USD", BigDecimal::from(51)).unwrap_err();
+        assert_eq!(err.to_string(), "Not enough USD buying power");
+        Ok(())
+    }
+
+    #[test]
+    fn build_set_invalid_slippage() {
+        let err = SimulatedBrokerBuilder::new("USD")
+            .set_slippage_percentage(BigDecimal::from(-1))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Slippage percentage must be between 0 and 100"
+        );
+    }
+
+    #[test]
+    fn sub_accounts() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(2))?;
+        broker.add_sub_account("momentum")?;
+
+        broker.transfer(MAIN_ACCOUNT, "momentum", "USD", BigDecimal::from(40))?;
+        let order_id =
+            broker
+                .get_sub_account_mut("momentum")?
+                .place_order(OrderRequest::limit_buy(
+                    Instrument::from_str("GBP/USD")?,
+                    Amount::Quantity {
+                        quantity: BigDecimal::from(10),
+                    },
+                    BigDecimal::from(1),
+                ))?;
+
+        assert_eq!(broker.get_balance("USD"), BigDecimal::from(60));
+        assert!(broker.get_orders(&OrderFilter::default()).is_empty());
+        let sub_account = broker.get_sub_account("momentum")?;
+        assert_eq!(sub_account.get_balance("USD"), BigDecimal::from(40));
+        assert_eq!(sub_account.get_buying_power("USD"), BigDecimal::from(30));
+
+        // Prices reach the sub-accounts
+        broker
+            .set_notional_value_per_unit(Instrument::from_str("GBP/USD")?, BigDecimal::from(1))?;
+        let sub_account = broker.get_sub_account("momentum")?;
+        assert_eq!(
+            sub_account.get_order(&order_id)?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(sub_account.get_balance("GBP"), BigDecimal::from(10));
+
+        broker.transfer("momentum", MAIN_ACCOUNT, "GBP", BigDecimal::from(4))?;
+        assert_eq!(broker.get_balance("GBP"), BigDecimal::from(4));
+        let err = broker
+            .transfer("momentum", MAIN_ACCOUNT, "USD", BigDecimal::from(31))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InsufficientBuyingPower { asset, needed, available }
+                if asset == "USD" && needed == 31 && available == 30
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn sub_accounts_follow_disabled_pairs_and_tag_events() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?;
+        let crypto_pair = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(crypto_pair.clone(), BigDecimal::from(2))?;
+        broker.add_sub_account("momentum")?;
+        broker.transfer(MAIN_ACCOUNT, "momentum", "USD", BigDecimal::from(40))?;
+        let event_bus = EventBus::new();
+        let receiver = event_bus.subscribe();
+        broker.set_event_bus(event_bus);
+
+        broker.set_pair_trading_enabled(crypto_pair.clone(), false);
+        let err = broker
+            .get_sub_account_mut("momentum")?
+            .place_order(create_market_buy_gbp(1)?)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Trading GBP/USD is disabled");
+
+        broker.set_pair_trading_enabled(crypto_pair.clone(), true);
+        let order_id = broker
+            .get_sub_account_mut("momentum")?
+            .place_order(create_market_buy_gbp(1)?)?;
+
+        let events: Vec<SimulationEvent> = receiver.try_iter().collect();
+        assert_eq!(
+            events[..2],
+            [
+                SimulationEvent::RiskAlert {
+                    account: MAIN_ACCOUNT.into(),
+                    risk_alert: RiskAlert::PairDisabled {
+                        crypto_pair: crypto_pair.clone(),
+                        reason: "Disabled manually".into(),
+                    },
+                },
+                SimulationEvent::RiskAlert {
+                    account: MAIN_ACCOUNT.into(),
+                    risk_alert: RiskAlert::PairEnabled(crypto_pair),
+                },
+            ]
+        );
+        let SimulationEvent::OrderUpdate(order_event) = &events[2] else {
+            panic!("Expected an order update, got {:?}", events[2]);
+        };
+        assert_eq!(order_event.account, "momentum");
+        assert_eq!(order_event.order.order_id, order_id);
+        Ok(())
+    }
+
+    #[test]
+    fn add_existing_sub_account() -> Result<()> {
+        let mut broker = SimulatedBrokerBuilder::new("USD").build()?;
+        broker.add_sub_account("momentum")?;
+
+        let err = broker.add_sub_account("momentum").unwrap_err();
+        assert_eq!(err.to_string(), "Account momentum already exists");
+        let err = broker
+            .transfer(MAIN_ACCOUNT, "unknown", "USD", BigDecimal::from(1))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Account unknown doesn't exist");
+        Ok(())
+    }
+
+    fn create_market_buy_gbp(quantity: i32) -> Result<OrderRequest> {
+        Ok(OrderRequest::market_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(quantity),
+            },
+        ))
+    }
 }
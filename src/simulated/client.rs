@@ -2,44 +2,203 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use crate::api::Client;
-use crate::api::common::{Account, CryptoPair, OpenPosition, Order};
-use crate::api::request::OrderRequest;
+use crate::api::common::{
+    Account, AccountActivity, Asset, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdate, OrderUpdateStream, OrderValidation, Trade,
+};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
 use crate::simulated::broker::SimulatedBroker;
-use anyhow::Result;
+use crate::simulated::event::{EventBus, OrderEvent, OrderListenerGuard};
+use crate::simulated::exchange::SimulatedExchange;
+use crate::simulated::time::Clock;
+use crate::{Error, Result};
 use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use async_trait::async_trait;
+use tokio::sync::mpsc::unbounded_channel;
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct SimulatedClient {
-    broker: SimulatedBroker,
+    backend: Backend,
+}
+
+#[derive(Debug)]
+enum Backend {
+    /// A broker copied by every clone of the client.
+    Broker(Box<RwLock<SimulatedBroker>>),
+    /// A broker shared with every clone of the client.
+    Shared(Arc<RwLock<SimulatedBroker>>),
+    /// An account of a [SimulatedExchange], shared with every other view of the exchange.
+    Exchange {
+        exchange: SimulatedExchange,
+        account_id: String,
+    },
+}
+
+impl Clone for Backend {
+    fn clone(&self) -> Self {
+        match self {
+            Backend::Broker(broker) => {
+                Backend::Broker(Box::new(RwLock::new(broker.read().unwrap().clone())))
+            }
+            Backend::Shared(broker) => Backend::Shared(broker.clone()),
+            Backend::Exchange {
+                exchange,
+                account_id,
+            } => Backend::Exchange {
+                exchange: exchange.clone(),
+                account_id: account_id.clone(),
+            },
+        }
+    }
 }
 
 impl SimulatedClient {
     pub fn new(broker: SimulatedBroker) -> Self {
-        Self { broker }
+        Self {
+            backend: Backend::Broker(Box::new(RwLock::new(broker))),
+        }
     }
+
+    /// Clones of the returned client trade the same account, so that it can be shared by several
+    /// concurrent strategy tasks.
+    pub fn new_shared(broker: SimulatedBroker) -> Self {
+        Self {
+            backend: Backend::Shared(Arc::new(RwLock::new(broker))),
+        }
+    }
+
+    pub(crate) fn new_exchange_account(exchange: SimulatedExchange, account_id: &str) -> Self {
+        Self {
+            backend: Backend::Exchange {
+                exchange,
+                account_id: account_id.into(),
+            },
+        }
+    }
+
+    /// Sets the notional per unit of the broker, or of every account when trading on a
+    /// [SimulatedExchange].
     pub fn set_notional_per_unit(
-        &mut self,
-        crypto_pair: CryptoPair,
+        &self,
+        crypto_pair: Instrument,
         notional_value_per_unit: BigDecimal,
     ) -> Result<()> {
-        self.broker
-            .set_notional_value_per_unit(crypto_pair, notional_value_per_unit)
+        match &self.backend {
+            Backend::Broker(broker) => broker
+                .write()
+                .unwrap()
+                .set_notional_value_per_unit(crypto_pair, notional_value_per_unit),
+            Backend::Shared(broker) => broker
+                .write()
+                .unwrap()
+                .set_notional_value_per_unit(crypto_pair, notional_value_per_unit),
+            Backend::Exchange { exchange, .. } => {
+                Ok(exchange.set_notional_per_unit(crypto_pair, notional_value_per_unit)?)
+            }
+        }
+    }
+
+    /// Like [SimulatedClient::set_notional_per_unit], with the price as of `updated_at`. See
+    /// [SimulatedBroker::set_notional_value_per_unit_at].
+    pub fn set_notional_per_unit_at(
+        &self,
+        crypto_pair: Instrument,
+        notional_value_per_unit: BigDecimal,
+        updated_at: DateTime<Utc>,
+    ) -> Result<()> {
+        match &self.backend {
+            Backend::Exchange { exchange, .. } => Ok(exchange.set_notional_per_unit_at(
+                crypto_pair,
+                notional_value_per_unit,
+                updated_at,
+            )?),
+            _ => self.with_broker(|broker| {
+                broker.set_notional_value_per_unit_at(
+                    crypto_pair,
+                    notional_value_per_unit,
+                    updated_at,
+                )
+            }),
+        }
+    }
+
+    pub fn set_clock<C>(&self, clock: C)
+    where
+        C: Clock + Send + Sync + 'static,
+    {
+        self.with_broker(|broker| broker.set_clock(clock));
+    }
+
+    pub fn set_trading_halted(&self, trading_halted: bool) -> Result<()> {
+        self.with_broker(|broker| broker.set_trading_halted(trading_halted))
+    }
+
+    pub fn set_pair_trading_enabled(&self, crypto_pair: Instrument, enabled: bool) {
+        self.with_broker(|broker| broker.set_pair_trading_enabled(crypto_pair, enabled));
+    }
+
+    pub fn expire_order(&self, order_id: &OrderId) -> Result<()> {
+        self.with_broker(|broker| broker.expire_order(order_id))
+    }
+
+    /// See [SimulatedBroker::reset_account].
+    pub fn reset_account(&self) {
+        self.with_broker(SimulatedBroker::reset_account);
+    }
+
+    pub fn set_event_bus(&self, event_bus: EventBus) {
+        self.with_broker(|broker| broker.set_event_bus(event_bus));
+    }
+
+    /// See [SimulatedBroker::add_order_listener].
+    pub fn add_order_listener<F>(&self, listener: F) -> OrderListenerGuard
+    where
+        F: FnMut(&OrderEvent) + Send + 'static,
+    {
+        self.with_broker(|broker| broker.add_order_listener(listener))
     }
 }
 
 impl SimulatedClient {
-    fn get_open_position(&self, asset_symbol: &str) -> Result<OpenPosition> {
-        let balance = self.broker.get_balance(asset_symbol);
-        let notional_per_unit = self.broker.get_notional_per_unit(&CryptoPair {
-            notional_coin: self.broker.get_currency(),
-            quantity_coin: asset_symbol.into(),
-        })?;
+    pub(crate) fn with_broker<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut SimulatedBroker) -> R,
+    {
+        match &self.backend {
+            Backend::Broker(broker) => f(&mut broker.write().unwrap()),
+            Backend::Shared(broker) => f(&mut broker.write().unwrap()),
+            Backend::Exchange {
+                exchange,
+                account_id,
+            } => exchange.with_account(account_id, f),
+        }
+    }
+
+    pub(crate) fn read_broker<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&SimulatedBroker) -> R,
+    {
+        match &self.backend {
+            Backend::Broker(broker) => f(&broker.read().unwrap()),
+            Backend::Shared(broker) => f(&broker.read().unwrap()),
+            Backend::Exchange {
+                exchange,
+                account_id,
+            } => exchange.with_account(account_id, |broker| f(broker)),
+        }
+    }
+
+    fn get_open_position(broker: &SimulatedBroker, asset_symbol: &str) -> Result<OpenPosition> {
+        let balance = broker.get_balance(asset_symbol);
+        let instrument = Instrument::crypto(asset_symbol, &broker.get_currency());
+        let notional_per_unit = broker.get_notional_per_unit(&instrument)?;
         let open_position = OpenPosition {
             asset_symbol: asset_symbol.into(),
             quantity: balance.clone(),
-            average_entry_price: None,
+            average_entry_price: broker.get_average_entry_price(asset_symbol),
             market_value: Some(balance * notional_per_unit),
         };
         Ok(open_position)
@@ -48,37 +207,162 @@ impl SimulatedClient {
 
 #[async_trait]
 impl Client for SimulatedClient {
-    async fn place_order(&mut self, req: OrderRequest) -> Result<String> {
-        let order_id = self.broker.place_order(req)?;
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        let order_id = match &self.backend {
+            Backend::Broker(broker) => broker.write().unwrap().place_order(req)?,
+            Backend::Shared(broker) => broker.write().unwrap().place_order(req)?,
+            Backend::Exchange {
+                exchange,
+                account_id,
+            } => exchange.place_order(account_id, req)?,
+        };
         Ok(order_id)
     }
 
-    async fn get_orders(&mut self) -> Result<Vec<Order>> {
-        let orders = self.broker.get_orders();
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        match &self.backend {
+            Backend::Broker(broker) => broker.write().unwrap().place_orders(reqs),
+            Backend::Shared(broker) => broker.write().unwrap().place_orders(reqs),
+            Backend::Exchange {
+                exchange,
+                account_id,
+            } => Ok(exchange.place_orders(account_id, reqs)?),
+        }
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        if let Backend::Exchange { .. } = self.backend
+            && req.limit_price.is_none()
+        {
+            return Err(Error::InvalidArgument(
+                "Only limit orders can be placed on the exchange".into(),
+            ));
+        }
+        self.read_broker(|broker| broker.validate_order(req))
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        let orders = self.read_broker(|broker| broker.get_orders(filter));
         Ok(orders)
     }
 
-    async fn get_order(&mut self, order_id: &str) -> Result<Order> {
-        let order = self.broker.get_order(order_id)?;
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
+        let orders = self.read_broker(|broker| broker.get_open_orders(filter));
+        Ok(orders)
+    }
+
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
+        let order = self.read_broker(|broker| broker.get_order(order_id))?;
         Ok(order)
     }
 
-    async fn get_account(&mut self) -> Result<Account> {
-        let currency = &self.broker.get_currency();
-        let mut open_positions = HashMap::new();
-        for symbol in self.broker.get_purchased_asset_symbols() {
-            let open_position = self.get_open_position(&symbol)?;
-            open_positions.insert(symbol, open_position);
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.read_broker(|broker| broker.get_orders_by_ids(order_ids))
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        let order = self.read_broker(|broker| broker.get_order_by_client_id(client_order_id))?;
+        Ok(order)
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        let trades = self.read_broker(|broker| broker.get_trades(filter));
+        Ok(trades)
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.read_broker(|broker| broker.get_order_fills(order_id))
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        let activities = self.read_broker(|broker| broker.get_account_activities(range));
+        Ok(activities)
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        self.read_broker(|broker| {
+            let currency = &broker.get_currency();
+            let mut open_positions = HashMap::new();
+            for symbol in broker.get_purchased_asset_symbols() {
+                let open_position = Self::get_open_position(broker, &symbol)?;
+                open_positions.insert(symbol, open_position);
+            }
+            let cash = broker.get_balance(currency);
+            let buying_power = broker.get_buying_power(currency);
+            let account = Account {
+                open_positions,
+                cash,
+                buying_power,
+                currency: currency.into(),
+                equity: broker.get_equity(),
+                initial_margin: broker.get_initial_margin(),
+                maintenance_margin: broker.get_maintenance_margin(),
+                fees_paid: broker.get_fees_paid(),
+                updated_at: broker.now(),
+            };
+            Ok(account)
+        })
+    }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        Ok(self.read_broker(SimulatedBroker::get_equity))
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.read_broker(|broker| broker.get_asset(crypto_pair))
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        Ok(self.read_broker(|broker| broker.list_asset_pairs()))
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.read_broker(|broker| {
+            if !broker.get_purchased_asset_symbols().contains(asset_symbol) {
+                return Ok(None);
+            }
+            Ok(Some(Self::get_open_position(broker, asset_symbol)?))
+        })
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        // Under one lock, so that the returned order is the one the cancellation left
+        self.with_broker(|broker| match broker.cancel_order(order_id) {
+            Ok(()) | Err(Error::OrderNotOpen(_)) => broker.get_order(order_id),
+            Err(err) => Err(err),
+        })
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.with_broker(|broker| broker.cancel_orders(order_ids))
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.with_broker(|broker| broker.cancel_all_orders())
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        if let Backend::Exchange { .. } = self.backend {
+            return Err(Error::InvalidArgument(
+                "Only limit orders can be placed on the exchange".into(),
+            ));
         }
-        let cash = self.broker.get_balance(currency);
-        let buying_power = self.broker.get_buying_power(currency);
-        let account = Account {
-            open_positions,
-            cash,
-            buying_power,
-            currency: currency.into(),
-        };
-        Ok(account)
+        self.with_broker(|broker| broker.close_all_positions())
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        let (sender, receiver) = unbounded_channel();
+        let guard = self.add_order_listener(move |order_event| {
+            // The stream may have been dropped, in which case updates are no longer needed
+            let _ = sender.send(OrderUpdate {
+                previous_status: order_event.previous_status.clone(),
+                order: order_event.order.clone(),
+            });
+        });
+        Ok(OrderUpdateStream::with_guard(receiver, guard))
     }
 }
 
@@ -86,7 +370,9 @@ impl Client for SimulatedClient {
 mod tests {
     use super::*;
     use crate::api::common::{Amount, Order, OrderSide, OrderStatus, OrderType};
+    use crate::api::request::Cursor;
     use crate::simulated::broker::SimulatedBrokerBuilder;
+    use anyhow::Result;
     use std::str::FromStr;
 
     const TEN_DOLLARS_COIN: &str = "TEN";
@@ -94,10 +380,10 @@ mod tests {
 
     #[tokio::test]
     async fn buy_market_returns_order_id() -> Result<()> {
-        let mut client = create_client()?;
+        let client = create_client()?;
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(10),
             },
@@ -105,17 +391,17 @@ mod tests {
 
         let order_id = client.place_order(order_request).await?;
 
-        assert_ne!(order_id, "");
+        assert_ne!(order_id.as_str(), "");
 
         Ok(())
     }
 
     #[tokio::test]
     async fn sell_market_returns_order_id() -> Result<()> {
-        let mut client = create_client()?;
+        let client = create_client()?;
 
         let buy_request = OrderRequest::market_buy(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(10),
             },
@@ -124,26 +410,26 @@ mod tests {
         client.place_order(buy_request).await?;
 
         let sell_request = OrderRequest::market_sell(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(10),
             },
         );
         let order_id = client.place_order(sell_request).await?;
 
-        assert_ne!(order_id, "");
+        assert_ne!(order_id.as_str(), "");
 
         Ok(())
     }
 
     #[tokio::test]
     async fn get_orders_returns_all_placed_orders() -> Result<()> {
-        let mut client = create_client()?;
+        let client = create_client()?;
 
-        assert_eq!(client.get_orders().await?.len(), 0);
+        assert_eq!(client.get_orders(&OrderFilter::default()).await?.len(), 0);
 
         let buy_request = OrderRequest::market_buy(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(10),
             },
@@ -151,10 +437,10 @@ mod tests {
 
         let buy_order_id = client.place_order(buy_request).await?;
 
-        assert_eq!(client.get_orders().await?.len(), 1);
+        assert_eq!(client.get_orders(&OrderFilter::default()).await?.len(), 1);
 
         let sell_request = OrderRequest::market_sell(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(10),
             },
@@ -162,7 +448,7 @@ mod tests {
 
         let sell_order_id = client.place_order(sell_request).await?;
 
-        assert_eq!(client.get_orders().await?.len(), 2);
+        assert_eq!(client.get_orders(&OrderFilter::default()).await?.len(), 2);
 
         let buy_order = client.get_order(&buy_order_id).await?;
 
@@ -178,6 +464,13 @@ mod tests {
             status: OrderStatus::Filled,
             type_: OrderType::Market,
             side: OrderSide::Buy,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
         };
 
         assert_eq!(buy_order, expected_order,);
@@ -197,12 +490,12 @@ mod tests {
 
     #[tokio::test]
     async fn get_cash_returns_current_balance() -> Result<()> {
-        let mut client = create_client()?;
+        let client = create_client()?;
 
         assert_eq!(client.get_account().await?.cash, BigDecimal::from(1000));
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(10),
             },
@@ -213,7 +506,7 @@ mod tests {
         assert_eq!(client.get_account().await?.cash, BigDecimal::from(990));
 
         let order_request = OrderRequest::market_sell(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(5),
             },
@@ -225,9 +518,86 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn get_account_margin_and_fees() -> Result<()> {
+        let mut builder = SimulatedBrokerBuilder::new("USD");
+        builder
+            .set_balance(BigDecimal::from(1000))
+            .set_fee_percentage_up_to_one_hundred(BigDecimal::from(1))?
+            .set_margin(BigDecimal::from(2), BigDecimal::from(25))?;
+        let client = SimulatedClient::new(builder.build()?);
+        let crypto_pair = Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?;
+        client.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(10))?;
+
+        client
+            .place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(10),
+                },
+            ))
+            .await?;
+
+        let account = client.get_account().await?;
+        assert_eq!(account.cash, BigDecimal::from(900));
+        assert_eq!(account.equity, BigDecimal::from(999));
+        assert_eq!(account.initial_margin, BigDecimal::from_str("49.5")?);
+        assert_eq!(account.maintenance_margin, BigDecimal::from_str("24.75")?);
+        assert_eq!(account.fees_paid, BigDecimal::from(1));
+        assert_eq!(account.updated_at, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_orders_and_trades_by_page() -> Result<()> {
+        let client = create_client()?;
+        let mut order_ids = Vec::new();
+        for _ in 0..3 {
+            let order_id = client
+                .place_order(OrderRequest::market_buy(
+                    Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+                    Amount::Notional {
+                        notional: BigDecimal::from(10),
+                    },
+                ))
+                .await?;
+            order_ids.push(order_id);
+        }
+
+        let filter = OrderFilter::default();
+        let page = client.get_orders_page(&filter, &Cursor::first(2)?).await?;
+        let page_ids: Vec<OrderId> = page.items.into_iter().map(|order| order.order_id).collect();
+        assert_eq!(page_ids, order_ids[..2]);
+        let next = page.next.unwrap();
+        let page = client.get_orders_page(&filter, &next).await?;
+        assert_eq!(page.items[0].order_id, order_ids[2]);
+        assert_eq!(page.next, None);
+
+        let filter = OrderFilter {
+            limit: Some(2),
+            ..OrderFilter::default()
+        };
+        let page = client.get_orders_page(&filter, &Cursor::first(2)?).await?;
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.next, None);
+
+        let page = client
+            .get_orders_page(&OrderFilter::default(), &Cursor::first(usize::MAX)?)
+            .await?;
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.next, None);
+
+        let page = client
+            .get_trades_page(&TradeFilter::default(), &next)
+            .await?;
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.next, None);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn get_open_position() -> Result<()> {
-        let mut client = create_client()?;
+        let client = create_client()?;
 
         assert_eq!(
             client
@@ -237,9 +607,10 @@ mod tests {
                 .get(TEN_DOLLARS_COIN),
             None
         );
+        assert_eq!(client.get_position(TEN_DOLLARS_COIN).await?, None);
 
         let order_request = OrderRequest::market_buy(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(15),
             },
@@ -251,14 +622,22 @@ mod tests {
             client.get_account().await?.open_positions[TEN_DOLLARS_COIN],
             OpenPosition {
                 asset_symbol: TEN_DOLLARS_COIN.into(),
-                average_entry_price: None,
+                average_entry_price: Some(BigDecimal::from(10)),
                 quantity: BigDecimal::from_str("1.5")?,
                 market_value: Some(BigDecimal::from(15)),
             }
         );
+        assert_eq!(
+            client.get_position(TEN_DOLLARS_COIN).await?,
+            client
+                .get_account()
+                .await?
+                .open_positions
+                .remove(TEN_DOLLARS_COIN)
+        );
 
         let order_request = OrderRequest::market_sell(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             Amount::Notional {
                 notional: BigDecimal::from(10),
             },
@@ -270,7 +649,7 @@ mod tests {
             client.get_account().await?.open_positions[TEN_DOLLARS_COIN],
             OpenPosition {
                 asset_symbol: TEN_DOLLARS_COIN.into(),
-                average_entry_price: None,
+                average_entry_price: Some(BigDecimal::from(10)),
                 quantity: BigDecimal::from_str("0.5")?,
                 market_value: Some(BigDecimal::from(5)),
             }
@@ -279,13 +658,164 @@ mod tests {
         Ok(())
     }
 
-    fn create_client() -> Result<impl Client> {
+    #[tokio::test]
+    async fn subscribe_order_updates() -> Result<()> {
+        let client = create_client()?;
+        let mut updates = client.subscribe_order_updates().await?;
+
+        let order_id = client
+            .place_order(OrderRequest::market_buy(
+                Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+                Amount::Notional {
+                    notional: BigDecimal::from(10),
+                },
+            ))
+            .await?;
+
+        let update = updates.next().await.unwrap();
+        assert_eq!(update.previous_status, None);
+        assert_eq!(update.order.order_id, order_id);
+        let mut last_update = update;
+        while let Some(update) = updates.try_next() {
+            last_update = update;
+        }
+        assert_eq!(last_update.order.status, OrderStatus::Filled);
+        assert!(last_update.previous_status.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn simulated_client_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SimulatedBroker>();
+        assert_send_sync::<SimulatedClient>();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn shared_client_used_by_concurrent_tasks() -> Result<()> {
+        let broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(1000))
+            .build()?;
+        let client = SimulatedClient::new_shared(broker);
+        client.set_notional_per_unit(
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            BigDecimal::from(10),
+        )?;
+
+        let mut handles = Vec::new();
+        for _ in 0..4 {
+            let client = client.clone();
+            handles.push(tokio::spawn(async move {
+                for _ in 0..5 {
+                    client
+                        .place_order(OrderRequest::market_buy(
+                            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+                            Amount::Notional {
+                                notional: BigDecimal::from(10),
+                            },
+                        ))
+                        .await?;
+                }
+                Ok::<(), anyhow::Error>(())
+            }));
+        }
+        for handle in handles {
+            handle.await??;
+        }
+
+        assert_eq!(client.get_orders(&OrderFilter::default()).await?.len(), 20);
+        assert_eq!(client.get_account().await?.cash, BigDecimal::from(800));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn client_behind_arc_used_by_concurrent_tasks() -> Result<()> {
+        let client = Arc::new(create_client()?);
+
+        let mut handles = Vec::new();
+        for _ in 0..4 {
+            let client = client.clone();
+            handles.push(tokio::spawn(async move {
+                client
+                    .place_order(OrderRequest::market_buy(
+                        Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+                        Amount::Notional {
+                            notional: BigDecimal::from(10),
+                        },
+                    ))
+                    .await?;
+                client.get_orders(&OrderFilter::default()).await
+            }));
+        }
+        for handle in handles {
+            handle.await??;
+        }
+
+        assert_eq!(client.get_orders(&OrderFilter::default()).await?.len(), 4);
+        assert_eq!(client.get_account().await?.cash, BigDecimal::from(960));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_equity() -> Result<()> {
+        let crypto_pair = Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?;
+        let client = SimulatedClient::new(
+            SimulatedBrokerBuilder::new("USD")
+                .set_balance(BigDecimal::from(1000))
+                .build()?,
+        );
+        client.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(10))?;
+        client
+            .place_order(OrderRequest::market_buy(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(20),
+                },
+            ))
+            .await?;
+        assert_eq!(client.get_equity().await?, BigDecimal::from(1000));
+
+        client.set_notional_per_unit(crypto_pair, BigDecimal::from(15))?;
+
+        assert_eq!(client.get_equity().await?, BigDecimal::from(1100));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cancel_order_returns_final_state() -> Result<()> {
+        let client = create_client()?;
+        let crypto_pair = Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?;
+        let amount = Amount::Quantity {
+            quantity: BigDecimal::from(1),
+        };
+        let limit_order_id = client
+            .place_order(OrderRequest::limit_buy(
+                crypto_pair.clone(),
+                amount.clone(),
+                BigDecimal::from(5),
+            ))
+            .await?;
+        let market_order_id = client
+            .place_order(OrderRequest::market_buy(crypto_pair, amount))
+            .await?;
+
+        let cancelled_order = client.cancel_order(&limit_order_id).await?;
+        let filled_order = client.cancel_order(&market_order_id).await?;
+
+        assert_eq!(cancelled_order.status, OrderStatus::Cancelled);
+        assert_eq!(filled_order.status, OrderStatus::Filled);
+        let err = client.cancel_order(&OrderId::new("unknown")).await.unwrap_err();
+        assert!(matches!(err, Error::UnknownOrder(_)));
+        Ok(())
+    }
+
+    fn create_client() -> Result<impl Client + Send + Sync + 'static> {
         let broker = SimulatedBrokerBuilder::new("USD")
             .set_balance(BigDecimal::from(1000))
-            .build();
-        let mut client = SimulatedClient::new(broker);
+            .build()?;
+        let client = SimulatedClient::new(broker);
         client.set_notional_per_unit(
-            CryptoPair::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
+            Instrument::from_str(TEN_DOLLARS_CRYPTO_PAIR)?,
             BigDecimal::from(10),
         )?;
         Ok(client)
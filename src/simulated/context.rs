@@ -2,12 +2,14 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 
 use crate::simulated::data::BarDataSource;
+use crate::simulated::event::EventBus;
 use crate::simulated::time::Clock;
 
 #[derive(Clone)]
 pub struct SimulatedContext {
     clock: Box<dyn Clock + Send + Sync>,
     bar_data_source: Box<dyn BarDataSource + Send + Sync>,
+    event_bus: EventBus,
 }
 
 impl SimulatedContext {
@@ -19,6 +21,7 @@ impl SimulatedContext {
         Self {
             bar_data_source: Box::new(bar_data_source),
             clock: Box::new(clock),
+            event_bus: EventBus::new(),
         }
     }
 
@@ -26,7 +29,17 @@ impl SimulatedContext {
         self.clock.as_ref()
     }
 
+    pub(crate) fn shared_clock(&self) -> Box<dyn Clock + Send + Sync> {
+        self.clock.clone()
+    }
+
     pub fn bar_data_source(&self) -> &dyn BarDataSource {
         self.bar_data_source.as_ref()
     }
+
+    /// Shared by every clone of this context, so events published by the environment and its
+    /// broker reach all subscribers.
+    pub fn event_bus(&self) -> &EventBus {
+        &self.event_bus
+    }
 }
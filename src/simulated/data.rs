@@ -1,18 +1,310 @@
 // Copyright (C) 2025 Agostinho Junior
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use crate::api::common::{Bar, CryptoPair};
+use crate::api::common::{Bar, Instrument};
 use anyhow::Result;
+use bigdecimal::BigDecimal;
 use chrono::{DateTime, Duration, Utc};
 use dyn_clone::DynClone;
+use std::collections::{BTreeMap, HashMap};
 
 pub trait BarDataSource: DynClone {
     fn get_bar(
         &self,
-        crypto_pair: &CryptoPair,
+        crypto_pair: &Instrument,
         date_time: &DateTime<Utc>,
         bar_duration: Duration,
     ) -> Result<Option<Bar>>;
+
+    /// Minute bars of `crypto_pair` starting within `start` inclusive and `end` exclusive, in
+    /// chronological order. The default looks up each minute of the range with
+    /// [get_bar](BarDataSource::get_bar), which sources holding their bars should override.
+    fn get_minute_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Bar>> {
+        let bar_duration = Duration::minutes(1);
+        let mut minute_bars: Vec<Bar> = Vec::new();
+        let mut date_time = start;
+        while date_time < end {
+            if let Some(bar) = self.get_bar(crypto_pair, &date_time, bar_duration)?
+                && bar.date_time >= date_time
+                && bar.date_time < end
+                && minute_bars
+                    .last()
+                    .is_none_or(|last| last.date_time < bar.date_time)
+            {
+                minute_bars.push(bar);
+            }
+            date_time += bar_duration;
+        }
+        Ok(minute_bars)
+    }
 }
 
 dyn_clone::clone_trait_object!(BarDataSource);
+
+impl BarDataSource for Box<dyn BarDataSource + Send + Sync> {
+    fn get_bar(
+        &self,
+        crypto_pair: &Instrument,
+        date_time: &DateTime<Utc>,
+        bar_duration: Duration,
+    ) -> Result<Option<Bar>> {
+        self.as_ref().get_bar(crypto_pair, date_time, bar_duration)
+    }
+
+    fn get_minute_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Bar>> {
+        self.as_ref().get_minute_bars(crypto_pair, start, end)
+    }
+}
+
+/// Bars kept in memory, returning the latest bar of a pair starting at or before the requested
+/// time, whatever the bar duration.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBarDataSource {
+    bars: HashMap<Instrument, BTreeMap<DateTime<Utc>, Bar>>,
+}
+
+impl InMemoryBarDataSource {
+    /// Flat bars of a single pair, one per `step` from `start`, priced at each of `closes`.
+    pub fn from_closes<I, T>(
+        crypto_pair: Instrument,
+        start: DateTime<Utc>,
+        step: Duration,
+        closes: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<BigDecimal>,
+    {
+        InMemoryBarDataSourceBuilder::new()
+            .add_closes(crypto_pair, start, step, closes)
+            .build()
+    }
+
+    /// Bars of `crypto_pair` in chronological order.
+    pub fn get_bars(&self, crypto_pair: &Instrument) -> Vec<Bar> {
+        self.bars
+            .get(crypto_pair)
+            .map(|bars| bars.values().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl BarDataSource for InMemoryBarDataSource {
+    fn get_bar(
+        &self,
+        crypto_pair: &Instrument,
+        date_time: &DateTime<Utc>,
+        _bar_duration: Duration,
+    ) -> Result<Option<Bar>> {
+        Ok(self.bars.get(crypto_pair).and_then(|bars| {
+            bars.range(..=*date_time)
+                .next_back()
+                .map(|(_, bar)| bar.clone())
+        }))
+    }
+
+    fn get_minute_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Bar>> {
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .bars
+            .get(crypto_pair)
+            .map(|bars| bars.range(start..end).map(|(_, bar)| bar.clone()).collect())
+            .unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryBarDataSourceBuilder {
+    bars: HashMap<Instrument, BTreeMap<DateTime<Utc>, Bar>>,
+}
+
+impl InMemoryBarDataSourceBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a bar of `crypto_pair`, replacing any bar of the pair starting at the same time.
+    pub fn add_bar(&mut self, crypto_pair: Instrument, bar: Bar) -> &mut Self {
+        self.bars
+            .entry(crypto_pair)
+            .or_default()
+            .insert(bar.date_time, bar);
+        self
+    }
+
+    pub fn add_bars<I>(&mut self, crypto_pair: Instrument, bars: I) -> &mut Self
+    where
+        I: IntoIterator<Item = Bar>,
+    {
+        for bar in bars {
+            self.add_bar(crypto_pair.clone(), bar);
+        }
+        self
+    }
+
+    /// Adds flat bars of `crypto_pair`, one per `step` from `start`, priced at each of `closes`.
+    pub fn add_closes<I, T>(
+        &mut self,
+        crypto_pair: Instrument,
+        start: DateTime<Utc>,
+        step: Duration,
+        closes: I,
+    ) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<BigDecimal>,
+    {
+        let mut date_time = start;
+        for close in closes {
+            let close = close.into();
+            self.add_bar(
+                crypto_pair.clone(),
+                Bar {
+                    low: close.clone(),
+                    high: close.clone(),
+                    open: close.clone(),
+                    close,
+                    date_time,
+                    volume: BigDecimal::from(0),
+                    trade_count: 0,
+                    vwap: None,
+                },
+            );
+            date_time += step;
+        }
+        self
+    }
+
+    pub fn build(&self) -> InMemoryBarDataSource {
+        InMemoryBarDataSource {
+            bars: self.bars.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn get_latest_bar_at_or_before() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::minutes(1),
+            [10, 11, 12],
+        );
+
+        let get_close = |date_time: DateTime<Utc>| -> Result<Option<BigDecimal>> {
+            Ok(data_source
+                .get_bar(&crypto_pair, &date_time, Duration::minutes(1))?
+                .map(|bar| bar.close))
+        };
+        assert_eq!(get_close(start - Duration::seconds(1))?, None);
+        assert_eq!(get_close(start)?, Some(BigDecimal::from(10)));
+        assert_eq!(
+            get_close(start + Duration::seconds(90))?,
+            Some(BigDecimal::from(11))
+        );
+        assert_eq!(
+            get_close(start + Duration::hours(1))?,
+            Some(BigDecimal::from(12))
+        );
+        assert_eq!(
+            data_source.get_bar(
+                &Instrument::from_str("COIN/USD")?,
+                &start,
+                Duration::minutes(1)
+            )?,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn get_minute_bars_within_range() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        // A year of hourly bars, which used to be looked up one minute at a time
+        let data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::hours(1),
+            0..365 * 24,
+        );
+
+        let get_closes = |from: DateTime<Utc>, to: DateTime<Utc>| -> Result<Vec<BigDecimal>> {
+            Ok(data_source
+                .get_minute_bars(&crypto_pair, from, to)?
+                .into_iter()
+                .map(|bar| bar.close)
+                .collect())
+        };
+        assert_eq!(
+            get_closes(start + Duration::minutes(30), start + Duration::hours(3))?,
+            vec![BigDecimal::from(1), BigDecimal::from(2)]
+        );
+        assert_eq!(
+            get_closes(start, start + Duration::days(365))?.len(),
+            365 * 24
+        );
+        assert!(get_closes(start + Duration::hours(1), start)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn build_bars_of_several_pairs() -> Result<()> {
+        let gbp_pair = Instrument::from_str("COIN/GBP")?;
+        let usd_pair = Instrument::from_str("COIN/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bar = Bar {
+            low: BigDecimal::from(8),
+            high: BigDecimal::from(12),
+            open: BigDecimal::from(9),
+            close: BigDecimal::from(11),
+            date_time: start + Duration::minutes(1),
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        };
+
+        let data_source = InMemoryBarDataSourceBuilder::new()
+            .add_bar(gbp_pair.clone(), bar.clone())
+            .add_closes(usd_pair.clone(), start, Duration::minutes(1), [5, 6])
+            .build();
+
+        assert_eq!(data_source.get_bars(&gbp_pair), vec![bar]);
+        assert_eq!(
+            data_source
+                .get_bars(&usd_pair)
+                .into_iter()
+                .map(|bar| (bar.date_time, bar.close))
+                .collect::<Vec<_>>(),
+            vec![
+                (start, BigDecimal::from(5)),
+                (start + Duration::minutes(1), BigDecimal::from(6))
+            ]
+        );
+        Ok(())
+    }
+}
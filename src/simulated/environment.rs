@@ -4,36 +4,72 @@
 use crate::api::Client;
 use crate::api::Environment;
 use crate::api::Market;
-use crate::api::common::{Account, Bar, CryptoPair, Order};
-use crate::api::request::OrderRequest;
+use crate::api::calendar::{AlwaysOpenCalendar, MarketCalendar, MarketClock};
+use crate::api::common::{
+    Account, AccountActivity, Asset, Bar, FlattenSummary, Instrument, OpenPosition, Order, OrderId,
+    OrderUpdateStream, OrderValidation, TimeframeBars, Trade,
+};
+use crate::api::market::{aggregate_timeframe_bars, check_timeframes, get_timeframes_start};
+use crate::api::request::{OrderFilter, OrderRequest, TimeRange, TradeFilter};
+use crate::api::timeframe::{Timeframe, resample_bars};
+#[cfg(feature = "config")]
+use crate::config::IrontradeConfig;
+use crate::simulated::broker::SimulatedBroker;
 use crate::simulated::client::SimulatedClient;
 use crate::simulated::context::SimulatedContext;
-use anyhow::{Result, anyhow};
+use crate::simulated::event::SimulationEvent;
+use crate::simulated::price::BarPriceModel;
+use crate::simulated::progress::{BacktestProgressStream, ProgressTracker};
+use crate::simulated::seed::RngState;
+use crate::{Error, Result};
+use anyhow::anyhow;
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Utc};
-use std::collections::HashSet;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
 /// [Environment] implementation that simulates price changes based on an internal clock,
 /// created by the caller and passed via a [SimulatedContext].
-/// The prices are set according to the average of the [Bar]'s low high at the "current" point in time.
+/// The prices are set according to the average of the [Bar]'s low high at the "current" point in time,
+/// unless another [BarPriceModel] is configured.
 /// THe "current" [Bar] is used for the effect price of a symbol,
 /// while the market's latest bar is set to the last non overlapping [Bar],
 /// since in a real world scenario there isn't a current minute aggregated [Bar].
 pub struct SimulatedEnvironment {
     context: SimulatedContext,
     client: SimulatedClient,
-    last_processed_time: Option<DateTime<Utc>>,
-    crypto_pairs_to_trade: HashSet<CryptoPair>,
-    bar_duration: Duration,
+    state: Mutex<UpdateState>,
+    crypto_pairs_to_trade: HashSet<Instrument>,
+    bar_timeframe: Timeframe,
     refresh_duration: Duration,
+    run_id: Option<String>,
+    strategy_id: Option<String>,
+    trading_calendar: Box<dyn MarketCalendar + Send + Sync>,
+    bar_price_model: BarPriceModel,
+}
+
+/// State advanced by every update of a [SimulatedEnvironment], locked for the whole update so
+/// that concurrent [Client] calls process each step once.
+struct UpdateState {
+    last_processed_time: Option<DateTime<Utc>>,
+    rng: ChaCha12Rng,
+    progress_tracker: Option<ProgressTracker>,
 }
 
 pub struct SimulatedEnvironmentBuilder {
     context: SimulatedContext,
     client: SimulatedClient,
-    crypto_pairs_to_trade: HashSet<CryptoPair>,
-    bar_duration: Duration,
+    crypto_pairs_to_trade: HashSet<Instrument>,
+    bar_timeframe: Timeframe,
     refresh_duration: Duration,
+    run_id: Option<String>,
+    strategy_id: Option<String>,
+    trading_calendar: Box<dyn MarketCalendar + Send + Sync>,
+    bar_price_model: BarPriceModel,
+    seed: Option<u64>,
 }
 
 impl SimulatedEnvironmentBuilder {
@@ -42,21 +78,26 @@ impl SimulatedEnvironmentBuilder {
             context,
             client,
             crypto_pairs_to_trade: HashSet::new(),
-            bar_duration: Duration::minutes(1),
+            bar_timeframe: Timeframe::OneMinute,
             refresh_duration: Duration::seconds(30),
+            run_id: None,
+            strategy_id: None,
+            trading_calendar: Box::new(AlwaysOpenCalendar),
+            bar_price_model: BarPriceModel::default(),
+            seed: None,
         }
     }
 
     pub fn set_crypto_pairs_to_trade(
         &mut self,
-        crypto_pairs_to_trade: HashSet<CryptoPair>,
+        crypto_pairs_to_trade: HashSet<Instrument>,
     ) -> &mut Self {
         self.crypto_pairs_to_trade = crypto_pairs_to_trade;
         self
     }
 
-    pub fn set_bar_duration(&mut self, bar_duration: Duration) -> &mut Self {
-        self.bar_duration = bar_duration;
+    pub fn set_bar_timeframe(&mut self, bar_timeframe: Timeframe) -> &mut Self {
+        self.bar_timeframe = bar_timeframe;
         self
     }
 
@@ -65,98 +106,337 @@ impl SimulatedEnvironmentBuilder {
         self
     }
 
-    pub fn build(&self) -> SimulatedEnvironment {
-        SimulatedEnvironment::new(
+    /// Sets the run id of every order placed through the environment without one.
+    pub fn set_run_id(&mut self, run_id: &str) -> &mut Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    /// Sets the strategy id of every order placed through the environment without one.
+    pub fn set_strategy_id(&mut self, strategy_id: &str) -> &mut Self {
+        self.strategy_id = Some(strategy_id.into());
+        self
+    }
+
+    /// Replaces the default [AlwaysOpenCalendar], orders placed while the market is closed are only
+    /// processed once it opens.
+    pub fn set_trading_calendar<T>(&mut self, trading_calendar: T) -> &mut Self
+    where
+        T: MarketCalendar + Send + Sync + 'static,
+    {
+        self.trading_calendar = Box::new(trading_calendar);
+        self
+    }
+
+    /// Replaces the default [BarPriceModel::Midpoint] used to price pairs from their current bar.
+    pub fn set_bar_price_model(&mut self, bar_price_model: BarPriceModel) -> &mut Self {
+        self.bar_price_model = bar_price_model;
+        self
+    }
+
+    /// Seeds the random number generator of the [BarPriceModel], so that repeated runs sample the
+    /// same prices.
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn build(&self) -> Result<SimulatedEnvironment> {
+        self.bar_timeframe.check()?;
+        if self.refresh_duration <= Duration::zero() {
+            return Err(Error::InvalidArgument(
+                "Refresh duration must be positive".into(),
+            ));
+        }
+        let mut environment = SimulatedEnvironment::new(
             self.context.clone(),
             self.client.clone(),
             self.crypto_pairs_to_trade.clone(),
-            self.bar_duration,
+            self.bar_timeframe,
             self.refresh_duration,
-        )
+        );
+        environment.run_id = self.run_id.clone();
+        environment.strategy_id = self.strategy_id.clone();
+        environment.trading_calendar = self.trading_calendar.clone();
+        environment.bar_price_model = self.bar_price_model.clone();
+        if let Some(seed) = self.seed {
+            environment.state.get_mut().unwrap().rng = ChaCha12Rng::seed_from_u64(seed);
+        }
+        Ok(environment)
     }
 }
 
 impl SimulatedEnvironment {
+    /// Creates an environment trading the configured pairs with the configured broker, reading
+    /// bars and time from `context`.
+    #[cfg(feature = "config")]
+    pub fn from_config(config: &IrontradeConfig, context: SimulatedContext) -> Result<Self> {
+        let client = SimulatedClient::new(config.get_broker_builder()?.build()?);
+        let mut builder = SimulatedEnvironmentBuilder::new(context, client);
+        builder
+            .set_crypto_pairs_to_trade(config.get_crypto_pairs()?)
+            .set_bar_price_model(config.environment.bar_price_model.clone());
+        if let Some(bar_duration_seconds) = config.environment.bar_duration_seconds {
+            builder.set_bar_timeframe(Timeframe::try_from(Duration::seconds(
+                bar_duration_seconds,
+            ))?);
+        }
+        if let Some(refresh_duration_seconds) = config.environment.refresh_duration_seconds {
+            builder.set_refresh_duration(Duration::seconds(refresh_duration_seconds));
+        }
+        if let Some(seed) = config.environment.seed {
+            builder.set_seed(seed);
+        }
+        if let Some(run_id) = &config.environment.run_id {
+            builder.set_run_id(run_id);
+        }
+        if let Some(strategy_id) = &config.environment.strategy_id {
+            builder.set_strategy_id(strategy_id);
+        }
+        builder.build()
+    }
+
     fn new(
         context: SimulatedContext,
         client: SimulatedClient,
-        crypto_pairs_to_trade: HashSet<CryptoPair>,
-        bar_duration: Duration,
+        crypto_pairs_to_trade: HashSet<Instrument>,
+        bar_timeframe: Timeframe,
         refresh_duration: Duration,
     ) -> Self {
+        client.set_event_bus(context.event_bus().clone());
+        client.set_clock(context.shared_clock());
         SimulatedEnvironment {
             context,
             client,
-            last_processed_time: None,
+            state: Mutex::new(UpdateState {
+                last_processed_time: None,
+                rng: ChaCha12Rng::from_os_rng(),
+                progress_tracker: None,
+            }),
             crypto_pairs_to_trade,
-            bar_duration,
+            bar_timeframe,
             refresh_duration,
+            run_id: None,
+            strategy_id: None,
+            trading_calendar: Box::new(AlwaysOpenCalendar),
+            bar_price_model: BarPriceModel::default(),
         }
     }
 
     /// Must be called once after the environment has been created and before any [Client] method call.
     pub fn init(&mut self) -> Result<()> {
-        if self.last_processed_time.is_some() {
-            return Err(anyhow!("Environment has already been initialized"));
+        let state = self.state.get_mut().unwrap();
+        if state.last_processed_time.is_some() {
+            return Err(Error::Other(anyhow!(
+                "Environment has already been initialized"
+            )));
         }
-        self.last_processed_time = Some(self.context.clock().now());
+        state.last_processed_time = Some(self.context.clock().now());
         self.update()
     }
 
-    fn update(&mut self) -> Result<()> {
-        if self.last_processed_time.is_none() {
-            return Err(anyhow!("Environment has not been initialized"));
+    /// Publishes the progress of a backtest from `start` until `end` on every step of the
+    /// environment, failing the next step once the returned stream aborts the backtest.
+    pub fn track_progress(
+        &mut self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<BacktestProgressStream> {
+        let (progress_tracker, progress_stream) = ProgressTracker::new(start, end)?;
+        self.state.get_mut().unwrap().progress_tracker = Some(progress_tracker);
+        Ok(progress_stream)
+    }
+
+    pub(crate) fn update(&self) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        if state.last_processed_time.is_none() {
+            return Err(Error::Other(anyhow!(
+                "Environment has not been initialized"
+            )));
         }
         let now = self.context.clock().now();
-        let mut last_processed_time = self.last_processed_time.unwrap_or(now);
+        let mut last_processed_time = state.last_processed_time.unwrap_or(now);
         while last_processed_time <= now {
+            if let Some(progress_tracker) = &state.progress_tracker {
+                progress_tracker.check_aborted()?;
+            }
+            self.context
+                .event_bus()
+                .publish(SimulationEvent::ClockTick(last_processed_time));
+            let is_open = self.trading_calendar.is_open(&last_processed_time);
+            if !is_open {
+                self.client.set_trading_halted(true)?;
+            }
             for crypto_pair in self.crypto_pairs_to_trade.clone() {
                 let bar = self.context.bar_data_source().get_bar(
                     &crypto_pair,
-                    &now,
-                    self.bar_duration,
+                    &last_processed_time,
+                    self.bar_timeframe.to_duration(),
                 )?;
                 if let Some(bar) = bar {
-                    let value = (bar.low + bar.high) / 2.0;
-                    self.client.set_notional_per_unit(crypto_pair, value)?;
+                    let value = self.bar_price_model.sample(&bar, &mut state.rng);
+                    self.client
+                        .set_notional_per_unit_at(crypto_pair, value, bar.date_time)?;
                 }
             }
+            if is_open {
+                self.client.set_trading_halted(false)?;
+            }
+            if let Some(progress_tracker) = &mut state.progress_tracker {
+                progress_tracker.track(
+                    last_processed_time,
+                    self.client.read_broker(SimulatedBroker::get_equity),
+                );
+            }
             if last_processed_time == now {
                 break;
             }
             last_processed_time = DateTime::min(last_processed_time + self.refresh_duration, now);
         }
-        self.last_processed_time = Some(now);
+        state.last_processed_time = Some(now);
         Ok(())
     }
+
+    pub(crate) fn client(&self) -> &SimulatedClient {
+        &self.client
+    }
+
+    /// State of the random number generator of the bar prices, kept in backtest checkpoints.
+    pub(crate) fn get_rng_state(&self) -> RngState {
+        RngState::of(&self.state.lock().unwrap().rng)
+    }
+
+    pub(crate) fn set_rng_state(&mut self, rng_state: &RngState) {
+        self.state.get_mut().unwrap().rng = rng_state.to_rng();
+    }
+
+    /// Sets the run and strategy ids of the environment on a request that doesn't have its own.
+    pub(crate) fn with_run_ids(&self, mut req: OrderRequest) -> OrderRequest {
+        if req.run_id.is_none() {
+            req.run_id = self.run_id.clone();
+        }
+        if req.strategy_id.is_none() {
+            req.strategy_id = self.strategy_id.clone();
+        }
+        req
+    }
 }
 
 #[async_trait]
 impl Client for SimulatedEnvironment {
-    async fn place_order(&mut self, req: OrderRequest) -> Result<String> {
+    async fn place_order(&self, req: OrderRequest) -> Result<OrderId> {
+        self.update()?;
+        self.client.place_order(self.with_run_ids(req)).await
+    }
+
+    async fn place_orders(&self, reqs: Vec<OrderRequest>) -> Result<Vec<OrderId>> {
+        self.update()?;
+        let reqs = reqs.into_iter().map(|req| self.with_run_ids(req)).collect();
+        self.client.place_orders(reqs).await
+    }
+
+    async fn validate_order(&self, req: &OrderRequest) -> Result<OrderValidation> {
+        self.update()?;
+        self.client.validate_order(req).await
+    }
+
+    async fn get_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
         self.update()?;
-        self.client.place_order(req).await
+        self.client.get_orders(filter).await
     }
 
-    async fn get_orders(&mut self) -> Result<Vec<Order>> {
+    async fn get_open_orders(&self, filter: &OrderFilter) -> Result<Vec<Order>> {
         self.update()?;
-        self.client.get_orders().await
+        self.client.get_open_orders(filter).await
     }
 
-    async fn get_order(&mut self, order_id: &str) -> Result<Order> {
+    async fn get_order(&self, order_id: &OrderId) -> Result<Order> {
         self.update()?;
         self.client.get_order(order_id).await
     }
 
-    async fn get_account(&mut self) -> Result<Account> {
+    async fn get_orders_by_ids(&self, order_ids: &[OrderId]) -> Result<Vec<Order>> {
+        self.update()?;
+        self.client.get_orders_by_ids(order_ids).await
+    }
+
+    async fn get_order_by_client_id(&self, client_order_id: &str) -> Result<Order> {
+        self.update()?;
+        self.client.get_order_by_client_id(client_order_id).await
+    }
+
+    async fn get_trades(&self, filter: &TradeFilter) -> Result<Vec<Trade>> {
+        self.update()?;
+        self.client.get_trades(filter).await
+    }
+
+    async fn get_order_fills(&self, order_id: &OrderId) -> Result<Vec<Trade>> {
+        self.update()?;
+        self.client.get_order_fills(order_id).await
+    }
+
+    async fn get_account_activities(&self, range: &TimeRange) -> Result<Vec<AccountActivity>> {
+        self.update()?;
+        self.client.get_account_activities(range).await
+    }
+
+    async fn get_account(&self) -> Result<Account> {
         self.update()?;
         self.client.get_account().await
     }
+
+    async fn get_equity(&self) -> Result<BigDecimal> {
+        self.update()?;
+        self.client.get_equity().await
+    }
+
+    async fn get_asset(&self, crypto_pair: &Instrument) -> Result<Asset> {
+        self.update()?;
+        self.client.get_asset(crypto_pair).await
+    }
+
+    async fn list_asset_pairs(&self) -> Result<Vec<Instrument>> {
+        self.update()?;
+        self.client.list_asset_pairs().await
+    }
+
+    async fn get_position(&self, asset_symbol: &str) -> Result<Option<OpenPosition>> {
+        self.update()?;
+        self.client.get_position(asset_symbol).await
+    }
+
+    async fn cancel_order(&self, order_id: &OrderId) -> Result<Order>
+    where
+        Self: Sync,
+    {
+        self.update()?;
+        self.client.cancel_order(order_id).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[OrderId]) -> Result<()> {
+        self.update()?;
+        self.client.cancel_orders(order_ids).await
+    }
+
+    async fn cancel_all_orders(&self) -> Result<Vec<OrderId>> {
+        self.update()?;
+        self.client.cancel_all_orders().await
+    }
+
+    async fn close_all_positions(&self) -> Result<FlattenSummary> {
+        self.update()?;
+        self.client.close_all_positions().await
+    }
+
+    async fn subscribe_order_updates(&self) -> Result<OrderUpdateStream> {
+        self.client.subscribe_order_updates().await
+    }
 }
 
 #[async_trait]
 impl Market for SimulatedEnvironment {
-    async fn get_latest_minute_bar(&self, crypto_pair: &CryptoPair) -> Result<Option<Bar>> {
+    async fn get_latest_minute_bar(&self, crypto_pair: &Instrument) -> Result<Option<Bar>> {
         let now = self.context.clock().now();
         let bar_duration = Duration::minutes(1);
         let bar = self
@@ -169,14 +449,75 @@ impl Market for SimulatedEnvironment {
         let bar = bar.unwrap();
         if bar.date_time + bar_duration > now {
             // In a real environment bars would only be returned for the past
-            return self.context.bar_data_source().get_bar(
-                &crypto_pair,
+            return Ok(self.context.bar_data_source().get_bar(
+                crypto_pair,
                 &(now - bar_duration),
                 bar_duration,
-            );
+            )?);
         }
         Ok(Some(bar))
     }
+
+    async fn get_latest_minute_bars(
+        &self,
+        crypto_pairs: &[Instrument],
+    ) -> Result<HashMap<Instrument, Bar>> {
+        let mut bars = HashMap::new();
+        for crypto_pair in crypto_pairs {
+            if let Some(bar) = self.get_latest_minute_bar(crypto_pair).await? {
+                bars.insert(crypto_pair.clone(), bar);
+            }
+        }
+        Ok(bars)
+    }
+
+    async fn get_timeframe_bars(
+        &self,
+        crypto_pair: &Instrument,
+        timeframes: &[Timeframe],
+    ) -> Result<Vec<TimeframeBars>> {
+        check_timeframes(timeframes)?;
+        let now = self.context.clock().now();
+        // Only minutes that have already closed are aggregated, to avoid looking ahead
+        let end = now
+            .duration_trunc(Duration::minutes(1))
+            .map_err(|err| Error::Other(err.into()))?;
+        let minute_bars = self.context.bar_data_source().get_minute_bars(
+            crypto_pair,
+            get_timeframes_start(timeframes, now)?,
+            end,
+        )?;
+        aggregate_timeframe_bars(&minute_bars, timeframes, now)
+    }
+
+    async fn get_bars(
+        &self,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        timeframe: Timeframe,
+    ) -> Result<Vec<Bar>> {
+        check_timeframes(&[timeframe])?;
+        // Only periods that have already closed are returned, to avoid looking ahead
+        let closed_end = timeframe.period_start(self.context.clock().now())?;
+        let minute_bars = self.context.bar_data_source().get_minute_bars(
+            crypto_pair,
+            timeframe.next_period_start(start)?,
+            timeframe.next_period_start(end)?.min(closed_end),
+        )?;
+        resample_bars(&minute_bars, timeframe)
+    }
+
+    async fn get_calendar(&self) -> Result<Box<dyn MarketCalendar + Send + Sync>> {
+        Ok(self.trading_calendar.clone())
+    }
+
+    async fn get_clock(&self) -> Result<MarketClock> {
+        Ok(MarketClock::from_calendar(
+            self.trading_calendar.as_ref(),
+            self.context.clock().now(),
+        ))
+    }
 }
 
 impl Environment for SimulatedEnvironment {}
@@ -185,13 +526,21 @@ impl Environment for SimulatedEnvironment {}
 mod tests {
     use crate::api::Client;
     use crate::api::Market;
-    use crate::api::common::{Amount, Bar, CryptoPair, OrderStatus};
-    use crate::api::request::OrderRequest;
-    use crate::simulated::broker::SimulatedBrokerBuilder;
+    use crate::api::calendar::SessionCalendarBuilder;
+    use crate::api::common::{Amount, Bar, Instrument, OrderId, OrderStatus};
+    use crate::api::request::{OrderFilter, OrderRequest};
+    use crate::api::timeframe::Timeframe;
+    #[cfg(feature = "config")]
+    use crate::config::IrontradeConfig;
+    use crate::simulated::broker::{MAIN_ACCOUNT, SimulatedBrokerBuilder};
     use crate::simulated::client::SimulatedClient;
     use crate::simulated::context::SimulatedContext;
-    use crate::simulated::data::BarDataSource;
+    use crate::simulated::data::{
+        BarDataSource, InMemoryBarDataSource, InMemoryBarDataSourceBuilder,
+    };
     use crate::simulated::environment::{SimulatedEnvironment, SimulatedEnvironmentBuilder};
+    use crate::simulated::event::{OrderEvent, SimulationEvent};
+    use crate::simulated::price::BarPriceModel;
     use crate::simulated::time::Clock;
     use anyhow::Result;
     use bigdecimal::BigDecimal;
@@ -200,9 +549,37 @@ mod tests {
     use std::str::FromStr;
     use std::sync::{Arc, RwLock};
 
+    #[test]
+    fn build_invalid_durations() -> Result<()> {
+        let client = SimulatedClient::new(SimulatedBrokerBuilder::new("GBP").build()?);
+        let err = SimulatedEnvironmentBuilder::new(
+            SimulatedContext::new(TestDataSource, TestClock),
+            client.clone(),
+        )
+        .set_bar_timeframe(Timeframe::Minutes(0))
+        .build()
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Timeframes must be a positive number of minutes"
+        );
+
+        let err = SimulatedEnvironmentBuilder::new(
+            SimulatedContext::new(TestDataSource, TestClock),
+            client,
+        )
+        .set_refresh_duration(Duration::seconds(-1))
+        .build()
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "Refresh duration must be positive");
+        Ok(())
+    }
+
     #[test]
     fn init_twice() -> Result<()> {
-        let mut env = create_environment(TestDataSource, TestClock, HashSet::new());
+        let mut env = create_environment(TestDataSource, TestClock, HashSet::new())?;
         env.init()?;
         let err = env.init().unwrap_err();
         assert_eq!(err.to_string(), "Environment has already been initialized");
@@ -211,7 +588,7 @@ mod tests {
 
     #[tokio::test]
     async fn place_order_without_init() -> Result<()> {
-        let mut env = create_environment(TestDataSource, TestClock, HashSet::new());
+        let env = create_environment(TestDataSource, TestClock, HashSet::new())?;
         let err = env
             .place_order(OrderRequest::market_buy(
                 "USDT/GBP".parse()?,
@@ -235,7 +612,7 @@ mod tests {
             initial_time: current_time - Duration::minutes(5),
             added_duration: added_duration.clone(),
         };
-        let mut env = create_environment(data_source, clock, HashSet::new());
+        let mut env = create_environment(data_source, clock, HashSet::new())?;
         env.init()?;
 
         let result = env
@@ -251,6 +628,54 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn reject_market_order_after_gap_in_bars() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let data_source = create_data_source(vec![
+            create_bar(10, 20, start),
+            create_bar(10, 20, start + Duration::minutes(1)),
+        ]);
+        let added_duration = Arc::new(RwLock::new(Duration::zero()));
+        let clock = StepClock {
+            initial_time: start,
+            added_duration: added_duration.clone(),
+        };
+        let mut broker = SimulatedBrokerBuilder::new("GBP")
+            .set_balance(BigDecimal::from(100_000))
+            .set_max_price_age(Duration::minutes(5))
+            .build()?;
+        broker.set_clock(clock.clone());
+        let mut env = SimulatedEnvironmentBuilder::new(
+            SimulatedContext::new(data_source, clock),
+            SimulatedClient::new(broker),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([crypto_pair.clone()]))
+        .build()?;
+        env.init()?;
+        let market_buy = || {
+            OrderRequest::market_buy(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            )
+        };
+
+        *added_duration.write().unwrap() += Duration::minutes(4);
+        env.place_order(market_buy()).await?;
+
+        // The price set on every refresh is still the one of the last bar
+        *added_duration.write().unwrap() += Duration::minutes(10);
+        let err = env.place_order(market_buy()).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "COIN/GBP price was last set at 2025-12-17 18:31:00 UTC and is stale"
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn place_market_order_after_updating_to_current_time() -> Result<()> {
         let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
@@ -262,8 +687,8 @@ mod tests {
             added_duration: added_duration.clone(),
         };
         let mut pairs_to_trade = HashSet::new();
-        pairs_to_trade.insert(CryptoPair::from_str("COIN/GBP")?);
-        let mut env = create_environment(data_source, clock, pairs_to_trade);
+        pairs_to_trade.insert(Instrument::from_str("COIN/GBP")?);
+        let mut env = create_environment(data_source, clock, pairs_to_trade)?;
         env.init()?;
         *added_duration.write().unwrap() += Duration::minutes(5);
         env.update()?;
@@ -276,7 +701,7 @@ mod tests {
                 },
             ))
             .await?;
-        assert_ne!(order_id, "");
+        assert_ne!(order_id.as_str(), "");
 
         Ok(())
     }
@@ -294,8 +719,8 @@ mod tests {
             added_duration: added_duration.clone(),
         };
         let mut pairs_to_trade = HashSet::new();
-        pairs_to_trade.insert(CryptoPair::from_str("COIN/GBP")?);
-        let mut env = create_environment(data_source, clock, pairs_to_trade);
+        pairs_to_trade.insert(Instrument::from_str("COIN/GBP")?);
+        let mut env = create_environment(data_source, clock, pairs_to_trade)?;
         env.init()?;
         *added_duration.write().unwrap() += Duration::minutes(2);
         env.update()?;
@@ -317,25 +742,300 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn place_order_with_run_and_strategy_ids() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let bar = create_bar(10, 20, current_time - Duration::minutes(3));
+        let mut pairs_to_trade = HashSet::new();
+        pairs_to_trade.insert(crypto_pair.clone());
+        let mut env = SimulatedEnvironmentBuilder::new(
+            SimulatedContext::new(create_data_source(vec![bar]), TestClock),
+            SimulatedClient::new(
+                SimulatedBrokerBuilder::new("GBP")
+                    .set_balance(BigDecimal::from(100_000))
+                    .build()?,
+            ),
+        )
+        .set_crypto_pairs_to_trade(pairs_to_trade)
+        .set_run_id("backtest-1")
+        .set_strategy_id("momentum")
+        .build()?;
+        env.init()?;
+
+        let amount = Amount::Quantity {
+            quantity: BigDecimal::from(1),
+        };
+        let order_id = env
+            .place_order(OrderRequest::market_buy(
+                crypto_pair.clone(),
+                amount.clone(),
+            ))
+            .await?;
+        let order = env.get_order(&order_id).await?;
+        assert_eq!(order.run_id, Some("backtest-1".into()));
+        assert_eq!(order.strategy_id, Some("momentum".into()));
+
+        let order_id = env
+            .place_order(OrderRequest {
+                strategy_id: Some("mean-reversion".into()),
+                ..OrderRequest::market_buy(crypto_pair, amount)
+            })
+            .await?;
+        let order = env.get_order(&order_id).await?;
+        assert_eq!(order.run_id, Some("backtest-1".into()));
+        assert_eq!(order.strategy_id, Some("mean-reversion".into()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn process_orders_once_market_opens() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let data_source = create_data_source(vec![
+            create_bar(10, 20, current_time - Duration::minutes(3)),
+            create_bar(20, 30, current_time + Duration::minutes(1)),
+        ]);
+        let added_duration = Arc::new(RwLock::new(Duration::zero()));
+        let clock = StepClock {
+            initial_time: current_time,
+            added_duration: added_duration.clone(),
+        };
+        let mut pairs_to_trade = HashSet::new();
+        pairs_to_trade.insert(crypto_pair.clone());
+        let mut env = SimulatedEnvironmentBuilder::new(
+            SimulatedContext::new(data_source, clock),
+            SimulatedClient::new(
+                SimulatedBrokerBuilder::new("GBP")
+                    .set_balance(BigDecimal::from(100_000))
+                    .build()?,
+            ),
+        )
+        .set_crypto_pairs_to_trade(pairs_to_trade)
+        .set_trading_calendar(
+            SessionCalendarBuilder::new()
+                .add_outage(
+                    current_time - Duration::minutes(1),
+                    current_time + Duration::minutes(1),
+                )?
+                .build()?,
+        )
+        .build()?;
+        env.init()?;
+
+        let order_id = env
+            .place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(10),
+                },
+            ))
+            .await?;
+        assert_eq!(env.get_order(&order_id).await?.status, OrderStatus::New);
+        let clock = env.get_clock().await?;
+        assert!(!clock.is_open);
+        assert_eq!(clock.next_open, Some(current_time + Duration::minutes(1)));
+
+        *added_duration.write().unwrap() += Duration::minutes(1);
+        let order = env.get_order(&order_id).await?;
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.average_fill_price, Some(BigDecimal::from(25)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sample_prices_with_seed() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let bar = create_bar(10, 20, current_time - Duration::minutes(3));
+        let mut prices = Vec::new();
+        for _ in 0..2 {
+            let context = SimulatedContext::new(create_data_source(vec![bar.clone()]), TestClock);
+            let receiver = context.event_bus().subscribe();
+            let mut pairs_to_trade = HashSet::new();
+            pairs_to_trade.insert(crypto_pair.clone());
+            let mut env = SimulatedEnvironmentBuilder::new(
+                context,
+                SimulatedClient::new(SimulatedBrokerBuilder::new("GBP").build()?),
+            )
+            .set_crypto_pairs_to_trade(pairs_to_trade)
+            .set_bar_price_model(BarPriceModel::Uniform)
+            .set_seed(42)
+            .build()?;
+            env.init()?;
+            let price = receiver
+                .try_iter()
+                .find_map(|event| match event {
+                    SimulationEvent::PriceUpdate {
+                        notional_per_unit, ..
+                    } => Some(notional_per_unit),
+                    _ => None,
+                })
+                .unwrap();
+            assert!(price >= bar.low && price <= bar.high);
+            prices.push(price);
+        }
+
+        assert_eq!(prices[0], prices[1]);
+        Ok(())
+    }
+
+    #[cfg(feature = "config")]
+    #[tokio::test]
+    async fn from_config() -> Result<()> {
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let bar = create_bar(10, 20, current_time - Duration::minutes(3));
+        let config = IrontradeConfig::from_toml(
+            r#"
+pairs = ["COIN/GBP"]
+
+[broker]
+currency = "GBP"
+balance = "1000"
+
+[environment]
+run_id = "config-run"
+"#,
+        )?;
+        let mut env = SimulatedEnvironment::from_config(
+            &config,
+            SimulatedContext::new(create_data_source(vec![bar]), TestClock),
+        )?;
+        env.init()?;
+
+        let order_id = env
+            .place_order(OrderRequest::market_buy(
+                Instrument::from_str("COIN/GBP")?,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(10),
+                },
+            ))
+            .await?;
+        let order = env.get_order(&order_id).await?;
+        assert_eq!(order.status, OrderStatus::Filled);
+        assert_eq!(order.run_id, Some("config-run".into()));
+        assert_eq!(env.get_account().await?.cash, BigDecimal::from(850));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn update_prices_at_each_refresh_time() -> Result<()> {
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let data_source = create_data_source(vec![
+            create_bar(14, 16, current_time - Duration::minutes(4)),
+            create_bar(6, 8, current_time - Duration::minutes(3)),
+            create_bar(14, 16, current_time - Duration::minutes(2)),
+            create_bar(14, 16, current_time - Duration::minutes(1)),
+        ]);
+        let added_duration = Arc::new(RwLock::new(Duration::zero()));
+        let clock = StepClock {
+            initial_time: current_time - Duration::minutes(4),
+            added_duration: added_duration.clone(),
+        };
+        let mut pairs_to_trade = HashSet::new();
+        pairs_to_trade.insert(Instrument::from_str("COIN/GBP")?);
+        let mut env = create_environment(data_source, clock, pairs_to_trade)?;
+        env.init()?;
+
+        let order_id = env
+            .place_order(OrderRequest::limit_buy(
+                "COIN/GBP".parse()?,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(10),
+                },
+                BigDecimal::from(9),
+            ))
+            .await?;
+        assert_eq!(env.get_order(&order_id).await?.status, OrderStatus::New);
+
+        // The price only dips below the limit at a refresh time before the current one
+        *added_duration.write().unwrap() += Duration::minutes(3);
+        assert_eq!(env.get_order(&order_id).await?.status, OrderStatus::Filled);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn publish_events_to_context_event_bus() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let bar = create_bar(10, 20, current_time - Duration::minutes(3));
+        let context = SimulatedContext::new(create_data_source(vec![bar]), TestClock);
+        let receiver = context.event_bus().subscribe();
+        let mut pairs_to_trade = HashSet::new();
+        pairs_to_trade.insert(crypto_pair.clone());
+        let mut env = SimulatedEnvironmentBuilder::new(
+            context,
+            SimulatedClient::new(
+                SimulatedBrokerBuilder::new("GBP")
+                    .set_balance(BigDecimal::from(100_000))
+                    .build()?,
+            ),
+        )
+        .set_crypto_pairs_to_trade(pairs_to_trade)
+        .build()?;
+        env.init()?;
+
+        assert_eq!(
+            receiver.try_recv()?,
+            SimulationEvent::ClockTick(current_time)
+        );
+        assert_eq!(
+            receiver.try_recv()?,
+            SimulationEvent::PriceUpdate {
+                crypto_pair: crypto_pair.clone(),
+                notional_per_unit: BigDecimal::from(15),
+            }
+        );
+
+        let order_id = env
+            .place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(10),
+                },
+            ))
+            .await?;
+
+        let order_events: Vec<OrderEvent> = receiver
+            .try_iter()
+            .filter_map(|event| match event {
+                SimulationEvent::OrderUpdate(order_event) => Some(*order_event),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(order_events.len(), 2);
+        assert_eq!(order_events[0].account, MAIN_ACCOUNT);
+        assert_eq!(order_events[0].previous_status, None);
+        assert_eq!(order_events[0].order.order_id, order_id);
+        assert_eq!(order_events[1].previous_status, Some(OrderStatus::New));
+        assert_eq!(order_events[1].order.status, OrderStatus::Filled);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn get_orders_without_init() -> Result<()> {
-        let mut env = create_environment(TestDataSource, TestClock, HashSet::new());
-        let err = env.get_orders().await.unwrap_err();
+        let env = create_environment(TestDataSource, TestClock, HashSet::new())?;
+        let err = env.get_orders(&OrderFilter::default()).await.unwrap_err();
         assert_eq!(err.to_string(), "Environment has not been initialized");
         Ok(())
     }
 
     #[tokio::test]
     async fn get_order_without_init() -> Result<()> {
-        let mut env = create_environment(TestDataSource, TestClock, HashSet::new());
-        let err = env.get_order("123").await.unwrap_err();
+        let env = create_environment(TestDataSource, TestClock, HashSet::new())?;
+        let err = env.get_order(&OrderId::new("123")).await.unwrap_err();
         assert_eq!(err.to_string(), "Environment has not been initialized");
         Ok(())
     }
 
     #[tokio::test]
     async fn get_account_without_init() -> Result<()> {
-        let mut env = create_environment(TestDataSource, TestClock, HashSet::new());
+        let env = create_environment(TestDataSource, TestClock, HashSet::new())?;
         let err = env.get_account().await.unwrap_err();
         assert_eq!(err.to_string(), "Environment has not been initialized");
         Ok(())
@@ -343,7 +1043,7 @@ mod tests {
 
     #[tokio::test]
     async fn get_latest_bar_current_time() -> Result<()> {
-        let crypto_pair = CryptoPair::from_str("COIN/GBP")?;
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
         let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
         let bar_from_three_minutes_ago = create_bar(10, 20, current_time - Duration::minutes(3));
         let data_source = create_data_source(vec![bar_from_three_minutes_ago.clone()]);
@@ -352,7 +1052,7 @@ mod tests {
             initial_time: current_time,
             added_duration: added_duration.clone(),
         };
-        let mut env = create_environment(data_source, clock, HashSet::new());
+        let mut env = create_environment(data_source, clock, HashSet::new())?;
         env.init()?;
 
         assert_eq!(
@@ -363,9 +1063,40 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn get_latest_bars_of_several_pairs() -> Result<()> {
+        let crypto_pairs = vec![
+            Instrument::from_str("COIN/GBP")?,
+            Instrument::from_str("COIN/USD")?,
+        ];
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let bar_from_three_minutes_ago = create_bar(10, 20, current_time - Duration::minutes(3));
+        let mut data_source = InMemoryBarDataSourceBuilder::new();
+        for crypto_pair in &crypto_pairs {
+            data_source.add_bar(crypto_pair.clone(), bar_from_three_minutes_ago.clone());
+        }
+        let clock = StepClock {
+            initial_time: current_time,
+            added_duration: Arc::new(RwLock::new(Duration::zero())),
+        };
+        let mut env = create_environment(data_source.build(), clock, HashSet::new())?;
+        env.init()?;
+
+        let bars = env.get_latest_minute_bars(&crypto_pairs).await?;
+
+        assert_eq!(
+            bars,
+            crypto_pairs
+                .into_iter()
+                .map(|crypto_pair| (crypto_pair, bar_from_three_minutes_ago.clone()))
+                .collect()
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn get_latest_bar_no_bars_yet_at_clock_time() -> Result<()> {
-        let crypto_pair = CryptoPair::from_str("COIN/GBP")?;
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
         let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
         let bar_from_three_minutes_ago = create_bar(10, 20, current_time - Duration::minutes(3));
         let data_source = create_data_source(vec![bar_from_three_minutes_ago]);
@@ -374,7 +1105,7 @@ mod tests {
             initial_time: current_time - Duration::minutes(5),
             added_duration: added_duration.clone(),
         };
-        let mut env = create_environment(data_source, clock, HashSet::new());
+        let mut env = create_environment(data_source, clock, HashSet::new())?;
         env.init()?;
 
         *added_duration.write().unwrap() += Duration::minutes(1) + Duration::seconds(59);
@@ -385,7 +1116,7 @@ mod tests {
 
     #[tokio::test]
     async fn get_latest_bar_overlapping_bar() -> Result<()> {
-        let crypto_pair = CryptoPair::from_str("COIN/GBP")?;
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
         let current_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
         let bar_from_three_minutes_ago = create_bar(10, 20, current_time - Duration::minutes(3));
         let bar_from_two_minutes_ago = create_bar(100, 200, current_time - Duration::minutes(2));
@@ -398,7 +1129,7 @@ mod tests {
             initial_time: current_time - Duration::minutes(5),
             added_duration: added_duration.clone(),
         };
-        let mut env = create_environment(data_source, clock, HashSet::new());
+        let mut env = create_environment(data_source, clock, HashSet::new())?;
         env.init()?;
 
         *added_duration.write().unwrap() += Duration::minutes(3) + Duration::seconds(59);
@@ -410,28 +1141,203 @@ mod tests {
         Ok(())
     }
 
-    fn create_data_source(ordered_bars: Vec<Bar>) -> impl BarDataSource {
-        #[derive(Clone)]
-        struct DataSource {
-            ordered_bars: Vec<Bar>,
-        }
-        let data_source = DataSource { ordered_bars };
-        impl BarDataSource for DataSource {
-            fn get_bar(
-                &self,
-                _crypto_pair: &CryptoPair,
-                date_time: &DateTime<Utc>,
-                _bar_duration: Duration,
-            ) -> Result<Option<Bar>> {
-                for bar in self.ordered_bars.iter().rev() {
-                    if bar.date_time <= *date_time {
-                        return Ok(Some(bar.clone()));
-                    }
-                }
-                Ok(None)
-            }
+    #[tokio::test]
+    async fn get_timeframe_bars_without_look_ahead() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let current_time = DateTime::<Utc>::from_str("2025-12-17T18:02:30+00:00")?;
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let data_source = create_data_source(vec![
+            create_bar(10, 20, hour - Duration::minutes(2)),
+            create_bar(5, 15, hour - Duration::minutes(1)),
+            create_bar(12, 14, hour),
+            create_bar(11, 18, hour + Duration::minutes(1)),
+            create_bar(1, 100, hour + Duration::minutes(2)),
+        ]);
+        let clock = StepClock {
+            initial_time: current_time,
+            added_duration: Arc::new(RwLock::new(Duration::zero())),
+        };
+        let mut env = create_environment(data_source, clock, HashSet::new())?;
+        env.init()?;
+
+        let timeframe_bars = env
+            .get_timeframe_bars(&crypto_pair, &[Timeframe::OneMinute, Timeframe::OneHour])
+            .await?;
+
+        assert_eq!(timeframe_bars.len(), 2);
+        assert_eq!(
+            timeframe_bars[0].latest,
+            Some(create_bar(11, 18, hour + Duration::minutes(1)))
+        );
+        assert_eq!(timeframe_bars[0].in_progress, None);
+        assert_eq!(
+            timeframe_bars[1].latest,
+            Some(Bar {
+                low: BigDecimal::from(5),
+                high: BigDecimal::from(20),
+                open: BigDecimal::from(10),
+                close: BigDecimal::from(15),
+                date_time: hour - Duration::hours(1),
+                volume: BigDecimal::from(0),
+                trade_count: 0,
+                vwap: None,
+            })
+        );
+        // The bar of the current minute hasn't closed yet and is left out
+        assert_eq!(
+            timeframe_bars[1].in_progress,
+            Some(Bar {
+                low: BigDecimal::from(11),
+                high: BigDecimal::from(18),
+                open: BigDecimal::from(12),
+                close: BigDecimal::from(18),
+                date_time: hour,
+                volume: BigDecimal::from(0),
+                trade_count: 0,
+                vwap: None,
+            })
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_bars_of_closed_periods() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let hour = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let data_source = create_data_source(
+            [-5, -2, 1, 4, 6, 11]
+                .into_iter()
+                .map(|minutes| {
+                    create_bar(
+                        minutes + 10,
+                        minutes + 20,
+                        hour + Duration::minutes(minutes.into()),
+                    )
+                })
+                .collect(),
+        );
+        let clock = StepClock {
+            initial_time: hour + Duration::minutes(12) + Duration::seconds(30),
+            added_duration: Arc::new(RwLock::new(Duration::zero())),
+        };
+        let mut env = create_environment(data_source, clock, HashSet::new())?;
+        env.init()?;
+
+        let bars = env
+            .get_bars(
+                &crypto_pair,
+                hour - Duration::minutes(8),
+                hour + Duration::minutes(30),
+                Timeframe::FiveMinutes,
+            )
+            .await?;
+
+        // The period before the start and the period still in progress are left out
+        assert_eq!(
+            bars,
+            vec![
+                create_bar(5, 18, hour - Duration::minutes(5)),
+                create_bar(11, 24, hour),
+                create_bar(16, 26, hour + Duration::minutes(5)),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_weekly_bars_from_mondays() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let monday = DateTime::<Utc>::from_str("2025-12-15T00:00:00+00:00")?;
+        let data_source = create_data_source(vec![
+            create_bar(10, 20, monday - Duration::days(1)),
+            create_bar(5, 15, monday + Duration::days(2)),
+            create_bar(12, 14, monday + Duration::days(6)),
+        ]);
+        let clock = StepClock {
+            initial_time: monday + Duration::weeks(1),
+            added_duration: Arc::new(RwLock::new(Duration::zero())),
+        };
+        let mut env = create_environment(data_source, clock, HashSet::new())?;
+        env.init()?;
+
+        let bars = env
+            .get_bars(
+                &crypto_pair,
+                monday - Duration::weeks(1),
+                monday + Duration::weeks(1),
+                Timeframe::Minutes(7 * 24 * 60),
+            )
+            .await?;
+
+        assert_eq!(
+            bars,
+            vec![
+                create_bar(10, 20, monday - Duration::weeks(1)),
+                Bar {
+                    low: BigDecimal::from(5),
+                    high: BigDecimal::from(15),
+                    open: BigDecimal::from(5),
+                    close: BigDecimal::from(14),
+                    date_time: monday,
+                    volume: BigDecimal::from(0),
+                    trade_count: 0,
+                    vwap: None,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_timeframe_bars_invalid_timeframe() -> Result<()> {
+        let env = create_environment(TestDataSource, TestClock, HashSet::new())?;
+        let err = env
+            .get_timeframe_bars(&Instrument::from_str("COIN/GBP")?, &[Timeframe::Minutes(0)])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Timeframes must be a positive number of minutes"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn track_progress() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let added_duration = Arc::new(RwLock::new(Duration::zero()));
+        let clock = StepClock {
+            initial_time: start,
+            added_duration: added_duration.clone(),
+        };
+        let mut env = create_environment(create_data_source(Vec::new()), clock, HashSet::new())?;
+        let mut progress_stream = env.track_progress(start, start + Duration::minutes(2))?;
+        env.init()?;
+
+        *added_duration.write().unwrap() += Duration::minutes(1);
+        env.get_orders(&OrderFilter::default()).await?;
+
+        let mut percentages = Vec::new();
+        while let Some(progress) = progress_stream.try_next() {
+            assert_eq!(progress.equity, BigDecimal::from(100_000));
+            percentages.push(progress.percent_complete);
         }
-        data_source
+        assert_eq!(percentages, vec![0.0, 0.0, 25.0, 50.0]);
+
+        progress_stream.abort();
+        *added_duration.write().unwrap() += Duration::minutes(1);
+        let err = env.get_orders(&OrderFilter::default()).await.unwrap_err();
+        assert_eq!(err.to_string(), "Backtest was aborted");
+        Ok(())
+    }
+
+    fn create_data_source(bars: Vec<Bar>) -> InMemoryBarDataSource {
+        InMemoryBarDataSourceBuilder::new()
+            .add_bars(Instrument::from_str("COIN/GBP").unwrap(), bars)
+            .build()
     }
 
     fn create_bar(low: i32, high: i32, date_time: DateTime<Utc>) -> Bar {
@@ -441,30 +1347,33 @@ mod tests {
             open: BigDecimal::from(low),
             close: BigDecimal::from(high),
             date_time,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
         }
     }
 
     fn create_environment<B, C>(
         data_source: B,
         clock: C,
-        pairs_to_trade: HashSet<CryptoPair>,
-    ) -> SimulatedEnvironment
+        pairs_to_trade: HashSet<Instrument>,
+    ) -> Result<SimulatedEnvironment>
     where
         B: BarDataSource + Send + Sync + 'static,
         C: Clock + Send + Sync + 'static,
     {
-        SimulatedEnvironmentBuilder::new(
+        Ok(SimulatedEnvironmentBuilder::new(
             SimulatedContext::new(data_source, clock),
             SimulatedClient::new(
                 SimulatedBrokerBuilder::new("GBP")
                     .set_balance(BigDecimal::from(100_000))
-                    .build(),
+                    .build()?,
             ),
         )
         .set_crypto_pairs_to_trade(pairs_to_trade)
-        .set_bar_duration(Duration::minutes(1))
+        .set_bar_timeframe(Timeframe::OneMinute)
         .set_refresh_duration(Duration::seconds(30))
-        .build()
+        .build()?)
     }
 
     #[derive(Clone)]
@@ -485,7 +1394,7 @@ mod tests {
     impl BarDataSource for TestDataSource {
         fn get_bar(
             &self,
-            _crypto_pair: &CryptoPair,
+            _crypto_pair: &Instrument,
             _date_time: &DateTime<Utc>,
             _bar_duration: Duration,
         ) -> Result<Option<Bar>> {
@@ -0,0 +1,187 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Instrument, Order, OrderStatus};
+use crate::simulated::broker::MarginCall;
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::fmt::{Debug, Formatter};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::sync::{Arc, Mutex, Weak};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimulationEvent {
+    PriceUpdate {
+        crypto_pair: Instrument,
+        notional_per_unit: BigDecimal,
+    },
+    OrderUpdate(Box<OrderEvent>),
+    ClockTick(DateTime<Utc>),
+    /// Risk alert of an account, [MAIN_ACCOUNT](crate::simulated::MAIN_ACCOUNT) or the name of a
+    /// sub-account or exchange account.
+    RiskAlert {
+        account: String,
+        risk_alert: RiskAlert,
+    },
+}
+
+/// Published whenever an order is created or changes status. `previous_status` is `None` for
+/// newly placed orders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderEvent {
+    /// Account of the order, [MAIN_ACCOUNT](crate::simulated::MAIN_ACCOUNT) or the name of a
+    /// sub-account or exchange account.
+    pub account: String,
+    pub previous_status: Option<OrderStatus>,
+    pub order: Order,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskAlert {
+    MarginCall(MarginCall),
+    /// Trading a pair was disabled, either manually or after repeated order rejections.
+    PairDisabled {
+        crypto_pair: Instrument,
+        reason: String,
+    },
+    PairEnabled(Instrument),
+}
+
+/// Central channel through which the simulated components publish [SimulationEvent]s.
+/// Clones share the same subscribers, so a bus can be handed to every component of a simulation.
+///
+/// The bus only reports what happened: the environment still sets prices and halts trading on
+/// its broker directly, and publishing an event doesn't change the simulation.
+#[derive(Debug, Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Sender<SimulationEvent>>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a receiver for every event published from now on.
+    pub fn subscribe(&self) -> Receiver<SimulationEvent> {
+        let (sender, receiver) = channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Sends the event to all subscribers, dropping the ones whose receiver has been dropped.
+    pub fn publish(&self, event: SimulationEvent) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+/// Callbacks invoked with every [OrderEvent], shared between clones of the component they were
+/// registered on.
+#[derive(Clone, Default)]
+pub(crate) struct OrderListeners(Arc<Mutex<ListenerRegistry>>);
+
+#[derive(Default)]
+struct ListenerRegistry {
+    next_id: u64,
+    listeners: Vec<(u64, Arc<Mutex<OrderListenerFn>>)>,
+}
+
+type OrderListenerFn = dyn FnMut(&OrderEvent) + Send;
+
+impl OrderListeners {
+    pub(crate) fn add<F>(&self, listener: F) -> OrderListenerGuard
+    where
+        F: FnMut(&OrderEvent) + Send + 'static,
+    {
+        let mut registry = self.0.lock().unwrap();
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.listeners.push((id, Arc::new(Mutex::new(listener))));
+        OrderListenerGuard {
+            registry: Arc::downgrade(&self.0),
+            id,
+        }
+    }
+
+    pub(crate) fn notify(&self, order_event: &OrderEvent) {
+        // Listeners are called without holding the registry, so that they can drop their guard
+        let listeners: Vec<Arc<Mutex<OrderListenerFn>>> = self
+            .0
+            .lock()
+            .unwrap()
+            .listeners
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect();
+        for listener in listeners {
+            (listener.lock().unwrap())(order_event)
+        }
+    }
+}
+
+impl Debug for OrderListeners {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("OrderListeners")
+    }
+}
+
+/// Keeps an order listener registered, unregistering it once dropped.
+#[must_use = "the listener is unregistered as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct OrderListenerGuard {
+    registry: Weak<Mutex<ListenerRegistry>>,
+    id: u64,
+}
+
+impl Drop for OrderListenerGuard {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry
+                .lock()
+                .unwrap()
+                .listeners
+                .retain(|(id, _)| *id != self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn publish_to_all_subscribers() -> Result<()> {
+        let event_bus = EventBus::new();
+        let first_receiver = event_bus.subscribe();
+        let second_receiver = event_bus.clone().subscribe();
+
+        let date_time = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        event_bus.publish(SimulationEvent::ClockTick(date_time));
+
+        assert_eq!(
+            first_receiver.try_recv()?,
+            SimulationEvent::ClockTick(date_time)
+        );
+        assert_eq!(
+            second_receiver.try_recv()?,
+            SimulationEvent::ClockTick(date_time)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn publish_drops_disconnected_subscribers() {
+        let event_bus = EventBus::new();
+        let receiver = event_bus.subscribe();
+        drop(receiver);
+
+        event_bus.publish(SimulationEvent::ClockTick(Utc::now()));
+
+        assert!(event_bus.subscribers.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,568 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Instrument, Order, OrderId, OrderSide, OrderStatus};
+use crate::api::request::OrderRequest;
+use crate::simulated::broker::SimulatedBroker;
+use crate::simulated::client::SimulatedClient;
+use crate::simulated::matching::{Execution, MarketState, MatchingEngine, get_remaining_quantity};
+use anyhow::{Result, anyhow};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// An in-memory exchange hosting several independent accounts, whose limit orders are matched
+/// against each other's instead of the market price. Matching follows price then time priority,
+/// trades execute at the price of the resting order and set the notional per unit of the pair
+/// in every account.
+///
+/// Each account is traded through its own [SimulatedClient] view, clones of the exchange share
+/// the same accounts.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedExchange {
+    state: Arc<Mutex<ExchangeState>>,
+}
+
+#[derive(Debug, Default)]
+struct ExchangeState {
+    accounts: HashMap<String, SimulatedBroker>,
+    /// Account and order ids of the open orders in the order they were placed.
+    open_orders: Vec<(String, OrderId)>,
+}
+
+/// Leaves the matching of orders to the exchange.
+#[derive(Clone)]
+struct ExchangeMatchingEngine;
+
+impl MatchingEngine for ExchangeMatchingEngine {
+    fn match_order(&self, _order: &Order, _market_state: &MarketState) -> Vec<Execution> {
+        Vec::new()
+    }
+}
+
+impl SimulatedExchange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hosts `broker` as a new account, returning the client to trade with it.
+    pub fn add_account(
+        &self,
+        account_id: &str,
+        mut broker: SimulatedBroker,
+    ) -> Result<SimulatedClient> {
+        let mut state = self.state.lock().unwrap();
+        if state.accounts.contains_key(account_id) {
+            return Err(anyhow!("Account {} already exists", account_id));
+        }
+        broker.set_matching_engine(ExchangeMatchingEngine);
+        broker.set_account(account_id);
+        state.accounts.insert(account_id.into(), broker);
+        Ok(SimulatedClient::new_exchange_account(
+            self.clone(),
+            account_id,
+        ))
+    }
+
+    pub fn get_account_ids(&self) -> Vec<String> {
+        self.state
+            .lock()
+            .unwrap()
+            .accounts
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Sets the notional per unit of a pair in every account.
+    pub fn set_notional_per_unit(
+        &self,
+        crypto_pair: Instrument,
+        notional_per_unit: BigDecimal,
+    ) -> Result<()> {
+        self.state
+            .lock()
+            .unwrap()
+            .set_notional_per_unit(&crypto_pair, &notional_per_unit, None)
+    }
+
+    /// Sets the notional per unit of a pair as of `updated_at` in every account.
+    pub fn set_notional_per_unit_at(
+        &self,
+        crypto_pair: Instrument,
+        notional_per_unit: BigDecimal,
+        updated_at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.state.lock().unwrap().set_notional_per_unit(
+            &crypto_pair,
+            &notional_per_unit,
+            Some(updated_at),
+        )
+    }
+
+    pub(crate) fn place_order(&self, account_id: &str, order_req: OrderRequest) -> Result<OrderId> {
+        check_limit_price(&order_req)?;
+        self.state
+            .lock()
+            .unwrap()
+            .place_order(account_id, order_req)
+    }
+
+    /// Places the orders of an account in turn, placing none of them when the account would
+    /// reject any, as [SimulatedBroker::place_orders] does. The orders may match each other as
+    /// they are placed, and no other order gets placed on the exchange in between.
+    pub(crate) fn place_orders(
+        &self,
+        account_id: &str,
+        order_reqs: Vec<OrderRequest>,
+    ) -> Result<Vec<OrderId>> {
+        for order_req in &order_reqs {
+            check_limit_price(order_req)?;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.get_account(account_id)?.check_orders(&order_reqs)?;
+        order_reqs
+            .into_iter()
+            .map(|order_req| state.place_order(account_id, order_req))
+            .collect()
+    }
+
+    pub(crate) fn with_account<F, R>(&self, account_id: &str, f: F) -> R
+    where
+        F: FnOnce(&mut SimulatedBroker) -> R,
+    {
+        let mut state = self.state.lock().unwrap();
+        f(state.accounts.get_mut(account_id).unwrap())
+    }
+}
+
+fn check_limit_price(order_req: &OrderRequest) -> Result<()> {
+    if order_req.limit_price.is_none() {
+        return Err(anyhow!("Only limit orders can be placed on the exchange"));
+    }
+    Ok(())
+}
+
+impl ExchangeState {
+    fn get_account(&mut self, account_id: &str) -> Result<&mut SimulatedBroker> {
+        self.accounts
+            .get_mut(account_id)
+            .ok_or(anyhow!("Account {} doesn't exist", account_id))
+    }
+
+    fn place_order(&mut self, account_id: &str, order_req: OrderRequest) -> Result<OrderId> {
+        let account = self.get_account(account_id)?;
+        if let Some(client_order_id) = &order_req.client_order_id
+            && let Ok(order) = account.get_order_by_client_id(client_order_id)
+        {
+            return Ok(order.order_id);
+        }
+        let order_id = account.place_order(order_req)?;
+        self.open_orders.push((account_id.into(), order_id.clone()));
+        self.match_order(account_id, &order_id)?;
+        Ok(order_id)
+    }
+
+    fn set_notional_per_unit(
+        &mut self,
+        crypto_pair: &Instrument,
+        notional_per_unit: &BigDecimal,
+        updated_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        for broker in self.accounts.values_mut() {
+            match updated_at {
+                Some(updated_at) => broker.set_notional_value_per_unit_at(
+                    crypto_pair.clone(),
+                    notional_per_unit.clone(),
+                    updated_at,
+                )?,
+                None => broker
+                    .set_notional_value_per_unit(crypto_pair.clone(), notional_per_unit.clone())?,
+            }
+        }
+        Ok(())
+    }
+
+    fn get_open_order(&self, account_id: &str, order_id: &OrderId) -> Option<Order> {
+        self.accounts
+            .get(account_id)
+            .and_then(|broker| broker.get_order(order_id).ok())
+            .filter(|order| {
+                matches!(
+                    order.status,
+                    OrderStatus::New | OrderStatus::PartiallyFilled
+                )
+            })
+    }
+
+    /// Matches an order against the crossing open orders of the other accounts.
+    fn match_order(&mut self, account_id: &str, order_id: &OrderId) -> Result<()> {
+        let Some(order) = self.get_open_order(account_id, order_id) else {
+            return Ok(());
+        };
+        let instrument = self
+            .get_account(account_id)?
+            .get_order_instrument(order_id)?;
+        let limit_price = order.limit_price.clone().unwrap();
+        let mut resting_orders: Vec<(String, Order)> = self
+            .open_orders
+            .iter()
+            .filter(|(resting_account_id, _)| resting_account_id != account_id)
+            .filter_map(|(resting_account_id, resting_order_id)| {
+                self.get_open_order(resting_account_id, resting_order_id)
+                    .map(|resting_order| (resting_account_id.clone(), resting_order))
+            })
+            .filter(|(resting_account_id, resting_order)| {
+                self.is_order_of_instrument(
+                    resting_account_id,
+                    &resting_order.order_id,
+                    &instrument,
+                ) && resting_order.side != order.side
+                    && resting_order
+                        .limit_price
+                        .as_ref()
+                        .is_some_and(|resting_limit_price| match order.side {
+                            OrderSide::Buy => *resting_limit_price <= limit_price,
+                            OrderSide::Sell => *resting_limit_price >= limit_price,
+                        })
+            })
+            .collect();
+        // Stable sort, so orders at the same price keep their time priority
+        resting_orders.sort_by(|(_, first), (_, second)| match order.side {
+            OrderSide::Buy => first.limit_price.cmp(&second.limit_price),
+            OrderSide::Sell => second.limit_price.cmp(&first.limit_price),
+        });
+
+        let mut last_price = None;
+        for (resting_account_id, resting_order) in resting_orders {
+            let Some(order) = self.get_open_order(account_id, order_id) else {
+                break;
+            };
+            let price = resting_order.limit_price.clone().unwrap();
+            let quantity = get_remaining_quantity(&order, &price)
+                .min(get_remaining_quantity(&resting_order, &price));
+            if quantity <= 0 {
+                continue;
+            }
+            let execution = Execution {
+                price: price.clone(),
+                quantity,
+            };
+            self.get_account(&resting_account_id)?
+                .execute_order(&resting_order.order_id, &execution)?;
+            self.get_account(account_id)?
+                .execute_order(&order.order_id, &execution)?;
+            last_price = Some(price);
+        }
+
+        let open_orders = self
+            .open_orders
+            .iter()
+            .filter(|(account_id, order_id)| self.get_open_order(account_id, order_id).is_some())
+            .cloned()
+            .collect();
+        self.open_orders = open_orders;
+
+        if let Some(last_price) = last_price {
+            self.set_notional_per_unit(&instrument, &last_price, None)?;
+        }
+        Ok(())
+    }
+
+    /// Whether an order was placed for `instrument`, as an equity and a crypto pair with the same
+    /// assets trade on different markets.
+    fn is_order_of_instrument(
+        &self,
+        account_id: &str,
+        order_id: &OrderId,
+        instrument: &Instrument,
+    ) -> bool {
+        self.accounts
+            .get(account_id)
+            .and_then(|broker| broker.get_order_instrument(order_id).ok())
+            .is_some_and(|order_instrument| {
+                order_instrument == *instrument
+                    && order_instrument.asset_class == instrument.asset_class
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Client;
+    use crate::api::common::Amount;
+    use crate::api::request::OrderFilter;
+    use crate::simulated::SimulatedBrokerBuilder;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn match_limit_orders_between_accounts() -> Result<()> {
+        let exchange = SimulatedExchange::new();
+        let buyer = exchange.add_account("buyer", create_broker(100, 0)?)?;
+        let seller = exchange.add_account("seller", create_broker(0, 10)?)?;
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        exchange.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(5))?;
+
+        let sell_order_id = seller
+            .place_order(OrderRequest::limit_sell(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(4),
+                },
+                BigDecimal::from(6),
+            ))
+            .await?;
+        assert_eq!(
+            seller.get_order(&sell_order_id).await?.status,
+            OrderStatus::New
+        );
+
+        let buy_order_id = buyer
+            .place_order(OrderRequest::limit_buy(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(6),
+                },
+                BigDecimal::from(7),
+            ))
+            .await?;
+
+        let sell_order = seller.get_order(&sell_order_id).await?;
+        assert_eq!(sell_order.status, OrderStatus::Filled);
+        let buy_order = buyer.get_order(&buy_order_id).await?;
+        assert_eq!(buy_order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(buy_order.filled_quantity, BigDecimal::from(4));
+        assert_eq!(buy_order.average_fill_price, Some(BigDecimal::from(6)));
+
+        let buyer_account = buyer.get_account().await?;
+        assert_eq!(buyer_account.cash, BigDecimal::from(76));
+        assert_eq!(buyer_account.open_positions["BTC"].quantity, 4);
+        assert_eq!(seller.get_account().await?.cash, BigDecimal::from(24));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn match_by_price_then_time_priority() -> Result<()> {
+        let exchange = SimulatedExchange::new();
+        let buyer = exchange.add_account("buyer", create_broker(100, 0)?)?;
+        let first_seller = exchange.add_account("first_seller", create_broker(0, 10)?)?;
+        let second_seller = exchange.add_account("second_seller", create_broker(0, 10)?)?;
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        exchange.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(5))?;
+
+        let first_order_id = first_seller
+            .place_order(create_limit_sell(&crypto_pair, 6))
+            .await?;
+        let second_order_id = second_seller
+            .place_order(create_limit_sell(&crypto_pair, 6))
+            .await?;
+        let best_order_id = second_seller
+            .place_order(create_limit_sell(&crypto_pair, 5))
+            .await?;
+
+        buyer
+            .place_order(OrderRequest::limit_buy(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(2),
+                },
+                BigDecimal::from(6),
+            ))
+            .await?;
+
+        assert_eq!(
+            second_seller.get_order(&best_order_id).await?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(
+            first_seller.get_order(&first_order_id).await?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(
+            second_seller.get_order(&second_order_id).await?.status,
+            OrderStatus::New
+        );
+        // The last trade sets the price of the pair
+        let account = buyer.get_account().await?;
+        assert_eq!(
+            account.open_positions["BTC"].market_value,
+            Some(BigDecimal::from(12))
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn place_invalid_orders() -> Result<()> {
+        let exchange = SimulatedExchange::new();
+        let client = exchange.add_account("account", create_broker(100, 0)?)?;
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        exchange.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(5))?;
+
+        let err = client
+            .place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Only limit orders can be placed on the exchange"
+        );
+
+        let err = exchange
+            .add_account("account", create_broker(100, 0)?)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Account account already exists");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn place_orders_all_or_none() -> Result<()> {
+        let exchange = SimulatedExchange::new();
+        let buyer = exchange.add_account("buyer", create_broker(100, 0)?)?;
+        let seller = exchange.add_account("seller", create_broker(0, 10)?)?;
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        exchange.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(5))?;
+        let sell_order_id = seller
+            .place_order(OrderRequest::limit_sell(
+                crypto_pair.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(4),
+                },
+                BigDecimal::from(6),
+            ))
+            .await?;
+
+        // The first order would fill against the resting sell, but the second can't be afforded
+        let err = buyer
+            .place_orders(vec![
+                create_limit_buy(&crypto_pair, 4, 6),
+                create_limit_buy(&crypto_pair, 20, 6),
+            ])
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Not enough USD buying power");
+        assert!(buyer.get_orders(&OrderFilter::default()).await?.is_empty());
+        assert_eq!(buyer.get_account().await?.cash, BigDecimal::from(100));
+        assert_eq!(
+            seller.get_order(&sell_order_id).await?.status,
+            OrderStatus::New
+        );
+
+        let order_ids = buyer
+            .place_orders(vec![
+                create_limit_buy(&crypto_pair, 4, 6),
+                create_limit_buy(&crypto_pair, 10, 5),
+            ])
+            .await?;
+        assert_eq!(
+            buyer.get_order(&order_ids[0]).await?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(
+            buyer.get_order(&order_ids[1]).await?.status,
+            OrderStatus::New
+        );
+        assert_eq!(
+            seller.get_order(&sell_order_id).await?.status,
+            OrderStatus::Filled
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn match_orders_of_the_same_instrument() -> Result<()> {
+        let exchange = SimulatedExchange::new();
+        let buyer_broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .add_notional_asset("EUR", Some(BigDecimal::from(100)))
+            .build()?;
+        let buyer = exchange.add_account("buyer", buyer_broker)?;
+        let seller_broker = SimulatedBrokerBuilder::new("USD")
+            .add_notional_asset("EUR", None)
+            .add_position("AAPL", BigDecimal::from(10), BigDecimal::from(1))?
+            .build()?;
+        let seller = exchange.add_account("seller", seller_broker)?;
+        let aapl_usd = Instrument::equity("AAPL", "USD");
+        let aapl_eur = Instrument::equity("AAPL", "EUR");
+        let aapl_usd_crypto = Instrument::crypto("AAPL", "USD");
+        for instrument in [&aapl_usd, &aapl_eur] {
+            exchange.set_notional_per_unit(instrument.clone(), BigDecimal::from(5))?;
+        }
+        let sell_order_id = seller
+            .place_order(OrderRequest::limit_sell(
+                aapl_usd.clone(),
+                Amount::Quantity {
+                    quantity: BigDecimal::from(4),
+                },
+                BigDecimal::from(6),
+            ))
+            .await?;
+
+        // Neither the same symbol quoted in another currency nor another asset class match it
+        for instrument in [&aapl_eur, &aapl_usd_crypto] {
+            let order_id = buyer
+                .place_order(create_limit_buy(instrument, 4, 7))
+                .await?;
+            assert_eq!(buyer.get_order(&order_id).await?.status, OrderStatus::New);
+        }
+        assert_eq!(
+            seller.get_order(&sell_order_id).await?.status,
+            OrderStatus::New
+        );
+
+        let order_id = buyer.place_order(create_limit_buy(&aapl_usd, 4, 7)).await?;
+        assert_eq!(
+            buyer.get_order(&order_id).await?.status,
+            OrderStatus::Filled
+        );
+        assert_eq!(
+            seller.get_order(&sell_order_id).await?.status,
+            OrderStatus::Filled
+        );
+        Ok(())
+    }
+
+    fn create_limit_buy(crypto_pair: &Instrument, quantity: i32, limit_price: i32) -> OrderRequest {
+        OrderRequest::limit_buy(
+            crypto_pair.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(quantity),
+            },
+            BigDecimal::from(limit_price),
+        )
+    }
+
+    /// Broker holding `balance` dollars and `btc_quantity` bitcoins bought at one dollar.
+    fn create_broker(balance: i32, btc_quantity: i32) -> Result<SimulatedBroker> {
+        let mut broker = SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(balance + btc_quantity))
+            .build()?;
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        broker.set_notional_value_per_unit(crypto_pair.clone(), BigDecimal::from(1))?;
+        if btc_quantity > 0 {
+            broker.place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(btc_quantity),
+                },
+            ))?;
+        }
+        Ok(broker)
+    }
+
+    fn create_limit_sell(crypto_pair: &Instrument, limit_price: i32) -> OrderRequest {
+        OrderRequest::limit_sell(
+            crypto_pair.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+            BigDecimal::from(limit_price),
+        )
+    }
+}
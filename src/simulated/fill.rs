@@ -0,0 +1,350 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Order, OrderSide};
+use crate::simulated::seed::RngState;
+use crate::{Error, Result};
+use bigdecimal::BigDecimal;
+use dyn_clone::DynClone;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::fmt::{Debug, Formatter};
+use std::sync::Mutex;
+
+/// Decides whether a pending order of the simulated broker gets filled at the current notional
+/// per unit of its asset pair, and at which price.
+pub trait FillPolicy: DynClone {
+    /// Returns the price the order is filled at, or `None` if the order should keep waiting.
+    fn fill_price(&self, order: &Order, notional_per_unit: &BigDecimal) -> Option<BigDecimal>;
+
+    /// Seeds the random number generator of policies filling orders at random, so that
+    /// repeated runs fill the same orders at the same prices. Does nothing by default.
+    fn reseed(&mut self, _seed: u64) {}
+
+    /// State of the random number generator of policies filling orders at random, kept in
+    /// backtest checkpoints. `None` by default.
+    fn get_rng_state(&self) -> Option<RngState> {
+        None
+    }
+
+    /// Restores a state returned by [FillPolicy::get_rng_state]. Does nothing by default.
+    fn set_rng_state(&mut self, _rng_state: &RngState) {}
+}
+
+dyn_clone::clone_trait_object!(FillPolicy);
+
+impl Debug for dyn FillPolicy + Send + Sync {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FillPolicy")
+    }
+}
+
+/// Fills market orders immediately and limit orders as soon as the notional per unit reaches the
+/// limit price, always at the current notional per unit.
+#[derive(Clone, Debug, Default)]
+pub struct TouchFillPolicy;
+
+impl FillPolicy for TouchFillPolicy {
+    fn fill_price(&self, order: &Order, notional_per_unit: &BigDecimal) -> Option<BigDecimal> {
+        match &order.limit_price {
+            Some(limit_price)
+                if notional_per_unit != limit_price
+                    && !is_price_better(order, notional_per_unit, limit_price) =>
+            {
+                None
+            }
+            _ => Some(notional_per_unit.clone()),
+        }
+    }
+}
+
+/// Only fills limit orders once the notional per unit is strictly better than the limit price,
+/// assuming that resting orders at the touched price are behind the rest of the queue.
+#[derive(Clone, Debug, Default)]
+pub struct ConservativeFillPolicy;
+
+impl FillPolicy for ConservativeFillPolicy {
+    fn fill_price(&self, order: &Order, notional_per_unit: &BigDecimal) -> Option<BigDecimal> {
+        match &order.limit_price {
+            Some(limit_price) if !is_price_better(order, notional_per_unit, limit_price) => None,
+            _ => Some(notional_per_unit.clone()),
+        }
+    }
+}
+
+/// Lets limit orders filled by another [FillPolicy] sometimes fill at a better price, like on
+/// venues with internal price improvement.
+#[derive(Debug)]
+pub struct PriceImprovementFillPolicy {
+    fill_policy: Box<dyn FillPolicy + Send + Sync>,
+    probability: f64,
+    max_improvement_multiplier: BigDecimal,
+    distribution: PriceImprovementDistribution,
+    rng: Mutex<ChaCha12Rng>,
+}
+
+/// How the improvement of a fill is picked, up to the maximum improvement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PriceImprovementDistribution {
+    /// Any improvement up to the maximum with the same probability.
+    #[default]
+    Uniform,
+    /// Always the maximum improvement.
+    Fixed,
+}
+
+impl PriceImprovementFillPolicy {
+    /// Improves the fill price of limit orders by up to `max_improvement_percentage` with the
+    /// given `probability`, between 0 and 1.
+    pub fn new<F>(
+        fill_policy: F,
+        probability: f64,
+        max_improvement_percentage: BigDecimal,
+    ) -> Result<Self>
+    where
+        F: FillPolicy + Send + Sync + 'static,
+    {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(Error::InvalidArgument(
+                "Price improvement probability must be between 0 and 1".into(),
+            ));
+        }
+        if !(BigDecimal::from(0)..=BigDecimal::from(100)).contains(&max_improvement_percentage) {
+            return Err(Error::InvalidArgument(
+                "Price improvement percentage must be between 0 and 100".into(),
+            ));
+        }
+        Ok(Self {
+            fill_policy: Box::new(fill_policy),
+            probability,
+            max_improvement_multiplier: max_improvement_percentage / BigDecimal::from(100),
+            distribution: PriceImprovementDistribution::default(),
+            rng: Mutex::new(ChaCha12Rng::from_os_rng()),
+        })
+    }
+
+    pub fn set_distribution(&mut self, distribution: PriceImprovementDistribution) -> &mut Self {
+        self.distribution = distribution;
+        self
+    }
+
+    /// Seeds the random number generator, so that repeated runs improve the same fills.
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.rng = Mutex::new(ChaCha12Rng::seed_from_u64(seed));
+        self
+    }
+
+    fn sample_improvement_multiplier(&self) -> Option<BigDecimal> {
+        let mut rng = self.rng.lock().unwrap();
+        if rng.random::<f64>() >= self.probability {
+            return None;
+        }
+        let fraction = match self.distribution {
+            PriceImprovementDistribution::Uniform => rng.random::<f64>(),
+            PriceImprovementDistribution::Fixed => 1.0,
+        };
+        // Rounded to keep the precision of the improved prices bounded
+        let fraction = BigDecimal::try_from(fraction)
+            .unwrap_or(BigDecimal::from(0))
+            .round(6);
+        Some(&self.max_improvement_multiplier * fraction)
+    }
+}
+
+impl Clone for PriceImprovementFillPolicy {
+    fn clone(&self) -> Self {
+        Self {
+            fill_policy: self.fill_policy.clone(),
+            probability: self.probability,
+            max_improvement_multiplier: self.max_improvement_multiplier.clone(),
+            distribution: self.distribution.clone(),
+            rng: Mutex::new(self.rng.lock().unwrap().clone()),
+        }
+    }
+}
+
+impl FillPolicy for PriceImprovementFillPolicy {
+    fn reseed(&mut self, seed: u64) {
+        self.set_seed(seed);
+    }
+
+    fn get_rng_state(&self) -> Option<RngState> {
+        Some(RngState::of(&self.rng.lock().unwrap()))
+    }
+
+    fn set_rng_state(&mut self, rng_state: &RngState) {
+        self.rng = Mutex::new(rng_state.to_rng());
+    }
+
+    fn fill_price(&self, order: &Order, notional_per_unit: &BigDecimal) -> Option<BigDecimal> {
+        let fill_price = self.fill_policy.fill_price(order, notional_per_unit)?;
+        if order.limit_price.is_none() {
+            return Some(fill_price);
+        }
+        match self.sample_improvement_multiplier() {
+            Some(improvement_multiplier) => Some(match order.side {
+                OrderSide::Buy => &fill_price * (1 - improvement_multiplier),
+                OrderSide::Sell => &fill_price * (1 + improvement_multiplier),
+            }),
+            None => Some(fill_price),
+        }
+    }
+}
+
+fn is_price_better(
+    order: &Order,
+    notional_per_unit: &BigDecimal,
+    limit_price: &BigDecimal,
+) -> bool {
+    match order.side {
+        OrderSide::Buy => notional_per_unit < limit_price,
+        OrderSide::Sell => notional_per_unit > limit_price,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, OrderId, OrderStatus, OrderType};
+    use anyhow::Result;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    #[test]
+    fn touch_fill_policy() {
+        let policy = TouchFillPolicy;
+        let buy = create_limit_order(OrderSide::Buy, 10);
+        assert_eq!(policy.fill_price(&buy, &BigDecimal::from(11)), None);
+        assert_eq!(
+            policy.fill_price(&buy, &BigDecimal::from(10)),
+            Some(BigDecimal::from(10))
+        );
+        assert_eq!(
+            policy.fill_price(&buy, &BigDecimal::from(9)),
+            Some(BigDecimal::from(9))
+        );
+
+        let sell = create_limit_order(OrderSide::Sell, 10);
+        assert_eq!(policy.fill_price(&sell, &BigDecimal::from(9)), None);
+        assert_eq!(
+            policy.fill_price(&sell, &BigDecimal::from(10)),
+            Some(BigDecimal::from(10))
+        );
+        assert_eq!(
+            policy.fill_price(&sell, &BigDecimal::from(11)),
+            Some(BigDecimal::from(11))
+        );
+    }
+
+    #[test]
+    fn conservative_fill_policy() {
+        let policy = ConservativeFillPolicy;
+        let buy = create_limit_order(OrderSide::Buy, 10);
+        assert_eq!(policy.fill_price(&buy, &BigDecimal::from(10)), None);
+        assert_eq!(
+            policy.fill_price(&buy, &BigDecimal::from(9)),
+            Some(BigDecimal::from(9))
+        );
+
+        let sell = create_limit_order(OrderSide::Sell, 10);
+        assert_eq!(policy.fill_price(&sell, &BigDecimal::from(10)), None);
+        assert_eq!(
+            policy.fill_price(&sell, &BigDecimal::from(11)),
+            Some(BigDecimal::from(11))
+        );
+    }
+
+    #[test]
+    fn market_orders_fill_at_current_price() {
+        let market_order = Order {
+            limit_price: None,
+            type_: OrderType::Market,
+            ..create_limit_order(OrderSide::Buy, 10)
+        };
+        assert_eq!(
+            TouchFillPolicy.fill_price(&market_order, &BigDecimal::from(12)),
+            Some(BigDecimal::from(12))
+        );
+        assert_eq!(
+            ConservativeFillPolicy.fill_price(&market_order, &BigDecimal::from(12)),
+            Some(BigDecimal::from(12))
+        );
+    }
+
+    #[test]
+    fn price_improvement_fill_policy() -> Result<()> {
+        let mut policy =
+            PriceImprovementFillPolicy::new(TouchFillPolicy, 1.0, BigDecimal::from(1))?;
+        policy.set_distribution(PriceImprovementDistribution::Fixed);
+
+        let buy = create_limit_order(OrderSide::Buy, 10);
+        assert_eq!(policy.fill_price(&buy, &BigDecimal::from(11)), None);
+        assert_eq!(
+            policy.fill_price(&buy, &BigDecimal::from(10)),
+            Some(BigDecimal::from_str("9.9")?)
+        );
+        let sell = create_limit_order(OrderSide::Sell, 10);
+        assert_eq!(
+            policy.fill_price(&sell, &BigDecimal::from(10)),
+            Some(BigDecimal::from_str("10.1")?)
+        );
+        let market_order = Order {
+            limit_price: None,
+            type_: OrderType::Market,
+            ..buy.clone()
+        };
+        assert_eq!(
+            policy.fill_price(&market_order, &BigDecimal::from(10)),
+            Some(BigDecimal::from(10))
+        );
+
+        let mut policy =
+            PriceImprovementFillPolicy::new(TouchFillPolicy, 0.5, BigDecimal::from(1))?;
+        policy.set_seed(3);
+        for _ in 0..100 {
+            let fill_price = policy.fill_price(&buy, &BigDecimal::from(10)).unwrap();
+            assert!(fill_price >= BigDecimal::from_str("9.9")? && fill_price <= 10);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn price_improvement_invalid_settings() {
+        let err =
+            PriceImprovementFillPolicy::new(TouchFillPolicy, 1.5, BigDecimal::from(1)).unwrap_err();
+        assert!(matches!(err, Error::InvalidArgument(_)));
+        assert_eq!(
+            err.to_string(),
+            "Price improvement probability must be between 0 and 1"
+        );
+        let err = PriceImprovementFillPolicy::new(TouchFillPolicy, 0.5, BigDecimal::from(-1))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Price improvement percentage must be between 0 and 100"
+        );
+    }
+
+    fn create_limit_order(side: OrderSide, limit_price: i32) -> Order {
+        Order {
+            order_id: OrderId::new("123"),
+            asset_symbol: "BTC/USD".into(),
+            amount: Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+            limit_price: Some(BigDecimal::from(limit_price)),
+            filled_quantity: BigDecimal::from(0),
+            average_fill_price: None,
+            status: OrderStatus::New,
+            type_: OrderType::Limit,
+            side,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
+        }
+    }
+}
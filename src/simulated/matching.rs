@@ -0,0 +1,222 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Amount, Instrument, Order};
+use crate::simulated::fill::{FillPolicy, TouchFillPolicy};
+use crate::simulated::seed::RngState;
+use bigdecimal::BigDecimal;
+use dyn_clone::DynClone;
+use std::fmt::{Debug, Formatter};
+
+/// Market conditions an order is matched against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketState {
+    pub crypto_pair: Instrument,
+    pub notional_per_unit: BigDecimal,
+}
+
+/// A single fill of an order, a fully filled order can be the result of several executions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Execution {
+    pub price: BigDecimal,
+    pub quantity: BigDecimal,
+}
+
+/// Produces the executions of a pending order of the simulated broker. Executions exceeding the
+/// remaining quantity of the order are capped by the broker, and orders are only considered
+/// filled once their remaining quantity reaches zero. On price updates, only the pending market
+/// orders and the limit orders whose limit price is crossed are matched.
+pub trait MatchingEngine: DynClone {
+    fn match_order(&self, order: &Order, market_state: &MarketState) -> Vec<Execution>;
+
+    /// Seeds the random number generator of engines matching orders at random, see
+    /// [FillPolicy::reseed]. Does nothing by default.
+    fn reseed(&mut self, _seed: u64) {}
+
+    /// State of the random number generator of engines matching orders at random, see
+    /// [FillPolicy::get_rng_state]. `None` by default.
+    fn get_rng_state(&self) -> Option<RngState> {
+        None
+    }
+
+    /// Restores a state returned by [MatchingEngine::get_rng_state]. Does nothing by default.
+    fn set_rng_state(&mut self, _rng_state: &RngState) {}
+}
+
+dyn_clone::clone_trait_object!(MatchingEngine);
+
+impl Debug for dyn MatchingEngine + Send + Sync {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MatchingEngine")
+    }
+}
+
+/// Fills the whole remaining quantity of an order in a single execution, whenever its
+/// [FillPolicy] allows it.
+#[derive(Clone, Debug)]
+pub struct FillPolicyMatchingEngine {
+    fill_policy: Box<dyn FillPolicy + Send + Sync>,
+}
+
+impl FillPolicyMatchingEngine {
+    pub fn new<F>(fill_policy: F) -> Self
+    where
+        F: FillPolicy + Send + Sync + 'static,
+    {
+        Self {
+            fill_policy: Box::new(fill_policy),
+        }
+    }
+}
+
+impl Default for FillPolicyMatchingEngine {
+    fn default() -> Self {
+        Self::new(TouchFillPolicy)
+    }
+}
+
+impl MatchingEngine for FillPolicyMatchingEngine {
+    fn match_order(&self, order: &Order, market_state: &MarketState) -> Vec<Execution> {
+        match self
+            .fill_policy
+            .fill_price(order, &market_state.notional_per_unit)
+        {
+            Some(price) => vec![Execution {
+                quantity: get_remaining_quantity(order, &price),
+                price,
+            }],
+            None => Vec::new(),
+        }
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.fill_policy.reseed(seed);
+    }
+
+    fn get_rng_state(&self) -> Option<RngState> {
+        self.fill_policy.get_rng_state()
+    }
+
+    fn set_rng_state(&mut self, rng_state: &RngState) {
+        self.fill_policy.set_rng_state(rng_state);
+    }
+}
+
+/// Quantity of the order still to be filled, priced at `notional_per_unit` for orders placed
+/// with a notional amount.
+pub fn get_remaining_quantity(order: &Order, notional_per_unit: &BigDecimal) -> BigDecimal {
+    match &order.amount {
+        Amount::Quantity { quantity } => quantity - &order.filled_quantity,
+        Amount::Notional { notional } => {
+            get_remaining_notional(order, notional) / notional_per_unit
+        }
+    }
+}
+
+pub(crate) fn get_remaining_notional(order: &Order, notional: &BigDecimal) -> BigDecimal {
+    match &order.average_fill_price {
+        Some(average_fill_price) => notional - &order.filled_quantity * average_fill_price,
+        None => notional.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{OrderId, OrderSide, OrderStatus, OrderType};
+    use crate::simulated::fill::ConservativeFillPolicy;
+    use anyhow::Result;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    #[test]
+    fn match_order_fills_remaining_quantity() -> Result<()> {
+        let engine = FillPolicyMatchingEngine::default();
+        let order = Order {
+            filled_quantity: BigDecimal::from(4),
+            average_fill_price: Some(BigDecimal::from(9)),
+            status: OrderStatus::PartiallyFilled,
+            ..create_order(Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            })
+        };
+
+        let executions = engine.match_order(&order, &create_market_state(8)?);
+
+        assert_eq!(
+            executions,
+            vec![Execution {
+                price: BigDecimal::from(8),
+                quantity: BigDecimal::from(6),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn match_order_fills_remaining_notional() -> Result<()> {
+        let engine = FillPolicyMatchingEngine::default();
+        let order = Order {
+            filled_quantity: BigDecimal::from(2),
+            average_fill_price: Some(BigDecimal::from(5)),
+            status: OrderStatus::PartiallyFilled,
+            ..create_order(Amount::Notional {
+                notional: BigDecimal::from(30),
+            })
+        };
+
+        let executions = engine.match_order(&order, &create_market_state(4)?);
+
+        assert_eq!(
+            executions,
+            vec![Execution {
+                price: BigDecimal::from(4),
+                quantity: BigDecimal::from(5),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn match_order_respects_fill_policy() -> Result<()> {
+        let engine = FillPolicyMatchingEngine::new(ConservativeFillPolicy);
+        let order = create_order(Amount::Quantity {
+            quantity: BigDecimal::from(10),
+        });
+
+        assert!(
+            engine
+                .match_order(&order, &create_market_state(9)?)
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    fn create_order(amount: Amount) -> Order {
+        Order {
+            order_id: OrderId::new("123"),
+            asset_symbol: "BTC/USD".into(),
+            amount,
+            limit_price: Some(BigDecimal::from(9)),
+            filled_quantity: BigDecimal::from(0),
+            average_fill_price: None,
+            status: OrderStatus::New,
+            type_: OrderType::Limit,
+            side: OrderSide::Buy,
+            display_quantity: None,
+            run_id: None,
+            strategy_id: None,
+            created_at: None,
+            tag: None,
+            metadata: HashMap::new(),
+            client_order_id: None,
+        }
+    }
+
+    fn create_market_state(notional_per_unit: i32) -> Result<MarketState> {
+        Ok(MarketState {
+            crypto_pair: Instrument::from_str("BTC/USD")?,
+            notional_per_unit: BigDecimal::from(notional_per_unit),
+        })
+    }
+}
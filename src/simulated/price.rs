@@ -0,0 +1,136 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::Bar;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use rand::Rng;
+
+/// How the simulated environment picks the notional per unit of a pair from its current [Bar].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum BarPriceModel {
+    /// Always the average of the low and high prices.
+    #[default]
+    Midpoint,
+    /// Any price between the low and high prices with the same probability.
+    Uniform,
+    /// Prices between the low and high prices, more likely towards the midpoint.
+    Triangular,
+    /// Prices between the low and high prices, more likely towards the close price.
+    CloseWeighted,
+}
+
+impl BarPriceModel {
+    pub fn sample<R>(&self, bar: &Bar, rng: &mut R) -> BigDecimal
+    where
+        R: Rng + ?Sized,
+    {
+        let range = &bar.high - &bar.low;
+        if *self == BarPriceModel::Midpoint || range <= 0 {
+            return (&bar.low + &bar.high) / 2;
+        }
+        let fraction = match self {
+            BarPriceModel::Uniform => rng.random::<f64>(),
+            BarPriceModel::Triangular => sample_triangular(rng, 0.5),
+            _ => {
+                let mode = ((&bar.close - &bar.low) / &range).to_f64().unwrap_or(0.5);
+                sample_triangular(rng, mode.clamp(0.0, 1.0))
+            }
+        };
+        // Rounded to keep the precision of the sampled prices bounded
+        let fraction = BigDecimal::try_from(fraction)
+            .unwrap_or(BigDecimal::from(0))
+            .round(6);
+        &bar.low + range * fraction
+    }
+}
+
+/// Samples a fraction between 0 and 1 from a triangular distribution peaking at `mode`.
+fn sample_triangular<R>(rng: &mut R, mode: f64) -> f64
+where
+    R: Rng + ?Sized,
+{
+    let uniform = rng.random::<f64>();
+    if uniform < mode {
+        (uniform * mode).sqrt()
+    } else {
+        1.0 - ((1.0 - uniform) * (1.0 - mode)).sqrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn midpoint() {
+        let bar = create_bar(10, 20, 12);
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            BarPriceModel::Midpoint.sample(&bar, &mut rng),
+            BigDecimal::from(15)
+        );
+    }
+
+    #[test]
+    fn sample_within_bar_range() {
+        let bar = create_bar(10, 20, 12);
+        let mut rng = StdRng::seed_from_u64(42);
+        for model in [
+            BarPriceModel::Uniform,
+            BarPriceModel::Triangular,
+            BarPriceModel::CloseWeighted,
+        ] {
+            for _ in 0..100 {
+                let price = model.sample(&bar, &mut rng);
+                assert!(price >= bar.low && price <= bar.high);
+            }
+        }
+    }
+
+    #[test]
+    fn sample_deterministic_with_seed() {
+        let bar = create_bar(10, 20, 12);
+        let mut first_rng = StdRng::seed_from_u64(7);
+        let mut second_rng = StdRng::seed_from_u64(7);
+        for _ in 0..10 {
+            assert_eq!(
+                BarPriceModel::Uniform.sample(&bar, &mut first_rng),
+                BarPriceModel::Uniform.sample(&bar, &mut second_rng)
+            );
+        }
+    }
+
+    #[test]
+    fn close_weighted_average_towards_close() {
+        let bar = create_bar(10, 20, 19);
+        let mut rng = StdRng::seed_from_u64(1);
+        let samples = 1000;
+        let sum: BigDecimal = (0..samples)
+            .map(|_| BarPriceModel::CloseWeighted.sample(&bar, &mut rng))
+            .sum();
+        // Mean of the triangular distribution is (low + high + close) / 3
+        let mean = sum / samples;
+        assert!(mean > 15 && mean < 17);
+    }
+
+    fn create_bar(low: i32, high: i32, close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(low),
+            close: BigDecimal::from(close),
+            date_time: DateTime::<Utc>::UNIX_EPOCH,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+}
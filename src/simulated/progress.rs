@@ -0,0 +1,136 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use anyhow::{Result, anyhow};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Published on every step of a tracked backtest, see
+/// [SimulatedEnvironment::track_progress](crate::simulated::SimulatedEnvironment::track_progress).
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestProgress {
+    pub percent_complete: f64,
+    pub date_time: DateTime<Utc>,
+    pub equity: BigDecimal,
+    /// Percentage of the peak equity lost since the peak.
+    pub drawdown_percentage: BigDecimal,
+}
+
+/// Asynchronous stream of the [BacktestProgress] of a backtest, which can also abort it.
+#[derive(Debug)]
+pub struct BacktestProgressStream {
+    receiver: UnboundedReceiver<BacktestProgress>,
+    aborted: Arc<AtomicBool>,
+}
+
+impl BacktestProgressStream {
+    /// Waits for the next progress, `None` once the backtest is dropped.
+    pub async fn next(&mut self) -> Option<BacktestProgress> {
+        self.receiver.recv().await
+    }
+
+    /// Returns the next progress if one was already published.
+    pub fn try_next(&mut self) -> Option<BacktestProgress> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Makes the next step of the backtest fail, stopping it early.
+    pub fn abort(&self) {
+        self.aborted.store(true, Ordering::Relaxed);
+    }
+}
+
+pub(crate) struct ProgressTracker {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    peak_equity: Option<BigDecimal>,
+    sender: UnboundedSender<BacktestProgress>,
+    aborted: Arc<AtomicBool>,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(Self, BacktestProgressStream)> {
+        if start >= end {
+            return Err(anyhow!("Backtest must start before it ends"));
+        }
+        let (sender, receiver) = unbounded_channel();
+        let aborted = Arc::new(AtomicBool::new(false));
+        let tracker = Self {
+            start,
+            end,
+            peak_equity: None,
+            sender,
+            aborted: aborted.clone(),
+        };
+        Ok((tracker, BacktestProgressStream { receiver, aborted }))
+    }
+
+    pub(crate) fn check_aborted(&self) -> Result<()> {
+        if self.aborted.load(Ordering::Relaxed) {
+            return Err(anyhow!("Backtest was aborted"));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn track(&mut self, date_time: DateTime<Utc>, equity: BigDecimal) {
+        let peak_equity = match &self.peak_equity {
+            Some(peak_equity) if *peak_equity > equity => peak_equity.clone(),
+            _ => equity.clone(),
+        };
+        let drawdown_percentage = if peak_equity > 0 {
+            (&peak_equity - &equity) * 100 / &peak_equity
+        } else {
+            BigDecimal::from(0)
+        };
+        let elapsed = (date_time - self.start).num_milliseconds() as f64;
+        let total = (self.end - self.start).num_milliseconds() as f64;
+        let percent_complete = (elapsed * 100.0 / total).clamp(0.0, 100.0);
+        self.peak_equity = Some(peak_equity);
+        // The stream may have been dropped, in which case progress is no longer needed
+        let _ = self.sender.send(BacktestProgress {
+            percent_complete,
+            date_time,
+            equity,
+            drawdown_percentage,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn track() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T00:00:00+00:00")?;
+        let (mut tracker, mut stream) = ProgressTracker::new(start, start + Duration::hours(4))?;
+
+        tracker.track(start + Duration::hours(1), BigDecimal::from(100));
+        tracker.track(start + Duration::hours(2), BigDecimal::from(80));
+
+        let progress = stream.next().await.unwrap();
+        assert_eq!(progress.percent_complete, 25.0);
+        assert_eq!(progress.drawdown_percentage, BigDecimal::from(0));
+        let progress = stream.next().await.unwrap();
+        assert_eq!(progress.percent_complete, 50.0);
+        assert_eq!(progress.equity, BigDecimal::from(80));
+        assert_eq!(progress.drawdown_percentage, BigDecimal::from(20));
+        assert!(stream.try_next().is_none());
+
+        tracker.check_aborted()?;
+        stream.abort();
+        assert_eq!(
+            tracker.check_aborted().unwrap_err().to_string(),
+            "Backtest was aborted"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Checks of the bars a backtest runs on, which would otherwise silently skew its results.
+
+use crate::api::common::{Bar, Instrument};
+use crate::simulated::data::BarDataSource;
+use crate::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::{Display, Formatter};
+
+/// A problem found with the bar at `index` of the checked bars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BarIssue {
+    OutOfOrder {
+        index: usize,
+        date_time: DateTime<Utc>,
+        previous_date_time: DateTime<Utc>,
+    },
+    Duplicate {
+        index: usize,
+        date_time: DateTime<Utc>,
+    },
+    HighBelowLow {
+        index: usize,
+        date_time: DateTime<Utc>,
+    },
+    /// The open or close is outside of the range between the low and the high.
+    PriceOutOfRange {
+        index: usize,
+        date_time: DateTime<Utc>,
+    },
+    Gap {
+        index: usize,
+        previous_date_time: DateTime<Utc>,
+        date_time: DateTime<Utc>,
+    },
+}
+
+impl Display for BarIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BarIssue::OutOfOrder {
+                date_time,
+                previous_date_time,
+                ..
+            } => write!(
+                f,
+                "Bar at {} comes after the bar at {}",
+                date_time, previous_date_time
+            ),
+            BarIssue::Duplicate { date_time, .. } => {
+                write!(f, "Bar at {} is duplicated", date_time)
+            }
+            BarIssue::HighBelowLow { date_time, .. } => {
+                write!(f, "Bar at {} has a high below its low", date_time)
+            }
+            BarIssue::PriceOutOfRange { date_time, .. } => write!(
+                f,
+                "Bar at {} opens or closes outside of its low and high",
+                date_time
+            ),
+            BarIssue::Gap {
+                previous_date_time,
+                date_time,
+                ..
+            } => write!(
+                f,
+                "No bars between {} and {}",
+                previous_date_time, date_time
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BarQualityReport {
+    pub bar_count: usize,
+    pub issues: Vec<BarIssue>,
+}
+
+impl BarQualityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BarValidator {
+    max_gap: Duration,
+}
+
+#[derive(Debug)]
+pub struct BarValidatorBuilder {
+    max_gap: Duration,
+}
+
+impl Default for BarValidatorBuilder {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::minutes(1),
+        }
+    }
+}
+
+impl BarValidatorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Longest time between the starts of consecutive bars not reported as a gap, one minute by
+    /// default.
+    pub fn set_max_gap(&mut self, max_gap: Duration) -> &mut Self {
+        self.max_gap = max_gap;
+        self
+    }
+
+    pub fn build(&self) -> Result<BarValidator> {
+        if self.max_gap <= Duration::zero() {
+            return Err(Error::InvalidArgument("Max gap must be positive".into()));
+        }
+        Ok(BarValidator {
+            max_gap: self.max_gap,
+        })
+    }
+}
+
+impl BarValidator {
+    pub fn get_max_gap(&self) -> Duration {
+        self.max_gap
+    }
+
+    /// Checks bars expected in chronological order.
+    pub fn validate(&self, bars: &[Bar]) -> BarQualityReport {
+        let mut issues = Vec::new();
+        for (index, bar) in bars.iter().enumerate() {
+            let date_time = bar.date_time;
+            if bar.high < bar.low {
+                issues.push(BarIssue::HighBelowLow { index, date_time });
+            } else if [&bar.open, &bar.close]
+                .into_iter()
+                .any(|price| *price < bar.low || *price > bar.high)
+            {
+                issues.push(BarIssue::PriceOutOfRange { index, date_time });
+            }
+            let Some(previous) = index.checked_sub(1).map(|previous| &bars[previous]) else {
+                continue;
+            };
+            let previous_date_time = previous.date_time;
+            if date_time < previous_date_time {
+                issues.push(BarIssue::OutOfOrder {
+                    index,
+                    date_time,
+                    previous_date_time,
+                });
+            } else if date_time == previous_date_time {
+                issues.push(BarIssue::Duplicate { index, date_time });
+            } else if date_time - previous_date_time > self.max_gap {
+                issues.push(BarIssue::Gap {
+                    index,
+                    previous_date_time,
+                    date_time,
+                });
+            }
+        }
+        BarQualityReport {
+            bar_count: bars.len(),
+            issues,
+        }
+    }
+
+    /// Checks the distinct bars `data_source` returns for `crypto_pair` at every `bar_duration`
+    /// within `start` inclusive and `end` exclusive.
+    pub fn validate_data_source(
+        &self,
+        data_source: &dyn BarDataSource,
+        crypto_pair: &Instrument,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        bar_duration: Duration,
+    ) -> Result<BarQualityReport> {
+        if bar_duration <= Duration::zero() {
+            return Err(Error::InvalidArgument(
+                "Bar duration must be positive".into(),
+            ));
+        }
+        let mut bars: Vec<Bar> = Vec::new();
+        let mut date_time = start;
+        while date_time < end {
+            // Sources may keep returning the latest bar until there is a new one
+            if let Some(bar) = data_source.get_bar(crypto_pair, &date_time, bar_duration)?
+                && bars.last() != Some(&bar)
+            {
+                bars.push(bar);
+            }
+            date_time += bar_duration;
+        }
+        Ok(self.validate(&bars))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulated::data::InMemoryBarDataSource;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use std::str::FromStr;
+
+    #[test]
+    fn validate_bars() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let minutes = |minutes: i64| start + Duration::minutes(minutes);
+        let bars = vec![
+            create_bar(minutes(0), 10, 12, 8, 11),
+            create_bar(minutes(1), 11, 8, 12, 11),
+            create_bar(minutes(1), 11, 12, 8, 13),
+            create_bar(minutes(0), 11, 12, 8, 11),
+            create_bar(minutes(5), 11, 12, 8, 11),
+        ];
+
+        let report = BarValidatorBuilder::new()
+            .set_max_gap(Duration::minutes(2))
+            .build()?
+            .validate(&bars);
+
+        assert_eq!(report.bar_count, 5);
+        assert_eq!(
+            report.issues,
+            vec![
+                BarIssue::HighBelowLow {
+                    index: 1,
+                    date_time: minutes(1)
+                },
+                BarIssue::PriceOutOfRange {
+                    index: 2,
+                    date_time: minutes(1)
+                },
+                BarIssue::Duplicate {
+                    index: 2,
+                    date_time: minutes(1)
+                },
+                BarIssue::OutOfOrder {
+                    index: 3,
+                    date_time: minutes(0),
+                    previous_date_time: minutes(1)
+                },
+                BarIssue::Gap {
+                    index: 4,
+                    previous_date_time: minutes(0),
+                    date_time: minutes(5)
+                },
+            ]
+        );
+        assert!(!report.is_clean());
+        Ok(())
+    }
+
+    #[test]
+    fn validate_data_source_gaps() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let mut data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::minutes(1),
+            [10, 11],
+        );
+        let validator = BarValidatorBuilder::new().build()?;
+
+        let report = validator.validate_data_source(
+            &data_source,
+            &crypto_pair,
+            start,
+            start + Duration::minutes(5),
+            Duration::minutes(1),
+        )?;
+        assert!(report.is_clean());
+        assert_eq!(report.bar_count, 2);
+
+        data_source = InMemoryBarDataSource::from_closes(
+            crypto_pair.clone(),
+            start,
+            Duration::minutes(3),
+            [10, 11],
+        );
+        let report = validator.validate_data_source(
+            &data_source,
+            &crypto_pair,
+            start,
+            start + Duration::minutes(5),
+            Duration::minutes(1),
+        )?;
+        assert_eq!(
+            report.issues,
+            vec![BarIssue::Gap {
+                index: 1,
+                previous_date_time: start,
+                date_time: start + Duration::minutes(3)
+            }]
+        );
+        Ok(())
+    }
+
+    fn create_bar(date_time: DateTime<Utc>, open: i32, high: i32, low: i32, close: i32) -> Bar {
+        Bar {
+            low: BigDecimal::from(low),
+            high: BigDecimal::from(high),
+            open: BigDecimal::from(open),
+            close: BigDecimal::from(close),
+            date_time,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+}
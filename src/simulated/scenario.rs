@@ -0,0 +1,369 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::api::common::{Instrument, Order, OrderId, OrderStatus};
+use crate::api::request::OrderRequest;
+use crate::simulated::broker::SimulatedBroker;
+use crate::simulated::environment::SimulatedEnvironment;
+use crate::simulated::time::{Clock, ManualClock};
+use anyhow::{Result, anyhow};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Step by step driver of a [SimulatedBroker], or of the broker of a [SimulatedEnvironment], for
+/// writing readable tests. Every step runs immediately, and expectations return an error
+/// describing the mismatch when they fail:
+///
+/// ```
+/// # use anyhow::Result;
+/// # use bigdecimal::BigDecimal;
+/// # use irontrade::api::common::{Amount, OrderStatus};
+/// # use irontrade::api::request::OrderRequest;
+/// # use irontrade::simulated::{Scenario, SimulatedBrokerBuilder};
+/// # fn main() -> Result<()> {
+/// let broker = SimulatedBrokerBuilder::new("USD")
+///     .set_balance(BigDecimal::from(20))
+///     .build()?;
+///
+/// Scenario::new(broker)
+///     .set_price("GBP/USD", "1.31")?
+///     .place_order(
+///         "buy",
+///         OrderRequest::limit_buy(
+///             "GBP/USD".parse()?,
+///             Amount::Quantity { quantity: BigDecimal::from(10) },
+///             "1.3".parse()?,
+///         ),
+///     )?
+///     .expect_order_status("buy", OrderStatus::New)?
+///     .expect_buying_power("USD", "7")?
+///     .set_price("GBP/USD", "1.29")?
+///     .expect_order_status("buy", OrderStatus::Filled)?
+///     .expect_balance("GBP", "10")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Scenario {
+    target: Target,
+    clock: ManualClock,
+    order_ids: HashMap<String, OrderId>,
+}
+
+/// What the steps of a [Scenario] act on.
+enum Target {
+    Broker(Box<SimulatedBroker>),
+    Environment(Box<SimulatedEnvironment>),
+}
+
+impl Scenario {
+    pub fn new(broker: SimulatedBroker) -> Self {
+        Self::new_at(broker, DateTime::UNIX_EPOCH)
+    }
+
+    /// Creates a scenario whose [ManualClock] starts at `start`, the clock is also handed to the
+    /// broker.
+    pub fn new_at(mut broker: SimulatedBroker, start: DateTime<Utc>) -> Self {
+        let clock = ManualClock::new(start);
+        broker.set_clock(clock.clone());
+        Self {
+            target: Target::Broker(Box::new(broker)),
+            clock,
+            order_ids: HashMap::new(),
+        }
+    }
+
+    /// Creates a scenario driving the broker of `environment`, which must read its time from
+    /// `clock` and not be initialized yet. Advancing the time refreshes the environment, setting
+    /// the prices of the bars in between.
+    pub fn with_environment(
+        mut environment: SimulatedEnvironment,
+        clock: ManualClock,
+    ) -> Result<Self> {
+        environment.init()?;
+        Ok(Self {
+            target: Target::Environment(Box::new(environment)),
+            clock,
+            order_ids: HashMap::new(),
+        })
+    }
+
+    pub fn set_price(&mut self, crypto_pair: &str, notional_per_unit: &str) -> Result<&mut Self> {
+        let crypto_pair = Instrument::from_str(crypto_pair)?;
+        let notional_per_unit = BigDecimal::from_str(notional_per_unit)?;
+        self.with_broker(|broker| {
+            broker.set_notional_value_per_unit(crypto_pair, notional_per_unit)
+        })?;
+        Ok(self)
+    }
+
+    pub fn advance_time(&mut self, duration: Duration) -> Result<&mut Self> {
+        self.clock.advance(duration);
+        if let Target::Environment(environment) = &self.target {
+            environment.update()?;
+        }
+        Ok(self)
+    }
+
+    /// Places the order and remembers its id under `label` for later expectations.
+    pub fn place_order(&mut self, label: &str, order_request: OrderRequest) -> Result<&mut Self> {
+        let order_id = self.submit_order(order_request)?;
+        self.order_ids.insert(label.into(), order_id);
+        Ok(self)
+    }
+
+    pub fn expect_rejection(
+        &mut self,
+        order_request: OrderRequest,
+        message: &str,
+    ) -> Result<&mut Self> {
+        match self.submit_order(order_request) {
+            Ok(order_id) => Err(anyhow!(
+                "Expected order to be rejected but it was placed with id {}",
+                order_id
+            )),
+            Err(err) if err.to_string() == message => Ok(self),
+            Err(err) => Err(anyhow!(
+                "Expected order to be rejected with \"{}\" but it was rejected with \"{}\"",
+                message,
+                err
+            )),
+        }
+    }
+
+    pub fn expect_order_status(&mut self, label: &str, status: OrderStatus) -> Result<&mut Self> {
+        let order = self.get_order(label)?;
+        if order.status != status {
+            return Err(anyhow!(
+                "Expected order {} to be {:?} but was {:?}",
+                label,
+                status,
+                order.status
+            ));
+        }
+        Ok(self)
+    }
+
+    pub fn expect_order<F>(&mut self, label: &str, predicate: F) -> Result<&mut Self>
+    where
+        F: FnOnce(&Order) -> bool,
+    {
+        let order = self.get_order(label)?;
+        if !predicate(&order) {
+            return Err(anyhow!("Order {} does not match: {:?}", label, order));
+        }
+        Ok(self)
+    }
+
+    pub fn expect_balance(&mut self, asset: &str, balance: &str) -> Result<&mut Self> {
+        let actual = self.read_broker(|broker| broker.get_balance(asset));
+        Self::expect_value("balance", asset, balance, actual)?;
+        Ok(self)
+    }
+
+    pub fn expect_buying_power(&mut self, asset: &str, buying_power: &str) -> Result<&mut Self> {
+        let actual = self.read_broker(|broker| broker.get_buying_power(asset));
+        Self::expect_value("buying power", asset, buying_power, actual)?;
+        Ok(self)
+    }
+
+    pub fn get_order(&self, label: &str) -> Result<Order> {
+        let order_id = self
+            .order_ids
+            .get(label)
+            .ok_or(anyhow!("No order placed with label {}", label))?;
+        Ok(self.read_broker(|broker| broker.get_order(order_id))?)
+    }
+
+    /// Runs `f` on the driven broker, e.g. to check what the expectations don't cover.
+    pub fn read_broker<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&SimulatedBroker) -> R,
+    {
+        match &self.target {
+            Target::Broker(broker) => f(broker),
+            Target::Environment(environment) => environment.client().read_broker(f),
+        }
+    }
+
+    /// Runs `f` on the driven broker, e.g. to change a setting in the middle of a scenario.
+    pub fn with_broker<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut SimulatedBroker) -> R,
+    {
+        match &mut self.target {
+            Target::Broker(broker) => f(broker),
+            Target::Environment(environment) => environment.client().with_broker(f),
+        }
+    }
+
+    /// The scenario's clock, to be shared with the components that need to follow its time.
+    pub fn clock(&self) -> &ManualClock {
+        &self.clock
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    fn submit_order(&mut self, order_request: OrderRequest) -> crate::Result<OrderId> {
+        match &mut self.target {
+            Target::Broker(broker) => broker.place_order(order_request),
+            Target::Environment(environment) => {
+                let order_request = environment.with_run_ids(order_request);
+                environment
+                    .client()
+                    .with_broker(|broker| broker.place_order(order_request))
+            }
+        }
+    }
+
+    fn expect_value(name: &str, asset: &str, expected: &str, actual: BigDecimal) -> Result<()> {
+        if actual != BigDecimal::from_str(expected)? {
+            return Err(anyhow!(
+                "Expected {} {} to be {} but was {}",
+                asset,
+                name,
+                expected,
+                actual
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, Bar};
+    use crate::simulated::broker::SimulatedBrokerBuilder;
+    use crate::simulated::client::SimulatedClient;
+    use crate::simulated::context::SimulatedContext;
+    use crate::simulated::data::InMemoryBarDataSourceBuilder;
+    use crate::simulated::environment::SimulatedEnvironmentBuilder;
+    use std::collections::HashSet;
+
+    #[test]
+    fn limit_buy_order_scenario() -> Result<()> {
+        Scenario::new(create_broker()?)
+            .set_price("GBP/USD", "1.31")?
+            .place_order("buy", create_limit_buy("1.3")?)?
+            .expect_order_status("buy", OrderStatus::New)?
+            .expect_balance("USD", "14.1")?
+            .expect_buying_power("USD", "1.1")?
+            .set_price("GBP/USD", "1.29")?
+            .expect_order_status("buy", OrderStatus::Filled)?
+            .expect_order("buy", |order| {
+                order.average_fill_price == Some(BigDecimal::from_str("1.29").unwrap())
+            })?
+            .expect_balance("USD", "1.2")?
+            .expect_balance("GBP", "10")?;
+        Ok(())
+    }
+
+    #[test]
+    fn failed_expectation() -> Result<()> {
+        let mut scenario = Scenario::new(create_broker()?);
+        let err = scenario
+            .set_price("GBP/USD", "1.31")?
+            .place_order("buy", create_limit_buy("1.3")?)?
+            .expect_order_status("buy", OrderStatus::Filled)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Expected order buy to be Filled but was New"
+        );
+
+        let err = scenario.expect_balance("USD", "1").err().unwrap();
+        assert_eq!(err.to_string(), "Expected USD balance to be 1 but was 14.1");
+        Ok(())
+    }
+
+    #[test]
+    fn expect_rejection() -> Result<()> {
+        Scenario::new(create_broker()?)
+            .set_price("GBP/USD", "1.31")?
+            .expect_rejection(create_limit_buy("2")?, "Not enough USD buying power")?;
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_label() -> Result<()> {
+        let err = Scenario::new(create_broker()?)
+            .expect_order_status("sell", OrderStatus::New)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "No order placed with label sell");
+        Ok(())
+    }
+
+    #[test]
+    fn advance_time() -> Result<()> {
+        let mut scenario = Scenario::new(SimulatedBrokerBuilder::new("USD").build()?);
+        scenario.advance_time(Duration::minutes(1))?;
+        assert_eq!(scenario.now(), DateTime::UNIX_EPOCH + Duration::minutes(1));
+        Ok(())
+    }
+
+    #[test]
+    fn advance_time_refreshes_environment() -> Result<()> {
+        let crypto_pair = Instrument::from_str("GBP/USD")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let data_source = InMemoryBarDataSourceBuilder::new()
+            .add_bars(
+                crypto_pair.clone(),
+                vec![
+                    create_bar("1.31", start),
+                    create_bar("1.29", start + Duration::minutes(1)),
+                ],
+            )
+            .build();
+        let clock = ManualClock::new(start);
+        let environment = SimulatedEnvironmentBuilder::new(
+            SimulatedContext::new(data_source, clock.clone()),
+            SimulatedClient::new(create_broker()?),
+        )
+        .set_crypto_pairs_to_trade(HashSet::from([crypto_pair]))
+        .build()?;
+
+        Scenario::with_environment(environment, clock)?
+            .place_order("buy", create_limit_buy("1.3")?)?
+            .expect_order_status("buy", OrderStatus::New)?
+            .advance_time(Duration::minutes(1))?
+            .expect_order_status("buy", OrderStatus::Filled)?
+            .expect_balance("GBP", "10")?;
+        Ok(())
+    }
+
+    fn create_broker() -> Result<SimulatedBroker> {
+        Ok(SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from_str("14.1")?)
+            .build()?)
+    }
+
+    fn create_bar(price: &str, date_time: DateTime<Utc>) -> Bar {
+        let price = BigDecimal::from_str(price).unwrap();
+        Bar {
+            low: price.clone(),
+            high: price.clone(),
+            open: price.clone(),
+            close: price,
+            date_time,
+            volume: BigDecimal::from(0),
+            trade_count: 0,
+            vwap: None,
+        }
+    }
+
+    fn create_limit_buy(limit_price: &str) -> Result<OrderRequest> {
+        Ok(OrderRequest::limit_buy(
+            Instrument::from_str("GBP/USD")?,
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+            BigDecimal::from_str(limit_price)?,
+        ))
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::simulated::fill::PriceImprovementFillPolicy;
+use crate::simulated::synthetic::SyntheticBarsBuilder;
+use crate::simulated::{SimulatedBrokerBuilder, SimulatedEnvironmentBuilder};
+use crate::{Error, Result};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand_chacha::ChaCha12Rng;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Randomness of a simulation, every random number generator being seeded from one master seed so
+/// that a whole backtest can be reproduced from that number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationConfig {
+    master_seed: u64,
+}
+
+/// Source of randomness of a simulation. Each source gets its own seed, so that drawing more
+/// numbers from one doesn't change what the others draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RandomSource {
+    /// Prices sampled from bars by the [BarPriceModel](crate::simulated::price::BarPriceModel).
+    BarPrices,
+    /// Price improvements of the [PriceImprovementFillPolicy].
+    Fills,
+    /// Bars generated by the [SyntheticBarsBuilder].
+    SyntheticBars,
+    /// Order and trade ids of the broker.
+    Ids,
+}
+
+/// Key of a random number generator and its position in the stream of numbers, from which a
+/// simulation resumed from a checkpoint draws the numbers it would have drawn uninterrupted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RngState {
+    seed: [u8; 32],
+    word_pos: u128,
+}
+
+impl SimulationConfig {
+    pub fn new(master_seed: u64) -> Self {
+        Self { master_seed }
+    }
+
+    pub fn get_master_seed(&self) -> u64 {
+        self.master_seed
+    }
+
+    /// Seed of `source`, the same for the same master seed across runs and platforms.
+    pub fn get_seed(&self, source: RandomSource) -> u64 {
+        let source_index = match source {
+            RandomSource::BarPrices => 1,
+            RandomSource::Fills => 2,
+            RandomSource::SyntheticBars => 3,
+            RandomSource::Ids => 4,
+        };
+        mix(self.master_seed ^ mix(source_index))
+    }
+
+    pub fn create_rng(&self, source: RandomSource) -> StdRng {
+        StdRng::seed_from_u64(self.get_seed(source))
+    }
+
+    pub fn seed_environment(&self, builder: &mut SimulatedEnvironmentBuilder) {
+        builder.set_seed(self.get_seed(RandomSource::BarPrices));
+    }
+
+    /// Seeds the ids of the broker and its fill policy.
+    pub fn seed_broker(&self, builder: &mut SimulatedBrokerBuilder) {
+        builder
+            .set_id_seed(self.get_seed(RandomSource::Ids))
+            .set_fill_seed(self.get_seed(RandomSource::Fills));
+    }
+
+    pub fn seed_fill_policy(&self, fill_policy: &mut PriceImprovementFillPolicy) {
+        fill_policy.set_seed(self.get_seed(RandomSource::Fills));
+    }
+
+    pub fn seed_synthetic_bars(&self, builder: &mut SyntheticBarsBuilder) {
+        builder.set_seed(self.get_seed(RandomSource::SyntheticBars));
+    }
+}
+
+impl RngState {
+    pub(crate) fn of(rng: &ChaCha12Rng) -> Self {
+        Self {
+            seed: rng.get_seed(),
+            word_pos: rng.get_word_pos(),
+        }
+    }
+
+    pub(crate) fn to_rng(&self) -> ChaCha12Rng {
+        let mut rng = ChaCha12Rng::from_seed(self.seed);
+        rng.set_word_pos(self.word_pos);
+        rng
+    }
+}
+
+/// Formats the key in hexadecimal followed by the position, e.g. `00ff…1a:42`.
+impl Display for RngState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in self.seed {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ":{}", self.word_pos)
+    }
+}
+
+impl FromStr for RngState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid =
+            || Error::InvalidArgument(format!("Invalid random number generator state {}", s));
+        let (seed_hex, word_pos) = s.split_once(':').ok_or_else(invalid)?;
+        if seed_hex.len() != 64 || !seed_hex.is_ascii() {
+            return Err(invalid());
+        }
+        let mut seed = [0; 32];
+        for (i, byte) in seed.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&seed_hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Self {
+            seed,
+            word_pos: word_pos.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Finalizer of SplitMix64, spreading close inputs to unrelated outputs.
+fn mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Client;
+    use crate::api::common::{Amount, Instrument};
+    use crate::api::request::OrderRequest;
+    use crate::simulated::SimulatedClient;
+    use anyhow::Result;
+    use bigdecimal::BigDecimal;
+    use rand::Rng;
+
+    #[test]
+    fn sources_have_distinct_stable_seeds() {
+        let config = SimulationConfig::new(42);
+        let sources = [
+            RandomSource::BarPrices,
+            RandomSource::Fills,
+            RandomSource::SyntheticBars,
+            RandomSource::Ids,
+        ];
+        for (i, source) in sources.iter().enumerate() {
+            assert_eq!(
+                config.get_seed(*source),
+                SimulationConfig::new(42).get_seed(*source)
+            );
+            assert_ne!(
+                config.get_seed(*source),
+                SimulationConfig::new(43).get_seed(*source)
+            );
+            for other in &sources[i + 1..] {
+                assert_ne!(config.get_seed(*source), config.get_seed(*other));
+            }
+        }
+    }
+
+    #[test]
+    fn resume_rng_from_state() -> Result<()> {
+        let mut rng = ChaCha12Rng::seed_from_u64(42);
+        rng.random::<u64>();
+        let rng_state = RngState::of(&rng);
+
+        let mut resumed_rng = RngState::from_str(&rng_state.to_string())?.to_rng();
+
+        assert_eq!(resumed_rng.random::<u64>(), rng.random::<u64>());
+        assert!(RngState::from_str("00:1").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn seeded_brokers_create_the_same_ids() -> Result<()> {
+        let (order_id, trade_id) = place_order(7).await?;
+
+        assert_eq!(place_order(7).await?, (order_id.clone(), trade_id.clone()));
+        assert_ne!(place_order(8).await?.0, order_id);
+        assert_ne!(order_id, trade_id);
+        Ok(())
+    }
+
+    async fn place_order(master_seed: u64) -> Result<(String, String)> {
+        let mut builder = SimulatedBrokerBuilder::new("USD");
+        builder.set_balance(BigDecimal::from(100));
+        SimulationConfig::new(master_seed).seed_broker(&mut builder);
+        let client = SimulatedClient::new(builder.build()?);
+        let crypto_pair = Instrument::from_str("BTC/USD")?;
+        client.set_notional_per_unit(crypto_pair.clone(), BigDecimal::from(10))?;
+        let order_id = client
+            .place_order(OrderRequest::market_buy(
+                crypto_pair,
+                Amount::Quantity {
+                    quantity: BigDecimal::from(1),
+                },
+            ))
+            .await?;
+        let trade_id = client.get_order_fills(&order_id).await?[0].trade_id.clone();
+        Ok((order_id.to_string(), trade_id))
+    }
+}
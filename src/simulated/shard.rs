@@ -0,0 +1,276 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::simulated::broker::SimulatedBroker;
+use anyhow::{Result, anyhow};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Splits a backtest from `start` to `end` into contiguous time shards.
+///
+/// When exact, shards run one after the other, each one starting from the broker the previous
+/// shard ended with. Otherwise all shards run in parallel from the initial broker and their
+/// balance changes are added up, which is only accurate for strategies that don't depend on the
+/// positions held at the start of a shard.
+#[derive(Debug, Clone)]
+pub struct ShardedBacktest {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    shard_count: u32,
+    exact: bool,
+}
+
+#[derive(Debug)]
+pub struct ShardedBacktestBuilder {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    shard_count: u32,
+    exact: bool,
+}
+
+/// A shard of a [ShardedBacktest] to be run from `start` until `end` with `broker`.
+#[derive(Debug, Clone)]
+pub struct BacktestShard {
+    pub index: u32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub broker: SimulatedBroker,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShardedBacktestResult {
+    /// Brokers each shard ended with, in time order.
+    pub brokers: Vec<SimulatedBroker>,
+    /// Final balances per asset, stitched together from every shard.
+    pub balances: HashMap<String, BigDecimal>,
+}
+
+impl ShardedBacktestBuilder {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            start,
+            end,
+            shard_count: 1,
+            exact: true,
+        }
+    }
+
+    pub fn set_shard_count(&mut self, shard_count: u32) -> Result<&mut Self> {
+        if shard_count == 0 {
+            return Err(anyhow!("Shard count must be at least 1"));
+        }
+        self.shard_count = shard_count;
+        Ok(self)
+    }
+
+    /// Disabling exactness runs the shards in parallel, see [ShardedBacktest].
+    pub fn set_exact(&mut self, exact: bool) -> &mut Self {
+        self.exact = exact;
+        self
+    }
+
+    pub fn build(&self) -> Result<ShardedBacktest> {
+        if self.start >= self.end {
+            return Err(anyhow!("Backtest must start before it ends"));
+        }
+        Ok(ShardedBacktest {
+            start: self.start,
+            end: self.end,
+            shard_count: self.shard_count,
+            exact: self.exact,
+        })
+    }
+}
+
+impl ShardedBacktest {
+    /// Start and end times of every shard, the last shard ending exactly at the backtest end.
+    pub fn get_shard_times(&self) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let shard_duration = (self.end - self.start) / self.shard_count as i32;
+        (0..self.shard_count)
+            .map(|index| {
+                let start = self.start + shard_duration * index as i32;
+                let end = if index + 1 == self.shard_count {
+                    self.end
+                } else {
+                    start + shard_duration
+                };
+                (start, end)
+            })
+            .collect()
+    }
+
+    /// Runs every shard with `run_shard`, which must simulate the shard's time range with its
+    /// broker and return the broker it ended with.
+    pub async fn run<F, Fut>(
+        &self,
+        broker: SimulatedBroker,
+        run_shard: F,
+    ) -> Result<ShardedBacktestResult>
+    where
+        F: Fn(BacktestShard) -> Fut,
+        Fut: Future<Output = Result<SimulatedBroker>> + Send + 'static,
+    {
+        let mut brokers = Vec::new();
+        if self.exact {
+            let mut shard_broker = broker.clone();
+            for (index, (start, end)) in self.get_shard_times().into_iter().enumerate() {
+                shard_broker = run_shard(BacktestShard {
+                    index: index as u32,
+                    start,
+                    end,
+                    broker: shard_broker,
+                })
+                .await?;
+                brokers.push(shard_broker.clone());
+            }
+        } else {
+            let mut handles = Vec::new();
+            for (index, (start, end)) in self.get_shard_times().into_iter().enumerate() {
+                handles.push(tokio::spawn(run_shard(BacktestShard {
+                    index: index as u32,
+                    start,
+                    end,
+                    broker: broker.clone(),
+                })));
+            }
+            for handle in handles {
+                brokers.push(handle.await??);
+            }
+        }
+
+        let balances = if self.exact {
+            get_balances(brokers.last().unwrap())
+        } else {
+            stitch_balances(&broker, &brokers)
+        };
+        Ok(ShardedBacktestResult { brokers, balances })
+    }
+}
+
+fn get_balances(broker: &SimulatedBroker) -> HashMap<String, BigDecimal> {
+    let mut assets = broker.get_purchased_asset_symbols();
+    assets.insert(broker.get_currency());
+    assets
+        .into_iter()
+        .map(|asset| {
+            let balance = broker.get_balance(&asset);
+            (asset, balance)
+        })
+        .collect()
+}
+
+/// Adds the balance changes of every shard to the initial balances.
+fn stitch_balances(
+    initial_broker: &SimulatedBroker,
+    brokers: &[SimulatedBroker],
+) -> HashMap<String, BigDecimal> {
+    let initial_balances = get_balances(initial_broker);
+    let mut balances = initial_balances.clone();
+    for broker in brokers {
+        for (asset, balance) in get_balances(broker) {
+            let initial_balance = initial_balances
+                .get(&asset)
+                .cloned()
+                .unwrap_or(BigDecimal::from(0));
+            *balances.entry(asset).or_insert(BigDecimal::from(0)) += balance - initial_balance;
+        }
+    }
+    balances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, Instrument};
+    use crate::api::request::OrderRequest;
+    use crate::simulated::SimulatedBrokerBuilder;
+    use chrono::Duration;
+    use std::str::FromStr;
+
+    #[test]
+    fn get_shard_times() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T00:00:00+00:00")?;
+        let backtest = ShardedBacktestBuilder::new(start, start + Duration::hours(10))
+            .set_shard_count(3)?
+            .build()?;
+
+        let shard_times = backtest.get_shard_times();
+
+        assert_eq!(shard_times.len(), 3);
+        assert_eq!(shard_times[0].0, start);
+        assert_eq!(shard_times[1].0, shard_times[0].1);
+        assert_eq!(shard_times[2].0, shard_times[1].1);
+        assert_eq!(shard_times[2].1, start + Duration::hours(10));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn run_exact_shards_sequentially() -> Result<()> {
+        let backtest = create_backtest(true)?;
+
+        let result = backtest.run(create_broker()?, buy_one_gbp).await?;
+
+        assert_eq!(result.brokers.len(), 4);
+        assert_eq!(result.balances["GBP"], BigDecimal::from(4));
+        assert_eq!(result.balances["USD"], BigDecimal::from(96));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn run_shards_in_parallel() -> Result<()> {
+        let backtest = create_backtest(false)?;
+
+        let result = backtest.run(create_broker()?, buy_one_gbp).await?;
+
+        // Every shard started from the initial broker
+        for broker in &result.brokers {
+            assert_eq!(broker.get_balance("GBP"), BigDecimal::from(1));
+        }
+        assert_eq!(result.balances["GBP"], BigDecimal::from(4));
+        assert_eq!(result.balances["USD"], BigDecimal::from(96));
+        Ok(())
+    }
+
+    #[test]
+    fn build_invalid_backtest() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T00:00:00+00:00")?;
+        let err = ShardedBacktestBuilder::new(start, start)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Backtest must start before it ends");
+
+        let err = ShardedBacktestBuilder::new(start, start + Duration::hours(1))
+            .set_shard_count(0)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Shard count must be at least 1");
+        Ok(())
+    }
+
+    fn create_backtest(exact: bool) -> Result<ShardedBacktest> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T00:00:00+00:00")?;
+        ShardedBacktestBuilder::new(start, start + Duration::days(1))
+            .set_shard_count(4)?
+            .set_exact(exact)
+            .build()
+    }
+
+    fn create_broker() -> Result<SimulatedBroker> {
+        Ok(SimulatedBrokerBuilder::new("USD")
+            .set_balance(BigDecimal::from(100))
+            .build()?)
+    }
+
+    async fn buy_one_gbp(shard: BacktestShard) -> Result<SimulatedBroker> {
+        let mut broker = shard.broker;
+        let crypto_pair = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(crypto_pair.clone(), BigDecimal::from(1))?;
+        broker.place_order(OrderRequest::market_buy(
+            crypto_pair,
+            Amount::Quantity {
+                quantity: BigDecimal::from(1),
+            },
+        ))?;
+        Ok(broker)
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+use crate::simulated::broker::{SimulatedBroker, SimulatedBrokerBuilder};
+use anyhow::{Result, anyhow};
+use bigdecimal::BigDecimal;
+
+/// Reruns a backtest for every combination of fee and slippage percentages, to quantify how
+/// robust a strategy is to execution costs.
+#[derive(Debug, Clone)]
+pub struct SensitivitySweep {
+    fee_percentages: Vec<BigDecimal>,
+    slippage_percentages: Vec<BigDecimal>,
+}
+
+#[derive(Debug)]
+pub struct SensitivitySweepBuilder {
+    fee_percentages: Vec<BigDecimal>,
+    slippage_percentages: Vec<BigDecimal>,
+}
+
+/// Metrics of a backtest run with a given fee and slippage.
+#[derive(Debug, Clone)]
+pub struct SensitivityResult {
+    pub fee_percentage: BigDecimal,
+    pub slippage_percentage: BigDecimal,
+    pub final_equity: BigDecimal,
+    /// Percentage of the initial equity gained, `None` when the initial equity is zero.
+    pub return_percentage: Option<BigDecimal>,
+    /// Final equity lost compared to the run with the first fee and slippage percentages.
+    pub equity_degradation: BigDecimal,
+    pub broker: SimulatedBroker,
+}
+
+impl SensitivitySweepBuilder {
+    pub fn new() -> Self {
+        Self {
+            fee_percentages: vec![BigDecimal::from(0)],
+            slippage_percentages: vec![BigDecimal::from(0)],
+        }
+    }
+
+    pub fn set_fee_percentages(&mut self, fee_percentages: Vec<BigDecimal>) -> Result<&mut Self> {
+        check_percentages(&fee_percentages)?;
+        self.fee_percentages = fee_percentages;
+        Ok(self)
+    }
+
+    pub fn set_slippage_percentages(
+        &mut self,
+        slippage_percentages: Vec<BigDecimal>,
+    ) -> Result<&mut Self> {
+        check_percentages(&slippage_percentages)?;
+        self.slippage_percentages = slippage_percentages;
+        Ok(self)
+    }
+
+    pub fn build(&self) -> Result<SensitivitySweep> {
+        Ok(SensitivitySweep {
+            fee_percentages: self.fee_percentages.clone(),
+            slippage_percentages: self.slippage_percentages.clone(),
+        })
+    }
+}
+
+impl Default for SensitivitySweepBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check_percentages(percentages: &[BigDecimal]) -> Result<()> {
+    if percentages.is_empty() {
+        return Err(anyhow!("Sweep must have at least one percentage"));
+    }
+    Ok(())
+}
+
+impl SensitivitySweep {
+    /// Runs the backtest with a broker built by `broker_builder` for every fee and slippage
+    /// combination, in the order of the fee percentages then the slippage percentages.
+    /// `run_backtest` must simulate the backtest with the broker and return the broker it ended
+    /// with.
+    pub async fn run<F, Fut>(
+        &self,
+        broker_builder: &SimulatedBrokerBuilder,
+        run_backtest: F,
+    ) -> Result<Vec<SensitivityResult>>
+    where
+        F: Fn(SimulatedBroker) -> Fut,
+        Fut: Future<Output = Result<SimulatedBroker>>,
+    {
+        let mut results: Vec<SensitivityResult> = Vec::new();
+        for fee_percentage in &self.fee_percentages {
+            for slippage_percentage in &self.slippage_percentages {
+                let broker = broker_builder
+                    .clone()
+                    .set_fee_percentage_up_to_one_hundred(fee_percentage.clone())?
+                    .set_slippage_percentage(slippage_percentage.clone())?
+                    .build()?;
+                let initial_equity = broker.get_equity();
+                let broker = run_backtest(broker).await?;
+                let final_equity = broker.get_equity();
+                let return_percentage = if initial_equity == 0 {
+                    None
+                } else {
+                    Some((&final_equity - &initial_equity) * 100 / &initial_equity)
+                };
+                let equity_degradation = match results.first() {
+                    Some(baseline) => &baseline.final_equity - &final_equity,
+                    None => BigDecimal::from(0),
+                };
+                results.push(SensitivityResult {
+                    fee_percentage: fee_percentage.clone(),
+                    slippage_percentage: slippage_percentage.clone(),
+                    final_equity,
+                    return_percentage,
+                    equity_degradation,
+                    broker,
+                });
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::common::{Amount, Instrument};
+    use crate::api::request::OrderRequest;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn run_sweep() -> Result<()> {
+        let sweep = SensitivitySweepBuilder::new()
+            .set_fee_percentages(vec![BigDecimal::from(0), BigDecimal::from(1)])?
+            .set_slippage_percentages(vec![BigDecimal::from(0), BigDecimal::from(5)])?
+            .build()?;
+        let mut broker_builder = SimulatedBrokerBuilder::new("USD");
+        broker_builder.set_balance(BigDecimal::from(100));
+
+        let results = sweep.run(&broker_builder, buy_and_sell_gbp).await?;
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0].final_equity, BigDecimal::from(110));
+        assert_eq!(results[0].return_percentage, Some(BigDecimal::from(10)));
+        assert_eq!(results[0].equity_degradation, BigDecimal::from(0));
+        assert_eq!(results[1].slippage_percentage, BigDecimal::from(5));
+        // Bought 10 at 1.05 and sold at 1.9
+        assert_eq!(results[1].final_equity, BigDecimal::from_str("108.5")?);
+        assert_eq!(results[1].equity_degradation, BigDecimal::from_str("1.5")?);
+        for result in &results[1..] {
+            assert!(result.final_equity < results[0].final_equity);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn build_without_percentages() {
+        let err = SensitivitySweepBuilder::new()
+            .set_fee_percentages(Vec::new())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Sweep must have at least one percentage");
+    }
+
+    async fn buy_and_sell_gbp(mut broker: SimulatedBroker) -> Result<SimulatedBroker> {
+        let crypto_pair = Instrument::from_str("GBP/USD")?;
+        broker.set_notional_value_per_unit(crypto_pair.clone(), BigDecimal::from(1))?;
+        broker.place_order(OrderRequest::market_buy(
+            crypto_pair.clone(),
+            Amount::Quantity {
+                quantity: BigDecimal::from(10),
+            },
+        ))?;
+        broker.set_notional_value_per_unit(crypto_pair.clone(), BigDecimal::from(2))?;
+        let quantity = broker.get_balance("GBP");
+        broker.place_order(OrderRequest::market_sell(
+            crypto_pair,
+            Amount::Quantity { quantity },
+        ))?;
+        Ok(broker)
+    }
+}
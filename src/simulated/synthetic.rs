@@ -0,0 +1,305 @@
+// Copyright (C) 2025 Agostinho Junior
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+//! Deterministic synthetic bars, to test strategies across controlled market scenarios without
+//! real data.
+
+use crate::api::common::{Bar, Instrument};
+use crate::simulated::data::{InMemoryBarDataSource, InMemoryBarDataSourceBuilder};
+use crate::{Error, Result};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Duration, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+
+/// Price steps simulated within each bar, to give bars a high and low apart from their open and
+/// close.
+const STEPS_PER_BAR: u32 = 10;
+
+/// How prices move over time. Drifts and volatilities are per bar.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntheticModel {
+    /// Prices move by normally distributed amounts with a standard deviation of `step_deviation`
+    /// per bar, floored at zero.
+    RandomWalk { step_deviation: f64 },
+    /// Log returns are normally distributed, so prices stay positive.
+    GeometricBrownianMotion { drift: f64, volatility: f64 },
+    /// Geometric Brownian motion starting with the first regime and switching to another one at
+    /// random with `switch_probability` every bar.
+    RegimeSwitching {
+        regimes: Vec<Regime>,
+        switch_probability: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Regime {
+    pub drift: f64,
+    pub volatility: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyntheticBarsBuilder {
+    model: SyntheticModel,
+    start: DateTime<Utc>,
+    bar_count: usize,
+    bar_duration: Duration,
+    seed: u64,
+    initial_prices: Vec<(Instrument, BigDecimal)>,
+}
+
+impl SyntheticBarsBuilder {
+    pub fn new(model: SyntheticModel, start: DateTime<Utc>, bar_count: usize) -> Self {
+        Self {
+            model,
+            start,
+            bar_count,
+            bar_duration: Duration::minutes(1),
+            seed: 0,
+            initial_prices: Vec::new(),
+        }
+    }
+
+    /// One minute by default.
+    pub fn set_bar_duration(&mut self, bar_duration: Duration) -> &mut Self {
+        self.bar_duration = bar_duration;
+        self
+    }
+
+    /// Bars built with the same seed and settings are the same.
+    pub fn set_seed(&mut self, seed: u64) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Adds a pair whose first bar opens at `initial_price`.
+    pub fn add_pair(&mut self, crypto_pair: Instrument, initial_price: BigDecimal) -> &mut Self {
+        self.initial_prices.push((crypto_pair, initial_price));
+        self
+    }
+
+    pub fn build(&self) -> Result<InMemoryBarDataSource> {
+        self.check()?;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut builder = InMemoryBarDataSourceBuilder::new();
+        for (crypto_pair, initial_price) in &self.initial_prices {
+            let bars = self.generate_bars(initial_price, &mut rng)?;
+            builder.add_bars(crypto_pair.clone(), bars);
+        }
+        Ok(builder.build())
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.bar_duration <= Duration::zero() {
+            return Err(Error::InvalidArgument(
+                "Bar duration must be positive".into(),
+            ));
+        }
+        if self
+            .initial_prices
+            .iter()
+            .any(|(_, initial_price)| *initial_price <= 0)
+        {
+            return Err(Error::InvalidArgument(
+                "Initial prices must be positive".into(),
+            ));
+        }
+        let volatilities = match &self.model {
+            SyntheticModel::RandomWalk { step_deviation } => vec![*step_deviation],
+            SyntheticModel::GeometricBrownianMotion { volatility, .. } => vec![*volatility],
+            SyntheticModel::RegimeSwitching {
+                regimes,
+                switch_probability,
+            } => {
+                if regimes.is_empty() {
+                    return Err(Error::InvalidArgument(
+                        "At least one regime is needed".into(),
+                    ));
+                }
+                if !(0.0..=1.0).contains(switch_probability) {
+                    return Err(Error::InvalidArgument(
+                        "Switch probability must be between 0 and 1".into(),
+                    ));
+                }
+                regimes.iter().map(|regime| regime.volatility).collect()
+            }
+        };
+        if volatilities.iter().any(|volatility| *volatility < 0.0) {
+            return Err(Error::InvalidArgument(
+                "Volatility must not be negative".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn generate_bars(&self, initial_price: &BigDecimal, rng: &mut StdRng) -> Result<Vec<Bar>> {
+        let mut price = initial_price.to_f64().unwrap_or_default();
+        let mut regime = 0;
+        let mut bars = Vec::with_capacity(self.bar_count);
+        let mut date_time = self.start;
+        for _ in 0..self.bar_count {
+            if let SyntheticModel::RegimeSwitching {
+                regimes,
+                switch_probability,
+            } = &self.model
+                && regimes.len() > 1
+                && rng.random::<f64>() < *switch_probability
+            {
+                // Any regime other than the current one
+                regime = (regime + rng.random_range(1..regimes.len())) % regimes.len();
+            }
+            let open = price;
+            let (mut low, mut high) = (price, price);
+            for _ in 0..STEPS_PER_BAR {
+                price = self.step(price, regime, rng);
+                low = low.min(price);
+                high = high.max(price);
+            }
+            bars.push(Bar {
+                low: to_price(low)?,
+                high: to_price(high)?,
+                open: to_price(open)?,
+                close: to_price(price)?,
+                date_time,
+                volume: BigDecimal::from(0),
+                trade_count: 0,
+                vwap: None,
+            });
+            date_time += self.bar_duration;
+        }
+        Ok(bars)
+    }
+
+    fn step(&self, price: f64, regime: usize, rng: &mut StdRng) -> f64 {
+        let steps = f64::from(STEPS_PER_BAR);
+        let normal = sample_standard_normal(rng);
+        let (drift, volatility) = match &self.model {
+            SyntheticModel::RandomWalk { step_deviation } => {
+                return (price + step_deviation / steps.sqrt() * normal).max(0.0);
+            }
+            SyntheticModel::GeometricBrownianMotion { drift, volatility } => (*drift, *volatility),
+            SyntheticModel::RegimeSwitching { regimes, .. } => {
+                (regimes[regime].drift, regimes[regime].volatility)
+            }
+        };
+        let log_return =
+            (drift - volatility * volatility / 2.0) / steps + volatility / steps.sqrt() * normal;
+        price * log_return.exp()
+    }
+}
+
+fn to_price(price: f64) -> Result<BigDecimal> {
+    // Rounded to keep the precision of the generated prices bounded
+    BigDecimal::try_from(price)
+        .map(|price| price.round(8))
+        .map_err(|_| Error::InvalidArgument(format!("Generated price {} is not finite", price)))
+}
+
+fn sample_standard_normal<R>(rng: &mut R) -> f64
+where
+    R: Rng + ?Sized,
+{
+    // Box-Muller transform, with the first uniform sample in (0, 1] to keep its logarithm finite
+    let uniform = 1.0 - rng.random::<f64>();
+    (-2.0 * uniform.ln()).sqrt() * (2.0 * PI * rng.random::<f64>()).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulated::quality::BarValidatorBuilder;
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn generate_deterministic_bars() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let model = SyntheticModel::RegimeSwitching {
+            regimes: vec![
+                Regime {
+                    drift: 0.001,
+                    volatility: 0.01,
+                },
+                Regime {
+                    drift: -0.002,
+                    volatility: 0.05,
+                },
+            ],
+            switch_probability: 0.1,
+        };
+        let mut builder = SyntheticBarsBuilder::new(model, start, 100);
+        builder
+            .set_seed(7)
+            .add_pair(crypto_pair.clone(), BigDecimal::from(100));
+
+        let bars = builder.build()?.get_bars(&crypto_pair);
+
+        assert_eq!(bars.len(), 100);
+        assert_eq!(bars[0].open, BigDecimal::from(100));
+        assert_eq!(bars[99].date_time, start + Duration::minutes(99));
+        assert!(
+            BarValidatorBuilder::new()
+                .build()?
+                .validate(&bars)
+                .is_clean()
+        );
+        assert_eq!(builder.build()?.get_bars(&crypto_pair), bars);
+        assert_ne!(builder.set_seed(8).build()?.get_bars(&crypto_pair), bars);
+        Ok(())
+    }
+
+    #[test]
+    fn generate_bars_without_volatility() -> Result<()> {
+        let crypto_pair = Instrument::from_str("COIN/GBP")?;
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let bars = SyntheticBarsBuilder::new(
+            SyntheticModel::GeometricBrownianMotion {
+                drift: 0.0,
+                volatility: 0.0,
+            },
+            start,
+            3,
+        )
+        .set_bar_duration(Duration::hours(1))
+        .add_pair(crypto_pair.clone(), BigDecimal::from(5))
+        .build()?
+        .get_bars(&crypto_pair);
+
+        assert!(
+            bars.iter()
+                .all(|bar| bar.low == 5 && bar.high == 5 && bar.close == 5)
+        );
+        assert_eq!(bars[2].date_time, start + Duration::hours(2));
+        Ok(())
+    }
+
+    #[test]
+    fn build_invalid_models() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:00:00+00:00")?;
+        let err = SyntheticBarsBuilder::new(
+            SyntheticModel::RegimeSwitching {
+                regimes: Vec::new(),
+                switch_probability: 0.5,
+            },
+            start,
+            3,
+        )
+        .build()
+        .unwrap_err();
+        assert_eq!(err.to_string(), "At least one regime is needed");
+
+        let err = SyntheticBarsBuilder::new(
+            SyntheticModel::RandomWalk {
+                step_deviation: -1.0,
+            },
+            start,
+            3,
+        )
+        .build()
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Volatility must not be negative");
+        Ok(())
+    }
+}
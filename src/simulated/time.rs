@@ -1,11 +1,85 @@
 // Copyright (C) 2025 Agostinho Junior
 // SPDX-License-Identifier: GPL-3.0-or-later
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use dyn_clone::DynClone;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
 
 pub trait Clock: DynClone {
     fn now(&self) -> DateTime<Utc>;
 }
 
-dyn_clone::clone_trait_object!(Clock);
\ No newline at end of file
+dyn_clone::clone_trait_object!(Clock);
+
+impl Debug for dyn Clock + Send + Sync {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+impl Clock for Box<dyn Clock + Send + Sync> {
+    fn now(&self) -> DateTime<Utc> {
+        self.as_ref().now()
+    }
+}
+
+/// [Clock] reading the current time of the system, e.g. for paper trading.
+#[derive(Clone, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// [Clock] that only moves when told to. Clones share the same time, so a clone can be handed to
+/// a [SimulatedContext](crate::simulated::SimulatedContext) while the original drives it.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<RwLock<DateTime<Utc>>>,
+}
+
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(RwLock::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.write().unwrap() = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.write().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.read().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    #[test]
+    fn manual_clock_shared_between_clones() -> Result<()> {
+        let start = DateTime::<Utc>::from_str("2025-12-17T18:30:00+00:00")?;
+        let clock = ManualClock::new(start);
+        let cloned_clock = clock.clone();
+
+        clock.advance(Duration::minutes(2));
+        assert_eq!(cloned_clock.now(), start + Duration::minutes(2));
+
+        cloned_clock.set(start);
+        assert_eq!(clock.now(), start);
+        Ok(())
+    }
+}